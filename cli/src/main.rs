@@ -5,7 +5,7 @@ use clap::{Parser, Subcommand};
 use rand::RngCore;
 use rand::rngs::OsRng;
 use zcash_protocol::consensus::Network;
-use zcash_wallet_core::{parse_transaction, scan_transaction as scan_tx};
+use zcash_wallet_core::{is_below_birthday, parse_transaction, scan_transaction as scan_tx};
 
 mod db;
 mod error;
@@ -37,6 +37,9 @@ enum Commands {
         /// Address index (diversifier index for shielded addresses). Default: 0
         #[arg(long, default_value = "0")]
         address_index: u32,
+        /// Current chain tip height, recorded as the wallet birthday
+        #[arg(long)]
+        birthday_height: Option<u32>,
     },
     /// Restore wallet from seed phrase
     Restore {
@@ -148,7 +151,8 @@ fn run() -> Result<()> {
             mainnet,
             account,
             address_index,
-        } => generate_wallet(&output, mainnet, account, address_index),
+            birthday_height,
+        } => generate_wallet(&output, mainnet, account, address_index, birthday_height),
         Commands::Restore {
             seed,
             output,
@@ -184,6 +188,7 @@ fn generate_wallet(
     mainnet: bool,
     account: u32,
     address_index: u32,
+    birthday_height: Option<u32>,
 ) -> Result<()> {
     // Check if output file already exists
     let path = Path::new(output_path);
@@ -203,8 +208,14 @@ fn generate_wallet(
     OsRng.fill_bytes(&mut entropy);
 
     // Use core library for wallet derivation
-    let wallet = zcash_wallet_core::generate_wallet(&entropy, network, account, address_index)
-        .map_err(|e| CliError::Wallet(e.to_string()))?;
+    let wallet = zcash_wallet_core::generate_wallet(
+        &entropy,
+        network,
+        account,
+        address_index,
+        birthday_height,
+    )
+    .map_err(|e| CliError::Wallet(e.to_string()))?;
 
     // Create JSON wallet data
     let wallet_json = serde_json::json!({
@@ -215,6 +226,7 @@ fn generate_wallet(
         "unified_address": wallet.unified_address,
         "unified_full_viewing_key": wallet.unified_full_viewing_key,
         "transparent_address": wallet.transparent_address,
+        "birthday_height": wallet.birthday_height,
     });
 
     // Write wallet to file
@@ -424,6 +436,19 @@ fn scan_transaction(
         _ => Network::TestNetwork,
     };
 
+    // Transactions below the wallet birthday cannot contain our notes
+    let birthday_height = wallet_json["birthday_height"]
+        .as_u64()
+        .and_then(|h| u32::try_from(h).ok());
+    if is_below_birthday(height, birthday_height) {
+        println!(
+            "Skipping transaction at height {} (below wallet birthday {})",
+            height.unwrap_or_default(),
+            birthday_height.unwrap_or_default()
+        );
+        return Ok(());
+    }
+
     // Get transaction hex
     let tx_hex = if let Some(hex) = raw_hex {
         hex
@@ -453,7 +478,7 @@ fn scan_transaction(
 
     // Store notes
     let mut notes_added = 0;
-    for note in &result.notes {
+    for note in result.notes.iter().filter(|n| !n.is_outgoing()) {
         let inserted = db.insert_note(
             &result.txid,
             note.output_index as i64,
@@ -479,7 +504,12 @@ fn scan_transaction(
     let notes_spent = db.mark_spent_by_nullifiers(&nullifier_strings, &result.txid)?;
 
     // Create ledger entry
-    let value_received: i64 = result.notes.iter().map(|n| n.value as i64).sum();
+    let value_received: i64 = result
+        .notes
+        .iter()
+        .filter(|n| !n.is_outgoing())
+        .map(|n| n.value as i64)
+        .sum();
     let value_spent: i64 = 0; // We don't know spent values from scan result
     let net_change = value_received - value_spent;
     let primary_pool = if result.notes.is_empty() {
@@ -0,0 +1,1042 @@
+//! Address inspection utilities.
+//!
+//! Helpers for examining user-supplied Zcash addresses independently of the
+//! network the wallet is currently running on, so the UI can give precise
+//! feedback (for example "this is a mainnet address") instead of a generic
+//! parse error.
+
+use std::collections::HashSet;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use qrcode::render::svg;
+use qrcode::{EcLevel, QrCode};
+use ripemd::Ripemd160;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zcash_keys::address::{Address, UnifiedAddress};
+use zcash_keys::keys::UnifiedSpendingKey;
+use zcash_protocol::consensus::Network;
+use zcash_transparent::address::TransparentAddress;
+use zcash_transparent::keys::NonHardenedChildIndex;
+
+use crate::compact_size::push_compact_size;
+use crate::wallet::{WalletError, account_id};
+
+/// Detect which network an address belongs to.
+///
+/// Transparent, Sapling, unified and TEX addresses are recognised for both
+/// mainnet and testnet, regardless of the network the wallet is using.
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+///
+/// # Returns
+///
+/// The network the address is encoded for, or `None` if the input is not a
+/// valid Zcash address.
+pub fn detect_address_network(addr: &str) -> Option<Network> {
+    let addr = addr.trim();
+    [Network::MainNetwork, Network::TestNetwork]
+        .into_iter()
+        .find(|network| Address::decode(network, addr).is_some())
+}
+
+/// The kind of a Zcash address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    /// Transparent pay-to-public-key-hash address (`t1`, testnet `tm`).
+    P2pkh,
+    /// Transparent pay-to-script-hash address (`t3`, testnet `t2`).
+    P2sh,
+    /// Sapling address (`zs`, testnet `ztestsapling`).
+    Sapling,
+    /// Unified address (`u1`, testnet `utest1`).
+    Unified,
+    /// ZIP 320 transparent-source-only address (`tex1`, testnet `textest1`).
+    Tex,
+}
+
+impl AddressKind {
+    /// Get the string representation of the address kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressKind::P2pkh => "p2pkh",
+            AddressKind::P2sh => "p2sh",
+            AddressKind::Sapling => "sapling",
+            AddressKind::Unified => "unified",
+            AddressKind::Tex => "tex",
+        }
+    }
+
+    /// Check whether the address pays to a transparent receiver.
+    pub fn is_transparent(&self) -> bool {
+        matches!(self, AddressKind::P2pkh | AddressKind::P2sh)
+    }
+}
+
+/// Classify an address by kind and network.
+///
+/// Like [`detect_address_network`], the address is decoded for both mainnet
+/// and testnet, so testnet forms (`tm`, `t2`, `ztestsapling`, `utest1`,
+/// `textest1`) are told apart from their mainnet counterparts.
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+///
+/// # Returns
+///
+/// The kind and network of the address, or `None` if the input is not a
+/// valid Zcash address.
+pub fn classify_address(addr: &str) -> Option<(AddressKind, Network)> {
+    let addr = addr.trim();
+    [Network::MainNetwork, Network::TestNetwork]
+        .into_iter()
+        .find_map(|network| {
+            let kind = match Address::decode(&network, addr)? {
+                Address::Transparent(TransparentAddress::PublicKeyHash(_)) => AddressKind::P2pkh,
+                Address::Transparent(TransparentAddress::ScriptHash(_)) => AddressKind::P2sh,
+                Address::Sapling(_) => AddressKind::Sapling,
+                Address::Unified(_) => AddressKind::Unified,
+                Address::Tex(_) => AddressKind::Tex,
+            };
+            Some((kind, network))
+        })
+}
+
+/// Normalize an address to its canonical encoding.
+///
+/// Surrounding whitespace is removed and the address is decoded and
+/// re-encoded, so for example an upper-case Bech32 address (as read from a
+/// QR code) becomes the usual lower-case form.
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+/// * `network` - The network the address must be encoded for
+///
+/// # Returns
+///
+/// The canonical encoding, or `None` if the input is not a valid address
+/// for `network`.
+pub fn normalize_address(addr: &str, network: Network) -> Option<String> {
+    Address::decode(&network, addr.trim()).map(|decoded| decoded.encode(&network))
+}
+
+/// Normalize a list of addresses and remove duplicates.
+///
+/// Addresses are compared by their canonical encoding (see
+/// [`normalize_address`]); invalid addresses are skipped.
+///
+/// # Arguments
+///
+/// * `addrs` - The encoded addresses
+/// * `network` - The network the addresses must be encoded for
+///
+/// # Returns
+///
+/// The canonical encodings, in the order each address was first seen.
+pub fn dedup_addresses(addrs: &[String], network: Network) -> Vec<String> {
+    let mut seen = HashSet::new();
+    addrs
+        .iter()
+        .filter_map(|addr| normalize_address(addr, network))
+        .filter(|addr| seen.insert(addr.clone()))
+        .collect()
+}
+
+/// Check whether an address is one of the receivers of a unified address.
+///
+/// Used to recognise self-payments: a transparent, Sapling or TEX address
+/// is "contained in" a unified address if its receiver appears in it. A TEX
+/// address matches the unified address's P2PKH receiver with the same key
+/// hash.
+///
+/// # Arguments
+///
+/// * `unified` - The encoded unified address
+/// * `component` - The encoded transparent, Sapling or TEX address
+/// * `network` - The network both addresses are encoded for
+///
+/// # Returns
+///
+/// Whether `component` is a receiver of `unified`, or
+/// `WalletError::InvalidAddress` if either address cannot be decoded or is
+/// of the wrong kind.
+pub fn address_contains_receiver(
+    unified: &str,
+    component: &str,
+    network: Network,
+) -> Result<bool, WalletError> {
+    let ua = decode_unified_address(unified, network)?;
+
+    match Address::decode(&network, component.trim()) {
+        Some(Address::Transparent(taddr)) => Ok(ua.transparent() == Some(&taddr)),
+        Some(Address::Tex(key_hash)) => Ok(matches!(
+            ua.transparent(),
+            Some(TransparentAddress::PublicKeyHash(hash)) if *hash == key_hash
+        )),
+        Some(Address::Sapling(pa)) => Ok(ua.sapling() == Some(&pa)),
+        Some(Address::Unified(_)) => Err(WalletError::InvalidAddress(
+            "Component address must not be a unified address".to_string(),
+        )),
+        None => Err(WalletError::InvalidAddress(format!(
+            "Could not decode address for {:?}",
+            network
+        ))),
+    }
+}
+
+/// Extract a single receiver of a unified address as a standalone address.
+///
+/// Transparent and Sapling receivers are encoded as ordinary t- and
+/// z-addresses. Orchard has no standalone encoding, so the Orchard receiver
+/// is returned as a unified address containing only that receiver.
+///
+/// # Arguments
+///
+/// * `unified` - The encoded unified address
+/// * `pool` - The pool of the receiver: `"transparent"`, `"sapling"` or
+///   `"orchard"`
+/// * `network` - The network the address is encoded for
+///
+/// # Returns
+///
+/// The encoded receiver, `None` if the unified address has no receiver for
+/// that pool, or `WalletError::InvalidAddress` if the address cannot be
+/// decoded or the pool is unknown.
+pub fn extract_receiver(
+    unified: &str,
+    pool: &str,
+    network: Network,
+) -> Result<Option<String>, WalletError> {
+    let ua = decode_unified_address(unified, network)?;
+
+    let receiver = match pool.trim().to_ascii_lowercase().as_str() {
+        "transparent" => ua
+            .transparent()
+            .map(|taddr| Address::Transparent(*taddr).encode(&network)),
+        "sapling" => ua
+            .sapling()
+            .map(|pa| Address::Sapling(pa.clone()).encode(&network)),
+        "orchard" => ua.orchard().and_then(|orchard| {
+            UnifiedAddress::from_receivers(Some(*orchard), None, None)
+                .map(|single| single.encode(&network))
+        }),
+        other => {
+            return Err(WalletError::InvalidAddress(format!(
+                "Unknown pool: {}",
+                other
+            )));
+        }
+    };
+    Ok(receiver)
+}
+
+/// Decode a unified address, rejecting other address kinds.
+fn decode_unified_address(unified: &str, network: Network) -> Result<UnifiedAddress, WalletError> {
+    match Address::decode(&network, unified.trim()) {
+        Some(Address::Unified(ua)) => Ok(ua),
+        Some(_) => Err(WalletError::InvalidAddress(
+            "Address is not a unified address".to_string(),
+        )),
+        None => Err(WalletError::InvalidAddress(format!(
+            "Could not decode unified address for {:?}",
+            network
+        ))),
+    }
+}
+
+/// Separator placed between the kept start and end of a truncated address.
+const TRUNCATION_ELLIPSIS: &str = "...";
+
+/// Shorten an address for display by eliding its middle.
+///
+/// For Bech32 encodings (unified, Sapling and TEX addresses) the
+/// human-readable prefix and separator (e.g. `u1`, `zs1`) are always kept
+/// and `prefix_len` counts characters after it. The address is split on
+/// character boundaries only.
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+/// * `prefix_len` - Number of data characters to keep at the start
+/// * `suffix_len` - Number of characters to keep at the end
+///
+/// # Returns
+///
+/// The truncated address, e.g. `u1abcdef...uvwxyz`, or the address unchanged
+/// if it is too short for truncation to save any space.
+pub fn truncate_address(addr: &str, prefix_len: usize, suffix_len: usize) -> String {
+    let addr = addr.trim();
+    let chars: Vec<char> = addr.chars().collect();
+    let hrp_len = bech32_hrp_len(addr);
+
+    let keep_start = hrp_len + prefix_len;
+    if chars.len() <= keep_start + TRUNCATION_ELLIPSIS.len() + suffix_len {
+        return addr.to_string();
+    }
+
+    let start: String = chars[..keep_start].iter().collect();
+    let end: String = chars[chars.len() - suffix_len..].iter().collect();
+    format!("{}{}{}", start, TRUNCATION_ELLIPSIS, end)
+}
+
+/// Length of the Bech32 human-readable prefix including the `1` separator,
+/// or 0 if the address does not look like a Bech32 encoding.
+fn bech32_hrp_len(addr: &str) -> usize {
+    // Bech32 strings are single-case and the HRPs used by Zcash are purely
+    // alphabetic; Base58 transparent addresses are mixed-case
+    if addr.chars().any(|c| c.is_ascii_uppercase()) {
+        return 0;
+    }
+    match addr.rfind('1') {
+        Some(pos) if pos > 0 && addr[..pos].chars().all(|c| c.is_ascii_lowercase()) => pos + 1,
+        _ => 0,
+    }
+}
+
+/// Size in pixels of one QR code module in [`address_qr_svg`] output.
+pub const QR_MODULE_SIZE: u32 = 4;
+
+/// Encode an address as a QR code.
+///
+/// Bech32 addresses are upper-cased first, as recommended by ZIP 316: the
+/// QR alphanumeric mode then applies, which makes QR codes for long unified
+/// addresses markedly smaller. Upper-case Bech32 is equally valid.
+fn address_qr_code(addr: &str) -> Result<QrCode, WalletError> {
+    let addr = addr.trim();
+    if detect_address_network(addr).is_none() {
+        return Err(WalletError::InvalidAddress(
+            "Not a valid Zcash address".to_string(),
+        ));
+    }
+
+    let data = if bech32_hrp_len(addr) > 0 {
+        addr.to_ascii_uppercase()
+    } else {
+        addr.to_string()
+    };
+
+    QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)
+        .map_err(|e| WalletError::InvalidAddress(format!("Cannot encode QR code: {}", e)))
+}
+
+/// Render an address as a QR code SVG image.
+///
+/// The version (size) of the QR code is chosen automatically, so long
+/// unified addresses get a larger symbol. The image includes the standard
+/// 4-module quiet zone and uses [`QR_MODULE_SIZE`] pixels per module.
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+///
+/// # Returns
+///
+/// The SVG document, or `WalletError::InvalidAddress` if the input is not a
+/// valid Zcash address.
+pub fn address_qr_svg(addr: &str) -> Result<String, WalletError> {
+    let code = address_qr_code(addr)?;
+    Ok(code
+        .render::<svg::Color<'_>>()
+        .module_dimensions(QR_MODULE_SIZE, QR_MODULE_SIZE)
+        .build())
+}
+
+/// BLAKE2b personalization for address identicons (16 bytes).
+const IDENTICON_PERSONALIZATION: &[u8; 16] = b"ZcashIdenticon__";
+
+/// Width and height of an identicon in cells.
+pub const IDENTICON_GRID_SIZE: usize = 5;
+
+/// Size in pixels of one identicon cell in [`Identicon::svg`].
+const IDENTICON_CELL_SIZE: usize = 10;
+
+/// A small deterministic image identifying an address.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identicon {
+    /// Foreground color as `#rrggbb`.
+    pub foreground: String,
+    /// Background color as `#rrggbb`.
+    pub background: String,
+    /// Filled cells, row by row. Each row is mirrored left to right.
+    pub cells: Vec<Vec<bool>>,
+    /// The identicon rendered as an SVG document.
+    pub svg: String,
+}
+
+/// Compute the identicon of an address.
+///
+/// The image is derived from a hash of the address's canonical encoding
+/// (see [`normalize_address`]), so the same address always gets the same
+/// identicon whatever its case or surrounding whitespace. Inputs that are
+/// not valid addresses are hashed as given (trimmed).
+///
+/// # Arguments
+///
+/// * `addr` - The encoded address
+///
+/// # Returns
+///
+/// The `Identicon` of the address.
+pub fn address_identicon(addr: &str) -> Identicon {
+    let canonical = classify_address(addr)
+        .and_then(|(_, network)| normalize_address(addr, network))
+        .unwrap_or_else(|| addr.trim().to_string());
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(IDENTICON_PERSONALIZATION)
+        .hash(canonical.as_bytes());
+    let bytes = hash.as_bytes();
+
+    let foreground = format!("#{:02x}{:02x}{:02x}", bytes[0], bytes[1], bytes[2]);
+    // A pale tint of the foreground keeps enough contrast
+    let background = format!(
+        "#{:02x}{:02x}{:02x}",
+        0xe0 | (bytes[0] >> 3),
+        0xe0 | (bytes[1] >> 3),
+        0xe0 | (bytes[2] >> 3)
+    );
+
+    // Only the left half (and middle column) is random, the rest is mirrored
+    let half = IDENTICON_GRID_SIZE.div_ceil(2);
+    let cells: Vec<Vec<bool>> = (0..IDENTICON_GRID_SIZE)
+        .map(|row| {
+            (0..IDENTICON_GRID_SIZE)
+                .map(|col| {
+                    let col = col.min(IDENTICON_GRID_SIZE - 1 - col);
+                    bytes[3 + row * half + col] & 1 == 1
+                })
+                .collect()
+        })
+        .collect();
+
+    let size = IDENTICON_GRID_SIZE * IDENTICON_CELL_SIZE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{size}\" height=\"{size}\" viewBox=\"0 0 {size} {size}\"><rect width=\"{size}\" height=\"{size}\" fill=\"{background}\"/>"
+    );
+    for (row, row_cells) in cells.iter().enumerate() {
+        for (col, _) in row_cells.iter().enumerate().filter(|(_, filled)| **filled) {
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{IDENTICON_CELL_SIZE}\" height=\"{IDENTICON_CELL_SIZE}\" fill=\"{foreground}\"/>",
+                col * IDENTICON_CELL_SIZE,
+                row * IDENTICON_CELL_SIZE
+            ));
+        }
+    }
+    svg.push_str("</svg>");
+
+    Identicon {
+        foreground,
+        background,
+        cells,
+        svg,
+    }
+}
+
+/// Prefix of messages signed with a transparent address key.
+const SIGNED_MESSAGE_MAGIC: &[u8] = b"Zcash Signed Message:\n";
+
+/// Length of a Bitcoin-style compact recoverable signature.
+const COMPACT_SIGNATURE_SIZE: usize = 65;
+
+/// Compute the double SHA-256 digest that is signed for a message.
+fn signed_message_hash(message: &str) -> [u8; 32] {
+    let mut data = Vec::with_capacity(SIGNED_MESSAGE_MAGIC.len() + message.len() + 10);
+    push_compact_size(&mut data, SIGNED_MESSAGE_MAGIC.len() as u64);
+    data.extend_from_slice(SIGNED_MESSAGE_MAGIC);
+    push_compact_size(&mut data, message.len() as u64);
+    data.extend_from_slice(message.as_bytes());
+    Sha256::digest(Sha256::digest(&data)).into()
+}
+
+/// Sign a message with the key of a wallet's transparent address.
+///
+/// Produces a signature in the format checked by
+/// [`verify_transparent_message`] and `zcashd`'s `verifymessage`.
+///
+/// # Arguments
+///
+/// * `seed` - The 64-byte wallet seed
+/// * `account` - The account index
+/// * `address_index` - The index of the external transparent address
+/// * `message` - The message to sign
+/// * `network` - The network the address is for
+///
+/// # Returns
+///
+/// The base64-encoded 65-byte recoverable signature.
+pub fn sign_transparent_message(
+    seed: &[u8],
+    account: u32,
+    address_index: u32,
+    message: &str,
+    network: Network,
+) -> Result<String, WalletError> {
+    let account_id = account_id(account)?;
+    let child_index = NonHardenedChildIndex::from_index(address_index).ok_or_else(|| {
+        WalletError::AddressGeneration(format!("Invalid address index: {}", address_index))
+    })?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let secret_key = usk
+        .transparent()
+        .derive_external_secret_key(child_index)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    let digest = Message::from_digest(signed_message_hash(message));
+    let (recovery_id, compact) = Secp256k1::signing_only()
+        .sign_ecdsa_recoverable(&digest, &secret_key)
+        .serialize_compact();
+
+    // Header byte for a compressed public key
+    let mut sig = Vec::with_capacity(COMPACT_SIGNATURE_SIZE);
+    sig.push(31 + recovery_id.to_i32() as u8);
+    sig.extend_from_slice(&compact);
+    Ok(BASE64_STANDARD.encode(sig))
+}
+
+/// Verify a signed message against a transparent address.
+///
+/// Uses the Bitcoin message signing scheme with the `"Zcash Signed
+/// Message:\n"` prefix, as produced by `zcashd`'s `signmessage`: the
+/// signature is a base64-encoded 65-byte recoverable signature, and it is
+/// valid if the public key recovered from it hashes to the address.
+///
+/// # Arguments
+///
+/// * `address` - The P2PKH transparent address
+/// * `message` - The signed message
+/// * `signature` - The base64-encoded signature
+/// * `network` - The network the address is encoded for
+///
+/// # Returns
+///
+/// Whether the signature was made by the address's key over `message`, or
+/// an error if the address is not a P2PKH address or the signature is
+/// malformed.
+pub fn verify_transparent_message(
+    address: &str,
+    message: &str,
+    signature: &str,
+    network: Network,
+) -> Result<bool, WalletError> {
+    let key_hash = match Address::decode(&network, address.trim()) {
+        Some(Address::Transparent(TransparentAddress::PublicKeyHash(hash))) => hash,
+        Some(_) => {
+            return Err(WalletError::InvalidAddress(
+                "Messages can only be verified for P2PKH transparent addresses".to_string(),
+            ));
+        }
+        None => {
+            return Err(WalletError::InvalidAddress(format!(
+                "Could not decode address for {:?}",
+                network
+            )));
+        }
+    };
+
+    let sig_bytes = BASE64_STANDARD
+        .decode(signature.trim())
+        .map_err(|e| WalletError::InvalidSignature(e.to_string()))?;
+    if sig_bytes.len() != COMPACT_SIGNATURE_SIZE {
+        return Err(WalletError::InvalidSignature(format!(
+            "Expected {} bytes, got {}",
+            COMPACT_SIGNATURE_SIZE,
+            sig_bytes.len()
+        )));
+    }
+
+    // Header byte: 27 + recovery id, plus 4 for compressed public keys
+    let header = sig_bytes[0];
+    if !(27..=34).contains(&header) {
+        return Err(WalletError::InvalidSignature(format!(
+            "Invalid header byte {}",
+            header
+        )));
+    }
+    let compressed = header >= 31;
+    let recovery_id = RecoveryId::from_i32(i32::from((header - 27) & 3))
+        .map_err(|e| WalletError::InvalidSignature(e.to_string()))?;
+    let signature = RecoverableSignature::from_compact(&sig_bytes[1..], recovery_id)
+        .map_err(|e| WalletError::InvalidSignature(e.to_string()))?;
+
+    let digest = Message::from_digest(signed_message_hash(message));
+    let Ok(pubkey) = Secp256k1::verification_only().recover_ecdsa(&digest, &signature) else {
+        return Ok(false);
+    };
+
+    let pubkey_hash: [u8; 20] = if compressed {
+        Ripemd160::digest(Sha256::digest(pubkey.serialize())).into()
+    } else {
+        Ripemd160::digest(Sha256::digest(pubkey.serialize_uncompressed())).into()
+    };
+    Ok(pubkey_hash == key_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::restore_wallet;
+
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn test_detect_mainnet_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        assert_eq!(
+            detect_address_network(&wallet.unified_address),
+            Some(Network::MainNetwork)
+        );
+    }
+
+    #[test]
+    fn test_detect_testnet_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        assert_eq!(
+            detect_address_network(&wallet.unified_address),
+            Some(Network::TestNetwork)
+        );
+    }
+
+    #[test]
+    fn test_detect_transparent_addresses() {
+        assert_eq!(
+            detect_address_network("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd"),
+            Some(Network::TestNetwork)
+        );
+
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet.transparent_address.expect("should have t-addr");
+        assert_eq!(detect_address_network(&taddr), Some(Network::MainNetwork));
+    }
+
+    #[test]
+    fn test_detect_garbage_returns_none() {
+        assert_eq!(detect_address_network("hello world"), None);
+        assert_eq!(detect_address_network(""), None);
+        assert_eq!(detect_address_network("u1notreallyanaddress"), None);
+    }
+
+    #[test]
+    fn test_truncate_long_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ua = &wallet.unified_address;
+
+        let truncated = truncate_address(ua, 6, 6);
+        assert_eq!(
+            truncated,
+            format!("u1{}...{}", &ua[2..8], &ua[ua.len() - 6..])
+        );
+    }
+
+    #[test]
+    fn test_truncate_short_transparent_address() {
+        let taddr = "tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd";
+
+        // Base58 addresses have no human-readable prefix to preserve
+        assert_eq!(truncate_address(taddr, 4, 4), "tmBs...PDJd");
+        // Below the threshold the address is returned unchanged
+        assert_eq!(truncate_address(taddr, 16, 16), taddr);
+    }
+
+    #[test]
+    fn test_truncate_address_keeps_sapling_hrp() {
+        let addr =
+            "ztestsapling1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqzzz";
+        assert_eq!(truncate_address(addr, 2, 3), "ztestsapling1qq...zzz");
+    }
+
+    #[test]
+    fn test_address_contains_own_transparent_receiver() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet.transparent_address.expect("should have t-addr");
+
+        let contained =
+            address_contains_receiver(&wallet.unified_address, &taddr, Network::TestNetwork)
+                .expect("addresses should decode");
+        assert!(contained);
+    }
+
+    #[test]
+    fn test_address_does_not_contain_other_transparent_receiver() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let other = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
+        let other_taddr = other.transparent_address.expect("should have t-addr");
+
+        let contained =
+            address_contains_receiver(&wallet.unified_address, &other_taddr, Network::TestNetwork)
+                .expect("addresses should decode");
+        assert!(!contained);
+    }
+
+    #[test]
+    fn test_address_contains_receiver_rejects_invalid_input() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet
+            .transparent_address
+            .clone()
+            .expect("should have t-addr");
+
+        // Arguments swapped: the first must be a unified address
+        assert!(matches!(
+            address_contains_receiver(&taddr, &wallet.unified_address, Network::TestNetwork),
+            Err(WalletError::InvalidAddress(_))
+        ));
+        // Wrong network
+        assert!(matches!(
+            address_contains_receiver(&wallet.unified_address, &taddr, Network::MainNetwork),
+            Err(WalletError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_address_qr_svg_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let svg = address_qr_svg(&wallet.unified_address).expect("QR code should render");
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        // Modules plus a 4-module quiet zone on each side
+        let modules = address_qr_code(&wallet.unified_address).unwrap().width() as u32;
+        let size = (modules + 8) * QR_MODULE_SIZE;
+        assert!(svg.contains(&format!("width=\"{}\"", size)));
+        assert!(svg.contains(&format!("height=\"{}\"", size)));
+    }
+
+    #[test]
+    fn test_address_qr_code_grows_with_address_length() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet.transparent_address.expect("should have t-addr");
+
+        let small = address_qr_code(&taddr).unwrap().width();
+        let large = address_qr_code(&wallet.unified_address).unwrap().width();
+        assert!(large > small);
+    }
+
+    #[test]
+    fn test_address_qr_svg_rejects_invalid_address() {
+        assert!(matches!(
+            address_qr_svg("hello world"),
+            Err(WalletError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_transparent_receiver() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let taddr = extract_receiver(&wallet.unified_address, "transparent", Network::TestNetwork)
+            .expect("address should decode");
+        assert_eq!(taddr, wallet.transparent_address);
+    }
+
+    #[test]
+    fn test_extract_sapling_receiver() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let zaddr = extract_receiver(&wallet.unified_address, "sapling", Network::TestNetwork)
+            .expect("address should decode")
+            .expect("should have a Sapling receiver");
+        assert!(zaddr.starts_with("ztestsapling1"));
+        assert!(
+            address_contains_receiver(&wallet.unified_address, &zaddr, Network::TestNetwork)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_orchard_receiver_is_single_receiver_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let orchard_only =
+            extract_receiver(&wallet.unified_address, "Orchard", Network::TestNetwork)
+                .expect("address should decode")
+                .expect("should have an Orchard receiver");
+        let ua = decode_unified_address(&orchard_only, Network::TestNetwork).unwrap();
+        assert!(ua.orchard().is_some());
+        assert!(ua.sapling().is_none());
+        assert!(ua.transparent().is_none());
+    }
+
+    #[test]
+    fn test_extract_receiver_missing_or_invalid() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let orchard_only =
+            extract_receiver(&wallet.unified_address, "orchard", Network::TestNetwork)
+                .unwrap()
+                .unwrap();
+
+        // The receiver is absent from an Orchard-only address
+        assert_eq!(
+            extract_receiver(&orchard_only, "sapling", Network::TestNetwork).unwrap(),
+            None
+        );
+        assert!(matches!(
+            extract_receiver(&wallet.unified_address, "sprout", Network::TestNetwork),
+            Err(WalletError::InvalidAddress(_))
+        ));
+        let taddr = wallet.transparent_address.clone().unwrap();
+        assert!(matches!(
+            extract_receiver(&taddr, "transparent", Network::TestNetwork),
+            Err(WalletError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_classify_testnet_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let testnet = Network::TestNetwork;
+
+        let ua = wallet.unified_address.clone();
+        let sapling = extract_receiver(&ua, "sapling", testnet).unwrap().unwrap();
+        let p2pkh = wallet.transparent_address.clone().unwrap();
+        let p2sh = Address::Transparent(TransparentAddress::ScriptHash([7u8; 20])).encode(&testnet);
+        let tex = Address::Tex([7u8; 20]).encode(&testnet);
+
+        assert!(ua.starts_with("utest1"));
+        assert!(sapling.starts_with("ztestsapling"));
+        assert!(p2pkh.starts_with("tm"));
+        assert!(p2sh.starts_with("t2"));
+        assert!(tex.starts_with("textest1"));
+
+        assert_eq!(classify_address(&ua), Some((AddressKind::Unified, testnet)));
+        assert_eq!(
+            classify_address(&sapling),
+            Some((AddressKind::Sapling, testnet))
+        );
+        assert_eq!(
+            classify_address(&p2pkh),
+            Some((AddressKind::P2pkh, testnet))
+        );
+        assert_eq!(classify_address(&p2sh), Some((AddressKind::P2sh, testnet)));
+        assert_eq!(classify_address(&tex), Some((AddressKind::Tex, testnet)));
+    }
+
+    #[test]
+    fn test_classify_mainnet_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let mainnet = Network::MainNetwork;
+
+        let p2sh = Address::Transparent(TransparentAddress::ScriptHash([7u8; 20])).encode(&mainnet);
+        assert!(p2sh.starts_with("t3"));
+
+        assert_eq!(
+            classify_address(&wallet.unified_address),
+            Some((AddressKind::Unified, mainnet))
+        );
+        assert_eq!(
+            classify_address(wallet.transparent_address.as_ref().unwrap()),
+            Some((AddressKind::P2pkh, mainnet))
+        );
+        assert_eq!(classify_address(&p2sh), Some((AddressKind::P2sh, mainnet)));
+        assert_eq!(classify_address("not an address"), None);
+    }
+
+    /// Sign a message the way `zcashd`'s `signmessage` does, returning the
+    /// signer's P2PKH address and the base64 signature.
+    fn sign_test_message(message: &str, network: Network) -> (String, String) {
+        let secp = Secp256k1::new();
+        let secret_key = secp256k1::SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let pubkey = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+        let key_hash: [u8; 20] = Ripemd160::digest(Sha256::digest(pubkey.serialize())).into();
+        let address =
+            Address::Transparent(TransparentAddress::PublicKeyHash(key_hash)).encode(&network);
+
+        let digest = Message::from_digest(signed_message_hash(message));
+        let (recovery_id, compact) = secp
+            .sign_ecdsa_recoverable(&digest, &secret_key)
+            .serialize_compact();
+        let mut sig = vec![27 + 4 + recovery_id.to_i32() as u8];
+        sig.extend_from_slice(&compact);
+        (address, BASE64_STANDARD.encode(sig))
+    }
+
+    #[test]
+    fn test_verify_transparent_message_valid() {
+        let (address, signature) = sign_test_message("I own this address", Network::TestNetwork);
+        assert!(
+            verify_transparent_message(
+                &address,
+                "I own this address",
+                &signature,
+                Network::TestNetwork
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_transparent_message_tampered() {
+        let (address, signature) = sign_test_message("I own this address", Network::TestNetwork);
+        assert!(
+            !verify_transparent_message(
+                &address,
+                "I own that address",
+                &signature,
+                Network::TestNetwork
+            )
+            .unwrap()
+        );
+
+        // Signed by a different key
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let other = wallet.transparent_address.unwrap();
+        assert!(
+            !verify_transparent_message(
+                &other,
+                "I own this address",
+                &signature,
+                Network::TestNetwork
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_transparent_message_rejects_malformed_input() {
+        let (address, signature) = sign_test_message("hello", Network::TestNetwork);
+
+        assert!(matches!(
+            verify_transparent_message(&address, "hello", "not base64!", Network::TestNetwork),
+            Err(WalletError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            verify_transparent_message(&address, "hello", "AAAA", Network::TestNetwork),
+            Err(WalletError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            verify_transparent_message(&address, "hello", &signature, Network::MainNetwork),
+            Err(WalletError::InvalidAddress(_))
+        ));
+    }
+
+    #[test]
+    fn test_sign_transparent_message_round_trip() {
+        let mnemonic =
+            bip39::Mnemonic::parse_in_normalized(bip39::Language::English, TEST_SEED_PHRASE)
+                .unwrap();
+        let seed = mnemonic.to_seed("");
+        let addresses = crate::wallet::derive_transparent_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            2,
+        )
+        .unwrap();
+
+        let signature =
+            sign_transparent_message(&seed, 0, 1, "proof of ownership", Network::TestNetwork)
+                .unwrap();
+
+        assert!(
+            verify_transparent_message(
+                &addresses[1],
+                "proof of ownership",
+                &signature,
+                Network::TestNetwork
+            )
+            .unwrap()
+        );
+        // Signed with the key of address 1, not address 0
+        assert!(
+            !verify_transparent_message(
+                &addresses[0],
+                "proof of ownership",
+                &signature,
+                Network::TestNetwork
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_dedup_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ua = wallet.unified_address.clone();
+        let taddr = wallet.transparent_address.clone().unwrap();
+
+        let addrs = vec![
+            format!("  {}\n", taddr),
+            ua.clone(),
+            "not an address".to_string(),
+            // Upper-case Bech32, as read from a QR code
+            ua.to_uppercase(),
+            taddr.clone(),
+        ];
+
+        assert_eq!(
+            dedup_addresses(&addrs, Network::TestNetwork),
+            vec![taddr, ua]
+        );
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ua = wallet.unified_address;
+
+        assert_eq!(
+            normalize_address(&ua.to_uppercase(), Network::TestNetwork),
+            Some(ua.clone())
+        );
+        // Wrong network
+        assert_eq!(normalize_address(&ua, Network::MainNetwork), None);
+    }
+
+    #[test]
+    fn test_address_identicon_is_deterministic() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ua = &wallet.unified_address;
+        let taddr = wallet.transparent_address.as_ref().unwrap();
+
+        let identicon = address_identicon(ua);
+        assert_eq!(identicon, address_identicon(ua));
+        // Case and whitespace do not matter for Bech32 addresses
+        assert_eq!(
+            identicon,
+            address_identicon(&format!(" {} ", ua.to_ascii_uppercase()))
+        );
+
+        assert_eq!(identicon.cells.len(), IDENTICON_GRID_SIZE);
+        for row in &identicon.cells {
+            let mirrored: Vec<bool> = row.iter().rev().copied().collect();
+            assert_eq!(row, &mirrored);
+        }
+        assert!(identicon.svg.starts_with("<svg"));
+        assert!(identicon.svg.contains(&identicon.foreground));
+
+        let other = address_identicon(taddr);
+        assert_ne!(identicon.svg, other.svg);
+        assert_ne!(identicon.foreground, other.foreground);
+    }
+}
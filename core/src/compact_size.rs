@@ -0,0 +1,83 @@
+//! Bitcoin-style CompactSize integers.
+//!
+//! Transactions, signed messages and unified encodings all prefix counts and
+//! lengths with this variable-length encoding: values below `0xfd` take one
+//! byte, larger values a marker byte followed by a little-endian `u16`, `u32`
+//! or `u64`.
+
+/// Append a CompactSize integer.
+pub(crate) fn push_compact_size(bytes: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => bytes.push(n as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+/// Read a CompactSize integer from the front of `bytes`, advancing it.
+pub(crate) fn take_compact_size(bytes: &mut &[u8]) -> Option<u64> {
+    let (&first, rest) = bytes.split_first()?;
+    let (n, rest) = match first {
+        0xfd => {
+            let (n, rest) = rest.split_first_chunk::<2>()?;
+            (u64::from(u16::from_le_bytes(*n)), rest)
+        }
+        0xfe => {
+            let (n, rest) = rest.split_first_chunk::<4>()?;
+            (u64::from(u32::from_le_bytes(*n)), rest)
+        }
+        0xff => {
+            let (n, rest) = rest.split_first_chunk::<8>()?;
+            (u64::from_le_bytes(*n), rest)
+        }
+        n => (u64::from(n), rest),
+    };
+    *bytes = rest;
+    Some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_size_encoding() {
+        let cases: [(u64, &[u8]); 6] = [
+            (0, &[0x00]),
+            (0xfc, &[0xfc]),
+            (0xfd, &[0xfd, 0xfd, 0x00]),
+            (0xffff, &[0xfd, 0xff, 0xff]),
+            (0x1_0000, &[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            (
+                0x1_0000_0000,
+                &[0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00],
+            ),
+        ];
+        for (n, expected) in cases {
+            let mut bytes = Vec::new();
+            push_compact_size(&mut bytes, n);
+            assert_eq!(bytes, expected, "{n:#x}");
+
+            let mut rest = &bytes[..];
+            assert_eq!(take_compact_size(&mut rest), Some(n));
+            assert!(rest.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_take_compact_size_truncated() {
+        assert_eq!(take_compact_size(&mut &[][..]), None);
+        assert_eq!(take_compact_size(&mut &[0xfd, 0x01][..]), None);
+        assert_eq!(take_compact_size(&mut &[0xfe, 0x01, 0x02, 0x03][..]), None);
+    }
+}
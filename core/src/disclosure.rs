@@ -0,0 +1,276 @@
+//! Selective disclosure of individual shielded outputs.
+//!
+//! A disclosure reveals one Orchard output of a transaction (its value, memo
+//! and recipient) together with the note's random seed. The seed determines
+//! the note commitment randomness and the ephemeral secret key, so a third
+//! party holding only the transaction can check the disclosure without
+//! learning anything about the rest of the wallet.
+
+use orchard::keys::{PreparedIncomingViewingKey, Scope};
+use orchard::note::{ExtractedNoteCommitment, RandomSeed, Rho};
+use orchard::note_encryption::OrchardDomain;
+use orchard::value::NoteValue;
+use serde::{Deserialize, Serialize};
+use zcash_keys::address::{Address, UnifiedAddress};
+use zcash_note_encryption::{Domain, try_note_decryption, try_output_recovery_with_pkd_esk};
+use zcash_primitives::transaction::Transaction;
+use zcash_protocol::consensus::Network;
+
+use crate::scanner::{extract_orchard_fvk, memo_to_text, recover_orchard_output};
+use crate::types::{MemoTrim, Pool};
+use crate::wallet::WalletError;
+
+/// The revealed contents of a single shielded output.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Disclosure {
+    /// Transaction ID as a hex string.
+    pub txid: String,
+    /// The pool of the output (currently always Orchard).
+    pub pool: Pool,
+    /// Index of the action within the Orchard bundle.
+    pub output_index: usize,
+    /// Value in zatoshis.
+    pub value: u64,
+    /// Memo text, if the memo is valid UTF-8 and not empty.
+    pub memo: Option<String>,
+    /// The recipient, as a unified address with only an Orchard receiver.
+    pub recipient: String,
+    /// The note's random seed (`rseed`) as hex.
+    pub rseed: String,
+}
+
+/// Disclose one Orchard output of a transaction.
+///
+/// The output must be decryptable by the viewing key, either as a note the
+/// wallet received or as an output it sent (recovered with its outgoing
+/// viewing key).
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `ufvk` - The encoded unified full viewing key (with an Orchard component)
+/// * `output_index` - Index of the action within the Orchard bundle
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// The `Disclosure`, or `WalletError::OutputNotFound` if the action does not
+/// exist or cannot be decrypted with the key.
+pub fn disclose_output(
+    tx: &Transaction,
+    ufvk: &str,
+    output_index: usize,
+    network: Network,
+) -> Result<Disclosure, WalletError> {
+    let fvk = extract_orchard_fvk(ufvk.trim(), network)
+        .map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?
+        .ok_or_else(|| {
+            WalletError::InvalidViewingKey("Viewing key has no Orchard component".to_string())
+        })?;
+    let action = tx
+        .orchard_bundle()
+        .and_then(|bundle| bundle.actions().get(output_index))
+        .ok_or_else(|| {
+            WalletError::OutputNotFound(format!("No Orchard action at index {}", output_index))
+        })?;
+
+    let domain = OrchardDomain::for_action(action);
+    let (note, recipient, memo_bytes) = [Scope::External, Scope::Internal]
+        .into_iter()
+        .find_map(|scope| {
+            let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(scope));
+            try_note_decryption(&domain, &ivk, action)
+        })
+        .or_else(|| recover_orchard_output(action, &fvk))
+        .ok_or_else(|| {
+            WalletError::OutputNotFound(format!(
+                "Orchard action {} cannot be decrypted with this key",
+                output_index
+            ))
+        })?;
+
+    let recipient = UnifiedAddress::from_receivers(Some(recipient), None, None)
+        .ok_or_else(|| WalletError::AddressGeneration("Invalid Orchard receiver".to_string()))?
+        .encode(&network);
+
+    Ok(Disclosure {
+        txid: tx.txid().to_string(),
+        pool: Pool::Orchard,
+        output_index,
+        value: note.value().inner(),
+        memo: memo_to_text(&memo_bytes, MemoTrim::default()),
+        recipient,
+        rseed: hex::encode(note.rseed().as_bytes()),
+    })
+}
+
+/// Check a disclosure against the transaction it refers to.
+///
+/// Rebuilds the note from the disclosed recipient, value and seed, checks
+/// that it matches the action's note commitment, and decrypts the action
+/// with the ephemeral secret key derived from the seed to check the memo.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `disclosure` - The disclosure to check
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// Whether the disclosure is correct for `tx`, or
+/// `WalletError::InvalidDisclosure` if it is malformed.
+pub fn verify_disclosure(
+    tx: &Transaction,
+    disclosure: &Disclosure,
+    network: Network,
+) -> Result<bool, WalletError> {
+    if disclosure.pool != Pool::Orchard || disclosure.txid != tx.txid().to_string() {
+        return Ok(false);
+    }
+    let Some(action) = tx
+        .orchard_bundle()
+        .and_then(|bundle| bundle.actions().get(disclosure.output_index))
+    else {
+        return Ok(false);
+    };
+
+    let recipient = match Address::decode(&network, disclosure.recipient.trim()) {
+        Some(Address::Unified(ua)) => ua.orchard().copied(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        WalletError::InvalidDisclosure("Recipient is not an Orchard address".to_string())
+    })?;
+    let rseed_bytes: [u8; 32] = hex::decode(&disclosure.rseed)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            WalletError::InvalidDisclosure("rseed must be 32 bytes of hex".to_string())
+        })?;
+
+    // The note's rho is the nullifier revealed by the same action
+    let Some(rho) = Option::from(Rho::from_bytes(&action.nullifier().to_bytes())) else {
+        return Ok(false);
+    };
+    let Some(rseed) = Option::from(RandomSeed::from_bytes(rseed_bytes, &rho)) else {
+        return Ok(false);
+    };
+    let Some(note) = Option::from(orchard::Note::from_parts(
+        recipient,
+        NoteValue::from_raw(disclosure.value),
+        rho,
+        rseed,
+    )) else {
+        return Ok(false);
+    };
+    if ExtractedNoteCommitment::from(note.commitment()) != *action.cmx() {
+        return Ok(false);
+    }
+
+    let Some(esk) = OrchardDomain::derive_esk(&note) else {
+        return Ok(false);
+    };
+    let pk_d = OrchardDomain::get_pk_d(&note);
+    let domain = OrchardDomain::for_action(action);
+    let Some((_, _, memo_bytes)) = try_output_recovery_with_pkd_esk(&domain, pk_d, esk, action)
+    else {
+        return Ok(false);
+    };
+
+    Ok(memo_to_text(&memo_bytes, MemoTrim::default()) == disclosure.memo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::build_test_orchard_tx;
+    use crate::wallet::restore_wallet;
+
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    /// Build a test transaction paying the test wallet and disclose its
+    /// decryptable output.
+    fn disclosed_test_output() -> (Transaction, Disclosure) {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = wallet.unified_full_viewing_key;
+        let tx = build_test_orchard_tx(&ufvk, 25_000, b"invoice 42");
+
+        // The bundle is padded with a dummy action in random order
+        let disclosure = (0..2)
+            .find_map(|i| disclose_output(&tx, &ufvk, i, Network::TestNetwork).ok())
+            .expect("one action should be decryptable");
+        (tx, disclosure)
+    }
+
+    #[test]
+    fn test_disclosure_verifies_against_transaction() {
+        let (tx, disclosure) = disclosed_test_output();
+
+        assert_eq!(disclosure.value, 25_000);
+        assert_eq!(disclosure.memo.as_deref(), Some("invoice 42"));
+        assert!(disclosure.recipient.starts_with("utest1"));
+        assert!(verify_disclosure(&tx, &disclosure, Network::TestNetwork).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_disclosure_fails_verification() {
+        let (tx, disclosure) = disclosed_test_output();
+
+        let tampered = Disclosure {
+            value: 250_000,
+            ..disclosure.clone()
+        };
+        assert!(!verify_disclosure(&tx, &tampered, Network::TestNetwork).unwrap());
+
+        let tampered = Disclosure {
+            memo: Some("invoice 43".to_string()),
+            ..disclosure.clone()
+        };
+        assert!(!verify_disclosure(&tx, &tampered, Network::TestNetwork).unwrap());
+
+        let tampered = Disclosure {
+            output_index: 1 - disclosure.output_index,
+            ..disclosure.clone()
+        };
+        assert!(!verify_disclosure(&tx, &tampered, Network::TestNetwork).unwrap());
+
+        let malformed = Disclosure {
+            rseed: "00".to_string(),
+            ..disclosure
+        };
+        assert!(matches!(
+            verify_disclosure(&tx, &malformed, Network::TestNetwork),
+            Err(WalletError::InvalidDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn test_disclose_output_not_found() {
+        let (tx, disclosure) = disclosed_test_output();
+        let other = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
+
+        // Another account cannot decrypt the output
+        assert!(matches!(
+            disclose_output(
+                &tx,
+                &other.unified_full_viewing_key,
+                disclosure.output_index,
+                Network::TestNetwork
+            ),
+            Err(WalletError::OutputNotFound(_))
+        ));
+        // Out of range
+        assert!(matches!(
+            disclose_output(
+                &tx,
+                &other.unified_full_viewing_key,
+                2,
+                Network::TestNetwork
+            ),
+            Err(WalletError::OutputNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+//! Shielded note encryption for outgoing outputs.
+//!
+//! This module provides the reverse of the trial decryption performed by the
+//! scanner: given a recipient address, a value and a memo, it produces the
+//! encrypted note ciphertext that would be placed in an Orchard action. It is
+//! the building block for a future shielded transaction builder.
+
+use orchard::Note;
+use orchard::keys::OutgoingViewingKey;
+use orchard::note::{ExtractedNoteCommitment, RandomSeed, Rho};
+use orchard::note_encryption::{OrchardDomain, OrchardNoteEncryption};
+use orchard::value::NoteValue;
+use rand::rngs::OsRng;
+use zcash_keys::address::Address;
+use zcash_note_encryption::Domain;
+use zcash_protocol::consensus::Network;
+
+use crate::wallet::WalletError;
+
+/// Maximum size of a Zcash memo field in bytes.
+pub const MEMO_SIZE: usize = 512;
+
+/// Size of an Orchard note ciphertext (note plaintext + memo + AEAD tag).
+pub const ORCHARD_ENC_CIPHERTEXT_SIZE: usize = 580;
+
+/// Size of the serialized output of [`encrypt_orchard_memo`].
+///
+/// Layout: `rho (32) || cmx (32) || epk (32) || enc_ciphertext (580)`.
+pub const ENCRYPTED_ORCHARD_NOTE_SIZE: usize = 32 + 32 + 32 + ORCHARD_ENC_CIPHERTEXT_SIZE;
+
+/// Encrypt an Orchard note carrying a memo for the given recipient.
+///
+/// The note's `rho` is the nullifier of the note spent by the same action,
+/// so it is taken from the caller; a random seed is sampled for the note.
+///
+/// # Arguments
+///
+/// * `recipient_addr` - A unified address containing an Orchard receiver.
+/// * `value` - The note value in zatoshis.
+/// * `memo` - The memo bytes (at most 512 bytes, zero-padded).
+/// * `rho` - The nullifier of the note spent by the action.
+/// * `network` - The network the address is encoded for.
+///
+/// # Returns
+///
+/// The concatenation `rho || cmx || epk || enc_ciphertext`
+/// ([`ENCRYPTED_ORCHARD_NOTE_SIZE`] bytes), or `WalletError::InvalidNote`
+/// if `rho` is not a valid nullifier.
+pub fn encrypt_orchard_memo(
+    recipient_addr: &str,
+    value: u64,
+    memo: &[u8],
+    rho: [u8; 32],
+    network: Network,
+) -> Result<Vec<u8>, WalletError> {
+    if memo.len() > MEMO_SIZE {
+        return Err(WalletError::InvalidMemo(format!(
+            "Memo is {} bytes, maximum is {}",
+            memo.len(),
+            MEMO_SIZE
+        )));
+    }
+
+    // Decode the recipient and extract its Orchard receiver
+    let recipient = match Address::decode(&network, recipient_addr.trim()) {
+        Some(Address::Unified(ua)) => ua.orchard().cloned().ok_or_else(|| {
+            WalletError::InvalidAddress("Unified address has no Orchard receiver".to_string())
+        })?,
+        Some(_) => {
+            return Err(WalletError::InvalidAddress(
+                "Address is not a unified address".to_string(),
+            ));
+        }
+        None => {
+            return Err(WalletError::InvalidAddress(format!(
+                "Could not decode address for {:?}",
+                network
+            )));
+        }
+    };
+
+    let note_rho = Option::from(Rho::from_bytes(&rho)).ok_or_else(|| {
+        WalletError::InvalidNote("rho is not a canonical Pallas base element".to_string())
+    })?;
+
+    let rseed = RandomSeed::random(&mut OsRng, &note_rho);
+    let note: Note = Option::from(Note::from_parts(
+        recipient,
+        NoteValue::from_raw(value),
+        note_rho,
+        rseed,
+    ))
+    .ok_or_else(|| WalletError::InvalidNote("Failed to construct Orchard note".to_string()))?;
+
+    // Zero-pad the memo to the full memo field size
+    let mut memo_array = [0u8; MEMO_SIZE];
+    memo_array[..memo.len()].copy_from_slice(memo);
+
+    let cmx = ExtractedNoteCommitment::from(note.commitment());
+    let encryptor = OrchardNoteEncryption::new(None::<OutgoingViewingKey>, note, memo_array);
+    let epk = OrchardDomain::epk_bytes(encryptor.epk());
+    let enc_ciphertext = encryptor.encrypt_note_plaintext();
+
+    let mut out = Vec::with_capacity(ENCRYPTED_ORCHARD_NOTE_SIZE);
+    out.extend_from_slice(&rho);
+    out.extend_from_slice(&cmx.to_bytes());
+    out.extend_from_slice(&epk.0);
+    out.extend_from_slice(&enc_ciphertext);
+
+    Ok(out)
+}
+
+/// Get the ephemeral secret key an Orchard note is encrypted with.
+///
+/// Under ZIP 212 the `esk` is derived from the note's random seed, so it can
+/// be reproduced from the note alone and handed to an auditor (see
+/// [`crate::scanner::decrypt_with_esk`]).
+///
+/// # Returns
+///
+/// The 32-byte `esk`, or `None` if the derived scalar is zero.
+pub fn orchard_note_esk(note: &Note) -> Option<[u8; 32]> {
+    let esk = OrchardDomain::derive_esk(note)?;
+    // The outgoing plaintext is pk_d || esk
+    let out_plaintext = OrchardDomain::outgoing_plaintext_bytes(note, &esk);
+    out_plaintext.0[32..].try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orchard::Action;
+    use orchard::keys::{FullViewingKey, PreparedIncomingViewingKey, Scope};
+    use orchard::note::{Nullifier, TransmittedNoteCiphertext};
+    use orchard::primitives::redpallas::{SpendAuth, VerificationKey};
+    use orchard::value::ValueCommitment;
+    use zcash_keys::keys::UnifiedFullViewingKey;
+    use zcash_note_encryption::try_note_decryption;
+
+    use crate::wallet::restore_wallet;
+
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    /// A canonical nullifier to use as the note's rho.
+    const TEST_RHO: [u8; 32] = [0x11; 32];
+
+    /// Rebuild an (unauthorized) Orchard action around an encrypted note so
+    /// it can be fed to the regular trial decryption.
+    fn action_from_encrypted(bytes: &[u8], fvk: &FullViewingKey) -> Action<()> {
+        let rho: [u8; 32] = bytes[0..32].try_into().unwrap();
+        let cmx: [u8; 32] = bytes[32..64].try_into().unwrap();
+        let epk_bytes: [u8; 32] = bytes[64..96].try_into().unwrap();
+        let enc_ciphertext: [u8; ORCHARD_ENC_CIPHERTEXT_SIZE] = bytes[96..].try_into().unwrap();
+
+        let ak: [u8; 32] = fvk.to_bytes()[..32].try_into().unwrap();
+        Action::from_parts(
+            Nullifier::from_bytes(&rho).unwrap(),
+            VerificationKey::<SpendAuth>::try_from(ak).unwrap(),
+            ExtractedNoteCommitment::from_bytes(&cmx).unwrap(),
+            TransmittedNoteCiphertext {
+                epk_bytes,
+                enc_ciphertext,
+                out_ciphertext: [0u8; 80],
+            },
+            ValueCommitment::from_bytes(&[0u8; 32]).unwrap(),
+            (),
+        )
+    }
+
+    #[test]
+    fn test_encrypt_orchard_memo_round_trip() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk =
+            UnifiedFullViewingKey::decode(&Network::TestNetwork, &wallet.unified_full_viewing_key)
+                .expect("UFVK should decode");
+        let fvk = ufvk.orchard().expect("UFVK should have Orchard").clone();
+
+        let memo = b"Thanks for the coffee";
+        let encrypted = encrypt_orchard_memo(
+            &wallet.unified_address,
+            123_456,
+            memo,
+            TEST_RHO,
+            Network::TestNetwork,
+        )
+        .expect("encryption should succeed");
+        assert_eq!(encrypted.len(), ENCRYPTED_ORCHARD_NOTE_SIZE);
+        assert_eq!(encrypted[0..32], TEST_RHO);
+
+        let action = action_from_encrypted(&encrypted, &fvk);
+        let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+        let (note, _, decrypted_memo) =
+            try_note_decryption(&OrchardDomain::for_action(&action), &ivk, &action)
+                .expect("our IVK should decrypt the note");
+
+        assert_eq!(note.value().inner(), 123_456);
+        assert_eq!(&decrypted_memo[..memo.len()], memo);
+        assert!(decrypted_memo[memo.len()..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_encrypt_orchard_memo_rejects_oversized_memo() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let memo = [0x41u8; MEMO_SIZE + 1];
+
+        let result = encrypt_orchard_memo(
+            &wallet.unified_address,
+            1,
+            &memo,
+            TEST_RHO,
+            Network::TestNetwork,
+        );
+        assert!(matches!(result, Err(WalletError::InvalidMemo(_))));
+    }
+
+    #[test]
+    fn test_encrypt_orchard_memo_accepts_full_size_memo() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let memo = [0x41u8; MEMO_SIZE];
+
+        let result = encrypt_orchard_memo(
+            &wallet.unified_address,
+            1,
+            &memo,
+            TEST_RHO,
+            Network::TestNetwork,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_encrypt_orchard_memo_rejects_transparent_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet.transparent_address.expect("should have t-addr");
+
+        let result = encrypt_orchard_memo(&taddr, 1, b"hi", TEST_RHO, Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn test_encrypt_orchard_memo_rejects_invalid_rho() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // Not below the Pallas base field modulus
+        let result = encrypt_orchard_memo(
+            &wallet.unified_address,
+            1,
+            b"hi",
+            [0xff; 32],
+            Network::TestNetwork,
+        );
+        assert!(matches!(result, Err(WalletError::InvalidNote(_))));
+    }
+}
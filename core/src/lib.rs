@@ -1,24 +1,79 @@
+pub mod address;
+pub mod compact;
+mod compact_size;
+pub mod disclosure;
+pub mod encryption;
+pub mod memo;
 pub mod scanner;
+#[cfg(any(test, feature = "test-dependencies"))]
+pub mod testing;
 pub mod transaction;
 pub mod types;
 pub mod wallet;
 
+pub use address::{
+    AddressKind, IDENTICON_GRID_SIZE, Identicon, QR_MODULE_SIZE, address_contains_receiver,
+    address_identicon, address_qr_svg, classify_address, dedup_addresses, detect_address_network,
+    extract_receiver, normalize_address, sign_transparent_message, truncate_address,
+    verify_transparent_message,
+};
+pub use compact::scan_compact_block;
+pub use disclosure::{Disclosure, disclose_output, verify_disclosure};
+pub use encryption::{encrypt_orchard_memo, orchard_note_esk};
+pub use memo::{
+    MAX_MESSAGE_PAYLOAD, MemoError, MemoMatch, MemoStats, Message, MessageType, encode_binary_memo,
+    extract_urls_from_memo, is_ack_memo, memo_fits, memo_stats, message_protocol_version,
+    parse_message_memo, sanitize_memo, search_memos, split_memo_text,
+};
+#[cfg(feature = "parallel")]
+pub use scanner::scan_transactions_parallel;
 pub use scanner::{
-    ScannerError, extract_nullifiers, parse_transaction, parse_viewing_key_capabilities,
-    scan_transaction, scan_transaction_hex,
+    MAX_TX_BYTES, PendingOutput, ScannerError, ZIP317_GRACE_ACTIONS, ZIP317_MARGINAL_FEE,
+    change_output_indices, clean_tx_hex, compute_orchard_nullifier, compute_sapling_nullifier,
+    crosses_turnstile, decrypt_output, decrypt_with_esk, distinct_recipients, encode_orchard_note,
+    extract_nullifiers, has_shielded_components, identify_commitment, is_below_birthday,
+    is_self_transfer, is_zip317_compliant, memo_capable_outputs, net_change, op_return_data,
+    orchard_anchor, orchard_cmx_list, orchard_ephemeral_keys, orchard_note_rho, output_counts,
+    parse_outputs, parse_transaction, parse_transaction_auto, parse_transaction_base64,
+    parse_transaction_with_limit, parse_unified_capabilities, parse_viewing_key_capabilities,
+    privacy_score, sapling_anchor, scan_transaction, scan_transaction_hex, scan_transaction_multi,
+    scan_transaction_with_options, scan_transactions, scan_work_units, total_memo_bytes,
+    transaction_fee, txid_and_auth_digest, txid_from_hex, value_by_address, verifies_payment,
+    zip317_conventional_fee,
 };
 pub use transaction::{
-    Recipient, SignedTransaction, TransactionError, UnsignedTransaction, Utxo,
-    build_transparent_transaction, build_unsigned_transaction, find_address_index,
+    Recipient, SelectError, SignedTransaction, TransactionError, UnsignedTransaction, Utxo,
+    build_transparent_transaction, build_unsigned_transaction, conventional_fee,
+    find_address_index, select_notes,
 };
 pub use types::{
-    DecryptedOrchardAction, DecryptedSaplingOutput, DecryptedTransaction, DecryptionResult,
-    DerivedAddress, LedgerCollection, LedgerEntry, MarkSpentResult, NetworkKind, NoteCollection,
-    Pool, ScanResult, ScanTransactionResult, ScannedNote, ScannedTransparentOutput, SpentNullifier,
-    StorageResult, StoredNote, StoredWallet, TransparentInput, TransparentOutput, TransparentSpend,
-    ViewingKeyInfo, ViewingKeyType, WalletCollection, WalletResult,
+    ActionDebugInfo, COINBASE_MATURITY, DecryptedOrchardAction, DecryptedSaplingOutput,
+    DecryptedTransaction, DecryptionCounts, DecryptionResult, DerivedAddress, LOCKTIME_THRESHOLD,
+    LedgerCollection, LedgerEntry, MarkSpentResult, MemoTrim, NetworkKind, NoteCollection,
+    NoteScope, OutputCounts, Pool, PoolBalanceSnapshot, PoolCompleteness, PrivacyDashboard,
+    PrivacyFactor, PrivacyReport, ReceivedBreakdown, ScanCompleteness, ScanCursor, ScanOptions,
+    ScanResult, ScanTransactionResult, ScanWarning, ScannedNote, ScannedTransparentOutput,
+    SnapshotDiff, SpentNullifier, StorageResult, StoredNote, StoredWallet, TransparentInput,
+    TransparentOutput, TransparentSpend, UnifiedCapabilities, ViewingKeyInfo, ViewingKeyType,
+    WalletCollection, WalletResult, balance_confidence, build_ledger, confirmations,
+    dedup_scan_results, pool_balance_history, privacy_dashboard, scan_completeness,
+    scan_results_to_csv, snapshot_diff,
 };
 pub use wallet::{
-    WalletInfo, derive_transparent_addresses, derive_unified_addresses, derive_wallet,
-    generate_wallet, restore_wallet,
+    AddressSet, DEFAULT_MAX_DIVERSIFIER_SEARCH, DIVERSIFIER_SEARCH_LIMIT, DiversifierSearch,
+    KeyCapabilities, LegacySaplingKey, MAX_ACCOUNT_INDEX, MAX_ADDRESS_BATCH, MAX_DERIVED_ACCOUNTS,
+    MAX_GENERATED_WALLETS, MAX_WORD_SUGGESTIONS, OneTimeAddressPool, SEED_LENGTH, UfvkReport,
+    UnknownWord, VALID_ENTROPY_LENGTHS, WalletInfo, account_label, address_for_contact,
+    change_address, derivation_path, derive_accounts, derive_internal_transparent_addresses,
+    derive_legacy_sapling_key, derive_sapling_diversified_address, derive_sapling_extfvk,
+    derive_storage_key, derive_transparent_addresses, derive_transparent_watch_set,
+    derive_transparent_watch_set_from_ufvk, derive_unified_addresses,
+    derive_unified_addresses_from_ufvk, derive_wallet, export_wallet_json, find_unknown_words,
+    generate_wallet, generate_wallet_rng, generate_wallet_with_entropy, generate_wallets,
+    import_wallet_json, is_expired, is_known_weak_seed, is_own_address, key_capabilities,
+    labeled_address, lightwalletd_import_payload, mnemonic_from_entropy, network_from_ufvk,
+    parse_zec_amount, receiving_addresses_for_note, recover_diversifier_index, restore_wallet,
+    restore_wallet_from_seed_hex, same_account_cross_network, suggest_word, ufvk_components,
+    ufvk_from_bytes, ufvk_matches_seed, ufvk_to_bytes, ufvk_to_uivk, validate_send_amount,
+    wallet_alias, wallet_from_ufvk,
 };
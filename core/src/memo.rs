@@ -0,0 +1,837 @@
+//! Memo field parsing.
+//!
+//! Shielded notes carry a 512-byte memo. Besides plain UTF-8 text, the wallet
+//! uses a small binary messaging format so that conversations can be built
+//! on top of shielded transactions.
+//!
+//! # Message Format
+//!
+//! ```text
+//! offset  size  field
+//! 0       1     version (0x01)
+//! 1       1     message type
+//! 2       4     timestamp (unix seconds, big-endian)
+//! 6       4     nonce (big-endian)
+//! 10      1     fragment index (zero-based)
+//! 11      1     fragment count
+//! 12      2     payload length (big-endian)
+//! 14      n     payload
+//! ```
+//!
+//! The remainder of the memo is zero padding.
+
+use serde::{Deserialize, Serialize};
+
+use crate::encryption::MEMO_SIZE;
+use crate::scanner::memo_len;
+use crate::types::{Pool, ScanResult};
+
+/// Version byte identifying a messaging-protocol memo.
+pub const MESSAGE_VERSION: u8 = 0x01;
+
+/// Size of the message header in bytes.
+pub const MESSAGE_HEADER_SIZE: usize = 14;
+
+/// The kind of a protocol message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageType {
+    /// Acknowledgement of a previously received message (`0x01`).
+    Ack,
+    /// A UTF-8 text message (`0x02`).
+    Text,
+    /// A message type this version does not know about.
+    Unknown(u8),
+}
+
+impl MessageType {
+    /// Parse a message type from its wire byte.
+    pub fn from_byte(byte: u8) -> Self {
+        match byte {
+            0x01 => Self::Ack,
+            0x02 => Self::Text,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// The wire byte of this message type.
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Self::Ack => 0x01,
+            Self::Text => 0x02,
+            Self::Unknown(byte) => byte,
+        }
+    }
+}
+
+/// A messaging-protocol memo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    /// The kind of message.
+    pub message_type: MessageType,
+    /// Sender timestamp in unix seconds.
+    pub timestamp: u32,
+    /// Nonce identifying the message (shared by all its fragments).
+    pub nonce: u32,
+    /// Zero-based index of this fragment.
+    pub fragment_index: u8,
+    /// Total number of fragments of the message.
+    pub fragment_count: u8,
+    /// Payload bytes of this fragment.
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    /// The payload as text, if this is a text message with valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        match self.message_type {
+            MessageType::Text => core::str::from_utf8(&self.payload).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a memo as a messaging-protocol message.
+///
+/// # Arguments
+///
+/// * `memo_bytes` - The memo bytes (with or without trailing zero padding)
+///
+/// # Returns
+///
+/// The parsed `Message`, or `None` if the memo is not a well-formed protocol
+/// memo (wrong version byte, truncated header, inconsistent fragment fields
+/// or a payload length exceeding the memo).
+pub fn parse_message_memo(memo_bytes: &[u8]) -> Option<Message> {
+    if memo_bytes.len() < MESSAGE_HEADER_SIZE || memo_bytes[0] != MESSAGE_VERSION {
+        return None;
+    }
+
+    let message_type = MessageType::from_byte(memo_bytes[1]);
+    let timestamp = u32::from_be_bytes(memo_bytes[2..6].try_into().ok()?);
+    let nonce = u32::from_be_bytes(memo_bytes[6..10].try_into().ok()?);
+    let fragment_index = memo_bytes[10];
+    let fragment_count = memo_bytes[11];
+    let payload_len = usize::from(u16::from_be_bytes([memo_bytes[12], memo_bytes[13]]));
+
+    if fragment_count == 0 || fragment_index >= fragment_count {
+        return None;
+    }
+
+    let payload = memo_bytes.get(MESSAGE_HEADER_SIZE..MESSAGE_HEADER_SIZE + payload_len)?;
+
+    Some(Message {
+        message_type,
+        timestamp,
+        nonce,
+        fragment_index,
+        fragment_count,
+        payload: payload.to_vec(),
+    })
+}
+
+/// Highest version byte treated as a messaging-protocol memo.
+///
+/// Versions are limited to the control characters below tab, which have no
+/// use in text. Text memos may still start with one, so a version byte is a
+/// strong hint rather than proof of a protocol memo.
+const MAX_MESSAGE_VERSION: u8 = 0x08;
+
+/// Get the messaging-protocol version of a memo.
+///
+/// Unlike [`parse_message_memo`], this also recognizes versions newer than
+/// [`MESSAGE_VERSION`], so a chat UI can tell an unsupported message apart
+/// from a plain memo. A memo of the current version must have a well-formed
+/// header; for later versions, which may change the rest of the header,
+/// only the version byte and the memo length are checked.
+///
+/// # Arguments
+///
+/// * `memo_bytes` - The memo bytes (with or without trailing zero padding)
+///
+/// # Returns
+///
+/// The version byte, or `None` if the memo is not a protocol memo.
+pub fn message_protocol_version(memo_bytes: &[u8]) -> Option<u8> {
+    let version = *memo_bytes.first()?;
+    if version == MESSAGE_VERSION {
+        return parse_message_memo(memo_bytes).map(|_| version);
+    }
+    ((MESSAGE_VERSION..=MAX_MESSAGE_VERSION).contains(&version)
+        && memo_bytes.len() >= MESSAGE_HEADER_SIZE)
+        .then_some(version)
+}
+
+/// Size statistics of a memo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoStats {
+    /// Meaningful bytes, without trailing zero padding (0 for "no memo").
+    pub trimmed_len: usize,
+    /// Size of the memo field, always [`MEMO_SIZE`].
+    pub raw_len: usize,
+    /// Whether the memo is non-empty UTF-8 text, as defined by ZIP 302.
+    pub is_text: bool,
+}
+
+/// Compute the displayed and raw sizes of a memo.
+///
+/// # Arguments
+///
+/// * `memo_bytes` - The memo bytes (with or without trailing zero padding)
+pub fn memo_stats(memo_bytes: &[u8]) -> MemoStats {
+    let trimmed_len = memo_len(memo_bytes);
+    let trimmed = &memo_bytes[..trimmed_len];
+    // ZIP 302 reserves first bytes above 0xF4 for non-text memos
+    let is_text =
+        trimmed.first().is_some_and(|&b| b <= 0xf4) && core::str::from_utf8(trimmed).is_ok();
+    MemoStats {
+        trimmed_len,
+        raw_len: MEMO_SIZE,
+        is_text,
+    }
+}
+
+/// Maximum payload bytes that fit in a single message memo.
+pub const MAX_MESSAGE_PAYLOAD: usize = MEMO_SIZE - MESSAGE_HEADER_SIZE;
+
+/// Errors that can occur when encoding a memo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoError {
+    /// The payload does not fit in a single message memo.
+    PayloadTooLarge { size: usize, max: usize },
+}
+
+impl core::fmt::Display for MemoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::PayloadTooLarge { size, max } => {
+                write!(f, "Payload is {} bytes, maximum is {}", size, max)
+            }
+        }
+    }
+}
+
+impl core::error::Error for MemoError {}
+
+/// Encode a binary payload as a single-fragment message memo.
+///
+/// The payload is copied as-is after the protocol header, so any bytes can
+/// be sent, not only UTF-8 text.
+///
+/// # Arguments
+///
+/// * `payload` - The raw payload bytes
+/// * `msg_type` - The message type byte (see [`MessageType::to_byte`])
+/// * `timestamp` - Sender timestamp in unix seconds
+/// * `nonce` - Nonce identifying the message
+///
+/// # Returns
+///
+/// The zero-padded 512-byte memo, or `MemoError::PayloadTooLarge` if the
+/// payload exceeds [`MAX_MESSAGE_PAYLOAD`].
+pub fn encode_binary_memo(
+    payload: &[u8],
+    msg_type: u8,
+    timestamp: u32,
+    nonce: u32,
+) -> Result<Vec<u8>, MemoError> {
+    if payload.len() > MAX_MESSAGE_PAYLOAD {
+        return Err(MemoError::PayloadTooLarge {
+            size: payload.len(),
+            max: MAX_MESSAGE_PAYLOAD,
+        });
+    }
+
+    let mut memo = vec![0u8; MEMO_SIZE];
+    memo[0] = MESSAGE_VERSION;
+    memo[1] = msg_type;
+    memo[2..6].copy_from_slice(&timestamp.to_be_bytes());
+    memo[6..10].copy_from_slice(&nonce.to_be_bytes());
+    memo[10] = 0; // fragment index
+    memo[11] = 1; // fragment count
+    memo[12..14].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+    memo[MESSAGE_HEADER_SIZE..MESSAGE_HEADER_SIZE + payload.len()].copy_from_slice(payload);
+    Ok(memo)
+}
+
+/// Check whether a text message fits in a single memo.
+///
+/// # Arguments
+///
+/// * `text` - The message text
+///
+/// # Returns
+///
+/// True if the UTF-8 encoding of `text` is at most [`MAX_MESSAGE_PAYLOAD`]
+/// bytes.
+pub fn memo_fits(text: &str) -> bool {
+    text.len() <= MAX_MESSAGE_PAYLOAD
+}
+
+/// Split a text message into memo-sized payload fragments.
+///
+/// Fragments are cut on character boundaries, so a multi-byte character is
+/// never split across two memos. Each fragment is at most
+/// [`MAX_MESSAGE_PAYLOAD`] bytes.
+///
+/// # Arguments
+///
+/// * `text` - The message text
+///
+/// # Returns
+///
+/// The fragments in order; empty if `text` is empty.
+pub fn split_memo_text(text: &str) -> Vec<String> {
+    let mut fragments = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if current.len() + c.len_utf8() > MAX_MESSAGE_PAYLOAD {
+            fragments.push(core::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        fragments.push(current);
+    }
+    fragments
+}
+
+/// Make decrypted memo text safe to display.
+///
+/// Control characters other than newline and tab (NUL, escape sequences,
+/// carriage returns, ...) are replaced with U+FFFD so a memo cannot break
+/// terminal or HTML rendering.
+///
+/// # Arguments
+///
+/// * `memo` - The decoded memo text
+///
+/// # Returns
+///
+/// The memo with unsafe control characters replaced.
+pub fn sanitize_memo(memo: &str) -> String {
+    memo.chars()
+        .map(|c| {
+            if c.is_control() && c != '\n' && c != '\t' {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Check whether a memo acknowledges a previously received message.
+///
+/// An acknowledgement is a message of type `0x01` carrying the nonce of the
+/// message it acknowledges in its header, so a chat UI can mark that
+/// message as delivered.
+///
+/// # Arguments
+///
+/// * `memo_bytes` - The memo bytes (with or without trailing zero padding)
+///
+/// # Returns
+///
+/// The nonce of the acknowledged message, or `None` if the memo is not an
+/// acknowledgement.
+pub fn is_ack_memo(memo_bytes: &[u8]) -> Option<u32> {
+    parse_message_memo(memo_bytes)
+        .filter(|message| message.message_type == MessageType::Ack)
+        .map(|message| message.nonce)
+}
+
+/// A note whose memo matched a search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoMatch {
+    /// Transaction ID of the note.
+    pub txid: String,
+    /// The pool of the note.
+    pub pool: Pool,
+    /// Index of the note's output within the transaction.
+    pub output_index: usize,
+    /// The matched text with some surrounding context.
+    pub snippet: String,
+}
+
+/// Characters of context kept on each side of a match in a snippet.
+const SNIPPET_CONTEXT_CHARS: usize = 20;
+
+/// Search the decrypted memos of scanned notes.
+///
+/// Matching is case-insensitive. Only memos that decoded as UTF-8 text are
+/// searched.
+///
+/// # Arguments
+///
+/// * `results` - Scan results to search
+/// * `query` - The text to look for
+///
+/// # Returns
+///
+/// One match per note whose memo contains `query`, in scan order. Empty if
+/// `query` is empty.
+pub fn search_memos(results: &[ScanResult], query: &str) -> Vec<MemoMatch> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    results
+        .iter()
+        .flat_map(|result| result.notes.iter().map(move |note| (result, note)))
+        .filter_map(|(result, note)| {
+            let memo = note.memo.as_deref()?;
+            let (start, end) = find_case_insensitive(memo, &query)?;
+            Some(MemoMatch {
+                txid: result.txid.clone(),
+                pool: note.pool,
+                output_index: note.output_index,
+                snippet: memo_snippet(memo, start, end),
+            })
+        })
+        .collect()
+}
+
+/// Find the byte range of the first case-insensitive occurrence of
+/// `needle`, which must already be lowercase.
+fn find_case_insensitive(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut lowered = String::new();
+        for (offset, c) in haystack[start..].char_indices() {
+            lowered.extend(c.to_lowercase());
+            if !needle.starts_with(&lowered) {
+                return None;
+            }
+            if lowered.len() == needle.len() {
+                return Some((start, start + offset + c.len_utf8()));
+            }
+        }
+        None
+    })
+}
+
+/// Cut a snippet around `memo[start..end]`, marking elided text with `…`.
+fn memo_snippet(memo: &str, start: usize, end: usize) -> String {
+    let before: Vec<char> = memo[..start].chars().collect();
+    let after: Vec<char> = memo[end..].chars().collect();
+
+    let mut snippet = String::new();
+    if before.len() > SNIPPET_CONTEXT_CHARS {
+        snippet.push('…');
+    }
+    snippet.extend(&before[before.len().saturating_sub(SNIPPET_CONTEXT_CHARS)..]);
+    snippet.push_str(&memo[start..end]);
+    snippet.extend(after.iter().take(SNIPPET_CONTEXT_CHARS));
+    if after.len() > SNIPPET_CONTEXT_CHARS {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// URL schemes recognized by [`extract_urls_from_memo`].
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Find the http and https URLs in a memo.
+///
+/// URLs end at whitespace, quotes or angle brackets. Trailing punctuation
+/// (as in "see https://z.cash.") and a closing bracket without a matching
+/// opening one are not part of the URL. Other schemes (`javascript:`,
+/// `data:`, ...) are ignored, so the results are safe to render as links.
+///
+/// # Arguments
+///
+/// * `memo` - The decoded memo text
+///
+/// # Returns
+///
+/// The URLs in the order they appear.
+pub fn extract_urls_from_memo(memo: &str) -> Vec<String> {
+    memo.split(|c: char| c.is_whitespace() || c.is_control() || "<>\"'`".contains(c))
+        .filter_map(|token| {
+            let lowered = token.to_ascii_lowercase();
+            let (start, scheme) = URL_SCHEMES
+                .iter()
+                .filter_map(|scheme| lowered.find(scheme).map(|start| (start, scheme)))
+                .min()?;
+            let mut url = &token[start..];
+            loop {
+                let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+                let trimmed = match trimmed.chars().last() {
+                    Some(close @ (')' | ']' | '}')) => {
+                        let open = match close {
+                            ')' => '(',
+                            ']' => '[',
+                            _ => '{',
+                        };
+                        if trimmed.matches(close).count() > trimmed.matches(open).count() {
+                            &trimmed[..trimmed.len() - 1]
+                        } else {
+                            trimmed
+                        }
+                    }
+                    _ => trimmed,
+                };
+                if trimmed == url {
+                    break;
+                }
+                url = trimmed;
+            }
+            // A URL needs a host after the scheme
+            url[scheme.len()..]
+                .starts_with(|c: char| c.is_alphanumeric())
+                .then(|| url.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ScannedNote;
+
+    fn build_memo(message_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut memo = vec![0u8; 512];
+        memo[0] = MESSAGE_VERSION;
+        memo[1] = message_type;
+        memo[2..6].copy_from_slice(&1_700_000_000u32.to_be_bytes());
+        memo[6..10].copy_from_slice(&0x0102_0304u32.to_be_bytes());
+        memo[10] = 0;
+        memo[11] = 1;
+        memo[12..14].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+        memo[14..14 + payload.len()].copy_from_slice(payload);
+        memo
+    }
+
+    #[test]
+    fn test_parse_text_message_memo() {
+        let memo = build_memo(0x02, b"hello from zcash");
+        let message = parse_message_memo(&memo).expect("should parse");
+
+        assert_eq!(message.message_type, MessageType::Text);
+        assert_eq!(message.timestamp, 1_700_000_000);
+        assert_eq!(message.nonce, 0x0102_0304);
+        assert_eq!(message.fragment_index, 0);
+        assert_eq!(message.fragment_count, 1);
+        assert_eq!(message.text(), Some("hello from zcash"));
+    }
+
+    #[test]
+    fn test_parse_plain_text_memo_is_not_message() {
+        let mut memo = vec![0u8; 512];
+        memo[..11].copy_from_slice(b"Thanks! :-)");
+        assert!(parse_message_memo(&memo).is_none());
+    }
+
+    #[test]
+    fn test_parse_message_memo_rejects_malformed() {
+        // Truncated header
+        assert!(parse_message_memo(&[MESSAGE_VERSION, 0x01, 0x00]).is_none());
+
+        // Header one byte short
+        assert!(parse_message_memo(&build_memo(0x01, b"")[..MESSAGE_HEADER_SIZE - 1]).is_none());
+
+        // Fragment index out of range
+        let mut memo = build_memo(0x01, b"hi");
+        memo[10] = 1;
+        assert!(parse_message_memo(&memo).is_none());
+
+        // Payload length exceeds the memo
+        let mut memo = build_memo(0x01, b"hi");
+        memo[12..14].copy_from_slice(&600u16.to_be_bytes());
+        assert!(parse_message_memo(&memo).is_none());
+    }
+
+    #[test]
+    fn test_message_type_round_trip() {
+        for byte in [0x01, 0x02, 0x7f] {
+            assert_eq!(MessageType::from_byte(byte).to_byte(), byte);
+        }
+        assert_eq!(MessageType::from_byte(0x01), MessageType::Ack);
+        assert_eq!(MessageType::from_byte(0x02), MessageType::Text);
+    }
+
+    #[test]
+    fn test_split_memo_text_ascii() {
+        assert!(memo_fits("hello"));
+        assert_eq!(split_memo_text("hello"), vec!["hello".to_string()]);
+        assert!(split_memo_text("").is_empty());
+
+        let long = "a".repeat(MAX_MESSAGE_PAYLOAD * 2 + 10);
+        assert!(!memo_fits(&long));
+        let fragments = split_memo_text(&long);
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[0].len(), MAX_MESSAGE_PAYLOAD);
+        assert_eq!(fragments[2].len(), 10);
+        assert_eq!(fragments.concat(), long);
+    }
+
+    #[test]
+    fn test_split_memo_text_exact_payload_size() {
+        // The 14-byte header leaves 498 bytes of a 512-byte memo for payload
+        assert_eq!(MAX_MESSAGE_PAYLOAD, 498);
+
+        let exact = "x".repeat(498);
+        assert!(memo_fits(&exact));
+        assert_eq!(split_memo_text(&exact), vec![exact.clone()]);
+
+        let over = "x".repeat(499);
+        assert!(!memo_fits(&over));
+        let sizes: Vec<usize> = split_memo_text(&over).iter().map(String::len).collect();
+        assert_eq!(sizes, vec![498, 1]);
+    }
+
+    #[test]
+    fn test_split_memo_text_never_splits_characters() {
+        // 10-byte groups of a 1-, 4-, 2- and 3-byte character, so fragments
+        // end short of the payload size where a character would not fit
+        let text = "a\u{1F600}é€".repeat(200);
+        assert!(!memo_fits(&text));
+
+        let fragments = split_memo_text(&text);
+        let sizes: Vec<usize> = fragments.iter().map(String::len).collect();
+        assert_eq!(sizes, vec![497, 498, 496, 496, 13]);
+        // The first fragment holds 49 groups and "a\u{1F600}é"; the 3-byte
+        // character after it would overflow
+        assert!(fragments[1].starts_with('€'));
+        assert_eq!(fragments.concat(), text);
+
+        // A single emoji after one ASCII byte leaves 497 bytes
+        let sizes: Vec<usize> = split_memo_text(&format!("x{}", "\u{1F600}".repeat(200)))
+            .iter()
+            .map(String::len)
+            .collect();
+        assert_eq!(sizes, vec![497, 304]);
+    }
+
+    #[test]
+    fn test_sanitize_memo_replaces_nul() {
+        assert_eq!(sanitize_memo("pay\0ment"), "pay\u{FFFD}ment");
+    }
+
+    #[test]
+    fn test_sanitize_memo_replaces_escape_sequence() {
+        assert_eq!(
+            sanitize_memo("\x1b[31mred\x1b[0m"),
+            "\u{FFFD}[31mred\u{FFFD}[0m"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_memo_keeps_multiline_text() {
+        let memo = "Thanks for dinner!\n\tSplit: 0.5 ZEC\nSee you ☕";
+        assert_eq!(sanitize_memo(memo), memo);
+    }
+
+    #[test]
+    fn test_is_ack_memo() {
+        // Acknowledgements are type 0x01
+        let memo = build_memo(0x01, b"");
+        assert_eq!(is_ack_memo(&memo), Some(0x0102_0304));
+    }
+
+    #[test]
+    fn test_text_memo_is_not_ack() {
+        let memo = build_memo(0x02, b"hello");
+        assert_eq!(is_ack_memo(&memo), None);
+
+        let mut plain = vec![0u8; 512];
+        plain[..5].copy_from_slice(b"hello");
+        assert_eq!(is_ack_memo(&plain), None);
+    }
+
+    fn memo_note(output_index: usize, memo: Option<&str>) -> ScannedNote {
+        ScannedNote {
+            output_index,
+            pool: Pool::Orchard,
+            value: 1_000,
+            commitment: String::new(),
+            nullifier: None,
+            memo: memo.map(str::to_string),
+            address: None,
+            debug: None,
+            scope: None,
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
+        }
+    }
+
+    fn memo_scan_result(txid: &str, notes: Vec<ScannedNote>) -> ScanResult {
+        ScanResult {
+            txid: txid.to_string(),
+            notes,
+            spent_nullifiers: vec![],
+            transparent_spends: vec![],
+            transparent_received: 0,
+            transparent_outputs: vec![],
+            lock_time: 0,
+            value_balance_consistent: true,
+            scanned_pools: vec![],
+            sapling_decryption: Default::default(),
+            orchard_decryption: Default::default(),
+            consensus_branch_id: String::new(),
+            warnings: Vec::new(),
+            is_coinbase: false,
+        }
+    }
+
+    #[test]
+    fn test_search_memos_matches_substring() {
+        let results = vec![
+            memo_scan_result(
+                "tx1",
+                vec![memo_note(0, Some("Rent for March")), memo_note(1, None)],
+            ),
+            memo_scan_result(
+                "tx2",
+                vec![
+                    memo_note(0, Some("Coffee")),
+                    memo_note(
+                        1,
+                        Some("Thanks for the PIZZA last night, see you next week at the office"),
+                    ),
+                ],
+            ),
+        ];
+
+        let matches = search_memos(&results, "pizza");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].txid, "tx2");
+        assert_eq!(matches[0].output_index, 1);
+        assert_eq!(
+            matches[0].snippet,
+            "Thanks for the PIZZA last night, see you…"
+        );
+
+        assert_eq!(search_memos(&results, "MARCH")[0].txid, "tx1");
+        assert!(search_memos(&results, "salary").is_empty());
+        assert!(search_memos(&results, "").is_empty());
+    }
+
+    #[test]
+    fn test_extract_urls_from_memo_single() {
+        assert_eq!(
+            extract_urls_from_memo("Invoice at https://example.com/pay?id=42."),
+            vec!["https://example.com/pay?id=42"]
+        );
+    }
+
+    #[test]
+    fn test_extract_urls_from_memo_multiple() {
+        let memo =
+            "Docs (https://zcash.readthedocs.io/en/latest/) and\nHTTP://Example.org/a_(b), thanks!";
+        assert_eq!(
+            extract_urls_from_memo(memo),
+            vec![
+                "https://zcash.readthedocs.io/en/latest/",
+                "HTTP://Example.org/a_(b)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_urls_from_memo_none() {
+        assert!(extract_urls_from_memo("thanks for lunch").is_empty());
+        assert!(extract_urls_from_memo("javascript:alert(1) ftp://host https://").is_empty());
+        assert!(extract_urls_from_memo("").is_empty());
+    }
+
+    #[test]
+    fn test_encode_binary_memo_round_trip() {
+        let payload = [0x00, 0xff, 0x10, 0x00, 0x80];
+        let memo = encode_binary_memo(&payload, 0x03, 1_700_000_000, 0xdead_beef).unwrap();
+        assert_eq!(memo.len(), MEMO_SIZE);
+        // Header fields are big-endian, the payload follows the 14-byte header
+        assert_eq!(
+            &memo[2..10],
+            &[0x65, 0x53, 0xf1, 0x00, 0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            &memo[MESSAGE_HEADER_SIZE..MESSAGE_HEADER_SIZE + 5],
+            &payload
+        );
+
+        let message = parse_message_memo(&memo).expect("should parse");
+        assert_eq!(message.message_type, MessageType::Unknown(0x03));
+        assert_eq!(message.timestamp, 1_700_000_000);
+        assert_eq!(message.nonce, 0xdead_beef);
+        assert_eq!(message.fragment_count, 1);
+        assert_eq!(message.payload, payload);
+        assert_eq!(message.text(), None);
+    }
+
+    #[test]
+    fn test_encode_binary_memo_rejects_oversized_payload() {
+        let payload = vec![0u8; MAX_MESSAGE_PAYLOAD];
+        assert!(encode_binary_memo(&payload, 0x01, 0, 0).is_ok());
+
+        let payload = vec![0u8; MAX_MESSAGE_PAYLOAD + 1];
+        assert_eq!(
+            encode_binary_memo(&payload, 0x01, 0, 0),
+            Err(MemoError::PayloadTooLarge {
+                size: MAX_MESSAGE_PAYLOAD + 1,
+                max: MAX_MESSAGE_PAYLOAD
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_protocol_version() {
+        let memo = build_memo(MessageType::Text.to_byte(), b"hello");
+        assert_eq!(message_protocol_version(&memo), Some(MESSAGE_VERSION));
+
+        // A newer version is recognized even though it cannot be parsed
+        let mut future = memo.clone();
+        future[0] = 0x02;
+        assert_eq!(message_protocol_version(&future), Some(0x02));
+        assert!(parse_message_memo(&future).is_none());
+
+        let mut plain = vec![0u8; 512];
+        plain[..5].copy_from_slice(b"hello");
+        assert_eq!(message_protocol_version(&plain), None);
+        assert_eq!(message_protocol_version(&[0xf6; 512]), None);
+        assert_eq!(message_protocol_version(&[0u8; 512]), None);
+        assert_eq!(message_protocol_version(&[MESSAGE_VERSION]), None);
+        assert_eq!(message_protocol_version(&[]), None);
+
+        // Text starting with a tab or line break is not a protocol memo
+        let mut indented = vec![0u8; 512];
+        indented[..6].copy_from_slice(b"\thello");
+        assert_eq!(message_protocol_version(&indented), None);
+
+        // A current-version memo with an invalid header is not recognized
+        let mut malformed = memo.clone();
+        malformed[11] = 0; // fragment count
+        assert_eq!(message_protocol_version(&malformed), None);
+    }
+
+    #[test]
+    fn test_memo_stats() {
+        let mut memo = vec![0u8; 512];
+        memo[..5].copy_from_slice(b"hello");
+        assert_eq!(
+            memo_stats(&memo),
+            MemoStats {
+                trimmed_len: 5,
+                raw_len: 512,
+                is_text: true
+            }
+        );
+
+        let mut empty = vec![0u8; 512];
+        empty[0] = 0xf6;
+        let stats = memo_stats(&empty);
+        assert_eq!(stats.trimmed_len, 0);
+        assert!(!stats.is_text);
+
+        // Arbitrary data (ZIP 302 0xFF prefix) is not text
+        let mut binary = vec![0u8; 512];
+        binary[..3].copy_from_slice(&[0xff, 0x01, 0x02]);
+        let stats = memo_stats(&binary);
+        assert_eq!(stats.trimmed_len, 3);
+        assert!(!stats.is_text);
+    }
+}
@@ -4,68 +4,313 @@
 //! It performs trial decryption using viewing keys to find notes belonging
 //! to the wallet and extracts nullifiers to track spent notes.
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
 use orchard::keys::{FullViewingKey as OrchardFvk, PreparedIncomingViewingKey, Scope};
+use orchard::note::{ExtractedNoteCommitment, RandomSeed, Rho};
 use orchard::note_encryption::OrchardDomain;
+use sapling_crypto::note_encryption::{Zip212Enforcement, try_sapling_output_recovery};
+use sapling_crypto::zip32::DiversifiableFullViewingKey as SaplingDfvk;
 use zcash_address::unified::{self, Container, Encoding};
-use zcash_keys::encoding::AddressCodec;
-use zcash_note_encryption::try_note_decryption;
+use zcash_keys::address::{Address, UnifiedAddress};
+use zcash_keys::encoding::{AddressCodec, decode_extended_full_viewing_key};
+use zcash_note_encryption::{
+    Domain, EphemeralKeyBytes, OutPlaintextBytes, try_note_decryption,
+    try_output_recovery_with_ovk, try_output_recovery_with_pkd_esk,
+};
 use zcash_primitives::transaction::Transaction;
-use zcash_protocol::consensus::{BranchId, Network};
+use zcash_protocol::consensus::{
+    BlockHeight, BranchId, Network, NetworkConstants, NetworkType, Parameters,
+};
+use zcash_transparent::address::TransparentAddress;
 
 use crate::types::{
-    Pool, ScanResult, ScannedNote, ScannedTransparentOutput, SpentNullifier, TransparentSpend,
+    ActionDebugInfo, DecryptionCounts, MemoTrim, NoteCollection, NoteScope, OutputCounts, Pool,
+    PrivacyFactor, PrivacyReport, ScanCursor, ScanOptions, ScanResult, ScanWarning, ScannedNote,
+    ScannedTransparentOutput, SpentNullifier, TransparentSpend, UnifiedCapabilities,
+};
+use crate::wallet::{
+    DIVERSIFIER_SEARCH_LIMIT, derive_transparent_watch_set_from_ufvk, is_internal_address,
+    is_own_address,
 };
 
+/// Emit a debug-level tracing event. Expands to nothing unless the `tracing`
+/// feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {};
+}
+
 /// Errors that can occur during scanning operations.
 #[derive(Debug)]
 pub enum ScannerError {
     InvalidTransactionHex(String),
+    InvalidTransactionBase64(String),
     TransactionParseFailed(String),
     UnrecognizedViewingKey,
+    InvalidNote(String),
+    NoteNotOwned,
+    InvalidCompactBlock(String),
+    NetworkMismatch(String),
+    UnsupportedVersion(u32),
+    InvalidAddress(String),
+    TransactionTooLarge { size: usize, max: usize },
 }
 
 impl core::fmt::Display for ScannerError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::InvalidTransactionHex(msg) => write!(f, "Invalid transaction hex: {}", msg),
+            Self::InvalidTransactionBase64(msg) => {
+                write!(f, "Invalid transaction base64: {}", msg)
+            }
             Self::TransactionParseFailed(msg) => write!(f, "Failed to parse transaction: {}", msg),
             Self::UnrecognizedViewingKey => write!(f, "Unrecognized viewing key format"),
+            Self::InvalidNote(msg) => write!(f, "Invalid note: {}", msg),
+            Self::NoteNotOwned => write!(f, "Note does not belong to the viewing key"),
+            Self::InvalidCompactBlock(msg) => write!(f, "Invalid compact block: {}", msg),
+            Self::NetworkMismatch(msg) => write!(f, "Network mismatch: {}", msg),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported transaction version: {}", version)
+            }
+            Self::InvalidAddress(msg) => write!(f, "Invalid address: {}", msg),
+            Self::TransactionTooLarge { size, max } => write!(
+                f,
+                "Transaction is too large: {} bytes (maximum {})",
+                size, max
+            ),
         }
     }
 }
 
 impl core::error::Error for ScannerError {}
 
+/// Normalize user-supplied transaction hex.
+///
+/// Strips all whitespace (including embedded newlines) and an optional `0x`
+/// prefix, then checks that the remainder is non-empty, even-length hex.
+///
+/// # Arguments
+///
+/// * `input` - The transaction hex as pasted by the user
+///
+/// # Returns
+///
+/// The cleaned, lowercase hex string.
+pub fn clean_tx_hex(input: &str) -> Result<String, ScannerError> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let hex = compact
+        .strip_prefix("0x")
+        .or_else(|| compact.strip_prefix("0X"))
+        .unwrap_or(&compact);
+
+    if hex.is_empty() {
+        return Err(ScannerError::InvalidTransactionHex(
+            "Transaction hex is empty".to_string(),
+        ));
+    }
+    if let Some((pos, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ScannerError::InvalidTransactionHex(format!(
+            "Invalid character {:?} at position {}",
+            c, pos
+        )));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(ScannerError::InvalidTransactionHex(format!(
+            "Odd number of hex digits ({})",
+            hex.len()
+        )));
+    }
+
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// Default size limit for transactions given to the parser, in bytes.
+///
+/// Consensus caps transactions at the 2 MB block size, so anything larger
+/// is corrupt and is rejected before being decoded.
+pub const MAX_TX_BYTES: usize = 2 * 1024 * 1024;
+
+/// Characters of encoded input allowed per transaction byte.
+///
+/// Hex takes two characters per byte and base64 fewer; the rest leaves room
+/// for whitespace and line wrapping.
+const MAX_INPUT_CHARS_PER_BYTE: usize = 4;
+
+/// Reject encoded transaction input too long to decode to at most
+/// `max_bytes`.
+///
+/// Checked on the input as given, before it is cleaned or decoded, so an
+/// oversized paste is never copied.
+fn check_input_len(input: &str, max_bytes: usize) -> Result<(), ScannerError> {
+    if input.len() > max_bytes.saturating_mul(MAX_INPUT_CHARS_PER_BYTE) {
+        return Err(ScannerError::TransactionTooLarge {
+            size: input.len(),
+            max: max_bytes,
+        });
+    }
+    Ok(())
+}
+
 /// Parse a transaction from hex bytes.
 ///
-/// Attempts parsing with multiple branch IDs (Nu6, Nu5, Canopy, Heartwood)
-/// to support transactions from different network upgrades.
+/// Attempts parsing with multiple branch IDs, newest first (Nu6.1, Nu6, Nu5,
+/// Canopy, Heartwood, Blossom, Sapling), to support v4 and v5 transactions
+/// from different network upgrades. Transactions larger than
+/// [`MAX_TX_BYTES`] are rejected.
 ///
 /// # Arguments
 ///
 /// * `tx_hex` - The raw transaction as a hexadecimal string
-/// * `_network` - The network (currently unused but included for future use)
+/// * `network` - The network (not needed to parse; kept for API stability)
 ///
 /// # Returns
 ///
 /// The parsed `Transaction` or an error if parsing fails.
-pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction, ScannerError> {
-    let tx_bytes = hex::decode(tx_hex.trim())
-        .map_err(|e| ScannerError::InvalidTransactionHex(e.to_string()))?;
+pub fn parse_transaction(tx_hex: &str, network: Network) -> Result<Transaction, ScannerError> {
+    parse_transaction_with_limit(tx_hex, network, MAX_TX_BYTES)
+}
+
+/// Parse a transaction from hex bytes, with a size limit.
+///
+/// Same as [`parse_transaction`], but rejects transactions larger than
+/// `max_bytes` instead of [`MAX_TX_BYTES`]. The input length is checked
+/// before the hex is cleaned or decoded.
+///
+/// # Arguments
+///
+/// * `tx_hex` - The raw transaction as a hexadecimal string
+/// * `_network` - The network (currently unused but included for future use)
+/// * `max_bytes` - The largest accepted transaction size in bytes
+///
+/// # Returns
+///
+/// The parsed `Transaction`, or `ScannerError::TransactionTooLarge` if the
+/// transaction exceeds `max_bytes`.
+pub fn parse_transaction_with_limit(
+    tx_hex: &str,
+    _network: Network,
+    max_bytes: usize,
+) -> Result<Transaction, ScannerError> {
+    check_input_len(tx_hex, max_bytes)?;
+    let tx_hex = clean_tx_hex(tx_hex)?;
+    let tx_bytes =
+        hex::decode(&tx_hex).map_err(|e| ScannerError::InvalidTransactionHex(e.to_string()))?;
+    read_transaction(&tx_bytes, max_bytes)
+}
+
+/// Parse a transaction from base64.
+///
+/// Whitespace (e.g. line wrapping) is ignored. Transactions larger than
+/// [`MAX_TX_BYTES`] are rejected.
+///
+/// # Arguments
+///
+/// * `tx_base64` - The raw transaction, base64-encoded
+/// * `_network` - The network (not needed to parse; kept for API stability)
+///
+/// # Returns
+///
+/// The parsed `Transaction` or an error if decoding or parsing fails.
+pub fn parse_transaction_base64(
+    tx_base64: &str,
+    _network: Network,
+) -> Result<Transaction, ScannerError> {
+    check_input_len(tx_base64, MAX_TX_BYTES)?;
+    let compact: String = tx_base64.chars().filter(|c| !c.is_whitespace()).collect();
+    let tx_bytes = BASE64_STANDARD
+        .decode(compact)
+        .map_err(|e| ScannerError::InvalidTransactionBase64(e.to_string()))?;
+    read_transaction(&tx_bytes, MAX_TX_BYTES)
+}
+
+/// Parse a transaction given as either hex or base64.
+///
+/// The input is treated as hex if it is valid hex, and as base64 otherwise.
+/// Transactions larger than [`MAX_TX_BYTES`] are rejected.
+///
+/// # Arguments
+///
+/// * `input` - The raw transaction, hex- or base64-encoded
+/// * `network` - The network
+///
+/// # Returns
+///
+/// The parsed `Transaction`. If the input is neither hex nor base64, the
+/// hex error is returned.
+pub fn parse_transaction_auto(input: &str, network: Network) -> Result<Transaction, ScannerError> {
+    check_input_len(input, MAX_TX_BYTES)?;
+    match clean_tx_hex(input) {
+        Ok(_) => parse_transaction(input, network),
+        Err(hex_error) => match parse_transaction_base64(input, network) {
+            Err(ScannerError::InvalidTransactionBase64(_)) => Err(hex_error),
+            result => result,
+        },
+    }
+}
+
+/// The `fOverwintered` bit of the transaction header.
+const OVERWINTERED_FLAG: u32 = 1 << 31;
+
+/// Transaction versions the parser understands (Sprout v1 to NU5 v5).
+const SUPPORTED_TX_VERSIONS: core::ops::RangeInclusive<u32> = 1..=5;
+
+/// Highest version number that is reported as an unsupported version rather
+/// than as a parse failure. Larger numbers are taken to be garbage.
+const MAX_PLAUSIBLE_TX_VERSION: u32 = 0xff;
 
-    // Try parsing with different branch IDs (newest first)
+/// Parse raw transaction bytes.
+///
+/// Rejects transactions larger than `max_bytes`, then attempts parsing with
+/// multiple branch IDs, newest first.
+fn read_transaction(tx_bytes: &[u8], max_bytes: usize) -> Result<Transaction, ScannerError> {
+    if tx_bytes.len() > max_bytes {
+        return Err(ScannerError::TransactionTooLarge {
+            size: tx_bytes.len(),
+            max: max_bytes,
+        });
+    }
+
+    // Try parsing with different branch IDs (newest first). Pre-Heartwood
+    // branches cover v4 transactions from the Sapling and Blossom eras.
     let branch_ids = [
         BranchId::Nu6_1,
         BranchId::Nu6,
         BranchId::Nu5,
         BranchId::Canopy,
         BranchId::Heartwood,
+        BranchId::Blossom,
+        BranchId::Sapling,
     ];
 
     for branch_id in branch_ids {
-        if let Ok(tx) = Transaction::read(&tx_bytes[..], branch_id) {
+        if let Ok(tx) = Transaction::read(tx_bytes, branch_id) {
             return Ok(tx);
         }
+        trace_event!(?branch_id, "parse failed, falling back to older branch ID");
+    }
+
+    // Only blame the version when the header looks like a real one: every
+    // version after Sprout sets the overwintered flag, and version numbers
+    // grow slowly
+    if let Some(header) = tx_bytes.get(..4) {
+        let header = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let version = header & !OVERWINTERED_FLAG;
+        if header & OVERWINTERED_FLAG != 0
+            && version <= MAX_PLAUSIBLE_TX_VERSION
+            && !SUPPORTED_TX_VERSIONS.contains(&version)
+        {
+            return Err(ScannerError::UnsupportedVersion(version));
+        }
     }
 
     Err(ScannerError::TransactionParseFailed(
@@ -73,6 +318,11 @@ pub fn parse_transaction(tx_hex: &str, _network: Network) -> Result<Transaction,
     ))
 }
 
+/// Format a consensus branch ID the way zcashd reports it (8 hex digits).
+fn branch_id_hex(branch_id: BranchId) -> String {
+    format!("{:08x}", u32::from(branch_id))
+}
+
 /// Extract nullifiers from a transaction.
 ///
 /// Nullifiers indicate which notes have been spent. By tracking nullifiers
@@ -112,20 +362,221 @@ pub fn extract_nullifiers(tx: &Transaction) -> Vec<SpentNullifier> {
     nullifiers
 }
 
+/// Check whether a transaction has any shielded components.
+///
+/// Returns `false` for purely transparent transactions, which carry no
+/// Sprout, Sapling or Orchard data and therefore need no trial decryption.
+pub fn has_shielded_components(tx: &Transaction) -> bool {
+    tx.sprout_bundle().is_some() || tx.sapling_bundle().is_some() || tx.orchard_bundle().is_some()
+}
+
+/// Decode a memo as text.
+///
+/// The memo is first trimmed according to `trim`; returns `None` for empty
+/// memos and memos that are not valid UTF-8.
+pub(crate) fn memo_to_text(memo_bytes: &[u8], trim: MemoTrim) -> Option<String> {
+    let trimmed = trim.apply(memo_bytes);
+    if trimmed.is_empty() {
+        return None;
+    }
+    String::from_utf8(trimmed.to_vec()).ok()
+}
+
+/// Number of meaningful bytes in a memo.
+///
+/// Trailing zero padding is not counted, and the ZIP 302 "no memo" encoding
+/// (`0xF6` followed by zeros) counts as empty.
+pub(crate) fn memo_len(memo_bytes: &[u8]) -> usize {
+    let end = memo_bytes
+        .iter()
+        .rposition(|&b| b != 0)
+        .map_or(0, |pos| pos + 1);
+    if end == 1 && memo_bytes[0] == 0xf6 {
+        return 0;
+    }
+    end
+}
+
+/// Sum the memo lengths of the Orchard actions decryptable with `fvk`,
+/// either as received notes or as outputs the wallet sent.
+fn orchard_memo_bytes<T>(actions: &[orchard::Action<T>], fvk: &OrchardFvk) -> usize {
+    let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+    actions
+        .iter()
+        .filter_map(|action| {
+            try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+                .or_else(|| recover_orchard_output(action, fvk))
+        })
+        .map(|(_, _, memo_bytes)| memo_len(&memo_bytes))
+        .sum()
+}
+
+/// Collect the distinct external recipients of the Orchard outputs the
+/// wallet sent, as raw address bytes.
+///
+/// Outputs are recovered with the wallet's outgoing viewing keys; outputs
+/// paying one of the wallet's own addresses (change or self-payments) are
+/// skipped.
+fn orchard_external_recipients<T>(
+    actions: &[orchard::Action<T>],
+    fvk: &OrchardFvk,
+) -> BTreeSet<[u8; 43]> {
+    actions
+        .iter()
+        .filter_map(|action| recover_orchard_output(action, fvk))
+        .map(|(_, recipient, _)| recipient)
+        .filter(|recipient| fvk.scope_for_address(recipient).is_none())
+        .map(|recipient| recipient.to_raw_address_bytes())
+        .collect()
+}
+
+/// Recover an Orchard output sent by the wallet using its outgoing viewing
+/// keys.
+///
+/// Tries the external OVK (payments to others) and the internal OVK (change
+/// sent back to the wallet).
+pub(crate) fn recover_orchard_output<T>(
+    action: &orchard::Action<T>,
+    fvk: &OrchardFvk,
+) -> Option<(orchard::Note, orchard::Address, [u8; 512])> {
+    let domain = OrchardDomain::for_action(action);
+    [Scope::External, Scope::Internal]
+        .into_iter()
+        .find_map(|scope| {
+            try_output_recovery_with_ovk(
+                &domain,
+                &fvk.to_ovk(scope),
+                action,
+                action.cv_net(),
+                &action.encrypted_note().out_ciphertext,
+            )
+        })
+}
+
+/// Collect the distinct external recipients of the Sapling outputs the
+/// wallet sent, as raw address bytes.
+///
+/// Like [`orchard_external_recipients`], outputs paying one of the wallet's
+/// own addresses are skipped.
+fn sapling_external_recipients<P>(
+    outputs: &[sapling_crypto::bundle::OutputDescription<P>],
+    dfvk: &SaplingDfvk,
+) -> BTreeSet<[u8; 43]> {
+    outputs
+        .iter()
+        .filter_map(|output| recover_sapling_output(output, dfvk))
+        .map(|(_, recipient, _)| recipient)
+        .filter(|recipient| dfvk.decrypt_diversifier(recipient).is_none())
+        .map(|recipient| recipient.to_bytes())
+        .collect()
+}
+
+/// Recover a Sapling output we sent with the outgoing viewing key.
+///
+/// Tries the external OVK first, then the internal (change) one. ZIP 212
+/// note plaintexts of either version are accepted, since the OVK already
+/// proves the output is ours and the lead byte only depends on its height.
+///
+/// # Returns
+///
+/// The recovered note, its recipient and the 512-byte memo, or `None` if
+/// the output was not sent by this key.
+pub(crate) fn recover_sapling_output<P>(
+    output: &sapling_crypto::bundle::OutputDescription<P>,
+    dfvk: &SaplingDfvk,
+) -> Option<(
+    sapling_crypto::Note,
+    sapling_crypto::PaymentAddress,
+    [u8; 512],
+)> {
+    [zip32::Scope::External, zip32::Scope::Internal]
+        .into_iter()
+        .find_map(|scope| {
+            try_sapling_output_recovery(&dfvk.to_ovk(scope), output, Zip212Enforcement::GracePeriod)
+        })
+}
+
 /// Extract the Orchard full viewing key from a UFVK string.
-fn extract_orchard_fvk(viewing_key: &str) -> Option<OrchardFvk> {
-    if let Ok((_, ufvk)) = unified::Ufvk::decode(viewing_key) {
-        for item in ufvk.items() {
-            if let unified::Fvk::Orchard(orchard_bytes) = item
-                && let Some(fvk) = OrchardFvk::from_bytes(&orchard_bytes)
+///
+/// Returns `Ok(None)` if the key is not a UFVK or has no Orchard component,
+/// and `ScannerError::NetworkMismatch` if the UFVK is encoded for a
+/// different network than `network`.
+pub(crate) fn extract_orchard_fvk(
+    viewing_key: &str,
+    network: Network,
+) -> Result<Option<OrchardFvk>, ScannerError> {
+    let Ok((key_network, ufvk)) = unified::Ufvk::decode(viewing_key) else {
+        return Ok(None);
+    };
+    check_key_network(key_network, network)?;
+    Ok(orchard_fvk_item(&ufvk))
+}
+
+/// Check that a viewing key is encoded for the expected network.
+fn check_key_network(key_network: NetworkType, network: Network) -> Result<(), ScannerError> {
+    if key_network != network.network_type() {
+        return Err(ScannerError::NetworkMismatch(format!(
+            "viewing key is for {:?}, expected {:?}",
+            key_network,
+            network.network_type()
+        )));
+    }
+    Ok(())
+}
+
+/// Get the Orchard full viewing key item of a decoded UFVK.
+fn orchard_fvk_item(ufvk: &unified::Ufvk) -> Option<OrchardFvk> {
+    ufvk.items().into_iter().find_map(|item| match item {
+        unified::Fvk::Orchard(orchard_bytes) => OrchardFvk::from_bytes(&orchard_bytes),
+        _ => None,
+    })
+}
+
+/// Extract the Orchard incoming viewing key from a UIVK string.
+fn extract_orchard_ivk(viewing_key: &str) -> Option<orchard::keys::IncomingViewingKey> {
+    if let Ok((_, uivk)) = unified::Uivk::decode(viewing_key) {
+        for item in uivk.items() {
+            if let unified::Ivk::Orchard(orchard_bytes) = item
+                && let Some(ivk) = Option::from(orchard::keys::IncomingViewingKey::from_bytes(
+                    &orchard_bytes,
+                ))
             {
-                return Some(fvk);
+                return Some(ivk);
             }
         }
     }
     None
 }
 
+/// Extract the Sapling diversifiable full viewing key from a UFVK or a
+/// legacy Sapling extended full viewing key (`zxview...`) string.
+///
+/// Returns `Ok(None)` if the key is neither or has no Sapling component, and
+/// `ScannerError::NetworkMismatch` if it is encoded for a different network
+/// than `network`.
+pub(crate) fn extract_sapling_dfvk(
+    viewing_key: &str,
+    network: Network,
+) -> Result<Option<SaplingDfvk>, ScannerError> {
+    if let Ok((key_network, ufvk)) = unified::Ufvk::decode(viewing_key) {
+        check_key_network(key_network, network)?;
+        return Ok(ufvk.items().into_iter().find_map(|item| match item {
+            unified::Fvk::Sapling(sapling_bytes) => SaplingDfvk::from_bytes(&sapling_bytes),
+            _ => None,
+        }));
+    }
+    for key_network in [Network::MainNetwork, Network::TestNetwork] {
+        if let Ok(extfvk) = decode_extended_full_viewing_key(
+            key_network.hrp_sapling_extended_full_viewing_key(),
+            viewing_key,
+        ) {
+            check_key_network(key_network.network_type(), network)?;
+            return Ok(Some(extfvk.to_diversifiable_full_viewing_key()));
+        }
+    }
+    Ok(None)
+}
+
 /// Parse a viewing key and determine its capabilities.
 ///
 /// # Returns
@@ -135,45 +586,287 @@ fn extract_orchard_fvk(viewing_key: &str) -> Option<OrchardFvk> {
 pub fn parse_viewing_key_capabilities(
     viewing_key: &str,
 ) -> Result<(bool, bool, bool), ScannerError> {
-    // Try to decode as UFVK
-    if let Ok((_, ufvk)) = unified::Ufvk::decode(viewing_key) {
-        let mut has_sapling = false;
-        let mut has_orchard = false;
-        let mut has_transparent = false;
+    // Unified viewing keys (UFVK or UIVK)
+    if unified::Ufvk::decode(viewing_key).is_ok() || unified::Uivk::decode(viewing_key).is_ok() {
+        let capabilities = parse_unified_capabilities(viewing_key)?;
+        return Ok((
+            capabilities.has_sapling,
+            capabilities.has_orchard,
+            capabilities.has_transparent,
+        ));
+    }
 
-        for item in ufvk.items() {
-            match item {
-                unified::Fvk::Sapling(_) => has_sapling = true,
-                unified::Fvk::Orchard(_) => has_orchard = true,
-                unified::Fvk::P2pkh(_) => has_transparent = true,
-                _ => {}
+    // Try legacy Sapling viewing key
+    if viewing_key.starts_with("zxview") || viewing_key.starts_with("zxviews") {
+        return Ok((true, false, false));
+    }
+
+    Err(ScannerError::UnrecognizedViewingKey)
+}
+
+/// Check that a transaction pays at least an amount to an address.
+///
+/// Sums the transparent outputs paying the address and the Orchard outputs
+/// to it that the viewing key can decrypt. For a unified address, payments
+/// to its Orchard and transparent receivers are counted. Sapling outputs
+/// are not decrypted by the scanner, so Sapling receivers are not counted,
+/// and an address with no Orchard or transparent receiver is rejected.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_key` - The viewing key (UFVK or UIVK) the address belongs to
+/// * `expected_address` - The encoded address the payment was requested at
+/// * `min_amount` - The requested amount in zatoshis
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// True if at least `min_amount` zatoshis were paid to `expected_address`.
+/// An error if the address cannot be decoded or can only be paid via
+/// Sapling, or if the paid values overflow.
+pub fn verifies_payment(
+    tx: &Transaction,
+    viewing_key: &str,
+    expected_address: &str,
+    min_amount: u64,
+    network: Network,
+) -> Result<bool, ScannerError> {
+    parse_viewing_key_capabilities(viewing_key)?;
+    let address = Address::decode(&network, expected_address.trim()).ok_or_else(|| {
+        ScannerError::InvalidAddress(format!("Could not decode address for {:?}", network))
+    })?;
+    let (orchard_receiver, transparent_receiver) = match address {
+        Address::Unified(ua) => (ua.orchard().copied(), ua.transparent().copied()),
+        Address::Transparent(taddr) => (None, Some(taddr)),
+        Address::Tex(key_hash) => (None, Some(TransparentAddress::PublicKeyHash(key_hash))),
+        Address::Sapling(_) => (None, None),
+    };
+    if orchard_receiver.is_none() && transparent_receiver.is_none() {
+        return Err(ScannerError::InvalidAddress(
+            "Payments to Sapling receivers cannot be verified".to_string(),
+        ));
+    }
+    let overflow = || ScannerError::TransactionParseFailed("Paid value overflows".to_string());
+
+    let mut received = 0u64;
+    if let (Some(receiver), Some(bundle)) = (transparent_receiver, tx.transparent_bundle()) {
+        for output in bundle
+            .vout
+            .iter()
+            .filter(|output| output.recipient_address() == Some(receiver))
+        {
+            received = received
+                .checked_add(u64::from(output.value()))
+                .ok_or_else(overflow)?;
+        }
+    }
+
+    let orchard_ivk = extract_orchard_fvk(viewing_key, network)?
+        .map(|fvk| fvk.to_ivk(Scope::External))
+        .or_else(|| extract_orchard_ivk(viewing_key));
+    if let (Some(receiver), Some(ivk), Some(bundle)) =
+        (orchard_receiver, orchard_ivk, tx.orchard_bundle())
+    {
+        let ivk = PreparedIncomingViewingKey::new(&ivk);
+        for action in bundle.actions() {
+            if let Some((note, recipient, _)) =
+                try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+                && recipient == receiver
+            {
+                received = received
+                    .checked_add(note.value().inner())
+                    .ok_or_else(overflow)?;
+            }
+        }
+    }
+
+    Ok(received >= min_amount)
+}
+
+/// Total the value a transaction pays to each of the wallet's addresses.
+///
+/// Orchard outputs decryptable with the incoming viewing key are keyed by
+/// the unified address of their Orchard receiver. Transparent outputs are
+/// included when they pay one of the key's first
+/// [`DIVERSIFIER_SEARCH_LIMIT`] external or internal (change) transparent
+/// addresses (UFVKs only).
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_key` - The viewing key (UFVK or UIVK)
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// The value in zatoshis received at each encoded address.
+pub fn value_by_address(
+    tx: &Transaction,
+    viewing_key: &str,
+    network: Network,
+) -> Result<HashMap<String, u64>, ScannerError> {
+    parse_viewing_key_capabilities(viewing_key)?;
+    let mut values: HashMap<String, u64> = HashMap::new();
+
+    let orchard_ivk = extract_orchard_fvk(viewing_key, network)?
+        .map(|fvk| fvk.to_ivk(Scope::External))
+        .or_else(|| extract_orchard_ivk(viewing_key));
+    if let (Some(ivk), Some(bundle)) = (orchard_ivk, tx.orchard_bundle()) {
+        let ivk = PreparedIncomingViewingKey::new(&ivk);
+        for action in bundle.actions() {
+            if let Some((note, recipient, _)) =
+                try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+                && let Some(ua) = UnifiedAddress::from_receivers(Some(recipient), None, None)
+            {
+                *values.entry(ua.encode(&network)).or_default() += note.value().inner();
             }
         }
+    }
 
-        return Ok((has_sapling, has_orchard, has_transparent));
+    if let Some(bundle) = tx.transparent_bundle() {
+        // A UIVK has no transparent full viewing key, so nothing is watched
+        let watched: HashSet<String> =
+            derive_transparent_watch_set_from_ufvk(viewing_key, network, DIVERSIFIER_SEARCH_LIMIT)
+                .map(|addresses| addresses.into_iter().collect())
+                .unwrap_or_default();
+        for output in &bundle.vout {
+            let Some(address) = output.recipient_address().map(|a| a.encode(&network)) else {
+                continue;
+            };
+            if watched.contains(&address) {
+                *values.entry(address).or_default() += u64::from(output.value());
+            }
+        }
     }
 
-    // Try to decode as UIVK
-    if let Ok((_, uivk)) = unified::Uivk::decode(viewing_key) {
-        let mut has_sapling = false;
-        let mut has_orchard = false;
-        let mut has_transparent = false;
+    Ok(values)
+}
 
-        for item in uivk.items() {
+/// Check whether a scanned transaction only pays the wallet itself.
+///
+/// Such a self-transfer (e.g. consolidating notes, or shielding the
+/// wallet's transparent funds) should not be shown as a received payment.
+/// The wallet must have authored the transaction: either outputs were
+/// recovered with its outgoing viewing key, or it spends one of the
+/// wallet's notes. Otherwise a third party paying the key would look like a
+/// self-transfer.
+///
+/// Notes decrypted with the incoming viewing key are the wallet's own, as
+/// are outgoing notes to one of its own addresses. Transparent outputs count
+/// when their address belongs to the key (searching
+/// [`DIVERSIFIER_SEARCH_LIMIT`] indices). Orchard actions that cannot be
+/// decrypted are taken to be bundle padding, and Sapling outputs that cannot
+/// be decrypted to be foreign.
+///
+/// # Arguments
+///
+/// * `result` - The scan result of the transaction
+/// * `ufvk` - The unified full viewing key it was scanned with
+/// * `network` - The network the key is for
+/// * `owned_nullifiers` - Nullifiers of the notes the wallet holds
+///
+/// # Returns
+///
+/// True if the wallet authored the transaction, it has at least one output
+/// and every output goes to the wallet.
+pub fn is_self_transfer(
+    result: &ScanResult,
+    ufvk: &str,
+    network: Network,
+    owned_nullifiers: &BTreeSet<String>,
+) -> Result<bool, ScannerError> {
+    parse_viewing_key_capabilities(ufvk)?;
+    let authored = result.notes.iter().any(ScannedNote::is_outgoing)
+        || result
+            .spent_nullifiers
+            .iter()
+            .any(|spent| owned_nullifiers.contains(&spent.nullifier));
+    if !authored {
+        return Ok(false);
+    }
+
+    let is_own = |address: &Option<String>| {
+        address.as_deref().is_some_and(|address| {
+            is_own_address(ufvk, address, network, u128::from(DIVERSIFIER_SEARCH_LIMIT))
+                .unwrap_or(false)
+        })
+    };
+
+    let mut own_outputs = 0;
+    for note in &result.notes {
+        let own = match (note.pool, note.scope) {
+            (_, Some(NoteScope::Incoming)) => true,
+            (_, Some(NoteScope::Outgoing)) => !note.is_external,
+            (Pool::Transparent, None) => is_own(&note.address),
+            (Pool::Orchard, None) => continue,
+            (Pool::Sapling, None) => false,
+        };
+        if !own {
+            return Ok(false);
+        }
+        own_outputs += 1;
+    }
+    Ok(own_outputs > 0)
+}
+
+/// Parse a unified container and report the items it contains.
+///
+/// Accepts unified addresses, UFVKs and UIVKs. Unlike
+/// [`parse_viewing_key_capabilities`], items with unknown typecodes are
+/// reported rather than ignored.
+///
+/// # Arguments
+///
+/// * `encoded` - The encoded unified address or viewing key
+///
+/// # Returns
+///
+/// The container's `UnifiedCapabilities`, or
+/// `ScannerError::UnrecognizedViewingKey` if the input is not a well-formed
+/// unified container.
+pub fn parse_unified_capabilities(encoded: &str) -> Result<UnifiedCapabilities, ScannerError> {
+    let encoded = encoded.trim();
+    let mut capabilities = UnifiedCapabilities::default();
+
+    if let Ok((_, ufvk)) = unified::Ufvk::decode(encoded) {
+        for item in ufvk.items() {
             match item {
-                unified::Ivk::Sapling(_) => has_sapling = true,
-                unified::Ivk::Orchard(_) => has_orchard = true,
-                unified::Ivk::P2pkh(_) => has_transparent = true,
-                _ => {}
+                unified::Fvk::Sapling(_) => capabilities.has_sapling = true,
+                unified::Fvk::Orchard(_) => capabilities.has_orchard = true,
+                unified::Fvk::P2pkh(_) => capabilities.has_transparent = true,
+                unified::Fvk::Unknown { typecode, .. } => capabilities.unknown_items.push(typecode),
             }
         }
+        return Ok(capabilities);
+    }
 
-        return Ok((has_sapling, has_orchard, has_transparent));
+    if let Ok((_, uivk)) = unified::Uivk::decode(encoded) {
+        for item in uivk.items() {
+            match item {
+                unified::Ivk::Sapling(_) => capabilities.has_sapling = true,
+                unified::Ivk::Orchard(_) => capabilities.has_orchard = true,
+                unified::Ivk::P2pkh(_) => capabilities.has_transparent = true,
+                unified::Ivk::Unknown { typecode, .. } => capabilities.unknown_items.push(typecode),
+            }
+        }
+        return Ok(capabilities);
     }
 
-    // Try legacy Sapling viewing key
-    if viewing_key.starts_with("zxview") || viewing_key.starts_with("zxviews") {
-        return Ok((true, false, false));
+    if let Ok((_, ua)) = unified::Address::decode(encoded) {
+        for item in ua.items() {
+            match item {
+                unified::Receiver::Sapling(_) => capabilities.has_sapling = true,
+                unified::Receiver::Orchard(_) => capabilities.has_orchard = true,
+                unified::Receiver::P2pkh(_) | unified::Receiver::P2sh(_) => {
+                    capabilities.has_transparent = true
+                }
+                unified::Receiver::Unknown { typecode, .. } => {
+                    capabilities.unknown_items.push(typecode)
+                }
+            }
+        }
+        return Ok(capabilities);
     }
 
     Err(ScannerError::UnrecognizedViewingKey)
@@ -190,7 +883,8 @@ pub fn parse_viewing_key_capabilities(
 /// * `tx` - The parsed transaction
 /// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
 /// * `network` - The network (used for encoding transparent addresses)
-/// * `_height` - Block height (currently unused, needed for full Sapling decryption)
+/// * `height` - Block height the transaction was mined at, if known. Used to
+///   check the consensus branch (see [`ScanWarning`])
 ///
 /// # Returns
 ///
@@ -199,22 +893,69 @@ pub fn scan_transaction(
     tx: &Transaction,
     viewing_key: &str,
     network: Network,
-    _height: Option<u32>,
+    height: Option<u32>,
+) -> Result<ScanResult, ScannerError> {
+    scan_transaction_with_options(tx, viewing_key, network, height, &ScanOptions::default())
+}
+
+/// Scan a transaction for notes belonging to a viewing key, with options.
+///
+/// Same as [`scan_transaction`], but allows enabling extra output such as
+/// per-action debug metadata.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network (used for encoding transparent addresses)
+/// * `height` - Block height the transaction was mined at, if known. Used to
+///   check the consensus branch (see [`ScanWarning`]), not yet for Sapling
+///   decryption
+/// * `options` - Scan options (see `ScanOptions`)
+///
+/// # Returns
+///
+/// A `ScanResult` containing found notes, spent nullifiers, and transparent outputs.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(txid = %tx.txid()))
+)]
+pub fn scan_transaction_with_options(
+    tx: &Transaction,
+    viewing_key: &str,
+    network: Network,
+    height: Option<u32>,
+    options: &ScanOptions,
 ) -> Result<ScanResult, ScannerError> {
     let txid = tx.txid().to_string();
+    let lock_time = tx.lock_time();
+    let is_coinbase = tx
+        .transparent_bundle()
+        .is_some_and(|bundle| bundle.is_coinbase());
+    let consensus_branch_id = branch_id_hex(tx.consensus_branch_id());
     let mut notes = Vec::new();
     let mut transparent_received = 0u64;
     let mut transparent_outputs = Vec::new();
 
     // Parse the viewing key capabilities
     let (has_sapling, has_orchard, has_transparent) = parse_viewing_key_capabilities(viewing_key)?;
-
-    // Extract Orchard FVK for decryption
-    let orchard_fvk = extract_orchard_fvk(viewing_key);
+    let transparent_only_warning = (has_transparent
+        && !has_sapling
+        && !has_orchard
+        && (options.scans_pool(Pool::Sapling) || options.scans_pool(Pool::Orchard)))
+    .then_some(ScanWarning::TransparentOnlyKey);
+    let has_sapling = has_sapling && options.scans_pool(Pool::Sapling);
+    let has_orchard = has_orchard && options.scans_pool(Pool::Orchard);
+    let has_transparent = has_transparent && options.scans_pool(Pool::Transparent);
+    // Incoming Sapling notes are not trial-decrypted (only outputs we sent
+    // are recovered), so the pool is never fully scanned
+    let scanned_pools = scanned_pools(has_transparent, false, has_orchard);
 
     // Extract transparent spends (inputs)
     let mut transparent_spends = Vec::new();
-    if let Some(transparent_bundle) = tx.transparent_bundle() {
+    if options.scans_pool(Pool::Transparent)
+        && let Some(transparent_bundle) = tx.transparent_bundle()
+    {
         for input in transparent_bundle.vin.iter() {
             let prevout = input.prevout();
             // The prevout hash is in internal byte order (little-endian).
@@ -231,6 +972,10 @@ pub fn scan_transaction(
     // Process transparent outputs
     if has_transparent && let Some(transparent_bundle) = tx.transparent_bundle() {
         for (i, output) in transparent_bundle.vout.iter().enumerate() {
+            // OP_RETURN outputs are unspendable, so their value is not received
+            if is_unspendable(&output.script_pubkey().0.0) {
+                continue;
+            }
             let value = u64::from(output.value());
             transparent_received += value;
 
@@ -251,93 +996,127 @@ pub fn scan_transaction(
                 nullifier: None,           // Transparent outputs use input references instead
                 memo: None,                // Transparent outputs don't have memos
                 address,
+                debug: None,
+                scope: None,
+                position: None,
+                from_tex: false,
+                memo_bytes: None,
+                commitment_verified: false,
+                is_external: false,
             });
         }
     }
 
-    // Process Sapling outputs (without full decryption - focusing on Orchard)
+    // Extract nullifiers (spent notes)
+    let mut spent_nullifiers = extract_nullifiers(tx);
+    spent_nullifiers.retain(|nf| options.scans_pool(nf.pool));
+
+    // Purely transparent transactions have nothing to trial-decrypt, so skip
+    // preparing the shielded viewing keys entirely
+    if !has_shielded_components(tx) {
+        return Ok(ScanResult {
+            txid,
+            notes,
+            spent_nullifiers,
+            transparent_spends,
+            transparent_received,
+            transparent_outputs,
+            lock_time,
+            value_balance_consistent: true,
+            scanned_pools,
+            sapling_decryption: DecryptionCounts::default(),
+            orchard_decryption: DecryptionCounts::default(),
+            consensus_branch_id,
+            warnings: transparent_only_warning.into_iter().collect(),
+            is_coinbase,
+        });
+    }
+
+    // Extract Orchard FVK for decryption
+    let orchard_fvk = extract_orchard_fvk(viewing_key, network)?;
+
+    // Process Sapling outputs. Incoming trial decryption requires height
+    // context, but outputs we sent can be recovered with our OVK.
+    let mut sapling_decryption = DecryptionCounts::default();
     if has_sapling && let Some(sapling_bundle) = tx.sapling_bundle() {
-        for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
-            let cmu = output.cmu();
-            let commitment = hex::encode(cmu.to_bytes());
+        let sapling_dfvk = extract_sapling_dfvk(viewing_key, network)?;
 
-            notes.push(ScannedNote {
-                output_index: i,
-                pool: Pool::Sapling,
-                value: 0, // Sapling decryption requires height context
-                commitment,
-                nullifier: None,
-                memo: None,
-                address: None,
-            });
+        for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
+            let note = scan_sapling_output(i, output, sapling_dfvk.as_ref(), options, network);
+            if sapling_dfvk.is_some() {
+                sapling_decryption.attempted += 1;
+            }
+            if note.scope.is_some() {
+                sapling_decryption.decrypted += 1;
+            }
+            notes.push(note);
         }
     }
 
+    let mut value_balance_consistent = true;
+    let mut orchard_decryption = DecryptionCounts::default();
+    let mut malformed_actions = Vec::new();
+
     // Process Orchard actions with trial decryption
     if has_orchard && let Some(orchard_bundle) = tx.orchard_bundle() {
-        // Prepare the incoming viewing key for decryption
+        let first_orchard_note = notes.len();
+
+        // Prepare the incoming viewing key for decryption. Incoming-only keys
+        // (UIVKs) can decrypt notes but not compute their nullifiers.
         let prepared_ivk = orchard_fvk
             .as_ref()
-            .map(|fvk| PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External)));
+            .map(|fvk| fvk.to_ivk(Scope::External))
+            .or_else(|| extract_orchard_ivk(viewing_key))
+            .map(|ivk| PreparedIncomingViewingKey::new(&ivk));
 
         for (i, action) in orchard_bundle.actions().iter().enumerate() {
-            let cmx = action.cmx();
-            let commitment = hex::encode(cmx.to_bytes());
-
-            let mut value = 0u64;
-            let mut memo = None;
-            let mut nullifier = None;
-            let mut address = None;
-
-            // Attempt trial decryption if we have the viewing key
-            if let Some(ref ivk) = prepared_ivk {
-                let domain = OrchardDomain::for_action(action);
-
-                if let Some((note, recipient_addr, memo_bytes)) =
-                    try_note_decryption(&domain, ivk, action)
-                {
-                    // Successfully decrypted!
-                    value = note.value().inner();
-
-                    // Extract memo (strip trailing zeros and convert to string if valid UTF-8)
-                    let memo_trimmed: Vec<u8> = memo_bytes
-                        .iter()
-                        .rev()
-                        .skip_while(|&&b| b == 0)
-                        .copied()
-                        .collect::<Vec<_>>()
-                        .into_iter()
-                        .rev()
-                        .collect();
-                    if !memo_trimmed.is_empty() {
-                        memo = String::from_utf8(memo_trimmed).ok();
-                    }
-
-                    // Compute the nullifier for this note
-                    if let Some(ref fvk) = orchard_fvk {
-                        let nf = note.nullifier(fvk);
-                        nullifier = Some(hex::encode(nf.to_bytes()));
-                    }
-
-                    // Encode the recipient address
-                    address = Some(format!("{:?}", recipient_addr));
-                }
+            // The parser does not validate ephemeral keys, and trial
+            // decryption silently fails on ones that are not curve points
+            let epk_bytes = EphemeralKeyBytes(action.encrypted_note().epk_bytes);
+            if OrchardDomain::epk(&epk_bytes).is_none() {
+                malformed_actions.push(ScanWarning::MalformedAction { action_index: i });
             }
 
-            notes.push(ScannedNote {
-                output_index: i,
-                pool: Pool::Orchard,
-                value,
-                commitment,
-                nullifier,
-                memo,
-                address,
-            });
+            let note = scan_orchard_action(
+                i,
+                action,
+                prepared_ivk.as_ref(),
+                orchard_fvk.as_ref(),
+                options,
+                network,
+            );
+            if prepared_ivk.is_some() {
+                orchard_decryption.attempted += 1;
+            }
+            if note.scope.is_some() {
+                orchard_decryption.decrypted += 1;
+            }
+            notes.push(note);
+        }
+
+        if options.verify_value_balance {
+            let output_values: Vec<Option<u64>> = notes[first_orchard_note..]
+                .iter()
+                .map(|note| note.scope.map(|_| note.value))
+                .collect();
+            value_balance_consistent = orchard_values_consistent(
+                &output_values,
+                i64::from(*orchard_bundle.value_balance()),
+            );
         }
     }
 
-    // Extract nullifiers (spent notes)
-    let spent_nullifiers = extract_nullifiers(tx);
+    flag_tex_note(tx, &mut notes);
+    let warnings = transparent_only_warning
+        .into_iter()
+        .chain(malformed_actions)
+        .chain(branch_mismatch_warning(
+            tx,
+            network,
+            height,
+            sapling_decryption.decrypted + orchard_decryption.decrypted,
+        ))
+        .collect();
 
     Ok(ScanResult {
         txid,
@@ -346,9 +1125,414 @@ pub fn scan_transaction(
         transparent_spends,
         transparent_received,
         transparent_outputs,
+        lock_time,
+        value_balance_consistent,
+        scanned_pools,
+        sapling_decryption,
+        orchard_decryption,
+        consensus_branch_id,
+        warnings,
+        is_coinbase,
+    })
+}
+
+/// Recover one Sapling output with the key's outgoing viewing keys.
+///
+/// Incoming trial decryption needs height context and is not done, so only
+/// outputs the key sent are decrypted.
+fn scan_sapling_output<P>(
+    i: usize,
+    output: &sapling_crypto::bundle::OutputDescription<P>,
+    dfvk: Option<&SaplingDfvk>,
+    options: &ScanOptions,
+    network: Network,
+) -> ScannedNote {
+    let commitment = hex::encode(output.cmu().to_bytes());
+
+    let mut value = 0u64;
+    let mut memo = None;
+    let mut address = None;
+    let mut scope = None;
+    let mut raw_memo = None;
+    let mut is_external = false;
+
+    if let Some(dfvk) = dfvk
+        && let Some((note, recipient_addr, memo_bytes)) = recover_sapling_output(output, dfvk)
+    {
+        value = note.value().inner();
+        is_external = dfvk.decrypt_diversifier(&recipient_addr).is_none();
+        memo = memo_to_text(&memo_bytes, options.memo_trim);
+        raw_memo = options.raw_memo.then(|| hex::encode(memo_bytes));
+        scope = Some(NoteScope::Outgoing);
+        trace_event!(
+            pool = "sapling",
+            output_index = i,
+            value,
+            "outgoing note recovered"
+        );
+        address = Some(Address::Sapling(recipient_addr).encode(&network));
+    }
+
+    ScannedNote {
+        output_index: i,
+        pool: Pool::Sapling,
+        value,
+        commitment,
+        nullifier: None,
+        memo,
+        address,
+        debug: None,
+        scope,
+        position: None,
+        from_tex: false,
+        memo_bytes: raw_memo,
+        commitment_verified: false,
+        is_external,
+    }
+}
+
+/// Trial-decrypt one Orchard action, falling back to recovering it with the
+/// outgoing viewing keys.
+fn scan_orchard_action<T>(
+    i: usize,
+    action: &orchard::Action<T>,
+    prepared_ivk: Option<&PreparedIncomingViewingKey>,
+    orchard_fvk: Option<&OrchardFvk>,
+    options: &ScanOptions,
+    network: Network,
+) -> ScannedNote {
+    let commitment = hex::encode(action.cmx().to_bytes());
+
+    // Raw action metadata is attached whether or not decryption succeeds
+    let debug = options.debug.then(|| ActionDebugInfo {
+        enc_ciphertext_len: action.encrypted_note().enc_ciphertext.len(),
+        ephemeral_key: hex::encode(action.encrypted_note().epk_bytes),
+        cmx: commitment.clone(),
+    });
+
+    let mut value = 0u64;
+    let mut memo = None;
+    let mut nullifier = None;
+    let mut address = None;
+    let mut scope = None;
+    let mut raw_memo = None;
+    let mut commitment_verified = false;
+    let mut is_external = false;
+
+    // Attempt trial decryption if we have the viewing key
+    if let Some(ivk) = prepared_ivk {
+        let domain = OrchardDomain::for_action(action);
+
+        if let Some((note, recipient_addr, memo_bytes)) = try_note_decryption(&domain, ivk, action)
+        {
+            // Successfully decrypted!
+            value = note.value().inner();
+            memo = memo_to_text(&memo_bytes, options.memo_trim);
+            raw_memo = options.raw_memo.then(|| hex::encode(memo_bytes));
+            scope = Some(NoteScope::Incoming);
+            commitment_verified = orchard_commitment_matches(&note, action.cmx());
+            trace_event!(pool = "orchard", output_index = i, value, "note decrypted");
+
+            // Compute the nullifier for this note
+            if let Some(fvk) = orchard_fvk {
+                let nf = note.nullifier(fvk);
+                nullifier = Some(hex::encode(nf.to_bytes()));
+            }
+
+            // Encode the recipient address
+            address = Some(format!("{:?}", recipient_addr));
+        }
+    }
+
+    // Outputs we sent to someone else can be recovered with our OVK
+    if scope.is_none()
+        && let Some(fvk) = orchard_fvk
+        && let Some((note, recipient_addr, memo_bytes)) = recover_orchard_output(action, fvk)
+    {
+        value = note.value().inner();
+        memo = memo_to_text(&memo_bytes, options.memo_trim);
+        raw_memo = options.raw_memo.then(|| hex::encode(memo_bytes));
+        scope = Some(NoteScope::Outgoing);
+        commitment_verified = orchard_commitment_matches(&note, action.cmx());
+        is_external = fvk.scope_for_address(&recipient_addr).is_none();
+        trace_event!(
+            pool = "orchard",
+            output_index = i,
+            value,
+            "outgoing note recovered"
+        );
+        address = UnifiedAddress::from_receivers(Some(recipient_addr), None, None)
+            .map(|ua| ua.encode(&network));
+    }
+
+    ScannedNote {
+        output_index: i,
+        pool: Pool::Orchard,
+        value,
+        commitment,
+        nullifier,
+        memo,
+        address,
+        debug,
+        scope,
+        position: None,
+        from_tex: false,
+        memo_bytes: raw_memo,
+        commitment_verified,
+        is_external,
+    }
+}
+
+/// A shielded output parsed from a transaction, not yet decrypted.
+///
+/// Returned by [`parse_outputs`] so a UI can show how many outputs a
+/// transaction has before decrypting them one by one with
+/// [`decrypt_output`].
+#[derive(Debug, Clone)]
+pub struct PendingOutput {
+    /// The pool the output is in.
+    pub pool: Pool,
+    /// Index of the output within its bundle (Sapling output or Orchard
+    /// action index).
+    pub output_index: usize,
+    /// The output's note commitment (`cmu` or `cmx`), as hex.
+    pub commitment: String,
+    output: PendingShieldedOutput,
+}
+
+/// The encrypted output data kept for decryption.
+#[derive(Debug, Clone)]
+enum PendingShieldedOutput {
+    Sapling(
+        Box<sapling_crypto::bundle::OutputDescription<sapling_crypto::bundle::GrothProofBytes>>,
+    ),
+    Orchard(Box<orchard::Action<()>>),
+}
+
+/// List a transaction's shielded outputs without decrypting them.
+///
+/// This is the first phase of [`scan_transaction`]: it is cheap and needs
+/// no key, so the number of outputs can be shown immediately.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The Sapling outputs followed by the Orchard actions, in the order
+/// [`scan_transaction`] reports them.
+pub fn parse_outputs(tx: &Transaction) -> Vec<PendingOutput> {
+    let mut outputs = Vec::new();
+    if let Some(bundle) = tx.sapling_bundle() {
+        for (i, output) in bundle.shielded_outputs().iter().enumerate() {
+            outputs.push(PendingOutput {
+                pool: Pool::Sapling,
+                output_index: i,
+                commitment: hex::encode(output.cmu().to_bytes()),
+                output: PendingShieldedOutput::Sapling(Box::new(output.clone())),
+            });
+        }
+    }
+    if let Some(bundle) = tx.orchard_bundle() {
+        for (i, action) in bundle.actions().iter().enumerate() {
+            outputs.push(PendingOutput {
+                pool: Pool::Orchard,
+                output_index: i,
+                commitment: hex::encode(action.cmx().to_bytes()),
+                output: PendingShieldedOutput::Orchard(Box::new(action.clone().map(|_| ()))),
+            });
+        }
+    }
+    outputs
+}
+
+/// Decrypt one output listed by [`parse_outputs`].
+///
+/// This is the second phase of [`scan_transaction`], and produces the same
+/// note it would for the output. The viewing key is parsed on every call;
+/// outputs in a pool the key cannot view are returned undecrypted.
+///
+/// # Arguments
+///
+/// * `pending` - The output to decrypt
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network the viewing key is for
+///
+/// # Returns
+///
+/// The scanned note, with `scope` set if the output belongs to the key.
+pub fn decrypt_output(
+    pending: &PendingOutput,
+    viewing_key: &str,
+    network: Network,
+) -> Result<ScannedNote, ScannerError> {
+    let (has_sapling, has_orchard, _) = parse_viewing_key_capabilities(viewing_key)?;
+    let options = ScanOptions::default();
+    let note = match &pending.output {
+        PendingShieldedOutput::Sapling(output) => {
+            let dfvk = extract_sapling_dfvk(viewing_key, network)?.filter(|_| has_sapling);
+            scan_sapling_output(
+                pending.output_index,
+                &**output,
+                dfvk.as_ref(),
+                &options,
+                network,
+            )
+        }
+        PendingShieldedOutput::Orchard(action) => {
+            let fvk = extract_orchard_fvk(viewing_key, network)?.filter(|_| has_orchard);
+            let ivk = fvk
+                .as_ref()
+                .map(|fvk| fvk.to_ivk(Scope::External))
+                .or_else(|| extract_orchard_ivk(viewing_key))
+                .filter(|_| has_orchard)
+                .map(|ivk| PreparedIncomingViewingKey::new(&ivk));
+            scan_orchard_action(
+                pending.output_index,
+                &**action,
+                ivk.as_ref(),
+                fvk.as_ref(),
+                &options,
+                network,
+            )
+        }
+    };
+    Ok(note)
+}
+
+/// Warn when no shielded output decrypted and the transaction's consensus
+/// branch is not the one active at `height`.
+fn branch_mismatch_warning(
+    tx: &Transaction,
+    network: Network,
+    height: Option<u32>,
+    decrypted: usize,
+) -> Option<ScanWarning> {
+    let height = height?;
+    let shielded_outputs = tx
+        .sapling_bundle()
+        .map_or(0, |bundle| bundle.shielded_outputs().len())
+        + tx.orchard_bundle()
+            .map_or(0, |bundle| bundle.actions().len());
+    if shielded_outputs == 0 || decrypted > 0 {
+        return None;
+    }
+
+    let parsed = tx.consensus_branch_id();
+    let expected = BranchId::for_height(&network, BlockHeight::from_u32(height));
+    (parsed != expected).then(|| ScanWarning::PossibleBranchMismatch {
+        parsed_branch_id: branch_id_hex(parsed),
+        height_branch_id: branch_id_hex(expected),
     })
 }
 
+/// Check a decrypted Orchard note against the action's note commitment.
+///
+/// Decryption already rejects plaintexts that do not match the commitment,
+/// so a mismatch means the decryption code itself is wrong.
+fn orchard_commitment_matches(note: &orchard::Note, cmx: &ExtractedNoteCommitment) -> bool {
+    ExtractedNoteCommitment::from(note.commitment()) == *cmx
+}
+
+/// Flag the note funded by a transparent-to-shielded (TEX) flow, if any.
+///
+/// A transaction spending transparent inputs into exactly one shielded note
+/// for the wallet looks like an auto-shielding transaction; that note is
+/// marked `from_tex`. The heuristic cannot tell this apart from a payment
+/// by a third party that spends transparent funds.
+fn flag_tex_note(tx: &Transaction, notes: &mut [ScannedNote]) {
+    let has_transparent_inputs = tx
+        .transparent_bundle()
+        .is_some_and(|bundle| !bundle.vin.is_empty());
+    if !has_transparent_inputs {
+        return;
+    }
+
+    let mut incoming = notes
+        .iter_mut()
+        .filter(|note| note.scope == Some(NoteScope::Incoming));
+    if let (Some(note), None) = (incoming.next(), incoming.next()) {
+        note.from_tex = true;
+    }
+}
+
+/// Scan a transaction with the viewing keys of several accounts.
+///
+/// Each account's key is tried in turn and the notes it received are
+/// tagged with its account index. Outputs recovered as sent and
+/// transparent outputs are not included, since they are not attributable
+/// to the receiving account.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_keys` - `(viewing key, account index)` pairs
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// The received notes with their account index, in `viewing_keys` order.
+pub fn scan_transaction_multi(
+    tx: &Transaction,
+    viewing_keys: &[(&str, u32)],
+    network: Network,
+) -> Result<Vec<(u32, ScannedNote)>, ScannerError> {
+    let mut notes = Vec::new();
+    for (viewing_key, account) in viewing_keys {
+        let result = scan_transaction(tx, viewing_key, network, None)?;
+        notes.extend(
+            result
+                .notes
+                .into_iter()
+                .filter(|note| note.scope == Some(NoteScope::Incoming))
+                .map(|note| (*account, note)),
+        );
+    }
+    Ok(notes)
+}
+
+/// List the pools a viewing key can scan, in `Pool` order.
+pub(crate) fn scanned_pools(
+    has_transparent: bool,
+    has_sapling: bool,
+    has_orchard: bool,
+) -> Vec<String> {
+    [
+        (Pool::Transparent, has_transparent),
+        (Pool::Sapling, has_sapling),
+        (Pool::Orchard, has_orchard),
+    ]
+    .into_iter()
+    .filter(|(_, scannable)| *scannable)
+    .map(|(pool, _)| pool.as_str().to_string())
+    .collect()
+}
+
+/// Check decrypted Orchard output values against the bundle's value balance.
+///
+/// The value balance is the total value of the spent notes minus the total
+/// value of the outputs. Spent values are never negative, so when every
+/// output was decrypted their sum can never be less than the value leaving
+/// the bundle (`-value_balance`). If any output could not be decrypted
+/// nothing can be concluded and the values are considered consistent.
+///
+/// # Arguments
+///
+/// * `output_values` - Value of each action's output, `None` if undecrypted
+/// * `value_balance` - The bundle's value balance in zatoshis
+fn orchard_values_consistent(output_values: &[Option<u64>], value_balance: i64) -> bool {
+    let Some(outputs) = output_values
+        .iter()
+        .try_fold(0i128, |sum, value| value.map(|v| sum + i128::from(v)))
+    else {
+        return true;
+    };
+
+    // Implied total of the spent notes
+    outputs + i128::from(value_balance) >= 0
+}
+
 /// Scan a transaction from hex for notes belonging to a viewing key.
 ///
 /// Convenience function that combines parsing and scanning.
@@ -373,93 +1557,1215 @@ pub fn scan_transaction_hex(
     scan_transaction(&tx, viewing_key, network, height)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Find and decrypt the Orchard output with a given note commitment.
+///
+/// Used to reconcile a transaction against an externally supplied list of
+/// note commitments.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `commitment_hex` - The note commitment (`cmx`) as hex, as reported in
+///   `ScannedNote::commitment`
+/// * `viewing_key` - The viewing key (UFVK or UIVK)
+/// * `network` - The network the transaction belongs to
+///
+/// # Returns
+///
+/// The decrypted note, or `None` if the transaction has no output with this
+/// commitment or the output was not received by the viewing key (outputs
+/// the wallet sent, recovered with the outgoing viewing key, do not match).
+pub fn identify_commitment(
+    tx: &Transaction,
+    commitment_hex: &str,
+    viewing_key: &str,
+    network: Network,
+) -> Result<Option<ScannedNote>, ScannerError> {
+    let commitment: [u8; 32] = hex::decode(commitment_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            ScannerError::InvalidNote("Commitment must be 32 bytes of hex".to_string())
+        })?;
 
-    // Test UFVK for reference
-    const TEST_UFVK: &str = "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd";
+    let Some(output_index) = tx.orchard_bundle().and_then(|bundle| {
+        bundle
+            .actions()
+            .iter()
+            .position(|action| action.cmx().to_bytes() == commitment)
+    }) else {
+        return Ok(None);
+    };
 
-    #[test]
-    fn test_parse_viewing_key_capabilities() {
-        let (sapling, orchard, transparent) = parse_viewing_key_capabilities(TEST_UFVK).unwrap();
-        assert!(sapling);
-        assert!(orchard);
-        assert!(transparent);
-    }
+    let options = ScanOptions {
+        pools: Some(vec![Pool::Orchard]),
+        ..ScanOptions::default()
+    };
+    let result = scan_transaction_with_options(tx, viewing_key, network, None, &options)?;
+    Ok(result
+        .notes
+        .into_iter()
+        .find(|note| note.output_index == output_index && note.scope == Some(NoteScope::Incoming)))
+}
 
-    #[test]
-    fn test_extract_orchard_fvk() {
-        let fvk = extract_orchard_fvk(TEST_UFVK);
-        assert!(fvk.is_some(), "Should extract Orchard FVK from UFVK");
-    }
+/// Compute the total memo storage used by a transaction.
+///
+/// Decrypts each Orchard output with the viewing key (as a received note, or
+/// with the outgoing viewing key for outputs the wallet sent) and sums the
+/// lengths of their non-empty memos. Sapling outputs are not decrypted by
+/// the scanner yet and are not counted.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_key` - The viewing key (UFVK with an Orchard component)
+/// * `network` - The network the viewing key is for
+///
+/// # Returns
+///
+/// The number of memo bytes, excluding zero padding.
+pub fn total_memo_bytes(
+    tx: &Transaction,
+    viewing_key: &str,
+    network: Network,
+) -> Result<usize, ScannerError> {
+    parse_viewing_key_capabilities(viewing_key)?;
 
-    #[test]
-    fn test_invalid_viewing_key() {
-        let result = parse_viewing_key_capabilities("invalid_key");
-        assert!(result.is_err());
-    }
+    let total = match (
+        tx.orchard_bundle(),
+        extract_orchard_fvk(viewing_key, network)?,
+    ) {
+        (Some(bundle), Some(fvk)) => orchard_memo_bytes(bundle.actions(), &fvk),
+        _ => 0,
+    };
+    Ok(total)
+}
 
-    #[test]
-    fn test_legacy_sapling_key() {
-        let result = parse_viewing_key_capabilities("zxviews1something");
-        assert!(result.is_ok());
-        let (sapling, orchard, transparent) = result.unwrap();
-        assert!(sapling);
-        assert!(!orchard);
-        assert!(!transparent);
+/// Count the shielded outputs that can carry a memo, without a key.
+///
+/// Whether an output has a memo is only known after decryption. Every
+/// output of a full transaction carries a full-length note ciphertext, memo
+/// field included (only compact ciphertexts served to light clients omit
+/// it), so every Sapling output and Orchard action counts.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The number of Sapling outputs and Orchard actions.
+pub fn memo_capable_outputs(tx: &Transaction) -> usize {
+    let sapling = tx
+        .sapling_bundle()
+        .map_or(0, |bundle| bundle.shielded_outputs().len());
+    let orchard = tx
+        .orchard_bundle()
+        .map_or(0, |bundle| bundle.actions().len());
+    sapling + orchard
+}
+
+/// Count a transaction's inputs and outputs in each pool.
+///
+/// This needs no viewing key and performs no decryption.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The transparent, Sapling and Orchard counts.
+pub fn output_counts(tx: &Transaction) -> OutputCounts {
+    let mut counts = OutputCounts::default();
+    if let Some(bundle) = tx.transparent_bundle() {
+        counts.transparent_inputs = bundle.vin.len();
+        counts.transparent_outputs = bundle.vout.len();
     }
+    if let Some(bundle) = tx.sapling_bundle() {
+        counts.sapling_spends = bundle.shielded_spends().len();
+        counts.sapling_outputs = bundle.shielded_outputs().len();
+    }
+    if let Some(bundle) = tx.orchard_bundle() {
+        counts.orchard_actions = bundle.actions().len();
+    }
+    counts
+}
 
-    /// Regression test for issue with scanning testnet transaction
-    /// txid: 0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141
-    /// seed: ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge
-    ///       safe era spread lawn goat potato punch physical lamp oyster crisp attract
-    ///
-    /// This transaction contains a transparent output to the first address of the wallet.
-    /// The scan should not panic with "index out of bounds".
-    #[test]
-    fn test_scan_testnet_transparent_transaction() {
-        // Seed phrase that generates the wallet
-        const SEED_PHRASE: &str = "ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge safe era spread lawn goat potato punch physical lamp oyster crisp attract";
+/// Work units for parsing a transaction and processing its transparent part.
+const BASE_WORK_UNITS: u64 = 1;
 
-        // Raw transaction hex loaded from testdata file
-        // txid: 0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141
-        const TX_HEX: &str = include_str!("testdata/tx_0411ffa7.hex");
+/// Work units per Sapling output.
+const SAPLING_OUTPUT_WORK_UNITS: u64 = 1;
 
-        // First, restore the wallet to get the viewing key
-        let wallet = crate::wallet::restore_wallet(
-            SEED_PHRASE,
-            Network::TestNetwork,
-            0, // account index
-            0, // address index
-        )
-        .expect("Failed to restore wallet");
+/// Work units per Orchard action: trial decryption with the incoming
+/// viewing key, then output recovery with the outgoing viewing keys.
+const ORCHARD_ACTION_WORK_UNITS: u64 = 2;
 
-        // Parse the transaction
-        let result = scan_transaction_hex(
-            TX_HEX,
-            &wallet.unified_full_viewing_key,
-            Network::TestNetwork,
-            None,
-        );
+/// Estimate the cost of scanning a transaction, for progress reporting.
+///
+/// Shielded outputs dominate scanning time, so the estimate is a weighted
+/// count of them plus a small constant for the rest of the transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The work units; only meaningful relative to other transactions.
+pub fn scan_work_units(tx: &Transaction) -> u64 {
+    let counts = output_counts(tx);
+    BASE_WORK_UNITS
+        + SAPLING_OUTPUT_WORK_UNITS * counts.sapling_outputs as u64
+        + ORCHARD_ACTION_WORK_UNITS * counts.orchard_actions as u64
+}
 
-        // The scan should succeed without panicking
-        assert!(
-            result.is_ok(),
-            "Scan should succeed, got error: {:?}",
-            result.err()
-        );
+/// Compute a transaction's ID and its authorizing data commitment.
+///
+/// The txid (ZIP 244 for v5 transactions) commits to the effecting data
+/// only; the auth digest commits to the signatures and proofs. Together they
+/// identify the exact transaction bytes.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The txid and auth digest as hex, both in display (reversed) byte order.
+pub fn txid_and_auth_digest(tx: &Transaction) -> (String, String) {
+    let mut auth_digest = tx.auth_commitment().as_bytes().to_vec();
+    auth_digest.reverse();
+    (tx.txid().to_string(), hex::encode(auth_digest))
+}
 
-        let scan_result = result.unwrap();
+/// ZIP 317 marginal fee per logical action, in zatoshis.
+pub const ZIP317_MARGINAL_FEE: u64 = 5_000;
 
-        // Transaction should have the correct txid (display format)
-        assert_eq!(
-            scan_result.txid,
-            "0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141"
-        );
+/// ZIP 317 number of logical actions covered by the minimum fee.
+pub const ZIP317_GRACE_ACTIONS: usize = 2;
 
-        // Should find transparent outputs (the transaction has 1 transparent output)
-        assert!(
+/// ZIP 317 size in bytes of a standard P2PKH transparent input.
+const P2PKH_STANDARD_INPUT_SIZE: usize = 150;
+
+/// ZIP 317 size in bytes of a standard P2PKH transparent output.
+const P2PKH_STANDARD_OUTPUT_SIZE: usize = 34;
+
+/// Count the ZIP 317 logical actions of a transaction.
+fn zip317_logical_actions(tx: &Transaction) -> usize {
+    let (tx_in_size, tx_out_size) = tx
+        .transparent_bundle()
+        .map(|bundle| {
+            let in_size = bundle
+                .vin
+                .iter()
+                .map(|input| {
+                    let mut bytes = Vec::new();
+                    input.write(&mut bytes).map(|_| bytes.len()).unwrap_or(0)
+                })
+                .sum();
+            let out_size = bundle
+                .vout
+                .iter()
+                .map(|output| {
+                    let mut bytes = Vec::new();
+                    output.write(&mut bytes).map(|_| bytes.len()).unwrap_or(0)
+                })
+                .sum();
+            (in_size, out_size)
+        })
+        .unwrap_or((0, 0));
+
+    let transparent = tx_in_size
+        .div_ceil(P2PKH_STANDARD_INPUT_SIZE)
+        .max(tx_out_size.div_ceil(P2PKH_STANDARD_OUTPUT_SIZE));
+    let sprout = tx
+        .sprout_bundle()
+        .map_or(0, |bundle| 2 * bundle.joinsplits.len());
+    let sapling = tx.sapling_bundle().map_or(0, |bundle| {
+        bundle
+            .shielded_spends()
+            .len()
+            .max(bundle.shielded_outputs().len())
+    });
+    let orchard = tx
+        .orchard_bundle()
+        .map_or(0, |bundle| bundle.actions().len());
+
+    transparent + sprout + sapling + orchard
+}
+
+/// Compute the ZIP 317 conventional fee of a transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// `ZIP317_MARGINAL_FEE` times the number of logical actions, with a
+/// minimum of `ZIP317_GRACE_ACTIONS` actions.
+pub fn zip317_conventional_fee(tx: &Transaction) -> u64 {
+    zip317_fee_for_actions(zip317_logical_actions(tx))
+}
+
+/// ZIP 317 conventional fee for a number of logical actions.
+pub(crate) fn zip317_fee_for_actions(logical_actions: usize) -> u64 {
+    ZIP317_MARGINAL_FEE * logical_actions.max(ZIP317_GRACE_ACTIONS) as u64
+}
+
+/// Compute the fee a transaction pays from its value balances.
+///
+/// Transparent inputs do not carry their values, so the fee can only be
+/// computed for transactions without transparent inputs (this also excludes
+/// coinbase transactions, which pay no fee).
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The fee in zatoshis, or `None` if the transaction spends transparent
+/// inputs, has a Sprout bundle or its balances do not add up to a
+/// non-negative fee.
+pub fn transaction_fee(tx: &Transaction) -> Option<u64> {
+    if tx.sprout_bundle().is_some() {
+        return None;
+    }
+
+    let mut fee = 0i64;
+    if let Some(bundle) = tx.transparent_bundle() {
+        if !bundle.vin.is_empty() {
+            return None;
+        }
+        for output in &bundle.vout {
+            fee = fee.checked_sub(i64::try_from(u64::from(output.value())).ok()?)?;
+        }
+    }
+    if let Some(bundle) = tx.sapling_bundle() {
+        fee = fee.checked_add(i64::from(*bundle.value_balance()))?;
+    }
+    if let Some(bundle) = tx.orchard_bundle() {
+        fee = fee.checked_add(i64::from(*bundle.value_balance()))?;
+    }
+    u64::try_from(fee).ok()
+}
+
+/// Check whether a transaction pays at least the ZIP 317 conventional fee.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// True if the fee is known (see [`transaction_fee`]) and at least
+/// [`zip317_conventional_fee`], false otherwise.
+pub fn is_zip317_compliant(tx: &Transaction) -> bool {
+    transaction_fee(tx).is_some_and(|fee| fee >= zip317_conventional_fee(tx))
+}
+
+/// Get the anchor of a transaction's Orchard bundle.
+///
+/// The anchor is the root of the Orchard note commitment tree that the
+/// bundle's spends were proven against.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The anchor as hex, or `None` if the transaction has no Orchard bundle.
+pub fn orchard_anchor(tx: &Transaction) -> Option<String> {
+    tx.orchard_bundle()
+        .map(|bundle| hex::encode(bundle.anchor().to_bytes()))
+}
+
+/// List the Orchard note commitments a transaction adds to the tree.
+///
+/// Every action adds its `cmx`, including padding actions, in bundle order.
+/// Appending them in this order to the Orchard note commitment tree gives
+/// the tree (and frontier) after the transaction.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The `cmx` values as hex, empty if the transaction has no Orchard bundle.
+pub fn orchard_cmx_list(tx: &Transaction) -> Vec<String> {
+    tx.orchard_bundle()
+        .map(|bundle| {
+            bundle
+                .actions()
+                .iter()
+                .map(|action| hex::encode(action.cmx().to_bytes()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// List the ephemeral public keys of a transaction's Orchard actions.
+///
+/// Together with the `cmx` and note ciphertext, the ephemeral key is all a
+/// client needs to trial-decrypt an action itself.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The 32-byte `epk` of each action as hex, in bundle order (including
+/// padding actions), empty if the transaction has no Orchard bundle.
+pub fn orchard_ephemeral_keys(tx: &Transaction) -> Vec<String> {
+    tx.orchard_bundle()
+        .map(|bundle| {
+            bundle
+                .actions()
+                .iter()
+                .map(|action| hex::encode(action.encrypted_note().epk_bytes))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the anchor used by a transaction's Sapling spends.
+///
+/// All spends of a v5 transaction share one anchor; for v4 transactions the
+/// anchor of the first spend is returned.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The anchor as hex, or `None` if the transaction has no Sapling spends.
+pub fn sapling_anchor(tx: &Transaction) -> Option<String> {
+    tx.sapling_bundle()
+        .and_then(|bundle| bundle.shielded_spends().first())
+        .map(|spend| hex::encode(spend.anchor().to_bytes()))
+}
+
+/// Count the distinct recipients a transaction paid on behalf of the wallet.
+///
+/// Only outputs the wallet sent can be attributed, by recovering them with
+/// its outgoing viewing keys, so the count is zero for transactions the
+/// wallet did not send. Outputs to the wallet's own addresses (change) are
+/// excluded, and several outputs to the same address count once. Orchard
+/// and Sapling outputs are counted; transparent outputs cannot be attributed
+/// to a sender with a viewing key.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `viewing_key` - The viewing key (UFVK with an Orchard or Sapling
+///   component)
+/// * `network` - The network the viewing key is for
+///
+/// # Returns
+///
+/// The number of distinct external recipients.
+pub fn distinct_recipients(
+    tx: &Transaction,
+    viewing_key: &str,
+    network: Network,
+) -> Result<usize, ScannerError> {
+    parse_viewing_key_capabilities(viewing_key)?;
+
+    let orchard = match (
+        tx.orchard_bundle(),
+        extract_orchard_fvk(viewing_key, network)?,
+    ) {
+        (Some(bundle), Some(fvk)) => orchard_external_recipients(bundle.actions(), &fvk).len(),
+        _ => 0,
+    };
+    let sapling = match (
+        tx.sapling_bundle(),
+        extract_sapling_dfvk(viewing_key, network)?,
+    ) {
+        (Some(bundle), Some(dfvk)) => {
+            sapling_external_recipients(bundle.shielded_outputs(), &dfvk).len()
+        }
+        _ => 0,
+    };
+    Ok(orchard + sapling)
+}
+
+/// Check whether a transaction was mined before a wallet's birthday.
+///
+/// Transactions below the birthday height cannot involve the wallet, so
+/// scanners can skip them. If either height is unknown the transaction is
+/// never considered to be below the birthday.
+///
+/// # Arguments
+///
+/// * `height` - The block height the transaction was mined at, if known
+/// * `birthday_height` - The wallet birthday height, if known
+pub fn is_below_birthday(height: Option<u32>, birthday_height: Option<u32>) -> bool {
+    match (height, birthday_height) {
+        (Some(height), Some(birthday)) => height < birthday,
+        _ => false,
+    }
+}
+
+/// Size of a serialized Orchard note: recipient (43) || value (8, LE) ||
+/// rho (32) || rseed (32).
+pub const ORCHARD_NOTE_SIZE: usize = 43 + 8 + 32 + 32;
+
+/// Size of a serialized Sapling note: recipient (43) || value (8, LE) ||
+/// rseed (32, post-ZIP 212) || position in the note commitment tree (8, LE).
+pub const SAPLING_NOTE_SIZE: usize = 43 + 8 + 32 + 8;
+
+/// Serialize an Orchard note into the layout accepted by
+/// [`compute_orchard_nullifier`].
+pub fn encode_orchard_note(note: &orchard::Note) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(ORCHARD_NOTE_SIZE);
+    bytes.extend_from_slice(&note.recipient().to_raw_address_bytes());
+    bytes.extend_from_slice(&note.value().inner().to_le_bytes());
+    bytes.extend_from_slice(&note.rho().to_bytes());
+    bytes.extend_from_slice(note.rseed().as_bytes());
+    bytes
+}
+
+/// Compute the nullifier of an Orchard note.
+///
+/// This is the same nullifier `scan_transaction` reports for a decrypted
+/// note, exposed so it can be reconciled against a light wallet server.
+///
+/// # Arguments
+///
+/// * `note_bytes` - The note serialized with [`encode_orchard_note`]
+/// * `ufvk` - The unified full viewing key that owns the note
+///
+/// # Returns
+///
+/// The nullifier as a hex string, or `ScannerError::NoteNotOwned` if the
+/// note's recipient was not derived from the viewing key.
+pub fn compute_orchard_nullifier(note_bytes: &[u8], ufvk: &str) -> Result<String, ScannerError> {
+    let (note, fvk) = decode_owned_orchard_note(note_bytes, ufvk)?;
+    Ok(hex::encode(note.nullifier(&fvk).to_bytes()))
+}
+
+/// Get the `rho` of an Orchard note.
+///
+/// `rho` is the nullifier of the note spent in the action that created the
+/// note, and the base its own nullifier is derived from. Clients verifying
+/// spends themselves need it to recompute the nullifier.
+///
+/// # Arguments
+///
+/// * `note_bytes` - The note serialized with [`encode_orchard_note`]
+/// * `ufvk` - The unified full viewing key that owns the note
+///
+/// # Returns
+///
+/// `rho` as a hex string, or `ScannerError::NoteNotOwned` if the note's
+/// recipient was not derived from the viewing key.
+pub fn orchard_note_rho(note_bytes: &[u8], ufvk: &str) -> Result<String, ScannerError> {
+    let (note, _) = decode_owned_orchard_note(note_bytes, ufvk)?;
+    Ok(hex::encode(note.rho().to_bytes()))
+}
+
+/// Decode a note serialized with [`encode_orchard_note`] and check that it
+/// was sent to an address of the viewing key.
+fn decode_owned_orchard_note(
+    note_bytes: &[u8],
+    ufvk: &str,
+) -> Result<(orchard::Note, OrchardFvk), ScannerError> {
+    let fvk = unified::Ufvk::decode(ufvk)
+        .ok()
+        .and_then(|(_, ufvk)| orchard_fvk_item(&ufvk))
+        .ok_or(ScannerError::UnrecognizedViewingKey)?;
+
+    let bytes: &[u8; ORCHARD_NOTE_SIZE] = note_bytes.try_into().map_err(|_| {
+        ScannerError::InvalidNote(format!(
+            "Expected {} bytes, got {}",
+            ORCHARD_NOTE_SIZE,
+            note_bytes.len()
+        ))
+    })?;
+
+    let recipient_bytes: [u8; 43] = bytes[0..43].try_into().expect("slice length is 43");
+    let value_bytes: [u8; 8] = bytes[43..51].try_into().expect("slice length is 8");
+    let rho_bytes: [u8; 32] = bytes[51..83].try_into().expect("slice length is 32");
+    let rseed_bytes: [u8; 32] = bytes[83..115].try_into().expect("slice length is 32");
+
+    let recipient = Option::from(orchard::Address::from_raw_address_bytes(&recipient_bytes))
+        .ok_or_else(|| ScannerError::InvalidNote("Invalid recipient address".to_string()))?;
+    let rho: Rho = Option::from(Rho::from_bytes(&rho_bytes))
+        .ok_or_else(|| ScannerError::InvalidNote("Invalid rho".to_string()))?;
+    let rseed = Option::from(RandomSeed::from_bytes(rseed_bytes, &rho))
+        .ok_or_else(|| ScannerError::InvalidNote("Invalid random seed".to_string()))?;
+    let note: orchard::Note = Option::from(orchard::Note::from_parts(
+        recipient,
+        orchard::value::NoteValue::from_raw(u64::from_le_bytes(value_bytes)),
+        rho,
+        rseed,
+    ))
+    .ok_or_else(|| ScannerError::InvalidNote("Invalid note commitment".to_string()))?;
+
+    // Only notes sent to an address of this FVK have a meaningful nullifier
+    if fvk.scope_for_address(&note.recipient()).is_none() {
+        return Err(ScannerError::NoteNotOwned);
+    }
+
+    Ok((note, fvk))
+}
+
+/// Compute the nullifier of a Sapling note.
+///
+/// Sapling nullifiers depend on the note's position in the note commitment
+/// tree, so the position is part of the serialized note.
+///
+/// # Arguments
+///
+/// * `note_bytes` - The note as recipient (43) || value (8, LE) ||
+///   rseed (32) || position (8, LE)
+/// * `ufvk` - The unified full viewing key that owns the note
+///
+/// # Returns
+///
+/// The nullifier as a hex string, or `ScannerError::NoteNotOwned` if the
+/// note's recipient was not derived from the viewing key.
+pub fn compute_sapling_nullifier(note_bytes: &[u8], ufvk: &str) -> Result<String, ScannerError> {
+    let dfvk = [Network::MainNetwork, Network::TestNetwork]
+        .into_iter()
+        .find_map(|network| extract_sapling_dfvk(ufvk, network).ok().flatten())
+        .ok_or(ScannerError::UnrecognizedViewingKey)?;
+
+    let bytes: &[u8; SAPLING_NOTE_SIZE] = note_bytes.try_into().map_err(|_| {
+        ScannerError::InvalidNote(format!(
+            "Expected {} bytes, got {}",
+            SAPLING_NOTE_SIZE,
+            note_bytes.len()
+        ))
+    })?;
+
+    let recipient_bytes: [u8; 43] = bytes[0..43].try_into().expect("slice length is 43");
+    let value_bytes: [u8; 8] = bytes[43..51].try_into().expect("slice length is 8");
+    let rseed_bytes: [u8; 32] = bytes[51..83].try_into().expect("slice length is 32");
+    let position_bytes: [u8; 8] = bytes[83..91].try_into().expect("slice length is 8");
+
+    let recipient = sapling_crypto::PaymentAddress::from_bytes(&recipient_bytes)
+        .ok_or_else(|| ScannerError::InvalidNote("Invalid recipient address".to_string()))?;
+
+    // The scope the address was derived under determines the nullifier key
+    let (_, scope) = dfvk
+        .decrypt_diversifier(&recipient)
+        .ok_or(ScannerError::NoteNotOwned)?;
+
+    let note = sapling_crypto::Note::from_parts(
+        recipient,
+        sapling_crypto::value::NoteValue::from_raw(u64::from_le_bytes(value_bytes)),
+        sapling_crypto::Rseed::AfterZip212(rseed_bytes),
+    );
+    let nullifier = note.nf(&dfvk.to_nk(scope), u64::from_le_bytes(position_bytes));
+
+    Ok(hex::encode(nullifier.0))
+}
+
+/// Scan a batch of transactions from hex, one after another.
+///
+/// Each transaction is parsed and scanned independently; a failure for one
+/// transaction does not abort the batch.
+///
+/// With a cursor, the transactions must be the ones following the cursor in
+/// chain order. Shielded notes are given their commitment tree positions and
+/// the cursor is advanced past each scanned transaction, including its
+/// height when known, so saving it lets an interrupted sync resume. The
+/// cursor stops advancing at the first transaction that fails, so a resumed
+/// scan retries it.
+///
+/// # Arguments
+///
+/// * `tx_hexes` - The raw transactions as hexadecimal strings
+/// * `heights` - The block height of each transaction, in the same order.
+///   Transactions past the end of the slice, or with `None`, have an
+///   unknown height.
+/// * `viewing_key` - The viewing key (UFVK, UIVK, or legacy Sapling)
+/// * `network` - The network to use for parsing
+/// * `cursor` - Optional scan progress to continue from and update
+///
+/// # Returns
+///
+/// One scan result per input transaction, in input order.
+pub fn scan_transactions<S: AsRef<str>>(
+    tx_hexes: &[S],
+    heights: &[Option<u32>],
+    viewing_key: &str,
+    network: Network,
+    mut cursor: Option<&mut ScanCursor>,
+) -> Vec<Result<ScanResult, ScannerError>> {
+    let mut results = Vec::with_capacity(tx_hexes.len());
+    for (i, tx_hex) in tx_hexes.iter().enumerate() {
+        let height = heights.get(i).copied().flatten();
+        let tx = match parse_transaction(tx_hex.as_ref(), network) {
+            Ok(tx) => tx,
+            Err(e) => {
+                cursor = None;
+                results.push(Err(e));
+                continue;
+            }
+        };
+        let mut result = scan_transaction(&tx, viewing_key, network, height);
+
+        if result.is_err() {
+            cursor = None;
+        }
+        if let (Ok(scan), Some(cursor)) = (&mut result, cursor.as_deref_mut()) {
+            for note in &mut scan.notes {
+                let base = match note.pool {
+                    Pool::Sapling => cursor.sapling_position,
+                    Pool::Orchard => cursor.orchard_position,
+                    Pool::Transparent => continue,
+                };
+                note.position = Some(base + note.output_index as u64);
+            }
+
+            let counts = output_counts(&tx);
+            cursor.sapling_position += counts.sapling_outputs as u64;
+            cursor.orchard_position += counts.orchard_actions as u64;
+            cursor.last_txid = Some(scan.txid.clone());
+            if height.is_some() {
+                cursor.last_height = height;
+            }
+        }
+        results.push(result);
+    }
+    results
+}
+
+/// Scan a batch of transactions from hex across all available cores.
+///
+/// Behaves exactly like [`scan_transactions`] without heights or a cursor, but
+/// trial-decrypts the transactions in parallel using rayon. Only available
+/// with the `parallel` feature, which must not be enabled for WASM builds.
+///
+/// # Returns
+///
+/// One scan result per input transaction, in input order.
+#[cfg(feature = "parallel")]
+pub fn scan_transactions_parallel<S: AsRef<str> + Sync>(
+    tx_hexes: &[S],
+    viewing_key: &str,
+    network: Network,
+) -> Vec<Result<ScanResult, ScannerError>> {
+    use rayon::prelude::*;
+
+    // Indexed parallel iterators preserve the input order on collect
+    tx_hexes
+        .par_iter()
+        .map(|tx_hex| scan_transaction_hex(tx_hex.as_ref(), viewing_key, network, None))
+        .collect()
+}
+
+/// Public structure of a transaction relevant to its privacy.
+#[derive(Debug, Clone, Copy, Default)]
+struct PrivacyInputs {
+    transparent_inputs: usize,
+    transparent_outputs: usize,
+    sapling_spends: usize,
+    sapling_outputs: usize,
+    orchard_actions: usize,
+    sapling_value_balance: i64,
+    orchard_value_balance: i64,
+}
+
+/// Estimate how much a transaction leaks from its public structure.
+///
+/// The score starts at 100 and is reduced for each publicly visible
+/// property: transparent inputs and outputs, values moving between the
+/// transparent and shielded pools, use of more than one shielded pool, and a
+/// lack of shielded outputs to hide among. Memos are encrypted and never
+/// visible, so they do not affect the score. No viewing key is required.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// A `PrivacyReport` with the score and the factors that lowered it.
+pub fn privacy_score(tx: &Transaction) -> PrivacyReport {
+    let mut inputs = PrivacyInputs::default();
+
+    if let Some(bundle) = tx.transparent_bundle() {
+        inputs.transparent_inputs = bundle.vin.len();
+        inputs.transparent_outputs = bundle.vout.len();
+    }
+    if let Some(bundle) = tx.sapling_bundle() {
+        inputs.sapling_spends = bundle.shielded_spends().len();
+        inputs.sapling_outputs = bundle.shielded_outputs().len();
+        inputs.sapling_value_balance = i64::from(*bundle.value_balance());
+    }
+    if let Some(bundle) = tx.orchard_bundle() {
+        inputs.orchard_actions = bundle.actions().len();
+        inputs.orchard_value_balance = i64::from(*bundle.value_balance());
+    }
+
+    score_privacy(&inputs)
+}
+
+/// Compute the privacy report for the given transaction structure.
+fn score_privacy(inputs: &PrivacyInputs) -> PrivacyReport {
+    let mut factors = Vec::new();
+    let mut add = |description: &str, penalty: u8| {
+        factors.push(PrivacyFactor {
+            description: description.to_string(),
+            penalty,
+        });
+    };
+
+    let shielded_outputs = inputs.sapling_outputs + inputs.orchard_actions;
+    let shielded_spends = inputs.sapling_spends + inputs.orchard_actions;
+
+    if shielded_outputs == 0 && shielded_spends == 0 {
+        add(
+            "No shielded components: all amounts and addresses are public",
+            40,
+        );
+    }
+    if inputs.transparent_inputs > 0 {
+        add(
+            "Transparent inputs reveal the sending addresses and amounts",
+            30,
+        );
+    }
+    if inputs.transparent_outputs > 0 {
+        add(
+            "Transparent outputs reveal the receiving addresses and amounts",
+            30,
+        );
+    }
+    if inputs.sapling_value_balance != 0 || inputs.orchard_value_balance != 0 {
+        add(
+            "Value moving in or out of a shielded pool is publicly visible",
+            10,
+        );
+    }
+    if inputs.sapling_spends + inputs.sapling_outputs > 0 && inputs.orchard_actions > 0 {
+        add(
+            "Using both Sapling and Orchard links activity across pools",
+            10,
+        );
+    }
+    if shielded_outputs == 1 {
+        add("A single shielded output provides no decoys", 5);
+    }
+
+    let penalty: u32 = factors.iter().map(|f| u32::from(f.penalty)).sum();
+    let score = 100u32.saturating_sub(penalty) as u8;
+
+    PrivacyReport { score, factors }
+}
+
+/// Detect value moving between pools across the turnstile.
+///
+/// Transitions are read from the Sapling and Orchard value balances: a
+/// positive balance moves value out of the pool, a negative one into it.
+/// Transparent inputs and outputs count as a source or destination when a
+/// shielded pool's balance points the matching way. A shielded pool paying
+/// only the fee is not reported, unless the transaction also has
+/// transparent outputs.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+///
+/// # Returns
+///
+/// The detected transitions as `"<from>-><to>"` (e.g. `"sapling->orchard"`,
+/// `"transparent->orchard"`), or an empty list if value stays in one pool.
+pub fn crosses_turnstile(tx: &Transaction) -> Vec<String> {
+    let sapling = tx
+        .sapling_bundle()
+        .map_or(0, |bundle| i64::from(*bundle.value_balance()));
+    let orchard = tx
+        .orchard_bundle()
+        .map_or(0, |bundle| i64::from(*bundle.value_balance()));
+    let (has_inputs, has_outputs) = tx.transparent_bundle().map_or((false, false), |bundle| {
+        (!bundle.vin.is_empty(), !bundle.vout.is_empty())
+    });
+
+    let shielded = [(Pool::Sapling, sapling), (Pool::Orchard, orchard)];
+    let mut transitions = Vec::new();
+    for (from, from_balance) in shielded {
+        for (to, to_balance) in shielded {
+            if from != to && from_balance > 0 && to_balance < 0 {
+                transitions.push(format!("{}->{}", from.as_str(), to.as_str()));
+            }
+        }
+    }
+    for (pool, balance) in shielded {
+        if has_inputs && balance < 0 {
+            transitions.push(format!("transparent->{}", pool.as_str()));
+        }
+        if has_outputs && balance > 0 {
+            transitions.push(format!("{}->transparent", pool.as_str()));
+        }
+    }
+    transitions
+}
+
+/// Decrypt one Orchard output with its ephemeral secret key.
+///
+/// Used to audit disclosures: the sender (or anyone holding the note) can
+/// hand out the `esk` of an output instead of a viewing key. The note
+/// encryption key is agreed between `esk` and the recipient's transmission
+/// key, so the recipient address is needed as well. The recovered note is
+/// checked against the action's commitment and the ZIP 212 `esk`
+/// derivation.
+///
+/// # Arguments
+///
+/// * `tx` - The parsed transaction
+/// * `output_index` - Index of the action within the Orchard bundle
+/// * `esk_hex` - The 32-byte ephemeral secret key as hex
+/// * `recipient` - The recipient address (unified, with an Orchard receiver)
+/// * `network` - The network the address is encoded for
+///
+/// # Returns
+///
+/// The decrypted note, with no scope since it was neither received nor
+/// recovered with our keys, or `ScannerError::InvalidNote` if the action
+/// does not exist or does not decrypt with `esk`.
+pub fn decrypt_with_esk(
+    tx: &Transaction,
+    output_index: usize,
+    esk_hex: &str,
+    recipient: &str,
+    network: Network,
+) -> Result<ScannedNote, ScannerError> {
+    let action = tx
+        .orchard_bundle()
+        .and_then(|bundle| bundle.actions().get(output_index))
+        .ok_or_else(|| {
+            ScannerError::InvalidNote(format!("No Orchard action at index {}", output_index))
+        })?;
+    let esk_bytes: [u8; 32] = hex::decode(esk_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ScannerError::InvalidNote("esk must be 32 bytes of hex".to_string()))?;
+    let recipient = match Address::decode(&network, recipient.trim()) {
+        Some(Address::Unified(ua)) => ua.orchard().copied(),
+        _ => None,
+    }
+    .ok_or_else(|| ScannerError::InvalidAddress("No Orchard receiver".to_string()))?;
+
+    // The outgoing plaintext is pk_d || esk, which is how the domain parses
+    // both keys from bytes
+    let mut out_plaintext = [0u8; 64];
+    out_plaintext[..32].copy_from_slice(&recipient.to_raw_address_bytes()[11..]);
+    out_plaintext[32..].copy_from_slice(&esk_bytes);
+    let out_plaintext = OutPlaintextBytes(out_plaintext);
+    let (Some(pk_d), Some(esk)) = (
+        OrchardDomain::extract_pk_d(&out_plaintext),
+        OrchardDomain::extract_esk(&out_plaintext),
+    ) else {
+        return Err(ScannerError::InvalidNote("Invalid esk".to_string()));
+    };
+
+    let domain = OrchardDomain::for_action(action);
+    let (note, address, memo_bytes) = try_output_recovery_with_pkd_esk(&domain, pk_d, esk, action)
+        .ok_or_else(|| {
+            ScannerError::InvalidNote(format!(
+                "Orchard action {} does not decrypt with this esk",
+                output_index
+            ))
+        })?;
+
+    Ok(ScannedNote {
+        output_index,
+        pool: Pool::Orchard,
+        value: note.value().inner(),
+        commitment: hex::encode(action.cmx().to_bytes()),
+        nullifier: None,
+        memo: memo_to_text(&memo_bytes, MemoTrim::default()),
+        address: UnifiedAddress::from_receivers(Some(address), None, None)
+            .map(|ua| ua.encode(&network)),
+        debug: None,
+        scope: None,
+        position: None,
+        from_tex: false,
+        memo_bytes: None,
+        commitment_verified: orchard_commitment_matches(&note, action.cmx()),
+        is_external: false,
+    })
+}
+
+/// Compute the txid of a pasted raw transaction.
+///
+/// The hex is cleaned with [`clean_tx_hex`] and parsed without needing to
+/// know the network. Transactions larger than [`MAX_TX_BYTES`] are rejected.
+///
+/// # Arguments
+///
+/// * `tx_hex` - The raw transaction as hex, as pasted by the user
+///
+/// # Returns
+///
+/// The txid in display (reversed) byte order.
+pub fn txid_from_hex(tx_hex: &str) -> Result<String, ScannerError> {
+    check_input_len(tx_hex, MAX_TX_BYTES)?;
+    let tx_hex = clean_tx_hex(tx_hex)?;
+    let tx_bytes =
+        hex::decode(&tx_hex).map_err(|e| ScannerError::InvalidTransactionHex(e.to_string()))?;
+    Ok(read_transaction(&tx_bytes, MAX_TX_BYTES)?
+        .txid()
+        .to_string())
+}
+
+/// List the shielded outputs of a transaction that are the wallet's change.
+///
+/// Change is sent to the key's internal addresses and encrypted with its
+/// internal OVK, so the scanner recovers it as an outgoing note. Those
+/// outgoing notes whose address is internal (see
+/// [`crate::wallet::is_internal_address`]) are reported, so the UI can hide
+/// them from the received list.
+///
+/// # Arguments
+///
+/// * `result` - The scan result of the transaction
+/// * `ufvk` - The unified full viewing key it was scanned with
+/// * `network` - The network the key is for
+///
+/// # Returns
+///
+/// The `(pool, output_index)` of each change output, in scan order.
+pub fn change_output_indices(
+    result: &ScanResult,
+    ufvk: &str,
+    network: Network,
+) -> Result<Vec<(String, usize)>, ScannerError> {
+    parse_viewing_key_capabilities(ufvk)?;
+    Ok(result
+        .notes
+        .iter()
+        .filter(|note| note.pool != Pool::Transparent && note.is_outgoing())
+        .filter(|note| {
+            note.address
+                .as_deref()
+                .is_some_and(|address| is_internal_address(ufvk, address, network).unwrap_or(false))
+        })
+        .map(|note| (note.pool.as_str().to_string(), note.output_index))
+        .collect())
+}
+
+/// Compute a transaction's net effect on the wallet's balance.
+///
+/// The net change is the value the transaction pays to the wallet minus the
+/// value of the wallet's notes it spends. Received are the notes the wallet
+/// owns (see [`ScannedNote::is_owned`]) and outputs recovered with its OVK
+/// that go back to one of its own addresses, such as change. Spent notes
+/// are looked up in `owned_notes`: shielded ones by nullifier, transparent
+/// ones by the outpoint the transaction's inputs reference. The fee is thus
+/// included in a send's net change.
+///
+/// # Arguments
+///
+/// * `result` - The scan result of the transaction
+/// * `owned_notes` - The wallet's notes, including spent ones
+/// * `transparent_addresses` - The wallet's transparent addresses, e.g.
+///   its transparent watch set
+///
+/// # Returns
+///
+/// The net change in zatoshis: positive for funds received, negative for
+/// funds sent.
+pub fn net_change(
+    result: &ScanResult,
+    owned_notes: &NoteCollection,
+    transparent_addresses: &[String],
+) -> i128 {
+    let received: u64 = result
+        .notes
+        .iter()
+        .filter(|note| {
+            note.is_owned(transparent_addresses) || (note.is_outgoing() && !note.is_external)
+        })
+        .map(|note| note.value)
+        .sum();
+
+    let spent: u64 = owned_notes
+        .notes
+        .iter()
+        .filter(|owned| match (owned.pool, &owned.nullifier) {
+            (Pool::Transparent, _) => result.transparent_spends.iter().any(|spend| {
+                spend.prevout_txid == owned.txid && spend.prevout_index == owned.output_index
+            }),
+            (_, Some(nullifier)) => result
+                .spent_nullifiers
+                .iter()
+                .any(|spent| &spent.nullifier == nullifier),
+            (_, None) => false,
+        })
+        .map(|owned| owned.value)
+        .sum();
+
+    i128::from(received) - i128::from(spent)
+}
+
+/// The `OP_RETURN` opcode, which marks an output as unspendable.
+const OP_RETURN: u8 = 0x6a;
+/// `OP_0` pushes an empty item; `OP_1` to `OP_16` push the numbers 1 to 16.
+const OP_0: u8 = 0x00;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+/// Opcodes pushing the next 1, 2 or 4 little-endian length bytes' worth of data.
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+
+/// Extract the data carried by an `OP_RETURN` script.
+///
+/// The script must start with `OP_RETURN` and contain only data pushes
+/// (including `OP_0` and `OP_1` to `OP_16`) after it. The pushed bytes are
+/// concatenated, so a single push yields its payload unchanged.
+///
+/// # Arguments
+///
+/// * `script` - The raw scriptPubKey bytes
+///
+/// # Returns
+///
+/// The data bytes (empty for a bare `OP_RETURN`), or `None` if the script
+/// is not a well-formed `OP_RETURN` script.
+pub fn op_return_data(script: &[u8]) -> Option<Vec<u8>> {
+    let (&OP_RETURN, mut rest) = script.split_first()? else {
+        return None;
+    };
+
+    let mut data = Vec::new();
+    while let Some((&opcode, tail)) = rest.split_first() {
+        let (len, tail) = match opcode {
+            OP_0 => (0, tail),
+            OP_1..=OP_16 => {
+                data.push(opcode - OP_1 + 1);
+                rest = tail;
+                continue;
+            }
+            0x01..OP_PUSHDATA1 => (usize::from(opcode), tail),
+            OP_PUSHDATA1 => (usize::from(*tail.first()?), &tail[1..]),
+            OP_PUSHDATA2 => {
+                let (len, tail) = tail.split_first_chunk::<2>()?;
+                (usize::from(u16::from_le_bytes(*len)), tail)
+            }
+            OP_PUSHDATA4 => {
+                let (len, tail) = tail.split_first_chunk::<4>()?;
+                (usize::try_from(u32::from_le_bytes(*len)).ok()?, tail)
+            }
+            _ => return None,
+        };
+        if tail.len() < len {
+            return None;
+        }
+        data.extend_from_slice(&tail[..len]);
+        rest = &tail[len..];
+    }
+    Some(data)
+}
+
+/// Check whether a script can never be spent.
+///
+/// Any script starting with `OP_RETURN` fails when executed, whether or not
+/// the data after it is well formed.
+fn is_unspendable(script: &[u8]) -> bool {
+    script.first() == Some(&OP_RETURN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test UFVK for reference
+    const TEST_UFVK: &str = "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd";
+
+    #[test]
+    fn test_parse_viewing_key_capabilities() {
+        let (sapling, orchard, transparent) = parse_viewing_key_capabilities(TEST_UFVK).unwrap();
+        assert!(sapling);
+        assert!(orchard);
+        assert!(transparent);
+    }
+
+    #[test]
+    fn test_extract_orchard_fvk() {
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork).unwrap();
+        assert!(fvk.is_some(), "Should extract Orchard FVK from UFVK");
+    }
+
+    #[test]
+    fn test_invalid_viewing_key() {
+        let result = parse_viewing_key_capabilities("invalid_key");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_legacy_sapling_key() {
+        let result = parse_viewing_key_capabilities("zxviews1something");
+        assert!(result.is_ok());
+        let (sapling, orchard, transparent) = result.unwrap();
+        assert!(sapling);
+        assert!(!orchard);
+        assert!(!transparent);
+    }
+
+    /// Regression test for issue with scanning testnet transaction
+    /// txid: 0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141
+    /// seed: ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge
+    ///       safe era spread lawn goat potato punch physical lamp oyster crisp attract
+    ///
+    /// This transaction contains a transparent output to the first address of the wallet.
+    /// The scan should not panic with "index out of bounds".
+    #[test]
+    fn test_scan_testnet_transparent_transaction() {
+        // Seed phrase that generates the wallet
+        const SEED_PHRASE: &str = "ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge safe era spread lawn goat potato punch physical lamp oyster crisp attract";
+
+        // Raw transaction hex loaded from testdata file
+        // txid: 0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141
+        const TX_HEX: &str = include_str!("testdata/tx_0411ffa7.hex");
+
+        // First, restore the wallet to get the viewing key
+        let wallet = crate::wallet::restore_wallet(
+            SEED_PHRASE,
+            Network::TestNetwork,
+            0, // account index
+            0, // address index
+        )
+        .expect("Failed to restore wallet");
+
+        // Parse the transaction
+        let result = scan_transaction_hex(
+            TX_HEX,
+            &wallet.unified_full_viewing_key,
+            Network::TestNetwork,
+            None,
+        );
+
+        // The scan should succeed without panicking
+        assert!(
+            result.is_ok(),
+            "Scan should succeed, got error: {:?}",
+            result.err()
+        );
+
+        let scan_result = result.unwrap();
+
+        // Transaction should have the correct txid (display format)
+        assert_eq!(
+            scan_result.txid,
+            "0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141"
+        );
+
+        // Should find transparent outputs (the transaction has 1 transparent output)
+        assert!(
             !scan_result.transparent_outputs.is_empty()
                 || !scan_result
                     .notes
@@ -468,245 +2774,2829 @@ mod tests {
             "Should find transparent outputs"
         );
 
-        // Get the wallet's first transparent address for comparison
-        let first_transparent_address = wallet
-            .transparent_address
-            .as_ref()
-            .expect("Wallet should have a transparent address");
+        // Get the wallet's first transparent address for comparison
+        let first_transparent_address = wallet
+            .transparent_address
+            .as_ref()
+            .expect("Wallet should have a transparent address");
+
+        // Verify at least one transparent note has a decoded address
+        let transparent_notes: Vec<_> = scan_result
+            .notes
+            .iter()
+            .filter(|n| n.pool == Pool::Transparent)
+            .collect();
+
+        assert!(
+            !transparent_notes.is_empty(),
+            "Should have transparent notes"
+        );
+
+        // Check that addresses are decoded (not None)
+        let notes_with_addresses: Vec<_> = transparent_notes
+            .iter()
+            .filter(|n| n.address.is_some())
+            .collect();
+
+        assert!(
+            !notes_with_addresses.is_empty(),
+            "Transparent notes should have decoded addresses"
+        );
+
+        // Verify that the transaction output matches the wallet's first transparent address
+        let matching_notes: Vec<_> = transparent_notes
+            .iter()
+            .filter(|n| n.address.as_deref() == Some(first_transparent_address.as_str()))
+            .collect();
+
+        assert!(
+            !matching_notes.is_empty(),
+            "Should find a transparent output matching the wallet's first address: {}",
+            first_transparent_address
+        );
+    }
+
+    /// Test that spending a transparent output is correctly detected.
+    ///
+    /// This test uses two transactions:
+    /// 1. tx_0411ffa7: Receives a transparent output to the wallet
+    /// 2. tx_5aa23ef4: Spends that output
+    ///
+    /// The scanner should detect the spend via transparent_spends matching
+    /// the original prevout reference (txid:vout).
+    ///
+    /// This test mirrors the flow used by the frontend:
+    /// 1. Scan receiving tx -> create StoredNote + LedgerEntry
+    /// 2. Scan spending tx -> mark note as spent + create LedgerEntry
+    /// 3. Verify balance from both note collection and ledger
+    #[test]
+    fn test_scan_detects_transparent_spend() {
+        use crate::types::{LedgerCollection, LedgerEntry, NoteCollection, Pool, StoredNote};
+
+        const WALLET_ID: &str = "test_wallet";
+
+        // Same seed phrase as the receiving test
+        const SEED_PHRASE: &str = "ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge safe era spread lawn goat potato punch physical lamp oyster crisp attract";
+
+        // Load the two transaction hexes
+        const RECEIVING_TX_HEX: &str = include_str!("testdata/tx_0411ffa7.hex");
+        const SPENDING_TX_HEX: &str = include_str!("testdata/tx_5aa23ef4.hex");
+
+        // Restore the wallet
+        let wallet = crate::wallet::restore_wallet(
+            SEED_PHRASE,
+            Network::TestNetwork,
+            0, // account index
+            0, // address index
+        )
+        .expect("Failed to restore wallet");
+        let watched: Vec<String> = wallet.transparent_address.clone().into_iter().collect();
+
+        // Initialize collections (like frontend's localStorage)
+        let mut note_collection = NoteCollection::new();
+        let mut ledger_collection = LedgerCollection::default();
+
+        // ========================================================
+        // Step 1: Scan the receiving transaction
+        // ========================================================
+        let receive_result = scan_transaction_hex(
+            RECEIVING_TX_HEX,
+            &wallet.unified_full_viewing_key,
+            Network::TestNetwork,
+            None,
+        )
+        .expect("Receiving tx scan should succeed");
+
+        // Verify we found a transparent output
+        let transparent_notes: Vec<_> = receive_result
+            .notes
+            .iter()
+            .filter(|n| n.pool == Pool::Transparent && n.value > 0)
+            .collect();
+
+        assert!(
+            !transparent_notes.is_empty(),
+            "Should find transparent output in receiving tx"
+        );
+
+        // Add notes to collection (like frontend's addNote)
+        let received_note = &transparent_notes[0];
+        let timestamp = "2024-01-01T00:00:00Z";
+        let stored_note = StoredNote::from_scanned_note(
+            received_note,
+            &receive_result.txid,
+            WALLET_ID,
+            timestamp,
+            None,
+            receive_result.is_coinbase,
+        )
+        .expect("received notes are stored");
+        note_collection.add_or_update(stored_note.clone());
+
+        // Create ledger entry for receiving transaction (like frontend's createLedgerEntry)
+        let receive_ledger = LedgerEntry::from_scan_result(
+            &receive_result,
+            WALLET_ID,
+            &watched,
+            vec![stored_note.id.clone()], // received note IDs
+            vec![],                       // no spent notes
+            &[],                          // no spent values
+            timestamp,
+        );
+        ledger_collection.add_or_update(receive_ledger);
+
+        // Verify initial state
+        let initial_balance = note_collection.total_balance();
+        let ledger_balance = ledger_collection.compute_balance(WALLET_ID);
+        assert_eq!(
+            initial_balance, received_note.value,
+            "Initial note balance should equal received amount"
+        );
+        assert!(ledger_balance > 0, "Ledger should show positive balance");
+
+        // ========================================================
+        // Step 2: Scan the spending transaction
+        // ========================================================
+        let spend_result = scan_transaction_hex(
+            SPENDING_TX_HEX,
+            &wallet.unified_full_viewing_key,
+            Network::TestNetwork,
+            None,
+        )
+        .expect("Spending tx scan should succeed");
+
+        // Verify the txid
+        assert_eq!(
+            spend_result.txid,
+            "5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f"
+        );
+
+        // Verify transparent_spends contains the prevout reference
+        assert!(
+            !spend_result.transparent_spends.is_empty(),
+            "Spending tx should have transparent_spends"
+        );
+
+        // Find the spend that matches our received note
+        let matching_spend = spend_result
+            .transparent_spends
+            .iter()
+            .find(|s| s.prevout_txid == receive_result.txid && s.prevout_index == 0);
+
+        assert!(
+            matching_spend.is_some(),
+            "Should find spend matching our received output. Spends: {:?}",
+            spend_result.transparent_spends
+        );
+
+        // Mark notes as spent (like frontend's markTransparentSpent)
+        let mark_result = note_collection.mark_spent_by_transparent(
+            &spend_result.transparent_spends,
+            &spend_result.txid,
+            Some(3760288), // block height from issue
+        );
+        assert_eq!(
+            mark_result.marked_count, 1,
+            "Should mark exactly one note as spent"
+        );
+        assert!(
+            mark_result.unmatched_transparent.is_empty(),
+            "All spends should be matched"
+        );
+
+        // Create ledger entry for spending transaction
+        // Note: In a real scenario, the spent note's value would be tracked
+        let spent_note_id = stored_note.id.clone();
+        let spent_value = stored_note.value;
+        let spend_ledger = LedgerEntry::from_scan_result(
+            &spend_result,
+            WALLET_ID,
+            &watched,
+            vec![],              // no new notes received in our wallet
+            vec![spent_note_id], // the note we're spending
+            &[spent_value],      // value of the spent note
+            timestamp,
+        );
+        ledger_collection.add_or_update(spend_ledger);
+
+        // ========================================================
+        // Step 3: Verify final state
+        // ========================================================
+
+        // Verify the note is marked as spent
+        let note = &note_collection.notes[0];
+        assert!(note.is_spent(), "Note should be marked as spent");
+        assert_eq!(
+            note.spent_txid.as_deref(),
+            Some("5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f")
+        );
+        assert_eq!(note.spent_at_height, Some(3760288));
+
+        // Verify balance from note collection is now zero
+        assert_eq!(
+            note_collection.total_balance(),
+            0,
+            "Note balance should be zero after spending"
+        );
+
+        // Verify ledger has two entries
+        let ledger_entries = ledger_collection.entries_for_wallet(WALLET_ID);
+        assert_eq!(ledger_entries.len(), 2, "Ledger should have 2 entries");
+
+        // Verify ledger entries track the flow correctly
+        let receive_entry = ledger_entries
+            .iter()
+            .find(|e| e.txid == receive_result.txid);
+        let spend_entry = ledger_entries.iter().find(|e| e.txid == spend_result.txid);
+
+        assert!(
+            receive_entry.is_some(),
+            "Should have receiving ledger entry"
+        );
+        assert!(spend_entry.is_some(), "Should have spending ledger entry");
+
+        // The receiving entry should show value received
+        assert!(
+            receive_entry.unwrap().value_received > 0,
+            "Receiving entry should have value_received > 0"
+        );
+    }
+
+    #[test]
+    fn test_is_below_birthday() {
+        assert!(is_below_birthday(Some(99), Some(100)));
+        assert!(!is_below_birthday(Some(100), Some(100)));
+        assert!(!is_below_birthday(Some(101), Some(100)));
+        // Unknown heights never skip
+        assert!(!is_below_birthday(None, Some(100)));
+        assert!(!is_below_birthday(Some(99), None));
+        assert!(!is_below_birthday(None, None));
+    }
+
+    // ZOO seed testnet UFVK, used as a non-owning key
+    fn other_ufvk() -> String {
+        crate::wallet::restore_wallet(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .expect("wallet derivation should succeed")
+        .unified_full_viewing_key
+    }
+
+    fn test_orchard_note() -> (orchard::Note, OrchardFvk) {
+        use rand::RngCore;
+
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let mut rng = rand::rngs::OsRng;
+        let mut rho_bytes = [0u8; 32];
+        rng.fill_bytes(&mut rho_bytes);
+        rho_bytes[31] &= 0x3f;
+        let rho = Rho::from_bytes(&rho_bytes).unwrap();
+        let note = orchard::Note::from_parts(
+            fvk.address_at(0u32, Scope::External),
+            orchard::value::NoteValue::from_raw(50_000),
+            rho,
+            RandomSeed::random(&mut rng, &rho),
+        )
+        .unwrap();
+        (note, fvk)
+    }
+
+    #[test]
+    fn test_compute_orchard_nullifier_matches_scanner() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 30_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let scanned = result
+            .notes
+            .iter()
+            .find(|n| n.scope == Some(NoteScope::Incoming))
+            .expect("the note should be received");
+
+        // Decrypt the same note to serialize it
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+        let action = &tx.orchard_bundle().unwrap().actions()[scanned.output_index];
+        let (note, _, _) = try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+            .expect("the scanned action should decrypt");
+
+        let nullifier = compute_orchard_nullifier(&encode_orchard_note(&note), TEST_UFVK)
+            .expect("nullifier computation should succeed");
+        assert_eq!(Some(nullifier), scanned.nullifier);
+    }
+
+    #[test]
+    fn test_compute_orchard_nullifier_rejects_foreign_note() {
+        let (note, _) = test_orchard_note();
+        let result = compute_orchard_nullifier(&encode_orchard_note(&note), &other_ufvk());
+        assert!(matches!(result, Err(ScannerError::NoteNotOwned)));
+    }
+
+    #[test]
+    fn test_orchard_note_rho_of_decrypted_note() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 30_000, b"");
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+        let (action, note) = tx
+            .orchard_bundle()
+            .unwrap()
+            .actions()
+            .iter()
+            .find_map(|action| {
+                try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+                    .map(|(note, _, _)| (action, note))
+            })
+            .expect("one action should decrypt");
+
+        let note_bytes = encode_orchard_note(&note);
+        let rho = orchard_note_rho(&note_bytes, TEST_UFVK).unwrap();
+        assert_eq!(rho.len(), 64);
+        assert_eq!(rho, orchard_note_rho(&note_bytes, TEST_UFVK).unwrap());
+        // A note's rho is the nullifier revealed by the action creating it
+        assert_eq!(rho, hex::encode(action.nullifier().to_bytes()));
+
+        assert!(matches!(
+            orchard_note_rho(&note_bytes, &other_ufvk()),
+            Err(ScannerError::NoteNotOwned)
+        ));
+    }
+
+    #[test]
+    fn test_compute_orchard_nullifier_rejects_bad_length() {
+        let result = compute_orchard_nullifier(&[0u8; 10], TEST_UFVK);
+        assert!(matches!(result, Err(ScannerError::InvalidNote(_))));
+    }
+
+    #[test]
+    fn test_compute_sapling_nullifier_matches_note() {
+        let dfvk = extract_sapling_dfvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .expect("UFVK should have Sapling");
+        let (_, address) = dfvk.default_address();
+        let rseed = [0x42u8; 32];
+        let note = sapling_crypto::Note::from_parts(
+            address,
+            sapling_crypto::value::NoteValue::from_raw(25_000),
+            sapling_crypto::Rseed::AfterZip212(rseed),
+        );
+        let position = 1234u64;
+        let expected = hex::encode(note.nf(&dfvk.to_nk(zip32::Scope::External), position).0);
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&address.to_bytes());
+        bytes.extend_from_slice(&25_000u64.to_le_bytes());
+        bytes.extend_from_slice(&rseed);
+        bytes.extend_from_slice(&position.to_le_bytes());
+
+        let nullifier =
+            compute_sapling_nullifier(&bytes, TEST_UFVK).expect("nullifier should compute");
+        assert_eq!(nullifier, expected);
+
+        let result = compute_sapling_nullifier(&bytes, &other_ufvk());
+        assert!(matches!(result, Err(ScannerError::NoteNotOwned)));
+    }
+
+    #[test]
+    fn test_scan_transactions_preserves_order() {
+        let tx_hexes = [
+            include_str!("testdata/tx_0411ffa7.hex"),
+            "not a transaction",
+            include_str!("testdata/tx_5aa23ef4.hex"),
+        ];
+
+        let results = scan_transactions(&tx_hexes, &[], TEST_UFVK, Network::TestNetwork, None);
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].as_ref().unwrap().txid,
+            "0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141"
+        );
+        assert!(results[1].is_err());
+        assert_eq!(
+            results[2].as_ref().unwrap().txid,
+            "5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f"
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_scan_transactions_parallel_matches_sequential() {
+        let tx_hexes: Vec<&str> = [
+            include_str!("testdata/tx_0411ffa7.hex"),
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            "zz",
+        ]
+        .into_iter()
+        .cycle()
+        .take(30)
+        .collect();
+
+        let sequential = scan_transactions(&tx_hexes, &[], TEST_UFVK, Network::TestNetwork, None);
+        let parallel = scan_transactions_parallel(&tx_hexes, TEST_UFVK, Network::TestNetwork);
+
+        assert_eq!(sequential.len(), parallel.len());
+        for (seq, par) in sequential.iter().zip(parallel.iter()) {
+            match (seq, par) {
+                (Ok(seq), Ok(par)) => assert_eq!(
+                    serde_json::to_string(seq).unwrap(),
+                    serde_json::to_string(par).unwrap()
+                ),
+                (Err(seq), Err(par)) => assert_eq!(seq.to_string(), par.to_string()),
+                _ => panic!("sequential and parallel results differ"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_has_shielded_components() {
+        let shielded = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert!(has_shielded_components(&shielded));
+
+        let transparent = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert!(!has_shielded_components(&transparent));
+    }
+
+    #[test]
+    fn test_scan_transparent_only_tx_skips_shielded_paths() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None)
+            .expect("scan should succeed");
+
+        // Only transparent data is reported
+        assert!(result.notes.iter().all(|n| n.pool == Pool::Transparent));
+        assert!(result.spent_nullifiers.is_empty());
+        assert!(!result.transparent_outputs.is_empty());
+        assert!(!result.transparent_spends.is_empty());
+        assert_eq!(result.notes.len(), result.transparent_outputs.len());
+    }
+
+    #[test]
+    fn test_scan_debug_mode_reports_undecryptable_actions() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        // Normal mode keeps the lean output
+        let lean = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(lean.notes.iter().all(|n| n.debug.is_none()));
+        let json = serde_json::to_string(&lean).unwrap();
+        assert!(!json.contains("\"debug\":{"));
+
+        // The test UFVK does not own the Orchard actions of this transaction
+        let options = ScanOptions {
+            debug: true,
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+        let orchard_notes: Vec<_> = result
+            .notes
+            .iter()
+            .filter(|n| n.pool == Pool::Orchard)
+            .collect();
+        assert!(!orchard_notes.is_empty());
+
+        for note in orchard_notes {
+            assert_eq!(note.value, 0, "action should not decrypt");
+            let debug = note.debug.as_ref().expect("debug info should be set");
+            assert_eq!(debug.enc_ciphertext_len, 580);
+            assert_eq!(debug.ephemeral_key.len(), 64);
+            assert_eq!(debug.cmx, note.commitment);
+        }
+    }
+
+    #[test]
+    fn test_clean_tx_hex_strips_prefix() {
+        assert_eq!(clean_tx_hex("0xDEADbeef").unwrap(), "deadbeef");
+        assert_eq!(clean_tx_hex("0Xdeadbeef").unwrap(), "deadbeef");
+    }
+
+    #[test]
+    fn test_clean_tx_hex_strips_whitespace() {
+        assert_eq!(clean_tx_hex("  dead\nbe\r\nef\t ").unwrap(), "deadbeef");
+        assert_eq!(clean_tx_hex("0x dead beef\n").unwrap(), "deadbeef");
+    }
+
+    #[test]
+    fn test_clean_tx_hex_rejects_odd_length() {
+        let result = clean_tx_hex("abc");
+        assert!(matches!(
+            result,
+            Err(ScannerError::InvalidTransactionHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_clean_tx_hex_rejects_invalid_input() {
+        assert!(clean_tx_hex("").is_err());
+        assert!(clean_tx_hex("0x").is_err());
+        assert!(clean_tx_hex("zz").is_err());
+    }
+
+    #[test]
+    fn test_parse_transaction_accepts_pasted_hex() {
+        let hex = include_str!("testdata/tx_5aa23ef4.hex").trim();
+        let (head, tail) = hex.split_at(100);
+        let pasted = format!("0x{}\n{}\n", head, tail);
+
+        let tx = parse_transaction(&pasted, Network::TestNetwork).expect("should parse");
+        assert_eq!(
+            tx.txid().to_string(),
+            "5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f"
+        );
+    }
+
+    #[test]
+    fn test_privacy_score_fully_shielded() {
+        // Orchard-only transaction with no value crossing pools
+        let report = score_privacy(&PrivacyInputs {
+            orchard_actions: 2,
+            ..PrivacyInputs::default()
+        });
+        assert_eq!(report.score, 100);
+        assert!(report.factors.is_empty());
+    }
+
+    #[test]
+    fn test_privacy_score_transparent_tx() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let report = privacy_score(&tx);
+        assert!(
+            report.score <= 10,
+            "transparent tx should score low, got {}",
+            report.score
+        );
+        assert!(report.factors.len() >= 3);
+    }
+
+    #[test]
+    fn test_privacy_score_deshielding_tx() {
+        // Orchard actions paying out to a transparent address
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let report = privacy_score(&tx);
+        assert!(report.score > 10 && report.score < 100);
+        assert!(
+            report
+                .factors
+                .iter()
+                .any(|f| f.description.contains("Transparent outputs"))
+        );
+    }
+
+    #[test]
+    fn test_scan_reads_lock_time() {
+        let hex = include_str!("testdata/tx_5aa23ef4.hex").trim();
+        let result = scan_transaction_hex(hex, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert_eq!(result.lock_time, 0);
+        assert!(!result.is_time_locked(1, 0));
+
+        // Set nLockTime (bytes 12..16 of a v5 transaction) to height 3,800,000
+        let mut bytes = hex::decode(hex).unwrap();
+        bytes[12..16].copy_from_slice(&3_800_000u32.to_le_bytes());
+        let result =
+            scan_transaction_hex(&hex::encode(&bytes), TEST_UFVK, Network::TestNetwork, None)
+                .unwrap();
+        assert_eq!(result.lock_time, 3_800_000);
+        assert!(result.is_time_locked(3_760_288, 0));
+        assert!(result.is_time_locked(3_800_000, 0));
+        assert!(!result.is_time_locked(3_800_001, 0));
+
+        // Timestamp-based lock time
+        bytes[12..16].copy_from_slice(&1_700_000_000u32.to_le_bytes());
+        let result =
+            scan_transaction_hex(&hex::encode(&bytes), TEST_UFVK, Network::TestNetwork, None)
+                .unwrap();
+        assert!(result.is_time_locked(u32::MAX, 1_600_000_000));
+        assert!(!result.is_time_locked(0, 1_700_000_001));
+    }
+
+    /// Build an Orchard action whose note was sent by `sender` (using the OVK
+    /// of the given scope) to `recipient`.
+    fn orchard_action_sent_by(
+        sender: &OrchardFvk,
+        ovk_scope: Scope,
+        recipient: orchard::Address,
+        value: u64,
+        memo: &[u8],
+    ) -> orchard::Action<()> {
+        use orchard::note::{ExtractedNoteCommitment, Nullifier, TransmittedNoteCiphertext};
+        use orchard::note_encryption::OrchardNoteEncryption;
+        use orchard::primitives::redpallas::{SpendAuth, VerificationKey};
+        use orchard::value::ValueCommitment;
+        use rand::RngCore;
+
+        let mut rng = rand::rngs::OsRng;
+        let mut rho_bytes = [0u8; 32];
+        rng.fill_bytes(&mut rho_bytes);
+        rho_bytes[31] &= 0x3f;
+        let rho = Rho::from_bytes(&rho_bytes).unwrap();
+        let note = orchard::Note::from_parts(
+            recipient,
+            orchard::value::NoteValue::from_raw(value),
+            rho,
+            RandomSeed::random(&mut rng, &rho),
+        )
+        .unwrap();
+
+        let mut memo_array = [0u8; 512];
+        memo_array[..memo.len()].copy_from_slice(memo);
+
+        let cmx = ExtractedNoteCommitment::from(note.commitment());
+        let cv_net = ValueCommitment::from_bytes(&[0u8; 32]).unwrap();
+        let encryptor =
+            OrchardNoteEncryption::new(Some(sender.to_ovk(ovk_scope)), note, memo_array);
+        let encrypted_note = TransmittedNoteCiphertext {
+            epk_bytes: OrchardDomain::epk_bytes(encryptor.epk()).0,
+            enc_ciphertext: encryptor.encrypt_note_plaintext(),
+            out_ciphertext: encryptor.encrypt_outgoing_plaintext(&cv_net, &cmx, &mut rng),
+        };
+
+        let ak: [u8; 32] = sender.to_bytes()[..32].try_into().unwrap();
+        orchard::Action::from_parts(
+            Nullifier::from_bytes(&rho_bytes).unwrap(),
+            VerificationKey::<SpendAuth>::try_from(ak).unwrap(),
+            cmx,
+            encrypted_note,
+            cv_net,
+            (),
+        )
+    }
+
+    #[test]
+    fn test_recover_orchard_output_sent_to_other_wallet() {
+        let our_fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let their_fvk = extract_orchard_fvk(&other_ufvk(), Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let recipient = their_fvk.address_at(0u32, Scope::External);
+
+        let action = orchard_action_sent_by(&our_fvk, Scope::External, recipient, 42_000, b"rent");
+
+        // Our IVK cannot decrypt it, but our OVK recovers it
+        let ivk = PreparedIncomingViewingKey::new(&our_fvk.to_ivk(Scope::External));
+        assert!(try_note_decryption(&OrchardDomain::for_action(&action), &ivk, &action).is_none());
+
+        let (note, address, memo) =
+            recover_orchard_output(&action, &our_fvk).expect("OVK should recover output");
+        assert_eq!(note.value().inner(), 42_000);
+        assert_eq!(address, recipient);
+        assert_eq!(
+            memo_to_text(&memo, MemoTrim::default()).as_deref(),
+            Some("rent")
+        );
+
+        // Someone else's OVK cannot
+        assert!(recover_orchard_output(&action, &their_fvk).is_none());
+    }
+
+    #[test]
+    fn test_recover_orchard_self_sent_change_output() {
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let change_address = fvk.address_at(0u32, Scope::Internal);
+
+        let action = orchard_action_sent_by(&fvk, Scope::Internal, change_address, 7_500, b"");
+
+        let (note, address, memo) =
+            recover_orchard_output(&action, &fvk).expect("internal OVK should recover change");
+        assert_eq!(note.value().inner(), 7_500);
+        assert_eq!(address, change_address);
+        assert_eq!(memo_to_text(&memo, MemoTrim::default()), None);
+    }
+
+    #[test]
+    fn test_memo_to_text() {
+        let mut memo = [0u8; 512];
+        assert_eq!(memo_to_text(&memo, MemoTrim::default()), None);
+        memo[..5].copy_from_slice(b"hello");
+        assert_eq!(
+            memo_to_text(&memo, MemoTrim::default()).as_deref(),
+            Some("hello")
+        );
+        memo[0] = 0xff;
+        assert_eq!(memo_to_text(&memo, MemoTrim::default()), None);
+    }
+
+    #[test]
+    fn test_outgoing_notes_not_counted_as_received() {
+        use crate::types::LedgerEntry;
+
+        let outgoing = ScannedNote {
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 10_000,
+            commitment: "cmx".to_string(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            debug: None,
+            scope: Some(NoteScope::Outgoing),
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
+        };
+        assert!(outgoing.is_outgoing());
+
+        let result = ScanResult {
+            txid: "txid".to_string(),
+            notes: vec![outgoing],
+            spent_nullifiers: vec![],
+            transparent_spends: vec![],
+            transparent_received: 0,
+            transparent_outputs: vec![],
+            lock_time: 0,
+            value_balance_consistent: true,
+            scanned_pools: vec![],
+            sapling_decryption: DecryptionCounts::default(),
+            orchard_decryption: DecryptionCounts::default(),
+            consensus_branch_id: String::new(),
+            warnings: Vec::new(),
+            is_coinbase: false,
+        };
+        let entry = LedgerEntry::from_scan_result(&result, "w", &[], vec![], vec![], &[], "now");
+        assert_eq!(entry.value_received, 0);
+    }
+
+    #[test]
+    fn test_orchard_values_consistent() {
+        // Fully shielding 1 ZEC into two outputs we own
+        assert!(orchard_values_consistent(
+            &[Some(60_000_000), Some(40_000_000)],
+            -100_000_000
+        ));
+        // Spending notes worth more than the outputs (fee and change)
+        assert!(orchard_values_consistent(&[Some(90_000)], 10_000));
+        // Undecrypted outputs make the check inconclusive
+        assert!(orchard_values_consistent(&[Some(1), None], -100_000_000));
+        assert!(orchard_values_consistent(&[], 0));
+    }
+
+    #[test]
+    fn test_orchard_values_inconsistent() {
+        // Outputs worth less than the value leaving the bundle would imply
+        // spent notes of negative value
+        assert!(!orchard_values_consistent(
+            &[Some(60_000_000), Some(30_000_000)],
+            -100_000_000
+        ));
+        assert!(!orchard_values_consistent(&[], -1));
+    }
+
+    #[test]
+    fn test_parse_unified_capabilities_ufvk() {
+        let capabilities = parse_unified_capabilities(TEST_UFVK).unwrap();
+        assert!(capabilities.has_sapling);
+        assert!(capabilities.has_orchard);
+        assert!(capabilities.has_transparent);
+        assert!(capabilities.unknown_items.is_empty());
+    }
+
+    #[test]
+    fn test_parse_unified_capabilities_reports_unknown_items() {
+        use zcash_protocol::consensus::NetworkType;
+
+        let ua = unified::Address::try_from_items(vec![
+            unified::Receiver::Orchard([7u8; 43]),
+            unified::Receiver::Unknown {
+                typecode: 0x0a,
+                data: vec![1u8; 32],
+            },
+        ])
+        .unwrap()
+        .encode(&NetworkType::Test);
+
+        let capabilities = parse_unified_capabilities(&ua).unwrap();
+        assert!(capabilities.has_orchard);
+        assert!(!capabilities.has_sapling);
+        assert!(!capabilities.has_transparent);
+        assert_eq!(capabilities.unknown_items, vec![0x0a]);
+    }
+
+    #[test]
+    fn test_parse_unified_capabilities_rejects_malformed() {
+        let truncated = &TEST_UFVK[..TEST_UFVK.len() - 10];
+        assert!(matches!(
+            parse_unified_capabilities(truncated),
+            Err(ScannerError::UnrecognizedViewingKey)
+        ));
+        assert!(matches!(
+            parse_unified_capabilities("not a unified container"),
+            Err(ScannerError::UnrecognizedViewingKey)
+        ));
+    }
+
+    #[test]
+    fn test_memo_len() {
+        let mut memo = [0u8; 512];
+        assert_eq!(memo_len(&memo), 0);
+        memo[0] = 0xf6;
+        assert_eq!(memo_len(&memo), 0);
+        memo[..5].copy_from_slice(b"hello");
+        assert_eq!(memo_len(&memo), 5);
+        memo[10] = 1;
+        assert_eq!(memo_len(&memo), 11);
+    }
+
+    #[test]
+    fn test_orchard_memo_bytes_sums_memo_bearing_notes() {
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let their_fvk = extract_orchard_fvk(&other_ufvk(), Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let our_address = fvk.address_at(0u32, Scope::External);
+        let their_address = their_fvk.address_at(0u32, Scope::External);
+
+        let actions = vec![
+            orchard_action_sent_by(&their_fvk, Scope::External, our_address, 1_000, b"hello"),
+            orchard_action_sent_by(&their_fvk, Scope::External, our_address, 2_000, b"again!"),
+            orchard_action_sent_by(&their_fvk, Scope::External, our_address, 3_000, b""),
+            // Not ours and not sent by us
+            orchard_action_sent_by(&their_fvk, Scope::External, their_address, 4_000, b"secret"),
+        ];
+
+        assert_eq!(orchard_memo_bytes(&actions, &fvk), 5 + 6);
+        assert_eq!(orchard_memo_bytes(&actions, &their_fvk), 5 + 6 + 6);
+    }
+
+    #[test]
+    fn test_memo_to_text_trim_modes() {
+        let mut memo = [0u8; 512];
+        memo[..7].copy_from_slice(b"ab\0cd\0\xf6");
+
+        // Invalid UTF-8 because of the sentinel byte
+        assert_eq!(memo_to_text(&memo, MemoTrim::TrailingZeros), None);
+        assert_eq!(memo_to_text(&memo, MemoTrim::None), None);
+        assert_eq!(
+            memo_to_text(&memo, MemoTrim::UpTo0xF6Sentinel).as_deref(),
+            Some("ab\0cd\0")
+        );
+
+        memo[6] = 0;
+        assert_eq!(
+            memo_to_text(&memo, MemoTrim::TrailingZeros).as_deref(),
+            Some("ab\0cd")
+        );
+        let full = memo_to_text(&memo, MemoTrim::None).unwrap();
+        assert_eq!(full.len(), 512);
+        assert!(full.starts_with("ab\0cd\0\0"));
+    }
+
+    #[test]
+    fn test_scan_built_orchard_tx_decrypts_note() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 250_000, b"direct");
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let received: Vec<_> = result
+            .notes
+            .iter()
+            .filter(|n| n.scope == Some(NoteScope::Incoming))
+            .collect();
+        assert_eq!(received.len(), 1);
+
+        let note = received[0];
+        assert_eq!(note.pool, Pool::Orchard);
+        assert_eq!(note.value, 250_000);
+        assert_eq!(note.memo.as_deref(), Some("direct"));
+        assert!(note.nullifier.is_some());
+
+        // Someone else's key sees the actions but cannot decrypt them
+        let other = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        assert!(
+            other
+                .notes
+                .iter()
+                .all(|n| n.scope.is_none() && n.value == 0)
+        );
+    }
+
+    #[test]
+    fn test_total_memo_bytes_built_tx() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, b"twelve bytes");
+        assert_eq!(
+            total_memo_bytes(&tx, TEST_UFVK, Network::TestNetwork).unwrap(),
+            12
+        );
+        // Nothing decrypts for another key
+        assert_eq!(
+            total_memo_bytes(&tx, &other_ufvk(), Network::TestNetwork).unwrap(),
+            0
+        );
+        assert!(matches!(
+            total_memo_bytes(&tx, "garbage", Network::TestNetwork),
+            Err(ScannerError::UnrecognizedViewingKey)
+        ));
+    }
+
+    #[test]
+    fn test_scan_verify_value_balance_built_tx() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 5_000, b"");
+        let options = ScanOptions {
+            verify_value_balance: true,
+            ..ScanOptions::default()
+        };
+
+        // The payment decrypts but the padding action does not, so the check
+        // must stay inconclusive rather than fail
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+        assert!(result.value_balance_consistent);
+
+        // Without the option the check is skipped
+        let unchecked = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(unchecked.value_balance_consistent);
+    }
+
+    #[test]
+    fn test_scan_verify_value_balance_detects_inconsistency() {
+        let tx = crate::testing::build_test_orchard_tx_multi(&[
+            (TEST_UFVK, 0, 20_000),
+            (TEST_UFVK, 1, 30_000),
+        ]);
+        let options = ScanOptions {
+            verify_value_balance: true,
+            ..ScanOptions::default()
+        };
+
+        // Both actions decrypt, and the outputs match the value balance
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+        assert_eq!(result.orchard_decryption.decrypted, 2);
+        assert!(result.value_balance_consistent);
+
+        // Claim 10_000 more left the bundle than its outputs hold
+        let mut bytes = Vec::new();
+        tx.write(&mut bytes).unwrap();
+        let balance = (-50_000i64).to_le_bytes();
+        let offset = bytes
+            .windows(balance.len())
+            .position(|window| window == balance)
+            .expect("value balance should be serialized");
+        bytes[offset..offset + balance.len()].copy_from_slice(&(-60_000i64).to_le_bytes());
+        let tampered = Transaction::read(&bytes[..], BranchId::Nu5).unwrap();
+        assert_eq!(
+            i64::from(*tampered.orchard_bundle().unwrap().value_balance()),
+            -60_000
+        );
+
+        let result = scan_transaction_with_options(
+            &tampered,
+            TEST_UFVK,
+            Network::TestNetwork,
+            None,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(result.orchard_decryption.decrypted, 2);
+        assert!(!result.value_balance_consistent);
+    }
+
+    #[test]
+    fn test_parse_v4_and_v5_transactions() {
+        use zcash_primitives::transaction::TxVersion;
+
+        // Synthetic v4 transaction with one transparent input and output
+        let v4 = parse_transaction(
+            include_str!("testdata/tx_v4_transparent.hex"),
+            Network::MainNetwork,
+        )
+        .expect("v4 transaction should parse");
+        assert!(matches!(v4.version(), TxVersion::V4));
+        assert!(v4.orchard_bundle().is_none());
+        let bundle = v4
+            .transparent_bundle()
+            .expect("should have transparent bundle");
+        assert_eq!(bundle.vin.len(), 1);
+        assert_eq!(bundle.vout.len(), 1);
+        assert_eq!(u64::from(bundle.vout[0].value()), 100_000_000);
+
+        // v4 transaction with a Sapling bundle and no JoinSplits
+        let mut bytes = Vec::new();
+        crate::testing::build_test_sapling_tx_v4(TEST_UFVK, &other_ufvk(), 12_000, b"v4")
+            .write(&mut bytes)
+            .unwrap();
+        let shielded = parse_transaction(&hex::encode(&bytes), Network::TestNetwork)
+            .expect("shielded v4 transaction should parse");
+        assert!(matches!(shielded.version(), TxVersion::V4));
+        let sapling = shielded
+            .sapling_bundle()
+            .expect("should have Sapling bundle");
+        assert_eq!(sapling.shielded_outputs().len(), 1);
+        assert_eq!(i64::from(*sapling.value_balance()), -12_000);
+        // Round-trips byte for byte
+        let mut reencoded = Vec::new();
+        shielded.write(&mut reencoded).unwrap();
+        assert_eq!(reencoded, bytes);
+
+        let v5 = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .expect("v5 transaction should parse");
+        assert!(matches!(v5.version(), TxVersion::V5));
+    }
+
+    #[test]
+    fn test_scan_with_uivk_finds_incoming_note() {
+        let uivk = crate::wallet::ufvk_to_uivk(TEST_UFVK, Network::TestNetwork).unwrap();
+        assert!(uivk.starts_with("uivktest"));
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 42_000, b"incoming");
+
+        let result = scan_transaction(&tx, &uivk, Network::TestNetwork, None).unwrap();
+        let received: Vec<_> = result
+            .notes
+            .iter()
+            .filter(|n| n.scope == Some(NoteScope::Incoming))
+            .collect();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].value, 42_000);
+        assert_eq!(received[0].memo.as_deref(), Some("incoming"));
+        // Nullifiers need the full viewing key
+        assert!(received[0].nullifier.is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_scan_emits_note_decrypted_event() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        struct MessageVisitor<'a>(&'a mut Vec<String>);
+
+        impl Visit for MessageVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.push(format!("{:?}", value));
+                }
+            }
+        }
+
+        /// Records the message of every event.
+        struct Recorder {
+            messages: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut messages = self.messages.lock().unwrap();
+                event.record(&mut MessageVisitor(&mut messages));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, b"");
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Recorder {
+            messages: messages.clone(),
+        };
+
+        tracing::subscriber::with_default(recorder, || {
+            scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        });
+
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().any(|m| m == "note decrypted"));
+    }
+
+    #[test]
+    fn test_output_counts_mixed_transaction() {
+        // Orchard actions paying out to a transparent output
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert_eq!(
+            output_counts(&tx),
+            OutputCounts {
+                transparent_inputs: 0,
+                transparent_outputs: 1,
+                sapling_spends: 0,
+                sapling_outputs: 0,
+                orchard_actions: 2,
+            }
+        );
+
+        let tx = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert_eq!(
+            output_counts(&tx),
+            OutputCounts {
+                transparent_inputs: 1,
+                transparent_outputs: 2,
+                ..OutputCounts::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_scan_reports_scanned_pools() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert_eq!(result.scanned_pools, vec!["transparent", "orchard"]);
+    }
+
+    #[test]
+    fn test_scan_with_sapling_only_ufvk_skips_orchard() {
+        use zcash_protocol::consensus::NetworkType;
+
+        let (_, ufvk) = unified::Ufvk::decode(TEST_UFVK).unwrap();
+        let sapling_only = unified::Ufvk::try_from_items(
+            ufvk.items()
+                .into_iter()
+                .filter(|item| matches!(item, unified::Fvk::Sapling(_)))
+                .collect(),
+        )
+        .unwrap()
+        .encode(&NetworkType::Test);
+
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let result = scan_transaction(&tx, &sapling_only, Network::TestNetwork, None).unwrap();
+
+        // Only outputs the key sent are recovered, so Sapling is not listed
+        assert!(result.scanned_pools.is_empty());
+        assert!(result.notes.iter().all(|note| note.pool != Pool::Orchard));
+        assert!(
+            result
+                .notes
+                .iter()
+                .all(|note| note.scope != Some(NoteScope::Incoming))
+        );
+        // Nullifiers are public and reported regardless of the key
+        assert_eq!(result.spent_nullifiers.len(), 2);
+    }
+
+    #[test]
+    fn test_orchard_anchor() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let anchor = orchard_anchor(&tx).expect("should have an Orchard bundle");
+        assert_eq!(anchor.len(), 64);
+        assert_ne!(anchor, "0".repeat(64));
+        assert_eq!(sapling_anchor(&tx), None);
+
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, b"");
+        assert_eq!(
+            orchard_anchor(&tx),
+            Some(hex::encode(orchard::tree::Anchor::empty_tree().to_bytes()))
+        );
+    }
+
+    #[test]
+    fn test_orchard_cmx_list() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let cmxs = orchard_cmx_list(&tx);
+        assert_eq!(cmxs.len(), tx.orchard_bundle().unwrap().actions().len());
+        assert_eq!(cmxs.len(), 2);
+
+        // Matches the commitments reported by the scanner, in order
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let scanned: Vec<String> = result
+            .notes
+            .iter()
+            .filter(|note| note.pool == Pool::Orchard)
+            .map(|note| note.commitment.clone())
+            .collect();
+        assert_eq!(orchard_cmx_list(&tx), scanned);
+    }
+
+    #[test]
+    fn test_anchors_absent_for_transparent_transaction() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert_eq!(orchard_anchor(&tx), None);
+        assert_eq!(sapling_anchor(&tx), None);
+        assert!(orchard_cmx_list(&tx).is_empty());
+    }
+
+    #[test]
+    fn test_zip317_compliant_fee() {
+        // 2 Orchard actions and one P2PKH output: 3 logical actions, and the
+        // Orchard value balance exceeds the transparent output by 15,000
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        assert_eq!(zip317_conventional_fee(&tx), 15_000);
+        assert_eq!(transaction_fee(&tx), Some(15_000));
+        assert!(is_zip317_compliant(&tx));
+    }
+
+    #[test]
+    fn test_zip317_non_compliant_fee() {
+        // Lower the Orchard value balance (at byte 1700 of this transaction)
+        // so that it only pays a 10,000 zatoshi fee
+        let mut bytes = hex::decode(include_str!("testdata/tx_0411ffa7.hex").trim()).unwrap();
+        bytes[1700..1708].copy_from_slice(&30_010_000i64.to_le_bytes());
+        let tx = parse_transaction(&hex::encode(&bytes), Network::TestNetwork).unwrap();
+
+        assert_eq!(transaction_fee(&tx), Some(10_000));
+        assert!(!is_zip317_compliant(&tx));
+    }
+
+    #[test]
+    fn test_transaction_fee_unknown_with_transparent_inputs() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        // 1 input and 2 outputs: max(1, 2) logical actions, below the grace
+        // actions
+        assert_eq!(zip317_conventional_fee(&tx), 10_000);
+        assert_eq!(transaction_fee(&tx), None);
+        assert!(!is_zip317_compliant(&tx));
+    }
+
+    #[test]
+    fn test_scan_only_orchard_pool() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        // The default scan reports the transparent output
+        let all = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(all.notes.iter().any(|note| note.pool == Pool::Transparent));
+
+        let options = ScanOptions {
+            pools: Some(vec![Pool::Orchard]),
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+
+        assert_eq!(result.scanned_pools, vec!["orchard"]);
+        assert!(result.notes.iter().all(|note| note.pool == Pool::Orchard));
+        assert_eq!(result.notes.len(), 2);
+        assert!(result.transparent_outputs.is_empty());
+        assert!(result.transparent_spends.is_empty());
+        assert_eq!(result.transparent_received, 0);
+        assert!(
+            result
+                .spent_nullifiers
+                .iter()
+                .all(|nf| nf.pool == Pool::Orchard)
+        );
+    }
+
+    #[test]
+    fn test_orchard_external_recipients_excludes_change() {
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let their_fvk = extract_orchard_fvk(&other_ufvk(), Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let alice = their_fvk.address_at(0u32, Scope::External);
+        let bob = their_fvk.address_at(1u32, Scope::External);
+        let change = fvk.address_at(0u32, Scope::Internal);
+
+        let actions = vec![
+            orchard_action_sent_by(&fvk, Scope::External, alice, 1_000, b"one"),
+            orchard_action_sent_by(&fvk, Scope::External, bob, 2_000, b"two"),
+            // A second payment to the same recipient counts once
+            orchard_action_sent_by(&fvk, Scope::External, alice, 3_000, b""),
+            orchard_action_sent_by(&fvk, Scope::Internal, change, 4_000, b""),
+        ];
+
+        assert_eq!(orchard_external_recipients(&actions, &fvk).len(), 2);
+        // Nothing was sent by the other wallet
+        assert!(orchard_external_recipients(&actions, &their_fvk).is_empty());
+    }
+
+    #[test]
+    fn test_distinct_recipients_of_payment() {
+        let alice = other_ufvk();
+        let bob = crate::wallet::restore_wallet(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            Network::TestNetwork,
+            1,
+            0,
+        )
+        .unwrap()
+        .unified_full_viewing_key;
+        let tx = crate::testing::build_test_orchard_payment_multi(
+            TEST_UFVK,
+            &[(&alice, 10_000), (&bob, 20_000)],
+            5_000,
+        );
+
+        // Two external recipients; the change output is not counted
+        assert_eq!(
+            distinct_recipients(&tx, TEST_UFVK, Network::TestNetwork).unwrap(),
+            2
+        );
+        // Neither recipient sent anything
+        assert_eq!(
+            distinct_recipients(&tx, &alice, Network::TestNetwork).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_distinct_recipients_of_sapling_payment() {
+        let recipient = other_ufvk();
+        let tx = crate::testing::build_test_sapling_tx(TEST_UFVK, &recipient, 42_000, b"");
+        assert_eq!(
+            distinct_recipients(&tx, TEST_UFVK, Network::TestNetwork).unwrap(),
+            1
+        );
+        assert_eq!(
+            distinct_recipients(&tx, &recipient, Network::TestNetwork).unwrap(),
+            0
+        );
+
+        // A Sapling output to the wallet's own address is not counted
+        let tx = crate::testing::build_test_sapling_tx(TEST_UFVK, TEST_UFVK, 42_000, b"");
+        assert_eq!(
+            distinct_recipients(&tx, TEST_UFVK, Network::TestNetwork).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_distinct_recipients_of_received_transaction() {
+        // The test builder encrypts without an OVK, like a transaction
+        // received from someone else
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 5_000, b"");
+        assert_eq!(
+            distinct_recipients(&tx, TEST_UFVK, Network::TestNetwork).unwrap(),
+            0
+        );
+        assert!(matches!(
+            distinct_recipients(&tx, "garbage", Network::TestNetwork),
+            Err(ScannerError::UnrecognizedViewingKey)
+        ));
+    }
 
-        // Verify at least one transparent note has a decoded address
-        let transparent_notes: Vec<_> = scan_result
+    #[test]
+    fn test_extract_orchard_fvk_rejects_wrong_network() {
+        let mainnet = crate::wallet::restore_wallet(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            Network::MainNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+        let ufvk = &mainnet.unified_full_viewing_key;
+
+        assert!(
+            extract_orchard_fvk(ufvk, Network::MainNetwork)
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            extract_orchard_fvk(ufvk, Network::TestNetwork),
+            Err(ScannerError::NetworkMismatch(_))
+        ));
+        assert!(matches!(
+            extract_orchard_fvk(TEST_UFVK, Network::MainNetwork),
+            Err(ScannerError::NetworkMismatch(_))
+        ));
+
+        // Scanning with a key for the other network fails instead of
+        // silently finding nothing
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"");
+        assert!(matches!(
+            scan_transaction(&tx, ufvk, Network::TestNetwork, None),
+            Err(ScannerError::NetworkMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_sapling_dfvk_rejects_wrong_network() {
+        const SEED_PHRASE: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+        let seed = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, SEED_PHRASE)
+            .unwrap()
+            .to_seed("");
+        let legacy =
+            crate::wallet::derive_legacy_sapling_key(&seed, Network::MainNetwork, 0).unwrap();
+
+        let other = other_ufvk();
+        for key in [TEST_UFVK, other.as_str()] {
+            assert!(
+                extract_sapling_dfvk(key, Network::TestNetwork)
+                    .unwrap()
+                    .is_some()
+            );
+            assert!(matches!(
+                extract_sapling_dfvk(key, Network::MainNetwork),
+                Err(ScannerError::NetworkMismatch(_))
+            ));
+        }
+        assert!(
+            extract_sapling_dfvk(&legacy.extfvk, Network::MainNetwork)
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            extract_sapling_dfvk(&legacy.extfvk, Network::TestNetwork),
+            Err(ScannerError::NetworkMismatch(_))
+        ));
+        assert!(
+            extract_sapling_dfvk("not a key", Network::TestNetwork)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_scan_work_units_grows_with_orchard_actions() {
+        let transparent = parse_transaction(
+            include_str!("testdata/tx_5aa23ef4.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let orchard = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+
+        assert_eq!(scan_work_units(&transparent), 1);
+        assert_eq!(scan_work_units(&orchard), 1 + 2 * 2);
+        assert!(scan_work_units(&orchard) > scan_work_units(&transparent));
+    }
+
+    #[test]
+    fn test_scan_reports_decryption_counts() {
+        // One real output to us plus a dummy padding action
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"counted");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert_eq!(
+            result.orchard_decryption,
+            DecryptionCounts {
+                attempted: 2,
+                decrypted: 1
+            }
+        );
+
+        // A key that matches nothing still reports the attempts
+        let result = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        assert_eq!(
+            result.orchard_decryption,
+            DecryptionCounts {
+                attempted: 2,
+                decrypted: 0
+            }
+        );
+        assert_eq!(result.sapling_decryption, DecryptionCounts::default());
+    }
+
+    #[test]
+    fn test_identify_commitment() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 33_000, b"reconcile");
+        let scanned = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let ours = scanned
             .notes
             .iter()
-            .filter(|n| n.pool == Pool::Transparent)
+            .find(|note| note.scope.is_some())
+            .expect("one output should decrypt");
+        let dummy = scanned
+            .notes
+            .iter()
+            .find(|note| note.scope.is_none())
+            .expect("the padding action should not decrypt");
+
+        let note = identify_commitment(&tx, &ours.commitment, TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .expect("the commitment should be found");
+        assert_eq!(note.value, 33_000);
+        assert_eq!(note.memo.as_deref(), Some("reconcile"));
+        assert_eq!(note.output_index, ours.output_index);
+
+        // Present but not ours
+        assert!(
+            identify_commitment(&tx, &dummy.commitment, TEST_UFVK, Network::TestNetwork)
+                .unwrap()
+                .is_none()
+        );
+        // Not present in the transaction
+        assert!(
+            identify_commitment(&tx, &"00".repeat(32), TEST_UFVK, Network::TestNetwork)
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            identify_commitment(&tx, "abcd", TEST_UFVK, Network::TestNetwork),
+            Err(ScannerError::InvalidNote(_))
+        ));
+    }
+
+    #[test]
+    fn test_identify_commitment_ignores_sent_outputs() {
+        let recipient = other_ufvk();
+        let tx = crate::testing::build_test_orchard_payment(TEST_UFVK, &recipient, 25_000, 5_000);
+        let sent = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None)
+            .unwrap()
+            .notes
+            .into_iter()
+            .find(|note| note.scope == Some(NoteScope::Outgoing) && note.value == 25_000)
+            .expect("the payment should be recovered with the OVK");
+
+        // The sender recovers the output, but did not receive it
+        assert!(
+            identify_commitment(&tx, &sent.commitment, TEST_UFVK, Network::TestNetwork)
+                .unwrap()
+                .is_none()
+        );
+        let received = identify_commitment(&tx, &sent.commitment, &recipient, Network::TestNetwork)
+            .unwrap()
+            .expect("the recipient received the output");
+        assert_eq!(received.value, 25_000);
+    }
+
+    #[test]
+    fn test_scan_transactions_resumes_from_cursor() {
+        let tx_hexes: Vec<String> = [10_000, 20_000, 30_000]
+            .into_iter()
+            .map(|value| {
+                let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, value, b"");
+                let mut bytes = Vec::new();
+                tx.write(&mut bytes).unwrap();
+                hex::encode(bytes)
+            })
             .collect();
+        let heights = [Some(2_000_000), None, Some(2_000_010)];
+        let start = ScanCursor {
+            orchard_position: 100,
+            ..ScanCursor::default()
+        };
+
+        // Scan everything in one go
+        let mut cursor = start.clone();
+        let full = scan_transactions(
+            &tx_hexes,
+            &heights,
+            TEST_UFVK,
+            Network::TestNetwork,
+            Some(&mut cursor),
+        );
+        assert_eq!(cursor.orchard_position, 106);
+        assert_eq!(
+            cursor.last_txid.as_deref(),
+            Some(full[2].as_ref().unwrap().txid.as_str())
+        );
+        assert_eq!(cursor.last_height, Some(2_000_010));
+
+        // Scan the first transaction, save the cursor, then resume
+        let mut resumed_cursor = start;
+        let first = scan_transactions(
+            &tx_hexes[..1],
+            &heights[..1],
+            TEST_UFVK,
+            Network::TestNetwork,
+            Some(&mut resumed_cursor),
+        );
+        let saved: ScanCursor =
+            serde_json::from_str(&serde_json::to_string(&resumed_cursor).unwrap()).unwrap();
+        assert_eq!(saved.orchard_position, 102);
+        assert_eq!(saved.last_height, Some(2_000_000));
+        let mut resumed_cursor = saved;
+        let rest = scan_transactions(
+            &tx_hexes[1..],
+            &heights[1..],
+            TEST_UFVK,
+            Network::TestNetwork,
+            Some(&mut resumed_cursor),
+        );
 
+        assert_eq!(resumed_cursor, cursor);
+        let positions = |results: &[Result<ScanResult, ScannerError>]| -> Vec<Option<u64>> {
+            results
+                .iter()
+                .flat_map(|result| result.as_ref().unwrap().notes.iter())
+                .map(|note| note.position)
+                .collect()
+        };
+        let mut resumed_positions = positions(&first);
+        resumed_positions.extend(positions(&rest));
+        assert_eq!(resumed_positions, positions(&full));
+        assert_eq!(positions(&full), (100..106).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_scan_cursor_stops_at_failed_transaction() {
+        let tx_hexes = [
+            include_str!("testdata/tx_0411ffa7.hex"),
+            "not a transaction",
+            include_str!("testdata/tx_0411ffa7.hex"),
+        ];
+        let mut cursor = ScanCursor::default();
+        let results = scan_transactions(
+            &tx_hexes,
+            &[],
+            TEST_UFVK,
+            Network::TestNetwork,
+            Some(&mut cursor),
+        );
+
+        assert_eq!(cursor.orchard_position, 2);
         assert!(
-            !transparent_notes.is_empty(),
-            "Should have transparent notes"
+            results[2]
+                .as_ref()
+                .unwrap()
+                .notes
+                .iter()
+                .all(|note| note.position.is_none())
         );
+    }
 
-        // Check that addresses are decoded (not None)
-        let notes_with_addresses: Vec<_> = transparent_notes
+    #[test]
+    fn test_scan_flags_auto_shielded_note() {
+        let tx = crate::testing::build_test_shielding_tx(TEST_UFVK, 90_000);
+        assert_eq!(tx.transparent_bundle().unwrap().vin.len(), 1);
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let shielded: Vec<_> = result
+            .notes
             .iter()
-            .filter(|n| n.address.is_some())
+            .filter(|note| note.scope == Some(NoteScope::Incoming))
             .collect();
+        assert_eq!(shielded.len(), 1);
+        assert_eq!(shielded[0].value, 90_000);
+        assert!(shielded[0].from_tex);
+        assert_eq!(result.transparent_spends.len(), 1);
+
+        // Without transparent inputs the note is a regular payment
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 90_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(result.notes.iter().all(|note| !note.from_tex));
+    }
+
+    #[test]
+    fn test_parse_transaction_base64() {
+        let tx_hex = include_str!("testdata/tx_0411ffa7.hex");
+        let tx_base64 = BASE64_STANDARD.encode(hex::decode(tx_hex.trim()).unwrap());
+
+        let from_hex = parse_transaction(tx_hex, Network::TestNetwork).unwrap();
+        let from_base64 = parse_transaction_base64(&tx_base64, Network::TestNetwork).unwrap();
+        assert_eq!(from_hex.txid(), from_base64.txid());
+
+        assert!(matches!(
+            parse_transaction_base64("not base64!", Network::TestNetwork),
+            Err(ScannerError::InvalidTransactionBase64(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_transaction_auto_detects_encoding() {
+        let tx_hex = include_str!("testdata/tx_0411ffa7.hex");
+        let tx_base64 = BASE64_STANDARD.encode(hex::decode(tx_hex.trim()).unwrap());
+        let expected = parse_transaction(tx_hex, Network::TestNetwork)
+            .unwrap()
+            .txid();
+
+        assert_eq!(
+            parse_transaction_auto(tx_hex, Network::TestNetwork)
+                .unwrap()
+                .txid(),
+            expected
+        );
+        assert_eq!(
+            parse_transaction_auto(&tx_base64, Network::TestNetwork)
+                .unwrap()
+                .txid(),
+            expected
+        );
+        assert!(matches!(
+            parse_transaction_auto("not a transaction!", Network::TestNetwork),
+            Err(ScannerError::InvalidTransactionHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_txid_and_auth_digest() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+
+        let (txid, auth_digest) = txid_and_auth_digest(&tx);
+        assert_eq!(txid, result.txid);
+        assert_eq!(auth_digest.len(), 64);
+        assert_ne!(auth_digest, "0".repeat(64));
+        assert_ne!(auth_digest, txid);
+    }
+
+    #[test]
+    fn test_parse_transaction_unsupported_version() {
+        // A v5 transaction with its version bumped to 6
+        let mut bytes = hex::decode(include_str!("testdata/tx_0411ffa7.hex").trim()).unwrap();
+        bytes[0] = 6;
+
+        let result = parse_transaction(&hex::encode(&bytes), Network::TestNetwork);
+        assert!(matches!(result, Err(ScannerError::UnsupportedVersion(6))));
+
+        // Garbage of a supported version keeps the generic error
+        let result = parse_transaction("0400008085202f89", Network::TestNetwork);
+        assert!(matches!(
+            result,
+            Err(ScannerError::TransactionParseFailed(_))
+        ));
+
+        // So does garbage whose header is not a plausible version
+        for garbage in ["deadbeefcafebabe", "0600000000000000", "ffffffff00000000"] {
+            let result = parse_transaction(garbage, Network::TestNetwork);
+            assert!(
+                matches!(result, Err(ScannerError::TransactionParseFailed(_))),
+                "{garbage}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_verifies_shielded_payment() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 50_000, b"order 7");
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let address =
+            UnifiedAddress::from_receivers(Some(fvk.address_at(0u32, Scope::External)), None, None)
+                .unwrap()
+                .encode(&Network::TestNetwork);
+
+        assert!(verifies_payment(&tx, TEST_UFVK, &address, 50_000, Network::TestNetwork).unwrap());
+        assert!(!verifies_payment(&tx, TEST_UFVK, &address, 50_001, Network::TestNetwork).unwrap());
+
+        // Paid to the wallet, but not at this address
+        let other_index =
+            UnifiedAddress::from_receivers(Some(fvk.address_at(1u32, Scope::External)), None, None)
+                .unwrap()
+                .encode(&Network::TestNetwork);
+        assert!(!verifies_payment(&tx, TEST_UFVK, &other_index, 1, Network::TestNetwork).unwrap());
+    }
+
+    #[test]
+    fn test_verifies_transparent_payment() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let address = tx.transparent_bundle().unwrap().vout[0]
+            .recipient_address()
+            .unwrap()
+            .encode(&Network::TestNetwork);
 
         assert!(
-            !notes_with_addresses.is_empty(),
-            "Transparent notes should have decoded addresses"
+            verifies_payment(&tx, TEST_UFVK, &address, 30_000_000, Network::TestNetwork).unwrap()
+        );
+        assert!(
+            !verifies_payment(&tx, TEST_UFVK, &address, 30_000_001, Network::TestNetwork).unwrap()
         );
+        assert!(matches!(
+            verifies_payment(&tx, TEST_UFVK, "not an address", 1, Network::TestNetwork),
+            Err(ScannerError::InvalidAddress(_))
+        ));
+    }
 
-        // Verify that the transaction output matches the wallet's first transparent address
-        let matching_notes: Vec<_> = transparent_notes
+    #[test]
+    fn test_verifies_payment_rejects_sapling_address() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 50_000, b"");
+        let sapling_address = extract_sapling_dfvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap()
+            .default_address()
+            .1;
+        let sapling_only = UnifiedAddress::from_receivers(None, Some(sapling_address), None)
+            .unwrap()
+            .encode(&Network::TestNetwork);
+        let legacy = Address::Sapling(sapling_address).encode(&Network::TestNetwork);
+
+        for address in [sapling_only, legacy] {
+            assert!(matches!(
+                verifies_payment(&tx, TEST_UFVK, &address, 1, Network::TestNetwork),
+                Err(ScannerError::InvalidAddress(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_scan_transaction_multi_attributes_accounts() {
+        let other = other_ufvk();
+        let tx = crate::testing::build_test_orchard_tx_multi(&[
+            (TEST_UFVK, 0, 11_000),
+            (&other, 0, 22_000),
+        ]);
+
+        let notes =
+            scan_transaction_multi(&tx, &[(TEST_UFVK, 0), (&other, 1)], Network::TestNetwork)
+                .unwrap();
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!((notes[0].0, notes[0].1.value), (0, 11_000));
+        assert_eq!((notes[1].0, notes[1].1.value), (1, 22_000));
+        assert_ne!(notes[0].1.output_index, notes[1].1.output_index);
+    }
+
+    #[test]
+    fn test_value_by_address() {
+        let tx = crate::testing::build_test_orchard_tx_multi(&[
+            (TEST_UFVK, 0, 10_000),
+            (TEST_UFVK, 1, 20_000),
+        ]);
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let address_at = |index: u32| {
+            UnifiedAddress::from_receivers(Some(fvk.address_at(index, Scope::External)), None, None)
+                .unwrap()
+                .encode(&Network::TestNetwork)
+        };
+
+        let values = value_by_address(&tx, TEST_UFVK, Network::TestNetwork).unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[&address_at(0)], 10_000);
+        assert_eq!(values[&address_at(1)], 20_000);
+
+        // A transparent output to someone else is not included
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert!(
+            value_by_address(&tx, TEST_UFVK, Network::TestNetwork)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Transparent change to the internal chain is included
+        let watch_set = crate::wallet::derive_transparent_watch_set_from_ufvk(
+            TEST_UFVK,
+            Network::TestNetwork,
+            1,
+        )
+        .unwrap();
+        let change = &watch_set[1];
+        let Some(Address::Transparent(change_address)) =
+            Address::decode(&Network::TestNetwork, change)
+        else {
+            panic!("change address should be transparent");
+        };
+        let tx = crate::testing::build_test_transparent_tx(&change_address, 70_000);
+        let values = value_by_address(&tx, TEST_UFVK, Network::TestNetwork).unwrap();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[change], 70_000);
+    }
+
+    #[test]
+    fn test_scan_with_raw_memo_keeps_message_header() {
+        let mut memo = vec![crate::memo::MESSAGE_VERSION, 0x02];
+        memo.extend_from_slice(&1_700_000_000u32.to_be_bytes());
+        memo.extend_from_slice(&0x0908_0706u32.to_be_bytes());
+        memo.extend_from_slice(&[0, 1, 0, 2]);
+        memo.extend_from_slice(b"hi");
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, &memo);
+
+        let options = ScanOptions {
+            raw_memo: true,
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+        let note = result
+            .notes
             .iter()
-            .filter(|n| n.address.as_deref() == Some(first_transparent_address.as_str()))
+            .find(|note| note.scope.is_some())
+            .unwrap();
+        let raw = hex::decode(note.memo_bytes.as_ref().unwrap()).unwrap();
+
+        assert_eq!(raw.len(), 512);
+        assert_eq!(&raw[..memo.len()], &memo[..]);
+        let message = crate::memo::parse_message_memo(&raw).unwrap();
+        assert_eq!(message.nonce, 0x0908_0706);
+        assert_eq!(message.text(), Some("hi"));
+
+        // Not attached by default
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(result.notes.iter().all(|note| note.memo_bytes.is_none()));
+    }
+
+    #[test]
+    fn test_scan_reports_consensus_branch_id() {
+        let result = scan_transaction_hex(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            TEST_UFVK,
+            Network::TestNetwork,
+            None,
+        )
+        .expect("scan should succeed");
+
+        // v5 transactions carry their branch ID; this one was mined under
+        // NU6.1 (header bytes 8..12 are f04dec4d, little-endian)
+        assert_eq!(result.consensus_branch_id, "4dec4df0");
+        assert_eq!(result.consensus_branch_id, branch_id_hex(BranchId::Nu6_1));
+        assert_eq!(branch_id_hex(BranchId::Nu5), "c2d6d0b4");
+    }
+
+    #[test]
+    fn test_is_self_transfer_consolidation() {
+        // Notes consolidated into two of the wallet's own addresses
+        let tx = crate::testing::build_test_orchard_tx_multi(&[
+            (TEST_UFVK, 0, 70_000),
+            (TEST_UFVK, 3, 20_000),
+        ]);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let owned: BTreeSet<String> = result
+            .spent_nullifiers
+            .iter()
+            .map(|spent| spent.nullifier.clone())
             .collect();
+        assert!(is_self_transfer(&result, TEST_UFVK, Network::TestNetwork, &owned).unwrap());
+
+        // Sending part of it to someone else is not a self-transfer
+        let other = other_ufvk();
+        let mut paying = result.clone();
+        paying.notes.push(ScannedNote {
+            scope: Some(NoteScope::Outgoing),
+            address: Some(
+                crate::wallet::wallet_from_ufvk(&other, Some(Network::TestNetwork), 0)
+                    .unwrap()
+                    .unified_address,
+            ),
+            is_external: true,
+            ..result.notes[0].clone()
+        });
+        assert!(!is_self_transfer(&paying, TEST_UFVK, Network::TestNetwork, &owned).unwrap());
 
+        // The other wallet received nothing it can see
+        let result = scan_transaction(&tx, &other, Network::TestNetwork, None).unwrap();
+        assert!(!is_self_transfer(&result, &other, Network::TestNetwork, &owned).unwrap());
+    }
+
+    #[test]
+    fn test_is_self_transfer_third_party_payment() {
+        // Someone else paid the key: every output is the wallet's, but it
+        // spends none of the wallet's notes
+        let tx = crate::testing::build_test_orchard_tx_multi(&[
+            (TEST_UFVK, 0, 70_000),
+            (TEST_UFVK, 3, 20_000),
+        ]);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(result.notes.iter().all(|note| !note.is_outgoing()));
         assert!(
-            !matching_notes.is_empty(),
-            "Should find a transparent output matching the wallet's first address: {}",
-            first_transparent_address
+            !is_self_transfer(&result, TEST_UFVK, Network::TestNetwork, &BTreeSet::new()).unwrap()
+        );
+
+        // Change recovered with the wallet's own OVK proves it sent the
+        // transaction
+        let tx = crate::testing::build_test_orchard_tx_with_change(TEST_UFVK, 20_000, 5_000);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(
+            is_self_transfer(&result, TEST_UFVK, Network::TestNetwork, &BTreeSet::new()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_scan_warns_on_possible_branch_mismatch() {
+        // Built under NU5 rules, but claimed to be mined after NU6 activated
+        // on testnet (height 2,976,000)
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 1_000, b"");
+        let other = other_ufvk();
+
+        let result = scan_transaction(&tx, &other, Network::TestNetwork, Some(3_000_000)).unwrap();
+        assert_eq!(
+            result.warnings,
+            vec![ScanWarning::PossibleBranchMismatch {
+                parsed_branch_id: branch_id_hex(BranchId::Nu5),
+                height_branch_id: branch_id_hex(BranchId::Nu6),
+            }]
+        );
+
+        // Consistent height, unknown height, or a note that decrypts
+        let result = scan_transaction(&tx, &other, Network::TestNetwork, Some(2_000_000)).unwrap();
+        assert!(result.warnings.is_empty());
+        let result = scan_transaction(&tx, &other, Network::TestNetwork, None).unwrap();
+        assert!(result.warnings.is_empty());
+        let result =
+            scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, Some(3_000_000)).unwrap();
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_scan_warns_on_misparsed_v4_transaction() {
+        // A v4 transaction mined in the Sapling era (testnet height 300,000)
+        // parses under the newest branch, since v4 does not commit to one
+        let mut bytes = Vec::new();
+        crate::testing::build_test_sapling_tx_v4(TEST_UFVK, &other_ufvk(), 7_000, b"")
+            .write(&mut bytes)
+            .unwrap();
+        let tx = parse_transaction(&hex::encode(bytes), Network::TestNetwork).unwrap();
+        assert_eq!(tx.consensus_branch_id(), BranchId::Nu6_1);
+
+        let result =
+            scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, Some(300_000)).unwrap();
+        assert_eq!(
+            result.warnings,
+            vec![ScanWarning::PossibleBranchMismatch {
+                parsed_branch_id: branch_id_hex(BranchId::Nu6_1),
+                height_branch_id: branch_id_hex(BranchId::Sapling),
+            }]
+        );
+
+        // A Sapling output recovered with the OVK counts as decrypted
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, Some(300_000)).unwrap();
+        assert_eq!(result.sapling_decryption.decrypted, 1);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_scan_recovers_sent_sapling_output() {
+        let recipient = other_ufvk();
+        let tx = crate::testing::build_test_sapling_tx(TEST_UFVK, &recipient, 42_000, b"sent");
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let note = &result.notes[0];
+        assert_eq!(note.pool, Pool::Sapling);
+        assert_eq!(note.scope, Some(NoteScope::Outgoing));
+        assert_eq!(note.value, 42_000);
+        assert_eq!(note.memo.as_deref(), Some("sent"));
+        assert!(note.nullifier.is_none());
+
+        let recipient_addr = extract_sapling_dfvk(&recipient, Network::TestNetwork)
+            .unwrap()
+            .unwrap()
+            .default_address()
+            .1;
+        assert_eq!(
+            note.address,
+            Some(Address::Sapling(recipient_addr).encode(&Network::TestNetwork))
+        );
+        assert_eq!(
+            result.sapling_decryption,
+            DecryptionCounts {
+                attempted: 1,
+                decrypted: 1
+            }
+        );
+
+        // The recipient cannot recover it with their own OVK
+        let result = scan_transaction(&tx, &recipient, Network::TestNetwork, None).unwrap();
+        assert_eq!(result.notes[0].scope, None);
+        assert_eq!(result.notes[0].value, 0);
+    }
+
+    #[test]
+    fn test_scan_with_legacy_sapling_extfvk() {
+        const SEED_PHRASE: &str = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+        let network = Network::TestNetwork;
+        let seed = bip39::Mnemonic::parse_in_normalized(bip39::Language::English, SEED_PHRASE)
+            .unwrap()
+            .to_seed("");
+        // Legacy Sapling address 1 is backed by the account 1 Sapling key
+        let sender = crate::wallet::restore_wallet(SEED_PHRASE, network, 1, 0)
+            .unwrap()
+            .unified_full_viewing_key;
+        let legacy = crate::wallet::derive_legacy_sapling_key(&seed, network, 1).unwrap();
+        let tx = crate::testing::build_test_sapling_tx(&sender, TEST_UFVK, 42_000, b"legacy");
+
+        assert_eq!(
+            parse_viewing_key_capabilities(&legacy.extfvk).unwrap(),
+            (true, false, false)
+        );
+        let result = scan_transaction(&tx, &legacy.extfvk, network, None).unwrap();
+        let note = &result.notes[0];
+        assert_eq!(note.pool, Pool::Sapling);
+        assert_eq!(note.scope, Some(NoteScope::Outgoing));
+        assert_eq!(note.value, 42_000);
+        assert_eq!(note.memo.as_deref(), Some("legacy"));
+
+        // Account 0's key belongs to a different legacy address
+        let other = crate::wallet::derive_legacy_sapling_key(&seed, network, 0).unwrap();
+        let result = scan_transaction(&tx, &other.extfvk, network, None).unwrap();
+        assert_eq!(result.notes[0].scope, None);
+    }
+
+    /// A v5 transaction spending 60_000 from Sapling into a 50_000 Orchard
+    /// note for TEST_UFVK, with a 10_000 fee.
+    fn sapling_to_orchard_tx() -> Transaction {
+        let mut bytes = Vec::new();
+        crate::testing::build_test_sapling_tx(TEST_UFVK, &other_ufvk(), 1_000, b"")
+            .write(&mut bytes)
+            .unwrap();
+        let mut orchard = Vec::new();
+        crate::testing::build_test_orchard_tx(TEST_UFVK, 50_000, b"")
+            .write(&mut orchard)
+            .unwrap();
+
+        // Make the Sapling bundle a net source of value
+        let balance_at = bytes.len() - 1 - 64 - 192 - 8;
+        bytes[balance_at..balance_at + 8].copy_from_slice(&60_000i64.to_le_bytes());
+        // Replace the empty Orchard bundle with the built one, which follows
+        // the header, empty transparent lists and empty Sapling bundle
+        bytes.pop();
+        bytes.extend_from_slice(&orchard[24..]);
+
+        Transaction::read(&bytes[..], BranchId::Nu5).unwrap()
+    }
+
+    #[test]
+    fn test_crosses_turnstile_sapling_to_orchard() {
+        let tx = sapling_to_orchard_tx();
+        assert_eq!(crosses_turnstile(&tx), vec!["sapling->orchard".to_string()]);
+    }
+
+    #[test]
+    fn test_crosses_turnstile_pure_orchard() {
+        // Orchard-only transactions move value between Orchard notes only
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 50_000, b"");
+        assert!(crosses_turnstile(&tx).is_empty());
+
+        // Shielding a transparent input is a transition into Orchard
+        let tx = crate::testing::build_test_shielding_tx(TEST_UFVK, 50_000);
+        assert_eq!(
+            crosses_turnstile(&tx),
+            vec!["transparent->orchard".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_with_esk() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 30_000, b"audit");
+        let fvk = extract_orchard_fvk(TEST_UFVK, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+        let actions = tx.orchard_bundle().unwrap().actions();
+        let (index, (note, address, _)) = actions
+            .iter()
+            .enumerate()
+            .find_map(|(i, action)| {
+                try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+                    .map(|decrypted| (i, decrypted))
+            })
+            .unwrap();
+
+        let esk = hex::encode(crate::encryption::orchard_note_esk(&note).unwrap());
+        let recipient = UnifiedAddress::from_receivers(Some(address), None, None)
+            .unwrap()
+            .encode(&Network::TestNetwork);
+
+        let decrypted =
+            decrypt_with_esk(&tx, index, &esk, &recipient, Network::TestNetwork).unwrap();
+        assert_eq!(decrypted.value, 30_000);
+        assert_eq!(decrypted.memo.as_deref(), Some("audit"));
+        assert_eq!(decrypted.address.as_deref(), Some(recipient.as_str()));
+        assert_eq!(decrypted.scope, None);
+
+        // The key only opens its own action
+        assert!(matches!(
+            decrypt_with_esk(&tx, 1 - index, &esk, &recipient, Network::TestNetwork),
+            Err(ScannerError::InvalidNote(_))
+        ));
+        assert!(decrypt_with_esk(&tx, index, "00", &recipient, Network::TestNetwork).is_err());
+    }
+
+    #[test]
+    fn test_txid_from_hex_ignores_whitespace() {
+        let hex = include_str!("testdata/tx_0411ffa7.hex").trim();
+        let (head, tail) = hex.split_at(64);
+        let pasted = format!("  0x{}\n{}\t\n", head, tail.to_ascii_uppercase());
+
+        assert_eq!(
+            txid_from_hex(&pasted).unwrap(),
+            "0411ffa70699e3fdd5bfe30573d8d49c26939bc9598c3c44f4c07cf44f24f141"
+        );
+        assert!(matches!(
+            txid_from_hex("zz"),
+            Err(ScannerError::InvalidTransactionHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_change_output_indices() {
+        let tx = crate::testing::build_test_orchard_tx_with_change(TEST_UFVK, 20_000, 5_000);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let change_index = result
+            .notes
+            .iter()
+            .find(|note| note.value == 5_000)
+            .map(|note| note.output_index)
+            .unwrap();
+
+        let change = change_output_indices(&result, TEST_UFVK, Network::TestNetwork).unwrap();
+        assert_eq!(change, vec![("orchard".to_string(), change_index)]);
+
+        // Another key sees no change of its own
+        let result = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        assert!(
+            change_output_indices(&result, &other_ufvk(), Network::TestNetwork)
+                .unwrap()
+                .is_empty()
         );
     }
 
-    /// Test that spending a transparent output is correctly detected.
-    ///
-    /// This test uses two transactions:
-    /// 1. tx_0411ffa7: Receives a transparent output to the wallet
-    /// 2. tx_5aa23ef4: Spends that output
-    ///
-    /// The scanner should detect the spend via transparent_spends matching
-    /// the original prevout reference (txid:vout).
-    ///
-    /// This test mirrors the flow used by the frontend:
-    /// 1. Scan receiving tx -> create StoredNote + LedgerEntry
-    /// 2. Scan spending tx -> mark note as spent + create LedgerEntry
-    /// 3. Verify balance from both note collection and ledger
     #[test]
-    fn test_scan_detects_transparent_spend() {
-        use crate::types::{LedgerCollection, LedgerEntry, NoteCollection, Pool, StoredNote};
+    fn test_scan_completeness_covers_every_action() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"");
+        let actions = tx.orchard_bundle().unwrap().actions().len();
 
-        const WALLET_ID: &str = "test_wallet";
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let completeness = crate::types::scan_completeness(&result, &output_counts(&tx));
+        assert_eq!(completeness.orchard.attempted, actions);
+        assert_eq!(completeness.orchard.total, actions);
+        assert!(completeness.is_complete());
+    }
 
-        // Same seed phrase as the receiving test
-        const SEED_PHRASE: &str = "ahead pupil festival wife avoid yellow noodle puzzle pact alone ginger judge safe era spread lawn goat potato punch physical lamp oyster crisp attract";
+    #[test]
+    fn test_scan_completeness_with_skipped_pool() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"");
+        let actions = tx.orchard_bundle().unwrap().actions().len();
+        let options = ScanOptions {
+            pools: Some(vec![Pool::Transparent, Pool::Sapling]),
+            ..ScanOptions::default()
+        };
 
-        // Load the two transaction hexes
-        const RECEIVING_TX_HEX: &str = include_str!("testdata/tx_0411ffa7.hex");
-        const SPENDING_TX_HEX: &str = include_str!("testdata/tx_5aa23ef4.hex");
+        let result =
+            scan_transaction_with_options(&tx, TEST_UFVK, Network::TestNetwork, None, &options)
+                .unwrap();
+        let completeness = crate::types::scan_completeness(&result, &output_counts(&tx));
+        assert_eq!(completeness.orchard.attempted, 0);
+        assert_eq!(completeness.orchard.total, actions);
+        assert!(!completeness.is_complete());
+    }
 
-        // Restore the wallet
-        let wallet = crate::wallet::restore_wallet(
-            SEED_PHRASE,
+    #[test]
+    fn test_orchard_ephemeral_keys() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
             Network::TestNetwork,
-            0, // account index
-            0, // address index
         )
-        .expect("Failed to restore wallet");
-
-        // Initialize collections (like frontend's localStorage)
-        let mut note_collection = NoteCollection::new();
-        let mut ledger_collection = LedgerCollection::default();
+        .unwrap();
+        let keys = orchard_ephemeral_keys(&tx);
+        assert_eq!(keys.len(), tx.orchard_bundle().unwrap().actions().len());
+        for key in &keys {
+            assert_eq!(hex::decode(key).unwrap().len(), 32);
+        }
 
-        // ========================================================
-        // Step 1: Scan the receiving transaction
-        // ========================================================
-        let receive_result = scan_transaction_hex(
-            RECEIVING_TX_HEX,
-            &wallet.unified_full_viewing_key,
-            Network::TestNetwork,
-            None,
+        let tx = parse_transaction(
+            include_str!("testdata/tx_v4_transparent.hex"),
+            Network::MainNetwork,
         )
-        .expect("Receiving tx scan should succeed");
+        .unwrap();
+        assert!(orchard_ephemeral_keys(&tx).is_empty());
+    }
 
-        // Verify we found a transparent output
-        let transparent_notes: Vec<_> = receive_result
-            .notes
-            .iter()
-            .filter(|n| n.pool == Pool::Transparent && n.value > 0)
-            .collect();
+    #[test]
+    fn test_scan_with_transparent_only_ufvk() {
+        use zcash_protocol::consensus::NetworkType;
 
+        // A UFVK must have a non-transparent item, so pair the transparent
+        // key with an item from a pool the wallet does not know about.
+        let (_, ufvk) = unified::Ufvk::decode(TEST_UFVK).unwrap();
+        let mut items: Vec<unified::Fvk> = ufvk
+            .items()
+            .into_iter()
+            .filter(|item| matches!(item, unified::Fvk::P2pkh(_)))
+            .collect();
+        items.push(unified::Fvk::Unknown {
+            typecode: 0x30,
+            data: vec![0; 32],
+        });
+        let transparent_only = unified::Ufvk::try_from_items(items)
+            .unwrap()
+            .encode(&NetworkType::Test);
         assert!(
-            !transparent_notes.is_empty(),
-            "Should find transparent output in receiving tx"
-        );
-
-        // Add notes to collection (like frontend's addNote)
-        let received_note = &transparent_notes[0];
-        let timestamp = "2024-01-01T00:00:00Z";
-        let stored_note = StoredNote::from_scanned_note(
-            received_note,
-            &receive_result.txid,
-            WALLET_ID,
-            timestamp,
+            parse_unified_capabilities(&transparent_only)
+                .unwrap()
+                .is_transparent_only()
         );
-        note_collection.add_or_update(stored_note.clone());
 
-        // Create ledger entry for receiving transaction (like frontend's createLedgerEntry)
-        let receive_ledger = LedgerEntry::from_scan_result(
-            &receive_result,
-            WALLET_ID,
-            vec![stored_note.id.clone()], // received note IDs
-            vec![],                       // no spent notes
-            &[],                          // no spent values
-            timestamp,
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let result = scan_transaction(&tx, &transparent_only, Network::TestNetwork, None).unwrap();
+        assert_eq!(result.scanned_pools, vec!["transparent"]);
+        assert!(
+            result
+                .notes
+                .iter()
+                .all(|note| note.pool == Pool::Transparent)
         );
-        ledger_collection.add_or_update(receive_ledger);
-
-        // Verify initial state
-        let initial_balance = note_collection.total_balance();
-        let ledger_balance = ledger_collection.compute_balance(WALLET_ID);
         assert_eq!(
-            initial_balance, received_note.value,
-            "Initial note balance should equal received amount"
+            result.transparent_outputs.len(),
+            tx.transparent_bundle()
+                .map_or(0, |bundle| bundle.vout.len())
         );
-        assert!(ledger_balance > 0, "Ledger should show positive balance");
+        assert_eq!(result.warnings, vec![ScanWarning::TransparentOnlyKey]);
 
-        // ========================================================
-        // Step 2: Scan the spending transaction
-        // ========================================================
-        let spend_result = scan_transaction_hex(
-            SPENDING_TX_HEX,
-            &wallet.unified_full_viewing_key,
+        // No warning when only the transparent pool was asked for
+        let options = ScanOptions {
+            pools: Some(vec![Pool::Transparent]),
+            ..ScanOptions::default()
+        };
+        let result = scan_transaction_with_options(
+            &tx,
+            &transparent_only,
             Network::TestNetwork,
             None,
+            &options,
         )
-        .expect("Spending tx scan should succeed");
+        .unwrap();
+        assert!(result.warnings.is_empty());
+    }
 
-        // Verify the txid
-        assert_eq!(
-            spend_result.txid,
-            "5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f"
-        );
+    /// A note the wallet holds, as it would be stored after a scan.
+    fn owned_note(
+        txid: &str,
+        pool: Pool,
+        output_index: u32,
+        value: u64,
+        nullifier: Option<&str>,
+    ) -> crate::types::StoredNote {
+        crate::types::StoredNote {
+            id: crate::types::StoredNote::generate_id(txid, pool, output_index),
+            wallet_id: "w".to_string(),
+            txid: txid.to_string(),
+            output_index,
+            pool,
+            value,
+            commitment: None,
+            nullifier: nullifier.map(str::to_string),
+            memo: None,
+            address: None,
+            spent_txid: None,
+            spent_at_height: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
+        }
+    }
 
-        // Verify transparent_spends contains the prevout reference
-        assert!(
-            !spend_result.transparent_spends.is_empty(),
-            "Spending tx should have transparent_spends"
-        );
+    /// A collection holding one note spent by the first nullifier of
+    /// `result`.
+    fn owning_first_spend(result: &ScanResult, pool: Pool, value: u64) -> NoteCollection {
+        let mut notes = NoteCollection::new();
+        notes.add_or_update(owned_note(
+            "prev",
+            pool,
+            0,
+            value,
+            Some(&result.spent_nullifiers[0].nullifier),
+        ));
+        notes
+    }
 
-        // Find the spend that matches our received note
-        let matching_spend = spend_result
-            .transparent_spends
-            .iter()
-            .find(|s| s.prevout_txid == receive_result.txid && s.prevout_index == 0);
+    #[test]
+    fn test_net_change_receive_only() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert_eq!(net_change(&result, &NoteCollection::new(), &[]), 10_000);
+    }
 
-        assert!(
-            matching_spend.is_some(),
-            "Should find spend matching our received output. Spends: {:?}",
-            spend_result.transparent_spends
-        );
+    #[test]
+    fn test_net_change_send_only() {
+        let tx = crate::testing::build_test_sapling_tx(TEST_UFVK, &other_ufvk(), 7_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        // The transaction spends no note the wallet knows of; the recovered
+        // payment to someone else is not received
+        assert_eq!(net_change(&result, &NoteCollection::new(), &[]), 0);
+    }
 
-        // Mark notes as spent (like frontend's markTransparentSpent)
-        let mark_result = note_collection.mark_spent_by_transparent(
-            &spend_result.transparent_spends,
-            &spend_result.txid,
-            Some(3760288), // block height from issue
+    #[test]
+    fn test_net_change_mixed() {
+        let tx =
+            crate::testing::build_test_orchard_payment(TEST_UFVK, &other_ufvk(), 20_000, 5_000);
+
+        // The sender spent a 30,000 note, paying 20,000 and a 5,000 fee,
+        // and gets its change back
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let owned = owning_first_spend(&result, Pool::Orchard, 30_000);
+        assert_eq!(net_change(&result, &owned, &[]), -25_000);
+
+        // The recipient gains the payment
+        let result = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        assert_eq!(net_change(&result, &NoteCollection::new(), &[]), 20_000);
+
+        // Sending to oneself only costs the fee
+        let tx = crate::testing::build_test_orchard_tx_with_change(TEST_UFVK, 20_000, 5_000);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let owned = owning_first_spend(&result, Pool::Orchard, 30_000);
+        assert_eq!(net_change(&result, &owned, &[]), -5_000);
+    }
+
+    #[test]
+    fn test_net_change_send_without_ovk() {
+        // Nothing can be recovered from the outputs, but the spent note is
+        // known to the wallet
+        let tx = crate::testing::build_test_orchard_tx(&other_ufvk(), 20_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(result.notes.iter().all(|note| note.scope.is_none()));
+
+        let owned = owning_first_spend(&result, Pool::Orchard, 25_000);
+        assert_eq!(net_change(&result, &owned, &[]), -25_000);
+    }
+
+    #[test]
+    fn test_net_change_transparent_send() {
+        // Spends outpoint 11..11:0 and pays 1 ZEC to someone else
+        let tx_hex = include_str!("testdata/tx_v4_transparent.hex").trim();
+        let result = scan_transaction_hex(tx_hex, TEST_UFVK, Network::MainNetwork, None).unwrap();
+
+        let mut owned = NoteCollection::new();
+        owned.add_or_update(owned_note(
+            &"11".repeat(32),
+            Pool::Transparent,
+            0,
+            100_010_000,
+            None,
+        ));
+        assert_eq!(net_change(&result, &owned, &[]), -100_010_000);
+
+        // Paying one of the wallet's own addresses only costs the fee
+        let payee = result.notes[0].address.clone().unwrap();
+        assert_eq!(net_change(&result, &owned, &[payee]), -10_000);
+    }
+
+    #[test]
+    fn test_stored_balance_excludes_sent_outputs() {
+        use crate::types::{NoteCollection, StoredNote};
+
+        // The wallet receives a note, then pays someone else
+        let received = crate::testing::build_test_orchard_tx(TEST_UFVK, 30_000, b"");
+        let payment =
+            crate::testing::build_test_orchard_payment(TEST_UFVK, &other_ufvk(), 20_000, 5_000);
+
+        let mut notes = NoteCollection::new();
+        for tx in [received, payment] {
+            let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+            for note in &result.notes {
+                if let Some(stored) = StoredNote::from_scanned_note(
+                    note,
+                    &result.txid,
+                    "w",
+                    "2024-01-01T00:00:00Z",
+                    None,
+                    result.is_coinbase,
+                ) {
+                    notes.add_or_update(stored);
+                }
+            }
+        }
+
+        // The recovered payment is not counted as the wallet's funds
+        assert_eq!(notes.total_balance(), 30_000);
+    }
+
+    #[test]
+    fn test_spendable_balance_of_scanned_note() {
+        use crate::types::{NoteCollection, StoredNote};
+
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 30_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(!result.is_coinbase);
+
+        let mut notes = NoteCollection::new();
+        for note in &result.notes {
+            if let Some(stored) = StoredNote::from_scanned_note(
+                note,
+                &result.txid,
+                "w",
+                "2024-01-01T00:00:00Z",
+                Some(1_000),
+                result.is_coinbase,
+            ) {
+                notes.add_or_update(stored);
+            }
+        }
+
+        assert_eq!(notes.total_balance(), 30_000);
+        assert_eq!(notes.spendable_balance(1_008, 10), 0);
+        assert_eq!(notes.spendable_balance(1_009, 10), 30_000);
+    }
+
+    #[test]
+    fn test_scan_detects_coinbase() {
+        // A v4 transaction whose only input has the null prevout
+        let coinbase_hex = concat!(
+            "0400008085202f8901",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "ffffffff03a08601ffffffff",
+            "0100e1f505000000001976a914222222222222222222222222222222222222222288ac",
+            "00000000000000000000000000000000000000",
         );
+        let result =
+            scan_transaction_hex(coinbase_hex, TEST_UFVK, Network::MainNetwork, None).unwrap();
+        assert!(result.is_coinbase);
+
+        let tx_hex = include_str!("testdata/tx_v4_transparent.hex").trim();
+        let result = scan_transaction_hex(tx_hex, TEST_UFVK, Network::MainNetwork, None).unwrap();
+        assert!(!result.is_coinbase);
+    }
+
+    #[test]
+    fn test_op_return_data() {
         assert_eq!(
-            mark_result.marked_count, 1,
-            "Should mark exactly one note as spent"
+            op_return_data(&hex::decode("6a04deadbeef").unwrap()),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
         );
-        assert!(
-            mark_result.unmatched_transparent.is_empty(),
-            "All spends should be matched"
+        assert_eq!(op_return_data(&[0x6a]), Some(Vec::new()));
+        let mut pushdata1 = vec![0x6a, 0x4c, 80];
+        pushdata1.extend([7u8; 80]);
+        assert_eq!(op_return_data(&pushdata1), Some(vec![7u8; 80]));
+        // OP_0 pushes nothing, OP_1 to OP_16 push their number
+        assert_eq!(
+            op_return_data(&[0x6a, 0x00, 0x51, 0x60, 0x01, 0xff]),
+            Some(vec![1, 16, 0xff])
         );
 
-        // Create ledger entry for spending transaction
-        // Note: In a real scenario, the spent note's value would be tracked
-        let spent_note_id = stored_note.id.clone();
-        let spent_value = stored_note.value;
-        let spend_ledger = LedgerEntry::from_scan_result(
-            &spend_result,
-            WALLET_ID,
-            vec![],              // no new notes received in our wallet
-            vec![spent_note_id], // the note we're spending
-            &[spent_value],      // value of the spent note
-            timestamp,
+        // Truncated pushes and P2PKH scripts are not OP_RETURN data
+        assert_eq!(op_return_data(&hex::decode("6a04dead").unwrap()), None);
+        let p2pkh = hex::decode("76a914222222222222222222222222222222222222222288ac").unwrap();
+        assert_eq!(op_return_data(&p2pkh), None);
+        assert_eq!(op_return_data(&[]), None);
+    }
+
+    #[test]
+    fn test_scan_skips_op_return_outputs() {
+        // Add a zero-value OP_RETURN output after the P2PKH output
+        let tx_hex = include_str!("testdata/tx_v4_transparent.hex")
+            .trim()
+            .replacen("ffffffff01", "ffffffff02", 1)
+            .replacen("88ac", "88ac0000000000000000066a04deadbeef", 1);
+        let tx = parse_transaction(&tx_hex, Network::MainNetwork).unwrap();
+        let bundle = tx.transparent_bundle().unwrap();
+        assert_eq!(bundle.vout.len(), 2);
+        assert_eq!(
+            op_return_data(&bundle.vout[1].script_pubkey().0.0),
+            Some(vec![0xde, 0xad, 0xbe, 0xef])
         );
-        ledger_collection.add_or_update(spend_ledger);
 
-        // ========================================================
-        // Step 3: Verify final state
-        // ========================================================
+        let result = scan_transaction(&tx, TEST_UFVK, Network::MainNetwork, None).unwrap();
+        assert_eq!(result.transparent_received, 100_000_000);
+        assert_eq!(result.transparent_outputs.len(), 1);
+        assert_eq!(result.transparent_outputs[0].index, 0);
+        assert!(result.notes.iter().all(|note| note.output_index == 0));
+    }
 
-        // Verify the note is marked as spent
-        let note = &note_collection.notes[0];
-        assert!(note.is_spent(), "Note should be marked as spent");
+    #[test]
+    fn test_scan_skips_malformed_op_return_outputs() {
+        // A valued OP_RETURN output whose push is truncated is still unspendable
+        let tx_hex = include_str!("testdata/tx_v4_transparent.hex")
+            .trim()
+            .replacen("ffffffff01", "ffffffff02", 1)
+            .replacen("88ac", "88ac1027000000000000046a04dead", 1);
+        let tx = parse_transaction(&tx_hex, Network::MainNetwork).unwrap();
+        let script = &tx.transparent_bundle().unwrap().vout[1].script_pubkey().0.0;
+        assert_eq!(op_return_data(script), None);
+        assert!(is_unspendable(script));
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::MainNetwork, None).unwrap();
+        assert_eq!(result.transparent_received, 100_000_000);
+        assert_eq!(result.transparent_outputs.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_transaction_rejects_oversized_input() {
+        let tx_hex = include_str!("testdata/tx_v4_transparent.hex").trim();
+        let size = tx_hex.len() / 2;
+
+        assert!(parse_transaction_with_limit(tx_hex, Network::MainNetwork, size).is_ok());
+        assert!(matches!(
+            parse_transaction_with_limit(tx_hex, Network::MainNetwork, size - 1),
+            Err(ScannerError::TransactionTooLarge { size: s, max }) if s == size && max == size - 1
+        ));
+
+        let oversized = "00".repeat(MAX_TX_BYTES + 1);
+        assert!(matches!(
+            parse_transaction(&oversized, Network::MainNetwork),
+            Err(ScannerError::TransactionTooLarge {
+                max: MAX_TX_BYTES,
+                ..
+            })
+        ));
+        assert!(matches!(
+            txid_from_hex(&oversized),
+            Err(ScannerError::TransactionTooLarge { .. })
+        ));
+        assert!(matches!(
+            parse_transaction_auto(&oversized, Network::MainNetwork),
+            Err(ScannerError::TransactionTooLarge { .. })
+        ));
+        let oversized_base64 = BASE64_STANDARD.encode(vec![0u8; MAX_TX_BYTES + 1]);
+        assert!(matches!(
+            parse_transaction_base64(&oversized_base64, Network::MainNetwork),
+            Err(ScannerError::TransactionTooLarge {
+                size,
+                max: MAX_TX_BYTES,
+            }) if size == MAX_TX_BYTES + 1
+        ));
+
+        // Input far too long is rejected before it is cleaned, whatever it
+        // contains
+        let garbage = "z".repeat(MAX_TX_BYTES * MAX_INPUT_CHARS_PER_BYTE + 1);
+        assert!(matches!(
+            parse_transaction(&garbage, Network::MainNetwork),
+            Err(ScannerError::TransactionTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_memo_capable_outputs() {
+        let tx = parse_transaction(
+            include_str!("testdata/tx_0411ffa7.hex"),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let counts = output_counts(&tx);
+        assert!(counts.sapling_outputs + counts.orchard_actions > 0);
         assert_eq!(
-            note.spent_txid.as_deref(),
-            Some("5aa23ef474d119dc0262b1a350b00cf4d806ee72036c460f6bcf8252da96695f")
+            memo_capable_outputs(&tx),
+            counts.sapling_outputs + counts.orchard_actions
         );
-        assert_eq!(note.spent_at_height, Some(3760288));
 
-        // Verify balance from note collection is now zero
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"hi");
         assert_eq!(
-            note_collection.total_balance(),
-            0,
-            "Note balance should be zero after spending"
+            memo_capable_outputs(&tx),
+            tx.orchard_bundle().unwrap().actions().len()
         );
 
-        // Verify ledger has two entries
-        let ledger_entries = ledger_collection.entries_for_wallet(WALLET_ID);
-        assert_eq!(ledger_entries.len(), 2, "Ledger should have 2 entries");
+        let transparent = parse_transaction(
+            include_str!("testdata/tx_v4_transparent.hex"),
+            Network::MainNetwork,
+        )
+        .unwrap();
+        assert_eq!(memo_capable_outputs(&transparent), 0);
+    }
 
-        // Verify ledger entries track the flow correctly
-        let receive_entry = ledger_entries
+    #[test]
+    fn test_decrypted_notes_have_verified_commitments() {
+        let tx =
+            crate::testing::build_test_orchard_payment(TEST_UFVK, &other_ufvk(), 20_000, 5_000);
+
+        // Outgoing notes recovered by the sender
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        for note in &result.notes {
+            assert_eq!(note.commitment_verified, note.scope.is_some());
+        }
+        assert!(result.notes.iter().any(|note| note.commitment_verified));
+
+        // The incoming note decrypted by the recipient
+        let result = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        let received = result
+            .notes
             .iter()
-            .find(|e| e.txid == receive_result.txid);
-        let spend_entry = ledger_entries.iter().find(|e| e.txid == spend_result.txid);
+            .find(|note| note.scope == Some(NoteScope::Incoming))
+            .unwrap();
+        assert!(received.commitment_verified);
+    }
 
-        assert!(
-            receive_entry.is_some(),
-            "Should have receiving ledger entry"
-        );
-        assert!(spend_entry.is_some(), "Should have spending ledger entry");
+    #[test]
+    fn test_scan_warns_on_malformed_action() {
+        let tx = crate::testing::build_test_orchard_tx(TEST_UFVK, 10_000, b"");
+        let epk = tx.orchard_bundle().unwrap().actions()[1]
+            .encrypted_note()
+            .epk_bytes;
+        let mut bytes = Vec::new();
+        tx.write(&mut bytes).unwrap();
 
-        // The receiving entry should show value received
-        assert!(
-            receive_entry.unwrap().value_received > 0,
-            "Receiving entry should have value_received > 0"
+        // 2^255 - 1 is not a canonical Pallas point encoding
+        let start = bytes.windows(32).position(|window| window == epk).unwrap();
+        bytes[start..start + 32].copy_from_slice(&[0xff; 32]);
+        let malformed = parse_transaction(&hex::encode(bytes), Network::TestNetwork).unwrap();
+
+        let result = scan_transaction(&malformed, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert_eq!(
+            result.warnings,
+            vec![ScanWarning::MalformedAction { action_index: 1 }]
         );
+
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_decrypt_output_matches_batch_scan() {
+        let txs = [
+            crate::testing::build_test_orchard_payment(TEST_UFVK, &other_ufvk(), 20_000, 5_000),
+            crate::testing::build_test_sapling_tx(TEST_UFVK, &other_ufvk(), 7_000, b"memo"),
+        ];
+        for tx in &txs {
+            let pending = parse_outputs(tx);
+            let counts = output_counts(tx);
+            assert_eq!(
+                pending.len(),
+                counts.sapling_outputs + counts.orchard_actions
+            );
+
+            for key in [TEST_UFVK.to_string(), other_ufvk()] {
+                let batch = scan_transaction(tx, &key, Network::TestNetwork, None).unwrap();
+                let progressive: Vec<ScannedNote> = pending
+                    .iter()
+                    .map(|output| decrypt_output(output, &key, Network::TestNetwork).unwrap())
+                    .collect();
+                assert_eq!(progressive, batch.notes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_outgoing_notes_flag_external_payments() {
+        let tx =
+            crate::testing::build_test_orchard_payment(TEST_UFVK, &other_ufvk(), 20_000, 5_000);
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let outgoing = |value| {
+            result
+                .notes
+                .iter()
+                .find(|note| note.is_outgoing() && note.value == value)
+                .unwrap()
+        };
+        assert!(outgoing(20_000).is_external);
+        assert!(outgoing(20_000).address.is_some());
+        assert!(!outgoing(5_000).is_external);
+
+        let tx = crate::testing::build_test_sapling_tx(TEST_UFVK, &other_ufvk(), 7_000, b"");
+        let result = scan_transaction(&tx, TEST_UFVK, Network::TestNetwork, None).unwrap();
+        let sent = result.notes.iter().find(|note| note.is_outgoing()).unwrap();
+        assert!(sent.is_external);
+
+        // Received notes are never external
+        let result = scan_transaction(&tx, &other_ufvk(), Network::TestNetwork, None).unwrap();
+        assert!(result.notes.iter().all(|note| !note.is_external));
     }
 }
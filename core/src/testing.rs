@@ -0,0 +1,487 @@
+//! Test vector generation.
+//!
+//! Builds reproducible transactions carrying shielded notes for a given
+//! viewing key, so the scanner can be tested with direct decryption
+//! assertions instead of relying on captured mainnet transactions that the
+//! test keys cannot decrypt.
+//!
+//! The generated transactions have valid note encryption but dummy proofs
+//! and signatures: they parse and scan, but would be rejected by consensus.
+//!
+//! Available in tests and with the `test-dependencies` feature.
+
+use orchard::builder::{Builder, BundleType};
+use orchard::bundle::Authorized;
+use orchard::circuit::Proof;
+use orchard::keys::Scope;
+use orchard::primitives::redpallas::{Binding, Signature, SpendAuth};
+use orchard::tree::Anchor;
+use orchard::value::NoteValue;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use sapling_crypto::note_encryption::{SaplingDomain, sapling_note_encryption};
+use sapling_crypto::value::{ValueCommitTrapdoor, ValueCommitment};
+use zcash_note_encryption::Domain;
+use zcash_primitives::transaction::{Transaction, TransactionData, TxVersion};
+use zcash_protocol::consensus::{BlockHeight, BranchId};
+use zcash_protocol::value::ZatBalance;
+use zcash_transparent::address::TransparentAddress;
+
+use crate::scanner::{extract_orchard_fvk, extract_sapling_dfvk};
+use crate::wallet::network_from_ufvk;
+
+/// Seed of the RNG used to build test transactions.
+const TEST_RNG_SEED: u64 = 0x5a5a_5a5a;
+
+/// Build a minimal v5 transaction with an Orchard output for a viewing key.
+///
+/// The transaction has no transparent or Sapling parts. Its Orchard bundle
+/// is padded with a dummy action, as real wallets do; exactly one action
+/// carries a note to the key's external address at index 0 with the given
+/// value and memo. The result is deterministic for given arguments.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key (with an Orchard component)
+/// * `value` - The note value in zatoshis
+/// * `memo` - The memo bytes (at most 512 bytes, zero-padded)
+///
+/// # Panics
+///
+/// If the UFVK has no Orchard component or the memo is longer than 512
+/// bytes.
+pub fn build_test_orchard_tx(ufvk: &str, value: u64, memo: &[u8]) -> Transaction {
+    let mut memo_bytes = [0u8; 512];
+    memo_bytes[..memo.len()].copy_from_slice(memo);
+
+    build_orchard_tx(&[(None, test_recipient(ufvk, 0), value, memo_bytes)], value)
+}
+
+/// Build a minimal v5 transaction with several Orchard outputs.
+///
+/// Like [`build_test_orchard_tx`], with one Orchard output (and an empty
+/// memo) per `(ufvk, address_index, value)`, each to the key's external
+/// address at that index. The keys may belong to different wallets.
+///
+/// # Panics
+///
+/// If a UFVK has no Orchard component.
+pub fn build_test_orchard_tx_multi(outputs: &[(&str, u32, u64)]) -> Transaction {
+    let outputs: Vec<_> = outputs
+        .iter()
+        .map(|(ufvk, index, value)| (None, test_recipient(ufvk, *index), *value, [0u8; 512]))
+        .collect();
+    let rng_seed = outputs
+        .iter()
+        .fold(0, |seed, (_, _, value, _)| seed ^ value);
+    build_orchard_tx(&outputs, rng_seed)
+}
+
+/// Build a minimal v5 transaction paying a key and returning change to it.
+///
+/// Like [`build_test_orchard_tx`], with two Orchard outputs: `value` to the
+/// key's external address at index 0, and `change` to its internal address
+/// at index 0. Both are encrypted with the key's OVK of the matching scope,
+/// as a wallet sending to itself would.
+///
+/// # Panics
+///
+/// If the UFVK has no Orchard component.
+pub fn build_test_orchard_tx_with_change(ufvk: &str, value: u64, change: u64) -> Transaction {
+    let fvk = test_orchard_fvk(ufvk);
+    build_orchard_tx(
+        &[
+            (
+                Some(fvk.to_ovk(Scope::External)),
+                fvk.address_at(0u32, Scope::External),
+                value,
+                [0u8; 512],
+            ),
+            (
+                Some(fvk.to_ovk(Scope::Internal)),
+                fvk.address_at(0u32, Scope::Internal),
+                change,
+                [0u8; 512],
+            ),
+        ],
+        value ^ change.rotate_left(32),
+    )
+}
+
+/// Build a minimal v5 transaction paying another key, with change.
+///
+/// Like [`build_test_orchard_tx_with_change`], but the `value` output goes
+/// to the external address at index 0 of `recipient_ufvk`. Both outputs are
+/// encrypted with OVKs of `sender_ufvk`, as a payment it made would be.
+///
+/// # Panics
+///
+/// If either UFVK has no Orchard component.
+pub fn build_test_orchard_payment(
+    sender_ufvk: &str,
+    recipient_ufvk: &str,
+    value: u64,
+    change: u64,
+) -> Transaction {
+    let fvk = test_orchard_fvk(sender_ufvk);
+    build_orchard_tx(
+        &[
+            (
+                Some(fvk.to_ovk(Scope::External)),
+                test_recipient(recipient_ufvk, 0),
+                value,
+                [0u8; 512],
+            ),
+            (
+                Some(fvk.to_ovk(Scope::Internal)),
+                fvk.address_at(0u32, Scope::Internal),
+                change,
+                [0u8; 512],
+            ),
+        ],
+        value.rotate_left(16) ^ change,
+    )
+}
+
+/// Build a minimal v5 transaction paying several keys, with change.
+///
+/// Like [`build_test_orchard_payment`], with one output per
+/// `(recipient_ufvk, value)` to the recipient's external address at index
+/// 0, all encrypted with the external OVK of `sender_ufvk`.
+///
+/// # Panics
+///
+/// If any UFVK has no Orchard component.
+pub fn build_test_orchard_payment_multi(
+    sender_ufvk: &str,
+    payments: &[(&str, u64)],
+    change: u64,
+) -> Transaction {
+    let fvk = test_orchard_fvk(sender_ufvk);
+    let mut outputs: Vec<TestOrchardOutput> = payments
+        .iter()
+        .map(|(recipient_ufvk, value)| {
+            (
+                Some(fvk.to_ovk(Scope::External)),
+                test_recipient(recipient_ufvk, 0),
+                *value,
+                [0u8; 512],
+            )
+        })
+        .collect();
+    outputs.push((
+        Some(fvk.to_ovk(Scope::Internal)),
+        fvk.address_at(0u32, Scope::Internal),
+        change,
+        [0u8; 512],
+    ));
+    let rng_seed = payments
+        .iter()
+        .fold(change, |seed, (_, value)| seed.rotate_left(16) ^ value);
+    build_orchard_tx(&outputs, rng_seed)
+}
+
+/// The Orchard full viewing key of a UFVK.
+fn test_orchard_fvk(ufvk: &str) -> orchard::keys::FullViewingKey {
+    let network = network_from_ufvk(ufvk).expect("UFVK must be valid");
+    extract_orchard_fvk(ufvk, network)
+        .expect("UFVK must be valid")
+        .expect("UFVK must have an Orchard component")
+}
+
+/// The Sapling diversifiable full viewing key of a UFVK.
+fn test_sapling_dfvk(ufvk: &str) -> sapling_crypto::zip32::DiversifiableFullViewingKey {
+    let network = network_from_ufvk(ufvk).expect("UFVK must be valid");
+    extract_sapling_dfvk(ufvk, network)
+        .expect("UFVK must be valid")
+        .expect("UFVK must have a Sapling component")
+}
+
+/// The external Orchard address of a UFVK at an index.
+fn test_recipient(ufvk: &str, index: u32) -> orchard::Address {
+    test_orchard_fvk(ufvk).address_at(index, Scope::External)
+}
+
+/// An Orchard output to build: sender OVK, recipient, value and memo.
+type TestOrchardOutput = (
+    Option<orchard::keys::OutgoingViewingKey>,
+    orchard::Address,
+    u64,
+    [u8; 512],
+);
+
+/// Build a v5 transaction with an Orchard output per `(ovk, recipient,
+/// value, memo)`, padded and with dummy proofs and signatures.
+fn build_orchard_tx(outputs: &[TestOrchardOutput], rng_seed: u64) -> Transaction {
+    let mut rng = StdRng::seed_from_u64(TEST_RNG_SEED ^ rng_seed);
+
+    let mut builder = Builder::new(BundleType::DEFAULT, Anchor::empty_tree());
+    for (ovk, recipient, value, memo) in outputs {
+        builder
+            .add_output(ovk.clone(), *recipient, NoteValue::from_raw(*value), *memo)
+            .expect("output should be accepted");
+    }
+    let (unauthorized, _) = builder
+        .build::<i64>(&mut rng)
+        .expect("bundle should build")
+        .expect("bundle should not be empty");
+
+    // Replace proving and signing with dummy values
+    let bundle = unauthorized
+        .map_authorization(
+            &mut rng,
+            |_, _, _| Signature::<SpendAuth>::from([0u8; 64]),
+            |_, _| {
+                Authorized::from_parts(
+                    Proof::new(vec![0u8; 32]),
+                    Signature::<Binding>::from([0u8; 64]),
+                )
+            },
+        )
+        .try_map_value_balance(ZatBalance::from_i64)
+        .expect("value balance should be in range");
+
+    TransactionData::from_parts(
+        TxVersion::V5,
+        BranchId::Nu5,
+        0,
+        BlockHeight::from_u32(0),
+        None,
+        None,
+        None,
+        Some(bundle),
+    )
+    .freeze()
+    .expect("transaction should freeze")
+}
+
+/// Size of the v5 header fields preceding the transparent bundle.
+const V5_HEADER_SIZE: usize = 20;
+
+/// Build a v5 transaction shielding a transparent input to a viewing key.
+///
+/// Like [`build_test_orchard_tx`], with one transparent input (spending a
+/// dummy outpoint with an empty script) and no transparent outputs, the
+/// shape of an auto-shielding transaction.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key (with an Orchard component)
+/// * `value` - The shielded note value in zatoshis
+///
+/// # Panics
+///
+/// If the UFVK has no Orchard component.
+pub fn build_test_shielding_tx(ufvk: &str, value: u64) -> Transaction {
+    let mut bytes = Vec::new();
+    build_test_orchard_tx(ufvk, value, b"")
+        .write(&mut bytes)
+        .expect("transaction should serialize");
+
+    // Replace the empty vin list with a single input
+    assert_eq!(
+        bytes[V5_HEADER_SIZE], 0,
+        "transaction should have no inputs"
+    );
+    let mut input = vec![1u8];
+    input.extend_from_slice(&[0x11; 32]); // prevout txid
+    input.extend_from_slice(&0u32.to_le_bytes()); // prevout index
+    input.push(0); // empty script_sig
+    input.extend_from_slice(&u32::MAX.to_le_bytes()); // sequence
+    bytes.splice(V5_HEADER_SIZE..=V5_HEADER_SIZE, input);
+
+    Transaction::read(&bytes[..], BranchId::Nu5).expect("transaction should parse")
+}
+
+/// Build a minimal v5 transaction with a single transparent output.
+///
+/// The transaction has no inputs and no shielded parts; its one output pays
+/// `value` to a P2PKH address.
+///
+/// # Panics
+///
+/// If the address is not a P2PKH address.
+pub fn build_test_transparent_tx(recipient: &TransparentAddress, value: u64) -> Transaction {
+    let TransparentAddress::PublicKeyHash(hash) = recipient else {
+        panic!("recipient must be a P2PKH address");
+    };
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0x8000_0005u32.to_le_bytes()); // overwintered v5
+    bytes.extend_from_slice(&0x26A7_270Au32.to_le_bytes()); // version group
+    bytes.extend_from_slice(&u32::from(BranchId::Nu5).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // lock time
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // expiry height
+    bytes.extend_from_slice(&[0, 1]); // no transparent inputs, one output
+    bytes.extend_from_slice(&value.to_le_bytes());
+    bytes.extend_from_slice(&[25, 0x76, 0xa9, 20]); // OP_DUP OP_HASH160 <20 bytes>
+    bytes.extend_from_slice(hash);
+    bytes.extend_from_slice(&[0x88, 0xac]); // OP_EQUALVERIFY OP_CHECKSIG
+    bytes.extend_from_slice(&[0, 0]); // no Sapling spends or outputs
+    bytes.push(0); // no Orchard actions
+
+    Transaction::read(&bytes[..], BranchId::Nu5).expect("transaction should parse")
+}
+
+/// Build a minimal v5 transaction with a Sapling output sent by one key.
+///
+/// The transaction has a single Sapling output, to the default Sapling
+/// address of `recipient_ufvk`, whose outgoing ciphertext is encrypted with
+/// the external OVK of `sender_ufvk`. It has no spends, transparent or
+/// Orchard parts. The result is deterministic for given arguments.
+///
+/// # Arguments
+///
+/// * `sender_ufvk` - The encoded UFVK whose OVK can recover the output
+/// * `recipient_ufvk` - The encoded UFVK receiving the note
+/// * `value` - The note value in zatoshis
+/// * `memo` - The memo bytes (at most 512 bytes, zero-padded)
+///
+/// # Panics
+///
+/// If either UFVK has no Sapling component or the memo is longer than 512
+/// bytes.
+pub fn build_test_sapling_tx(
+    sender_ufvk: &str,
+    recipient_ufvk: &str,
+    value: u64,
+    memo: &[u8],
+) -> Transaction {
+    let output = sapling_output_bytes(sender_ufvk, recipient_ufvk, value, memo);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0x8000_0005u32.to_le_bytes()); // overwintered v5
+    bytes.extend_from_slice(&0x26A7_270Au32.to_le_bytes()); // version group
+    bytes.extend_from_slice(&u32::from(BranchId::Nu5).to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // lock time
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // expiry height
+    bytes.extend_from_slice(&[0, 0]); // no transparent inputs or outputs
+    bytes.extend_from_slice(&[0, 1]); // no Sapling spends, one output
+    bytes.extend_from_slice(&output);
+    bytes.extend_from_slice(&(-(value as i64)).to_le_bytes()); // value balance
+    bytes.extend_from_slice(&[0u8; 192]); // output proof
+    bytes.extend_from_slice(&[0u8; 64]); // binding signature
+    bytes.push(0); // no Orchard actions
+
+    Transaction::read(&bytes[..], BranchId::Nu5).expect("transaction should parse")
+}
+
+/// Build a minimal v4 (Sapling era) transaction with a Sapling output sent
+/// by one key.
+///
+/// Like [`build_test_sapling_tx`], in the v4 format. v4 transactions do not
+/// commit to a consensus branch, so the parser accepts them under any
+/// branch from Sapling on.
+///
+/// # Panics
+///
+/// If either UFVK has no Sapling component or the memo is longer than 512
+/// bytes.
+pub fn build_test_sapling_tx_v4(
+    sender_ufvk: &str,
+    recipient_ufvk: &str,
+    value: u64,
+    memo: &[u8],
+) -> Transaction {
+    let output = sapling_output_bytes(sender_ufvk, recipient_ufvk, value, memo);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0x8000_0004u32.to_le_bytes()); // overwintered v4
+    bytes.extend_from_slice(&0x892F_2085u32.to_le_bytes()); // version group
+    bytes.extend_from_slice(&[0, 0]); // no transparent inputs or outputs
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // lock time
+    bytes.extend_from_slice(&0u32.to_le_bytes()); // expiry height
+    bytes.extend_from_slice(&(-(value as i64)).to_le_bytes()); // value balance
+    bytes.extend_from_slice(&[0, 1]); // no Sapling spends, one output
+    bytes.extend_from_slice(&output);
+    bytes.extend_from_slice(&[0u8; 192]); // output proof
+    bytes.push(0); // no JoinSplits
+    bytes.extend_from_slice(&[0u8; 64]); // binding signature
+
+    Transaction::read(&bytes[..], BranchId::Sapling).expect("transaction should parse")
+}
+
+/// Encrypt a Sapling output from one key to another, serialized as
+/// `cv || cmu || epk || enc_ciphertext || out_ciphertext`.
+fn sapling_output_bytes(
+    sender_ufvk: &str,
+    recipient_ufvk: &str,
+    value: u64,
+    memo: &[u8],
+) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(TEST_RNG_SEED ^ value);
+    let mut memo_bytes = [0u8; 512];
+    memo_bytes[..memo.len()].copy_from_slice(memo);
+
+    let sender = test_sapling_dfvk(sender_ufvk);
+    let recipient = test_sapling_dfvk(recipient_ufvk).default_address().1;
+
+    let mut rseed = [0u8; 32];
+    rng.fill_bytes(&mut rseed);
+    let note = sapling_crypto::Note::from_parts(
+        recipient,
+        sapling_crypto::value::NoteValue::from_raw(value),
+        sapling_crypto::Rseed::AfterZip212(rseed),
+    );
+    let cv = ValueCommitment::derive(note.value(), ValueCommitTrapdoor::random(&mut rng));
+    let cmu = note.cmu();
+    let encryptor = sapling_note_encryption(Some(sender.fvk().ovk), note, memo_bytes, &mut rng);
+    let enc_ciphertext = encryptor.encrypt_note_plaintext();
+    let out_ciphertext = encryptor.encrypt_outgoing_plaintext(&cv, &cmu, &mut rng);
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&cv.to_bytes());
+    bytes.extend_from_slice(&cmu.to_bytes());
+    bytes.extend_from_slice(&SaplingDomain::epk_bytes(encryptor.epk()).0);
+    bytes.extend_from_slice(enc_ciphertext.as_ref());
+    bytes.extend_from_slice(&out_ciphertext);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use orchard::keys::PreparedIncomingViewingKey;
+    use orchard::note_encryption::OrchardDomain;
+    use zcash_note_encryption::try_note_decryption;
+    use zcash_protocol::consensus::Network;
+
+    use crate::wallet::restore_wallet;
+
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn test_build_test_orchard_tx_is_decryptable() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let tx = build_test_orchard_tx(&wallet.unified_full_viewing_key, 77_000, b"test vector");
+
+        let fvk = extract_orchard_fvk(&wallet.unified_full_viewing_key, Network::TestNetwork)
+            .unwrap()
+            .unwrap();
+        let ivk = PreparedIncomingViewingKey::new(&fvk.to_ivk(Scope::External));
+        let bundle = tx.orchard_bundle().expect("should have Orchard bundle");
+
+        let decrypted: Vec<_> = bundle
+            .actions()
+            .iter()
+            .filter_map(|action| {
+                try_note_decryption(&OrchardDomain::for_action(action), &ivk, action)
+            })
+            .collect();
+        assert_eq!(decrypted.len(), 1);
+
+        let (note, _, memo) = &decrypted[0];
+        assert_eq!(note.value().inner(), 77_000);
+        assert_eq!(&memo[..11], b"test vector");
+    }
+
+    #[test]
+    fn test_build_test_orchard_tx_is_deterministic() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let tx1 = build_test_orchard_tx(&wallet.unified_full_viewing_key, 1_000, b"");
+        let tx2 = build_test_orchard_tx(&wallet.unified_full_viewing_key, 1_000, b"");
+        assert_eq!(tx1.txid(), tx2.txid());
+    }
+}
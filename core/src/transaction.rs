@@ -21,7 +21,9 @@ use zcash_transparent::bundle::{OutPoint, TxOut};
 use zcash_transparent::keys::{AccountPrivKey, IncomingViewingKey, NonHardenedChildIndex};
 use zip32::AccountId;
 
-use crate::types::{Pool, StoredNote};
+use crate::compact_size::push_compact_size;
+use crate::scanner::zip317_fee_for_actions;
+use crate::types::{Pool, ScannedNote, StoredNote};
 
 /// Errors that can occur during transaction operations.
 #[derive(Debug)]
@@ -78,6 +80,35 @@ impl core::fmt::Display for TransactionError {
 
 impl core::error::Error for TransactionError {}
 
+/// Errors that can occur when selecting notes to spend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectError {
+    /// The notes do not cover the target plus fee.
+    InsufficientFunds { available: u64, shortfall: u64 },
+    /// The target plus fee does not fit in a `u64`.
+    AmountOverflow,
+}
+
+impl core::fmt::Display for SelectError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InsufficientFunds {
+                available,
+                shortfall,
+            } => {
+                write!(
+                    f,
+                    "Insufficient funds: available {} zatoshis, {} zatoshis short",
+                    available, shortfall
+                )
+            }
+            Self::AmountOverflow => write!(f, "Target plus fee overflows"),
+        }
+    }
+}
+
+impl core::error::Error for SelectError {}
+
 /// A UTXO (unspent transparent output) to be spent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Utxo {
@@ -165,6 +196,106 @@ pub struct UnsignedTransaction {
     pub network: Network,
 }
 
+/// Pools in the order they are preferred when selecting notes.
+const SELECTION_POOL_ORDER: [Pool; 3] = [Pool::Orchard, Pool::Sapling, Pool::Transparent];
+
+/// Pick notes covering a target amount plus fee.
+///
+/// Notes are taken largest first. If a single pool can cover the amount on
+/// its own, only notes from that pool are used (Orchard first, then Sapling,
+/// then transparent), so the transaction does not move value across pools.
+/// Otherwise notes from every pool are combined. Outgoing notes and notes
+/// that could not be decrypted (value 0) are never selected.
+///
+/// # Arguments
+///
+/// * `unspent` - The wallet's unspent notes
+/// * `target` - Amount to send in zatoshis
+/// * `fee` - Transaction fee in zatoshis
+///
+/// # Returns
+///
+/// The selected notes, or `SelectError::InsufficientFunds` with the
+/// shortfall if all spendable notes together are not enough.
+pub fn select_notes(
+    unspent: &[ScannedNote],
+    target: u64,
+    fee: u64,
+) -> Result<Vec<ScannedNote>, SelectError> {
+    let required = target.checked_add(fee).ok_or(SelectError::AmountOverflow)?;
+
+    let mut spendable: Vec<&ScannedNote> = unspent
+        .iter()
+        .filter(|n| n.value > 0 && !n.is_outgoing())
+        .collect();
+    spendable.sort_by(|a, b| b.value.cmp(&a.value));
+
+    let take_largest = |notes: &[&ScannedNote]| -> Option<Vec<ScannedNote>> {
+        let mut selected = Vec::new();
+        let mut total = 0u64;
+        for note in notes {
+            if total >= required {
+                break;
+            }
+            total = total.saturating_add(note.value);
+            selected.push((*note).clone());
+        }
+        (total >= required).then_some(selected)
+    };
+
+    for pool in SELECTION_POOL_ORDER {
+        let pool_notes: Vec<&ScannedNote> = spendable
+            .iter()
+            .copied()
+            .filter(|n| n.pool == pool)
+            .collect();
+        if let Some(selected) = take_largest(&pool_notes) {
+            return Ok(selected);
+        }
+    }
+
+    take_largest(&spendable).ok_or_else(|| {
+        let available = spendable
+            .iter()
+            .fold(0u64, |total, n| total.saturating_add(n.value));
+        SelectError::InsufficientFunds {
+            available,
+            shortfall: required - available,
+        }
+    })
+}
+
+/// Compute the ZIP 317 conventional fee for a transaction shape.
+///
+/// The number of logical actions is the larger of the transparent input and
+/// output counts, plus the larger of the Sapling spend and output counts,
+/// plus the Orchard actions. Transparent inputs and outputs are assumed to
+/// be standard P2PKH.
+///
+/// # Arguments
+///
+/// * `inputs` - Number of transparent inputs
+/// * `outputs` - Number of transparent outputs
+/// * `orchard_actions` - Number of Orchard actions (including padding)
+/// * `sapling_spends` - Number of Sapling spends
+/// * `sapling_outputs` - Number of Sapling outputs
+///
+/// # Returns
+///
+/// `ZIP317_MARGINAL_FEE` times the number of logical actions, with a
+/// minimum of `ZIP317_GRACE_ACTIONS` actions.
+pub fn conventional_fee(
+    inputs: usize,
+    outputs: usize,
+    orchard_actions: usize,
+    sapling_spends: usize,
+    sapling_outputs: usize,
+) -> u64 {
+    zip317_fee_for_actions(
+        inputs.max(outputs) + sapling_spends.max(sapling_outputs) + orchard_actions,
+    )
+}
+
 /// Find the address index for a given transparent address.
 ///
 /// This function iterates through address indices (0 to max_index) to find
@@ -543,7 +674,7 @@ fn serialize_transaction(
     // Write transparent bundle
     if let Some(bundle) = tx.transparent_bundle() {
         // Write vin count (CompactSize)
-        write_compact_size(&mut bytes, bundle.vin.len())?;
+        push_compact_size(&mut bytes, bundle.vin.len() as u64);
 
         // Write each input
         for vin in &bundle.vin {
@@ -561,7 +692,7 @@ fn serialize_transaction(
 
             // Write scriptSig
             let script_sig = vin.script_sig();
-            write_compact_size(&mut bytes, script_sig.0.0.len())?;
+            push_compact_size(&mut bytes, script_sig.0.0.len() as u64);
             bytes.write_all(&script_sig.0.0).map_err(|e| {
                 TransactionError::BuildFailed(format!("Failed to write scriptSig: {}", e))
             })?;
@@ -575,7 +706,7 @@ fn serialize_transaction(
         }
 
         // Write vout count (CompactSize)
-        write_compact_size(&mut bytes, bundle.vout.len())?;
+        push_compact_size(&mut bytes, bundle.vout.len() as u64);
 
         // Write each output
         for vout in &bundle.vout {
@@ -588,66 +719,115 @@ fn serialize_transaction(
 
             // Write scriptPubKey
             let script_pubkey = vout.script_pubkey();
-            write_compact_size(&mut bytes, script_pubkey.0.0.len())?;
+            push_compact_size(&mut bytes, script_pubkey.0.0.len() as u64);
             bytes.write_all(&script_pubkey.0.0).map_err(|e| {
                 TransactionError::BuildFailed(format!("Failed to write scriptPubKey: {}", e))
             })?;
         }
     } else {
         // Empty transparent bundle
-        write_compact_size(&mut bytes, 0)?; // vin count
-        write_compact_size(&mut bytes, 0)?; // vout count
+        push_compact_size(&mut bytes, 0); // vin count
+        push_compact_size(&mut bytes, 0); // vout count
     }
 
     // Write empty Sapling bundle (nSpendsSapling = 0, nOutputsSapling = 0)
-    write_compact_size(&mut bytes, 0)?; // nSpendsSapling
-    write_compact_size(&mut bytes, 0)?; // nOutputsSapling
+    push_compact_size(&mut bytes, 0); // nSpendsSapling
+    push_compact_size(&mut bytes, 0); // nOutputsSapling
 
     // Write empty Orchard bundle (nActionsOrchard = 0)
-    write_compact_size(&mut bytes, 0)?; // nActionsOrchard
+    push_compact_size(&mut bytes, 0); // nActionsOrchard
 
     Ok(bytes)
 }
 
-/// Write a CompactSize integer.
-fn write_compact_size(bytes: &mut Vec<u8>, n: usize) -> Result<(), TransactionError> {
-    use std::io::Write;
-
-    if n < 253 {
-        bytes.write_all(&[n as u8]).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-    } else if n <= 0xFFFF {
-        bytes.write_all(&[253]).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-        bytes.write_all(&(n as u16).to_le_bytes()).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-    } else if n <= 0xFFFFFFFF {
-        bytes.write_all(&[254]).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-        bytes.write_all(&(n as u32).to_le_bytes()).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-    } else {
-        bytes.write_all(&[255]).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-        bytes.write_all(&(n as u64).to_le_bytes()).map_err(|e| {
-            TransactionError::BuildFailed(format!("Failed to write compact size: {}", e))
-        })?;
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
 
+    fn unspent_note(pool: Pool, output_index: usize, value: u64) -> ScannedNote {
+        ScannedNote {
+            output_index,
+            pool,
+            value,
+            commitment: String::new(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            debug: None,
+            scope: None,
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
+        }
+    }
+
+    #[test]
+    fn test_select_notes_exact() {
+        let unspent = vec![
+            unspent_note(Pool::Orchard, 0, 40_000),
+            unspent_note(Pool::Orchard, 1, 60_000),
+        ];
+
+        let selected = select_notes(&unspent, 90_000, 10_000).unwrap();
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected.iter().map(|n| n.value).sum::<u64>(), 100_000);
+    }
+
+    #[test]
+    fn test_select_notes_over_covered() {
+        let unspent = vec![
+            unspent_note(Pool::Orchard, 0, 20_000),
+            unspent_note(Pool::Orchard, 1, 500_000),
+            unspent_note(Pool::Orchard, 2, 30_000),
+        ];
+
+        // The largest note alone covers the amount
+        let selected = select_notes(&unspent, 100_000, 10_000).unwrap();
+        assert_eq!(selected, vec![unspent[1].clone()]);
+    }
+
+    #[test]
+    fn test_select_notes_prefers_single_pool() {
+        let unspent = vec![
+            unspent_note(Pool::Transparent, 0, 900_000),
+            unspent_note(Pool::Orchard, 1, 60_000),
+            unspent_note(Pool::Orchard, 2, 50_000),
+            unspent_note(Pool::Sapling, 3, 80_000),
+        ];
+
+        let selected = select_notes(&unspent, 100_000, 10_000).unwrap();
+        assert!(selected.iter().all(|n| n.pool == Pool::Orchard));
+        assert_eq!(selected.len(), 2);
+
+        // No pool covers it alone, so pools are combined
+        let selected = select_notes(&unspent, 1_000_000, 10_000).unwrap();
+        assert_eq!(selected.len(), 3);
+        assert_eq!(selected[0].pool, Pool::Transparent);
+    }
+
+    #[test]
+    fn test_select_notes_insufficient() {
+        let mut outgoing = unspent_note(Pool::Orchard, 1, 1_000_000);
+        outgoing.scope = Some(crate::types::NoteScope::Outgoing);
+        let unspent = vec![unspent_note(Pool::Orchard, 0, 50_000), outgoing];
+
+        assert_eq!(
+            select_notes(&unspent, 100_000, 10_000),
+            Err(SelectError::InsufficientFunds {
+                available: 50_000,
+                shortfall: 60_000,
+            })
+        );
+        assert_eq!(
+            select_notes(&unspent, u64::MAX, 1),
+            Err(SelectError::AmountOverflow)
+        );
+    }
+
     #[test]
     fn test_find_address_index() {
         // First, derive an address at a known index
@@ -780,6 +960,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         };
 
         let utxo = Utxo::from_stored_note(&note);
@@ -808,6 +990,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         };
 
         let utxo = Utxo::from_stored_note(&note);
@@ -832,6 +1016,8 @@ mod tests {
                 spent_txid: None,
                 spent_at_height: None,
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                received_at_height: None,
+                is_coinbase: false,
             },
             // Spent transparent - should NOT be included
             StoredNote {
@@ -848,6 +1034,8 @@ mod tests {
                 spent_txid: Some("spending_tx".to_string()),
                 spent_at_height: Some(100),
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                received_at_height: None,
+                is_coinbase: false,
             },
             // Orchard note - should NOT be included
             StoredNote {
@@ -864,6 +1052,8 @@ mod tests {
                 spent_txid: None,
                 spent_at_height: None,
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                received_at_height: None,
+                is_coinbase: false,
             },
         ];
 
@@ -927,4 +1117,71 @@ mod tests {
         assert_eq!(signed.txid.len(), 64);
         assert!(hex::decode(&signed.txid).is_ok(), "txid is not valid hex");
     }
+
+    #[test]
+    fn test_build_signed_transaction_parses_back() {
+        let addresses = crate::wallet::derive_transparent_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            0,
+            1,
+        )
+        .unwrap();
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+
+        // Spend the whole input minus the fee, so there is no change output
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 90000,
+        }];
+
+        let signed = build_transparent_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+            0,
+        )
+        .expect("transaction should build");
+
+        let tx = crate::scanner::parse_transaction(&signed.tx_hex, Network::TestNetwork)
+            .expect("built transaction should parse");
+        let counts = crate::scanner::output_counts(&tx);
+        assert_eq!(counts.transparent_inputs, 1);
+        assert_eq!(counts.transparent_outputs, 1);
+        assert_eq!(counts.orchard_actions, 0);
+
+        let bundle = tx.transparent_bundle().unwrap();
+        assert_eq!(u64::from(bundle.vout[0].value()), 90000);
+        assert_eq!(
+            bundle.vout[0]
+                .recipient_address()
+                .map(|a| a.encode(&Network::TestNetwork)),
+            Some(addresses[0].clone())
+        );
+    }
+
+    #[test]
+    fn test_conventional_fee() {
+        // One Orchard spend and two outputs fit in two padded actions
+        assert_eq!(conventional_fee(0, 0, 2, 0, 0), 10_000);
+        // Small transactions pay the grace-action minimum
+        assert_eq!(conventional_fee(1, 1, 0, 0, 0), 10_000);
+        assert_eq!(conventional_fee(0, 0, 0, 0, 0), 10_000);
+        // Transparent and Sapling counts take the larger side
+        assert_eq!(conventional_fee(3, 2, 0, 0, 0), 15_000);
+        assert_eq!(conventional_fee(0, 0, 0, 1, 4), 20_000);
+        // Pools add up
+        assert_eq!(conventional_fee(1, 0, 2, 1, 2), 25_000);
+    }
 }
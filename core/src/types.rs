@@ -226,7 +226,7 @@ pub struct TransparentInput {
 /// A transparent transaction output.
 ///
 /// Creates a new UTXO that can be spent by the holder of the corresponding private key.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TransparentOutput {
     /// Zero-based index of this output within the transaction.
     pub index: usize,
@@ -236,6 +236,10 @@ pub struct TransparentOutput {
     pub script_pubkey: String,
     /// Decoded transparent address, if the script is a standard P2PKH or P2SH.
     pub address: Option<String>,
+    /// Data carried by an `OP_RETURN` output, with the push opcodes removed.
+    /// `None` if the script is not an `OP_RETURN` script.
+    #[serde(default)]
+    pub op_return: Option<Vec<u8>>,
 }
 
 /// Information about a parsed viewing key.
@@ -260,6 +264,52 @@ pub struct ViewingKeyInfo {
     pub error: Option<String>,
 }
 
+/// Items found in a unified container (address, UFVK or UIVK).
+///
+/// Returned by `parse_unified_capabilities`. Items with typecodes the wallet
+/// does not understand (metadata, padding or future receiver types) are
+/// reported in `unknown_items` instead of being silently dropped, so callers
+/// can preserve them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnifiedCapabilities {
+    /// Whether the container has a Sapling item.
+    pub has_sapling: bool,
+    /// Whether the container has an Orchard item.
+    pub has_orchard: bool,
+    /// Whether the container has a transparent (P2PKH or P2SH) item.
+    pub has_transparent: bool,
+    /// Typecodes of items that were not recognised, in container order.
+    pub unknown_items: Vec<u32>,
+}
+
+impl UnifiedCapabilities {
+    /// Check whether the transparent item is the only one the wallet can use.
+    ///
+    /// Such a container can only see transparent funds; any shielded
+    /// payments to its owner are invisible to it.
+    pub fn is_transparent_only(&self) -> bool {
+        self.has_transparent && !self.has_sapling && !self.has_orchard
+    }
+}
+
+/// Per-pool counts of a transaction's inputs and outputs.
+///
+/// Computed from the transaction structure alone, without a viewing key, so
+/// it describes the whole transaction rather than the wallet's part of it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutputCounts {
+    /// Number of transparent inputs.
+    pub transparent_inputs: usize,
+    /// Number of transparent outputs.
+    pub transparent_outputs: usize,
+    /// Number of Sapling spends.
+    pub sapling_spends: usize,
+    /// Number of Sapling outputs.
+    pub sapling_outputs: usize,
+    /// Number of Orchard actions. Each action is both a spend and an output.
+    pub orchard_actions: usize,
+}
+
 /// Result of a transaction decryption operation.
 ///
 /// Wraps the decryption result with success/error status for easy
@@ -341,7 +391,7 @@ impl<'de> Deserialize<'de> for Pool {
 /// For transparent outputs, `commitment` and `nullifier` will be empty/None since
 /// transparent outputs don't use these cryptographic mechanisms. Instead, transparent
 /// outputs are identified by txid:output_index and spent via transparent inputs.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ScannedNote {
     /// Zero-based index of this output within the transaction.
     pub output_index: usize,
@@ -360,13 +410,92 @@ pub struct ScannedNote {
     pub memo: Option<String>,
     /// Recipient address if available.
     pub address: Option<String>,
+    /// Raw action metadata, only populated when scanning in debug mode.
+    #[serde(default)]
+    pub debug: Option<ActionDebugInfo>,
+    /// How a shielded note was decrypted. None if it could not be decrypted
+    /// (and for transparent outputs).
+    #[serde(default)]
+    pub scope: Option<NoteScope>,
+    /// Position of the note commitment in its pool's commitment tree. Only
+    /// known when scanning with a [`ScanCursor`].
+    #[serde(default)]
+    pub position: Option<u64>,
+    /// Heuristic: the note is the only shielded output to the wallet in a
+    /// transaction that also spends transparent inputs, as when funds from a
+    /// transparent (or TEX) address are shielded.
+    #[serde(default)]
+    pub from_tex: bool,
+    /// The full, untrimmed 512-byte memo as hex, including any message
+    /// header. Only populated when scanning with `ScanOptions::raw_memo`.
+    #[serde(default)]
+    pub memo_bytes: Option<String>,
+    /// Whether the note commitment recomputed from the decrypted note
+    /// matches the action's `cmx`. Only checked for Orchard notes; false
+    /// for notes that were not decrypted and for other pools.
+    #[serde(default)]
+    pub commitment_verified: bool,
+    /// Whether this outgoing note paid an address outside the wallet, as
+    /// opposed to change or another payment to the wallet itself. False for
+    /// notes that are not outgoing.
+    #[serde(default)]
+    pub is_external: bool,
+}
+
+impl ScannedNote {
+    /// Check whether this is an output the wallet sent to someone else,
+    /// recovered with the outgoing viewing key. Such notes do not belong to
+    /// the wallet and must not be counted as received funds.
+    pub fn is_outgoing(&self) -> bool {
+        self.scope == Some(NoteScope::Outgoing)
+    }
+
+    /// Check whether this note belongs to the wallet.
+    ///
+    /// Every transparent output of a transaction is reported, so transparent
+    /// notes only count when paid to one of `transparent_addresses` (e.g.
+    /// the wallet's transparent watch set). Shielded notes are the wallet's
+    /// unless they were recovered as outgoing.
+    pub fn is_owned(&self, transparent_addresses: &[String]) -> bool {
+        match self.pool {
+            Pool::Transparent => self
+                .address
+                .as_ref()
+                .is_some_and(|address| transparent_addresses.contains(address)),
+            Pool::Sapling | Pool::Orchard => !self.is_outgoing(),
+        }
+    }
+}
+
+/// How a shielded note was recovered by the scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteScope {
+    /// Decrypted with the incoming viewing key: the note was sent to us.
+    Incoming,
+    /// Recovered with the outgoing viewing key: the wallet sent this note.
+    Outgoing,
+}
+
+/// Raw metadata of a shielded output, reported in debug scans.
+///
+/// Populated for every Orchard action when debug mode is enabled, including
+/// actions that could not be decrypted, to help diagnose failed decryption.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ActionDebugInfo {
+    /// Length of the encrypted note ciphertext in bytes.
+    pub enc_ciphertext_len: usize,
+    /// Ephemeral public key as a hex string.
+    pub ephemeral_key: String,
+    /// Extracted note commitment (cmx) as a hex string.
+    pub cmx: String,
 }
 
 /// A nullifier found in a transaction, indicating a spent shielded note.
 ///
 /// When scanning transactions, nullifiers reveal which shielded notes have been spent.
 /// By tracking nullifiers, we can compute the wallet's unspent balance.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SpentNullifier {
     /// The shielded pool this nullifier belongs to.
     pub pool: Pool,
@@ -378,7 +507,7 @@ pub struct SpentNullifier {
 ///
 /// Transparent outputs are spent by referencing them via txid:output_index.
 /// By tracking these inputs, we can mark transparent outputs as spent.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TransparentSpend {
     /// Transaction ID of the output being spent, as a hex string.
     pub prevout_txid: String,
@@ -412,7 +541,7 @@ impl MarkSpentResult {
 /// A transparent output found during scanning.
 ///
 /// Simpler than `TransparentOutput` - only contains data needed for balance tracking.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ScannedTransparentOutput {
     /// Zero-based index of this output within the transaction.
     pub index: usize,
@@ -427,7 +556,7 @@ pub struct ScannedTransparentOutput {
 /// Contains all notes/outputs belonging to the wallet found in the transaction,
 /// as well as nullifiers and transparent spends that indicate previously-received
 /// notes/outputs being spent.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ScanResult {
     /// Transaction ID as a hex string.
     pub txid: String,
@@ -441,6 +570,641 @@ pub struct ScanResult {
     pub transparent_received: u64,
     /// Raw transparent outputs (kept for backward compatibility).
     pub transparent_outputs: Vec<ScannedTransparentOutput>,
+    /// The transaction's `nLockTime` (0 if not time-locked).
+    #[serde(default)]
+    pub lock_time: u32,
+    /// False if the decrypted Orchard output values cannot be reconciled
+    /// with the bundle's value balance. Only checked when
+    /// `ScanOptions::verify_value_balance` is set, true otherwise.
+    #[serde(default = "default_value_balance_consistent")]
+    pub value_balance_consistent: bool,
+    /// Pools the viewing key could scan (`"transparent"`, `"sapling"`,
+    /// `"orchard"`). A pool missing here was skipped because the key has no
+    /// component for it, not because the transaction had nothing for us.
+    ///
+    /// Full transaction scans never list `"sapling"`: incoming Sapling notes
+    /// are not trial-decrypted, only outputs the key sent are recovered with
+    /// its outgoing viewing key. Compact block scans decrypt them.
+    #[serde(default)]
+    pub scanned_pools: Vec<String>,
+    /// Sapling outputs trial-decrypted, and how many belonged to the key
+    /// (received in compact blocks, or recovered as sent with the OVK).
+    #[serde(default)]
+    pub sapling_decryption: DecryptionCounts,
+    /// Orchard actions trial-decrypted, and how many belonged to the key
+    /// (received or recovered as sent).
+    #[serde(default)]
+    pub orchard_decryption: DecryptionCounts,
+    /// Consensus branch ID the transaction was parsed with, as 8 hex digits
+    /// (e.g. `c2d6d0b4` for NU5). Empty when the raw transaction was not
+    /// available, as for compact blocks.
+    #[serde(default)]
+    pub consensus_branch_id: String,
+    /// Possible problems with the scan, e.g. a transaction that may have
+    /// been parsed under the wrong consensus branch.
+    #[serde(default)]
+    pub warnings: Vec<ScanWarning>,
+    /// Whether this is a coinbase transaction, whose outputs only become
+    /// spendable after [`COINBASE_MATURITY`] confirmations.
+    #[serde(default)]
+    pub is_coinbase: bool,
+}
+
+/// Trial decryption counters for one pool of a transaction.
+///
+/// Lets a UI tell "the key matched nothing" apart from "there was nothing to
+/// try", e.g. "tried 4 Orchard outputs, matched 0".
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DecryptionCounts {
+    /// Number of outputs trial decryption was attempted on.
+    pub attempted: usize,
+    /// Number of outputs that decrypted.
+    pub decrypted: usize,
+}
+
+/// Trial decryption coverage of one pool's outputs.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolCompleteness {
+    /// Number of outputs trial decryption was attempted on.
+    pub attempted: usize,
+    /// Number of outputs the scan listed for the pool.
+    pub total: usize,
+}
+
+/// Whether a scan attempted every shielded output it listed.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScanCompleteness {
+    /// Sapling output coverage.
+    pub sapling: PoolCompleteness,
+    /// Orchard action coverage.
+    pub orchard: PoolCompleteness,
+}
+
+impl ScanCompleteness {
+    /// Check whether every output of both pools was attempted.
+    pub fn is_complete(&self) -> bool {
+        [self.sapling, self.orchard]
+            .iter()
+            .all(|pool| pool.attempted >= pool.total)
+    }
+}
+
+/// A possible problem noticed while scanning a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScanWarning {
+    /// No shielded output decrypted, and the transaction was parsed under a
+    /// different consensus branch than the one active at its height. The
+    /// transaction may have been parsed with the wrong network upgrade
+    /// rules, or the height is wrong.
+    PossibleBranchMismatch {
+        /// Branch ID the transaction was parsed with, as 8 hex digits.
+        parsed_branch_id: String,
+        /// Branch ID active at the given height, as 8 hex digits.
+        height_branch_id: String,
+    },
+    /// Shielded pools were selected for scanning, but the viewing key only
+    /// has a transparent component, so only transparent outputs were
+    /// processed.
+    TransparentOnlyKey,
+    /// The ephemeral key of an Orchard action is not a valid curve point,
+    /// so the action cannot be decrypted by anyone. The transaction is
+    /// malformed.
+    MalformedAction {
+        /// Index of the action within the Orchard bundle.
+        action_index: usize,
+    },
+}
+
+fn default_value_balance_consistent() -> bool {
+    true
+}
+
+/// Progress of a batch scan, saved so an interrupted sync can resume.
+///
+/// The note commitment tree positions are the positions the next Sapling
+/// output and Orchard action will be appended at. They are only correct if
+/// every shielded transaction since the wallet birthday is scanned, in chain
+/// order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScanCursor {
+    /// Transaction ID of the last transaction scanned.
+    #[serde(default)]
+    pub last_txid: Option<String>,
+    /// Height of the last block scanned. Raw transactions do not carry their
+    /// height, so this is only updated for transactions scanned with a known
+    /// height (see [`crate::scanner::scan_transactions`]).
+    #[serde(default)]
+    pub last_height: Option<u32>,
+    /// Position of the next Sapling note commitment.
+    #[serde(default)]
+    pub sapling_position: u64,
+    /// Position of the next Orchard note commitment.
+    #[serde(default)]
+    pub orchard_position: u64,
+    /// Height the scan started from, normally the wallet birthday. `None`
+    /// if the whole chain is scanned.
+    #[serde(default)]
+    pub birthday_height: Option<u32>,
+}
+
+/// Estimate how far a displayed balance can be trusted, from scan coverage.
+///
+/// The confidence is the fraction of the blocks from the birthday (or
+/// genesis, if unknown) to the tip that have been scanned. Funds in blocks
+/// not yet scanned are missing from the balance.
+///
+/// # Arguments
+///
+/// * `cursor` - The scan progress
+/// * `tip_height` - Height of the current chain tip
+///
+/// # Returns
+///
+/// A value from 0.0 (nothing scanned) to 1.0 (scanned up to the tip).
+pub fn balance_confidence(cursor: &ScanCursor, tip_height: u32) -> f32 {
+    let Some(last_height) = cursor.last_height else {
+        return 0.0;
+    };
+    let start = cursor.birthday_height.unwrap_or(0);
+    if last_height >= tip_height || start >= tip_height {
+        return 1.0;
+    }
+    let scanned = last_height.saturating_sub(start) as f32;
+    let total = (tip_height - start) as f32;
+    (scanned / total).clamp(0.0, 1.0)
+}
+
+/// Lock times below this value are block heights, above it unix timestamps.
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+
+impl ScanResult {
+    /// Check whether the transaction is still subject to its lock time.
+    ///
+    /// Follows the consensus finality rule: a lock time below
+    /// [`LOCKTIME_THRESHOLD`] is a block height, otherwise a unix timestamp,
+    /// and the transaction is final once the lock time is strictly below the
+    /// current height or time. Input sequence numbers are not considered.
+    ///
+    /// # Arguments
+    ///
+    /// * `current_height` - Height of the next block to be mined
+    /// * `current_time` - Current unix time in seconds
+    pub fn is_time_locked(&self, current_height: u32, current_time: u64) -> bool {
+        if self.lock_time == 0 {
+            return false;
+        }
+        if self.lock_time < LOCKTIME_THRESHOLD {
+            self.lock_time >= current_height
+        } else {
+            u64::from(self.lock_time) >= current_time
+        }
+    }
+
+    /// Get the received notes worth less than `threshold`.
+    ///
+    /// Such notes cost more to spend than they are worth when the threshold
+    /// is the marginal fee of spending them (5,000 zatoshis under ZIP 317).
+    /// Outgoing notes and outputs that could not be decrypted (value 0) are
+    /// not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - Value in zatoshis below which a note is dust
+    pub fn dust_notes(&self, threshold: u64) -> Vec<&ScannedNote> {
+        self.notes
+            .iter()
+            .filter(|n| n.value > 0 && n.value < threshold && !n.is_outgoing())
+            .collect()
+    }
+
+    /// A deterministic scan result for testing code that consumes scan
+    /// results, without building a transaction.
+    ///
+    /// The result has a received Orchard note, an outgoing Sapling note, a
+    /// transparent output to an address on `network`, and spends one Orchard
+    /// nullifier. Every call with the same network returns the same value.
+    #[cfg(any(test, feature = "test-dependencies"))]
+    pub fn sample(network: Network) -> ScanResult {
+        use zcash_keys::encoding::AddressCodec;
+        use zcash_transparent::address::TransparentAddress;
+
+        let txid = "5a".repeat(32);
+        let transparent_address = TransparentAddress::PublicKeyHash([0x11; 20]).encode(&network);
+        let note = |output_index, pool, value| ScannedNote {
+            output_index,
+            pool,
+            value,
+            commitment: String::new(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            debug: None,
+            scope: None,
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
+        };
+
+        ScanResult {
+            txid,
+            notes: vec![
+                ScannedNote {
+                    commitment: "01".repeat(32),
+                    nullifier: Some("02".repeat(32)),
+                    memo: Some("Thanks for the coffee".to_string()),
+                    scope: Some(NoteScope::Incoming),
+                    commitment_verified: true,
+                    ..note(0, Pool::Orchard, 150_000)
+                },
+                ScannedNote {
+                    commitment: "03".repeat(32),
+                    scope: Some(NoteScope::Outgoing),
+                    is_external: true,
+                    ..note(0, Pool::Sapling, 80_000)
+                },
+                ScannedNote {
+                    address: Some(transparent_address.clone()),
+                    ..note(0, Pool::Transparent, 25_000)
+                },
+            ],
+            spent_nullifiers: vec![SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "04".repeat(32),
+            }],
+            transparent_spends: vec![],
+            transparent_received: 25_000,
+            transparent_outputs: vec![ScannedTransparentOutput {
+                index: 0,
+                value: 25_000,
+                address: Some(transparent_address),
+            }],
+            lock_time: 0,
+            value_balance_consistent: true,
+            scanned_pools: ["transparent", "orchard"].map(String::from).to_vec(),
+            sapling_decryption: DecryptionCounts {
+                attempted: 1,
+                decrypted: 1,
+            },
+            orchard_decryption: DecryptionCounts {
+                attempted: 1,
+                decrypted: 1,
+            },
+            consensus_branch_id: "c8e71055".to_string(),
+            warnings: Vec::new(),
+            is_coinbase: false,
+        }
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Export the notes of scan results as CSV.
+///
+/// Emits one row per note owned by the wallet (see
+/// [`ScannedNote::is_owned`]); outgoing notes and transparent outputs to
+/// other addresses are skipped. A note is marked spent when one of the results spends it (by nullifier,
+/// or by outpoint for transparent outputs). Scan results carry no block
+/// time, so the `Date` column is left empty for the caller to fill in.
+///
+/// # Arguments
+///
+/// * `results` - The scan results to export
+/// * `transparent_addresses` - The wallet's transparent addresses
+///
+/// # Returns
+///
+/// The CSV text, starting with a header row.
+pub fn scan_results_to_csv(results: &[ScanResult], transparent_addresses: &[String]) -> String {
+    let spent_nullifiers: std::collections::HashSet<&str> = results
+        .iter()
+        .flat_map(|r| r.spent_nullifiers.iter().map(|nf| nf.nullifier.as_str()))
+        .collect();
+    let spent_outpoints: std::collections::HashSet<(&str, u32)> = results
+        .iter()
+        .flat_map(|r| {
+            r.transparent_spends
+                .iter()
+                .map(|spend| (spend.prevout_txid.as_str(), spend.prevout_index))
+        })
+        .collect();
+
+    let mut csv = String::from("Date,TxID,Pool,Value (ZEC),Memo,Spent\n");
+    for result in results {
+        for note in result
+            .notes
+            .iter()
+            .filter(|n| n.is_owned(transparent_addresses))
+        {
+            let spent = match &note.nullifier {
+                Some(nf) => spent_nullifiers.contains(nf.as_str()),
+                None => u32::try_from(note.output_index)
+                    .is_ok_and(|index| spent_outpoints.contains(&(result.txid.as_str(), index))),
+            };
+            csv.push_str(&format!(
+                ",{},{},{:.8},{},{}\n",
+                csv_field(&result.txid),
+                note.pool,
+                note.value as f64 / 100_000_000.0,
+                csv_field(note.memo.as_deref().unwrap_or("")),
+                spent
+            ));
+        }
+    }
+
+    csv
+}
+
+/// Build a time-ordered ledger from scan results.
+///
+/// Entries are ordered by mined height, oldest first, with transactions at
+/// the same height ordered by txid and unmined transactions (missing from
+/// `heights`) last. Spent values are found by matching each transaction's
+/// nullifiers and transparent inputs against the notes received in earlier
+/// entries. Only notes owned by the wallet (see [`ScannedNote::is_owned`])
+/// are received. The entries have no wallet ID or timestamps; callers
+/// storing them fill those in.
+///
+/// # Arguments
+///
+/// * `results` - The scan results of the wallet's transactions
+/// * `heights` - Mined height of each transaction, by txid
+/// * `transparent_addresses` - The wallet's transparent addresses
+///
+/// # Returns
+///
+/// The ledger entries with `running_balance` set.
+pub fn build_ledger(
+    results: &[ScanResult],
+    heights: &std::collections::HashMap<String, u32>,
+    transparent_addresses: &[String],
+) -> Vec<LedgerEntry> {
+    let mut balance = 0i64;
+    let mut ledger = Vec::with_capacity(results.len());
+    for step in ledger_steps(results, heights, transparent_addresses) {
+        let (spent_note_ids, spent_values): (Vec<String>, Vec<u64>) = step
+            .spent
+            .into_iter()
+            .map(|(id, _, value)| (id, value))
+            .unzip();
+        let received_note_ids = step
+            .received
+            .iter()
+            .map(|note| {
+                StoredNote::generate_id(&step.result.txid, note.pool, note.output_index as u32)
+            })
+            .collect();
+
+        let mut entry = LedgerEntry::from_scan_result(
+            step.result,
+            "",
+            transparent_addresses,
+            received_note_ids,
+            spent_note_ids,
+            &spent_values,
+            "",
+        );
+        balance += entry.net_change;
+        entry.block_height = heights.get(&step.result.txid).copied();
+        entry.running_balance = Some(balance);
+        ledger.push(entry);
+    }
+    ledger
+}
+
+/// A transaction in ledger order, with the wallet's notes it spends and
+/// receives.
+struct LedgerStep<'a> {
+    result: &'a ScanResult,
+    /// Notes received in earlier transactions that this one spends: ID,
+    /// pool and value.
+    spent: Vec<(String, Pool, u64)>,
+    /// Notes with value this transaction pays to the wallet.
+    received: Vec<&'a ScannedNote>,
+}
+
+/// Walk scan results in ledger order, matching each transaction's
+/// nullifiers and transparent inputs against the notes received in earlier
+/// transactions.
+///
+/// Only notes owned by the wallet (see [`ScannedNote::is_owned`]) are
+/// received, and so can be spent.
+fn ledger_steps<'a>(
+    results: &'a [ScanResult],
+    heights: &std::collections::HashMap<String, u32>,
+    transparent_addresses: &[String],
+) -> Vec<LedgerStep<'a>> {
+    // Unspent received notes: id, pool and value, by nullifier or outpoint
+    let mut by_nullifier: std::collections::HashMap<String, (String, Pool, u64)> =
+        std::collections::HashMap::new();
+    let mut by_outpoint: std::collections::HashMap<(String, u32), (String, Pool, u64)> =
+        std::collections::HashMap::new();
+
+    let mut steps = Vec::with_capacity(results.len());
+    for result in order_by_height(results, heights) {
+        let spent = result
+            .spent_nullifiers
+            .iter()
+            .filter_map(|nf| by_nullifier.remove(&nf.nullifier))
+            .chain(result.transparent_spends.iter().filter_map(|spend| {
+                by_outpoint.remove(&(spend.prevout_txid.clone(), spend.prevout_index))
+            }))
+            .collect();
+
+        let mut received = Vec::new();
+        for note in result
+            .notes
+            .iter()
+            .filter(|n| n.value > 0 && n.is_owned(transparent_addresses))
+        {
+            let output_index = note.output_index as u32;
+            let id = StoredNote::generate_id(&result.txid, note.pool, output_index);
+            match &note.nullifier {
+                Some(nf) => {
+                    by_nullifier.insert(nf.clone(), (id, note.pool, note.value));
+                }
+                None => {
+                    by_outpoint.insert(
+                        (result.txid.clone(), output_index),
+                        (id, note.pool, note.value),
+                    );
+                }
+            }
+            received.push(note);
+        }
+
+        steps.push(LedgerStep {
+            result,
+            spent,
+            received,
+        });
+    }
+    steps
+}
+
+/// Order scan results by mined height, then txid, with unmined
+/// transactions (missing from `heights`) last.
+fn order_by_height<'a>(
+    results: &'a [ScanResult],
+    heights: &std::collections::HashMap<String, u32>,
+) -> Vec<&'a ScanResult> {
+    let mut ordered: Vec<&ScanResult> = results.iter().collect();
+    // Unmined transactions sort after every height
+    let height_key = |r: &ScanResult| heights.get(&r.txid).map_or((1, 0), |&h| (0, h));
+    ordered.sort_by(|a, b| {
+        height_key(a)
+            .cmp(&height_key(b))
+            .then_with(|| a.txid.cmp(&b.txid))
+    });
+    ordered
+}
+
+/// The wallet's balance in each pool after a transaction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PoolBalanceSnapshot {
+    /// The transaction the balances are taken after.
+    pub txid: String,
+    /// Mined height of the transaction, or `None` if unmined.
+    pub block_height: Option<u32>,
+    /// Transparent balance in zatoshis.
+    pub transparent: u64,
+    /// Sapling balance in zatoshis.
+    pub sapling: u64,
+    /// Orchard balance in zatoshis.
+    pub orchard: u64,
+}
+
+impl PoolBalanceSnapshot {
+    fn balance_mut(&mut self, pool: Pool) -> &mut u64 {
+        match pool {
+            Pool::Transparent => &mut self.transparent,
+            Pool::Sapling => &mut self.sapling,
+            Pool::Orchard => &mut self.orchard,
+        }
+    }
+}
+
+/// Compute the per-pool balances after each transaction, e.g. for a
+/// balance-over-time chart.
+///
+/// Transactions are ordered, and the wallet's notes matched, as in
+/// [`build_ledger`]: each spent note's value is taken from its pool.
+///
+/// # Arguments
+///
+/// * `results` - The scan results of the wallet's transactions
+/// * `heights` - Mined height of each transaction, by txid
+/// * `transparent_addresses` - The wallet's transparent addresses
+///
+/// # Returns
+///
+/// One snapshot per transaction, in ledger order.
+pub fn pool_balance_history(
+    results: &[ScanResult],
+    heights: &std::collections::HashMap<String, u32>,
+    transparent_addresses: &[String],
+) -> Vec<PoolBalanceSnapshot> {
+    let mut balances = PoolBalanceSnapshot::default();
+    let mut history = Vec::with_capacity(results.len());
+    for step in ledger_steps(results, heights, transparent_addresses) {
+        for (_, pool, value) in step.spent {
+            let balance = balances.balance_mut(pool);
+            *balance = balance.saturating_sub(value);
+        }
+        for note in step.received {
+            *balances.balance_mut(note.pool) += note.value;
+        }
+
+        history.push(PoolBalanceSnapshot {
+            txid: step.result.txid.clone(),
+            block_height: heights.get(&step.result.txid).copied(),
+            ..balances.clone()
+        });
+    }
+    history
+}
+
+/// Options controlling how a transaction is scanned.
+///
+/// The default options produce the regular, lean scan output.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScanOptions {
+    /// Attach raw action metadata (`ScannedNote::debug`) to every shielded
+    /// output, including ones that could not be decrypted.
+    #[serde(default)]
+    pub debug: bool,
+    /// Check decrypted Orchard output values against the bundle's value
+    /// balance (`ScanResult::value_balance_consistent`).
+    #[serde(default)]
+    pub verify_value_balance: bool,
+    /// How decrypted memo bytes are trimmed before being decoded as text.
+    #[serde(default)]
+    pub memo_trim: MemoTrim,
+    /// Pools to scan. `None` scans every pool the viewing key supports;
+    /// other pools are skipped entirely and produce no entries.
+    #[serde(default)]
+    pub pools: Option<Vec<Pool>>,
+    /// Attach the raw memo bytes (`ScannedNote::memo_bytes`) to decrypted
+    /// notes, e.g. to reassemble fragmented messages.
+    #[serde(default)]
+    pub raw_memo: bool,
+}
+
+impl ScanOptions {
+    /// Check whether a pool is selected for scanning.
+    pub fn scans_pool(&self, pool: Pool) -> bool {
+        self.pools
+            .as_ref()
+            .is_none_or(|pools| pools.contains(&pool))
+    }
+}
+
+/// Policy for trimming the 512-byte memo field before decoding it as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoTrim {
+    /// Strip trailing zero padding.
+    #[default]
+    TrailingZeros,
+    /// Keep all 512 bytes, including padding.
+    None,
+    /// Keep the bytes before the first `0xF6` byte, which cannot occur in
+    /// UTF-8 text and so can terminate memos whose content ends in zeros.
+    /// Memos without the sentinel are kept whole.
+    #[serde(rename = "up_to_0xf6_sentinel")]
+    UpTo0xF6Sentinel,
+}
+
+impl MemoTrim {
+    /// Apply the trimming policy to raw memo bytes.
+    pub fn apply(self, memo_bytes: &[u8]) -> &[u8] {
+        match self {
+            Self::TrailingZeros => {
+                let end = memo_bytes
+                    .iter()
+                    .rposition(|&b| b != 0)
+                    .map_or(0, |pos| pos + 1);
+                &memo_bytes[..end]
+            }
+            Self::None => memo_bytes,
+            Self::UpTo0xF6Sentinel => {
+                let end = memo_bytes
+                    .iter()
+                    .position(|&b| b == 0xf6)
+                    .unwrap_or(memo_bytes.len());
+                &memo_bytes[..end]
+            }
+        }
+    }
 }
 
 /// Result of a transaction scan operation.
@@ -456,6 +1220,41 @@ pub struct ScanTransactionResult {
     pub error: Option<String>,
 }
 
+/// A single contributor to a transaction's privacy score.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrivacyFactor {
+    /// Human-readable description of what leaks information.
+    pub description: String,
+    /// Points deducted from the maximum score of 100.
+    pub penalty: u8,
+}
+
+/// Heuristic privacy assessment of a transaction.
+///
+/// Derived purely from the public structure of the transaction, so no
+/// viewing key is needed. A score of 100 means nothing beyond the fee and
+/// the number of shielded outputs is publicly visible.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrivacyReport {
+    /// Privacy score from 0 (fully public) to 100 (fully shielded).
+    pub score: u8,
+    /// The factors that lowered the score.
+    pub factors: Vec<PrivacyFactor>,
+}
+
+/// Privacy statistics aggregated over a wallet's scanned transactions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PrivacyDashboard {
+    /// Shielded notes received (decrypted with the incoming viewing key).
+    pub shielded_receipts: usize,
+    /// Transparent outputs received.
+    pub transparent_receipts: usize,
+    /// Addresses that received funds in more than one transaction.
+    pub reused_addresses: usize,
+    /// Transactions with inputs or outputs in more than one pool.
+    pub turnstile_crossings: usize,
+}
+
 // ============================================================================
 // Wallet Types
 // ============================================================================
@@ -482,6 +1281,9 @@ pub struct WalletResult {
     pub transparent_address: Option<String>,
     /// Unified Full Viewing Key for watching incoming transactions.
     pub unified_full_viewing_key: Option<String>,
+    /// Block height at which the wallet was created, if known.
+    #[serde(default)]
+    pub birthday_height: Option<u32>,
     /// Error message if the operation failed.
     pub error: Option<String>,
 }
@@ -630,8 +1432,17 @@ pub struct StoredNote {
     pub spent_at_height: Option<u32>,
     /// Creation timestamp in ISO 8601 format.
     pub created_at: String,
+    /// Block height of the transaction that created this note, if mined.
+    #[serde(default)]
+    pub received_at_height: Option<u32>,
+    /// Whether this note was created by a coinbase transaction.
+    #[serde(default)]
+    pub is_coinbase: bool,
 }
 
+/// Number of confirmations a coinbase output needs before it can be spent.
+pub const COINBASE_MATURITY: u32 = 100;
+
 impl StoredNote {
     /// Generate the unique ID for a note.
     pub fn generate_id(txid: &str, pool: Pool, output_index: u32) -> String {
@@ -639,14 +1450,26 @@ impl StoredNote {
     }
 
     /// Create a new StoredNote from a scanned note.
+    ///
+    /// `received_at_height` is the height of the block that mined the
+    /// transaction, if known, and `is_coinbase` comes from
+    /// [`ScanResult::is_coinbase`]; both are needed to compute spendability.
+    ///
+    /// Returns `None` for notes recovered with the outgoing viewing key:
+    /// they were sent to someone else and are not part of the wallet's funds.
     pub fn from_scanned_note(
         note: &ScannedNote,
         txid: &str,
         wallet_id: &str,
         created_at: &str,
-    ) -> Self {
+        received_at_height: Option<u32>,
+        is_coinbase: bool,
+    ) -> Option<Self> {
+        if note.is_outgoing() {
+            return None;
+        }
         let id = Self::generate_id(txid, note.pool, note.output_index as u32);
-        StoredNote {
+        Some(StoredNote {
             id,
             wallet_id: wallet_id.to_string(),
             txid: txid.to_string(),
@@ -664,7 +1487,9 @@ impl StoredNote {
             spent_txid: None,
             spent_at_height: None,
             created_at: created_at.to_string(),
-        }
+            received_at_height,
+            is_coinbase,
+        })
     }
 
     /// Mark this note as spent.
@@ -682,6 +1507,44 @@ impl StoredNote {
     pub fn has_value(&self) -> bool {
         self.value > 0
     }
+
+    /// Number of confirmations of the creating transaction at `tip_height`.
+    ///
+    /// A note mined in the tip block has one confirmation; unmined notes
+    /// (and notes above the tip) have none.
+    pub fn confirmations(&self, tip_height: u32) -> u32 {
+        self.received_at_height
+            .map_or(0, |height| confirmations(height, tip_height))
+    }
+
+    /// Check if this unspent note can be spent at `tip_height`.
+    ///
+    /// Requires `min_confirmations`, or [`COINBASE_MATURITY`] for coinbase
+    /// outputs if that is stricter.
+    pub fn is_spendable(&self, tip_height: u32, min_confirmations: u32) -> bool {
+        let required = if self.is_coinbase {
+            min_confirmations.max(COINBASE_MATURITY)
+        } else {
+            min_confirmations
+        };
+        !self.is_spent() && self.has_value() && self.confirmations(tip_height) >= required.max(1)
+    }
+}
+
+/// Number of confirmations of a transaction mined at `mined_height`.
+///
+/// A transaction mined in the tip block has one confirmation. Heights above
+/// the tip (e.g. after a reorg) have none.
+///
+/// # Arguments
+///
+/// * `mined_height` - Height of the block containing the transaction
+/// * `tip_height` - Height of the current chain tip
+pub fn confirmations(mined_height: u32, tip_height: u32) -> u32 {
+    if mined_height > tip_height {
+        return 0;
+    }
+    (tip_height - mined_height).saturating_add(1)
 }
 
 /// Collection of notes for balance calculation and storage.
@@ -778,6 +1641,20 @@ impl NoteCollection {
         self.unspent_notes().iter().map(|n| n.value).sum()
     }
 
+    /// Calculate the balance that can be spent at `tip_height`.
+    ///
+    /// Only counts unspent notes whose transaction has at least
+    /// `min_confirmations` confirmations (and [`COINBASE_MATURITY`] for
+    /// coinbase outputs). Unmined notes are never spendable. The remainder
+    /// of `total_balance` is still confirming or maturing.
+    pub fn spendable_balance(&self, tip_height: u32, min_confirmations: u32) -> u64 {
+        self.notes
+            .iter()
+            .filter(|n| n.is_spendable(tip_height, min_confirmations))
+            .map(|n| n.value)
+            .sum()
+    }
+
     /// Calculate balance by pool.
     pub fn balance_by_pool(&self) -> std::collections::HashMap<Pool, u64> {
         let mut balances = std::collections::HashMap::new();
@@ -787,6 +1664,45 @@ impl NoteCollection {
         balances
     }
 
+    /// Check whether funds must be shielded before a shielded send.
+    ///
+    /// True when a shielded send is wanted but the only unspent funds are
+    /// transparent, so the UI has to offer a shielding step first.
+    ///
+    /// # Arguments
+    ///
+    /// * `want_shielded` - Whether the user wants to send from the shielded pools
+    pub fn needs_shielding(&self, want_shielded: bool) -> bool {
+        if !want_shielded {
+            return false;
+        }
+        let balances = self.balance_by_pool();
+        let balance = |pool| balances.get(&pool).copied().unwrap_or(0);
+        balance(Pool::Transparent) > 0 && balance(Pool::Sapling) == 0 && balance(Pool::Orchard) == 0
+    }
+
+    /// Split the value received per pool into confirmed and pending.
+    ///
+    /// Every received note counts, spent or not. Notes without a known
+    /// height count as pending.
+    ///
+    /// # Arguments
+    ///
+    /// * `tip_height` - Height of the current chain tip
+    /// * `min_conf` - Confirmations needed to count as confirmed (at least 1)
+    pub fn received_by_status(&self, tip_height: u32, min_conf: u32) -> ReceivedBreakdown {
+        let mut breakdown = ReceivedBreakdown::default();
+        for note in self.notes.iter().filter(|n| n.has_value()) {
+            let totals = if note.confirmations(tip_height) >= min_conf.max(1) {
+                &mut breakdown.confirmed
+            } else {
+                &mut breakdown.pending
+            };
+            *totals.entry(note.pool).or_insert(0) += note.value;
+        }
+        breakdown
+    }
+
     /// Get all notes for a specific wallet.
     pub fn notes_for_wallet(&self, wallet_id: &str) -> Vec<&StoredNote> {
         self.notes
@@ -796,6 +1712,66 @@ impl NoteCollection {
     }
 }
 
+/// Received value per pool, split by confirmation status.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReceivedBreakdown {
+    /// Value in zatoshis of notes with enough confirmations, by pool.
+    pub confirmed: std::collections::HashMap<Pool, u64>,
+    /// Value in zatoshis of unmined or insufficiently confirmed notes, by pool.
+    pub pending: std::collections::HashMap<Pool, u64>,
+}
+
+/// Changes between two syncs of a wallet's notes.
+///
+/// Returned by [`snapshot_diff`] so the UI can update only what changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Notes with value that were not in the older collection.
+    pub received: Vec<StoredNote>,
+    /// Notes that are spent in the newer collection but were unspent (or
+    /// missing) in the older one.
+    pub spent: Vec<StoredNote>,
+    /// Change in the unspent balance, in zatoshis.
+    pub balance_delta: i64,
+}
+
+/// Compare two snapshots of a wallet's notes.
+///
+/// Notes are matched by ID. A note received and spent between the two
+/// snapshots is reported in both lists.
+///
+/// # Arguments
+///
+/// * `old` - The notes before the sync
+/// * `new` - The notes after the sync
+///
+/// # Returns
+///
+/// The newly received and newly spent notes, in `new` order, and the
+/// balance change.
+pub fn snapshot_diff(old: &NoteCollection, new: &NoteCollection) -> SnapshotDiff {
+    let old_notes: std::collections::HashMap<&str, &StoredNote> = old
+        .notes
+        .iter()
+        .map(|note| (note.id.as_str(), note))
+        .collect();
+
+    let mut diff = SnapshotDiff {
+        balance_delta: new.total_balance() as i64 - old.total_balance() as i64,
+        ..SnapshotDiff::default()
+    };
+    for note in &new.notes {
+        let previous = old_notes.get(note.id.as_str());
+        if previous.is_none() && note.has_value() {
+            diff.received.push(note.clone());
+        }
+        if note.is_spent() && !previous.is_some_and(|previous| previous.is_spent()) {
+            diff.spent.push(note.clone());
+        }
+    }
+    diff
+}
+
 // ============================================================================
 // Ledger Types
 // ============================================================================
@@ -836,6 +1812,10 @@ pub struct LedgerEntry {
     pub created_at: String,
     /// When this entry was last updated (ISO 8601).
     pub updated_at: String,
+    /// Wallet balance after this transaction, in zatoshis. Only set by
+    /// [`build_ledger`].
+    #[serde(default)]
+    pub running_balance: Option<i64>,
 }
 
 impl LedgerEntry {
@@ -854,11 +1834,22 @@ impl LedgerEntry {
         self.net_change < 0
     }
 
+    /// Number of confirmations of the transaction at `tip_height`, 0 while
+    /// it is unmined (see [`confirmations`]).
+    pub fn confirmations(&self, tip_height: u32) -> u32 {
+        self.block_height
+            .map_or(0, |height| confirmations(height, tip_height))
+    }
+
     /// Create a ledger entry from a scan result and note information.
     ///
+    /// Only notes owned by the wallet (see [`ScannedNote::is_owned`]) count
+    /// as received.
+    ///
     /// # Arguments
     /// * `scan_result` - The result from scanning a transaction
     /// * `wallet_id` - The wallet this entry belongs to
+    /// * `transparent_addresses` - The wallet's transparent addresses
     /// * `received_note_ids` - IDs of notes that were added to our wallet
     /// * `spent_note_ids` - IDs of notes that were spent (from previous transactions)
     /// * `spent_values` - Values of the notes that were spent
@@ -866,6 +1857,7 @@ impl LedgerEntry {
     pub fn from_scan_result(
         scan_result: &ScanResult,
         wallet_id: &str,
+        transparent_addresses: &[String],
         received_note_ids: Vec<String>,
         spent_note_ids: Vec<String>,
         spent_values: &[u64],
@@ -875,7 +1867,7 @@ impl LedgerEntry {
         let value_received: u64 = scan_result
             .notes
             .iter()
-            .filter(|n| n.value > 0)
+            .filter(|n| n.value > 0 && n.is_owned(transparent_addresses))
             .map(|n| n.value)
             .sum();
 
@@ -923,6 +1915,7 @@ impl LedgerEntry {
             primary_pool,
             created_at: timestamp.to_string(),
             updated_at: timestamp.to_string(),
+            running_balance: None,
         }
     }
 }
@@ -1125,24 +2118,206 @@ pub struct StorageResult<T> {
     pub error: Option<String>,
 }
 
-impl<T> StorageResult<T> {
-    /// Create a successful result.
-    pub fn ok(data: T) -> Self {
-        StorageResult {
-            success: true,
-            data: Some(data),
-            error: None,
+impl<T> StorageResult<T> {
+    /// Create a successful result.
+    pub fn ok(data: T) -> Self {
+        StorageResult {
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// Create an error result.
+    pub fn err(message: impl Into<String>) -> Self {
+        StorageResult {
+            success: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Aggregate privacy statistics over a wallet's scan results.
+///
+/// Only the wallet's own receipts and spends are counted: shielded notes
+/// decrypted with the incoming viewing key, transparent outputs to
+/// `transparent_addresses`, and spends of those notes and outputs. A
+/// transaction counts as crossing the turnstile when its spends and
+/// received notes together touch more than one pool. Scan results do not
+/// carry value balances, so this is based on which pools the transaction
+/// has parts in.
+///
+/// # Arguments
+///
+/// * `results` - The scan results of the wallet's transactions
+/// * `transparent_addresses` - The wallet's transparent addresses
+///
+/// # Returns
+///
+/// The aggregated `PrivacyDashboard`.
+pub fn privacy_dashboard(
+    results: &[ScanResult],
+    transparent_addresses: &[String],
+) -> PrivacyDashboard {
+    let received = |note: &ScannedNote| match note.pool {
+        Pool::Transparent => note.is_owned(transparent_addresses),
+        Pool::Sapling | Pool::Orchard => note.scope == Some(NoteScope::Incoming),
+    };
+    let owned_nullifiers: std::collections::HashSet<&str> = results
+        .iter()
+        .flat_map(|r| r.notes.iter())
+        .filter(|n| received(n))
+        .filter_map(|n| n.nullifier.as_deref())
+        .collect();
+    let owned_outpoints: std::collections::HashSet<(&str, u32)> = results
+        .iter()
+        .flat_map(|r| {
+            r.notes
+                .iter()
+                .filter(|n| n.pool == Pool::Transparent && received(n))
+                .map(|n| (r.txid.as_str(), n.output_index as u32))
+        })
+        .collect();
+
+    let mut dashboard = PrivacyDashboard::default();
+    let mut receiving_txids: std::collections::HashMap<&str, std::collections::HashSet<&str>> =
+        std::collections::HashMap::new();
+
+    for result in results {
+        let mut pools = std::collections::HashSet::new();
+        if result.transparent_spends.iter().any(|spend| {
+            owned_outpoints.contains(&(spend.prevout_txid.as_str(), spend.prevout_index))
+        }) {
+            pools.insert(Pool::Transparent);
+        }
+        pools.extend(
+            result
+                .spent_nullifiers
+                .iter()
+                .filter(|nf| owned_nullifiers.contains(nf.nullifier.as_str()))
+                .map(|nf| nf.pool),
+        );
+
+        for note in result.notes.iter().filter(|n| received(n)) {
+            match note.pool {
+                Pool::Transparent => dashboard.transparent_receipts += 1,
+                Pool::Sapling | Pool::Orchard => dashboard.shielded_receipts += 1,
+            }
+            pools.insert(note.pool);
+            if let Some(address) = &note.address {
+                receiving_txids
+                    .entry(address)
+                    .or_default()
+                    .insert(&result.txid);
+            }
+        }
+
+        if pools.len() > 1 {
+            dashboard.turnstile_crossings += 1;
         }
     }
 
-    /// Create an error result.
-    pub fn err(message: impl Into<String>) -> Self {
-        StorageResult {
-            success: false,
-            data: None,
-            error: Some(message.into()),
+    dashboard.reused_addresses = receiving_txids
+        .values()
+        .filter(|txids| txids.len() > 1)
+        .count();
+    dashboard
+}
+
+/// Report how many of a transaction's shielded outputs were trial-decrypted.
+///
+/// The totals are the outputs of the transaction's bundles, whether or not
+/// the scan covered their pool, so a pool the scan skipped (see
+/// [`ScanResult::scanned_pools`]) or a key without a usable component for
+/// it leaves the scan incomplete.
+///
+/// # Arguments
+///
+/// * `result` - The scan result of the transaction
+/// * `counts` - The transaction's output counts (see
+///   [`crate::scanner::output_counts`])
+pub fn scan_completeness(result: &ScanResult, counts: &OutputCounts) -> ScanCompleteness {
+    ScanCompleteness {
+        sapling: PoolCompleteness {
+            attempted: result.sapling_decryption.attempted,
+            total: counts.sapling_outputs,
+        },
+        orchard: PoolCompleteness {
+            attempted: result.orchard_decryption.attempted,
+            total: counts.orchard_actions,
+        },
+    }
+}
+
+/// Merge scan results of the same transaction into one result per txid.
+///
+/// Results keep the order in which each txid first appears. When a txid was
+/// scanned more than once (e.g. with different keys or pool filters), the
+/// later results' notes, nullifiers, transparent spends and outputs, and
+/// scanned pools are merged into the first one. A note is identified by its
+/// pool and output index; a decrypted note replaces an undecrypted one for
+/// the same output. Other fields are taken from the first result.
+///
+/// # Arguments
+///
+/// * `results` - Scan results, possibly with repeated txids
+///
+/// # Returns
+///
+/// One result per distinct txid.
+pub fn dedup_scan_results(results: Vec<ScanResult>) -> Vec<ScanResult> {
+    let mut merged: Vec<ScanResult> = Vec::with_capacity(results.len());
+    let mut by_txid: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+    for result in results {
+        let Some(&index) = by_txid.get(&result.txid) else {
+            by_txid.insert(result.txid.clone(), merged.len());
+            merged.push(result);
+            continue;
+        };
+        let target = &mut merged[index];
+
+        for note in result.notes {
+            match target
+                .notes
+                .iter_mut()
+                .find(|n| n.pool == note.pool && n.output_index == note.output_index)
+            {
+                Some(existing) if existing.scope.is_none() && note.scope.is_some() => {
+                    *existing = note
+                }
+                Some(_) => {}
+                None => target.notes.push(note),
+            }
+        }
+        for nullifier in result.spent_nullifiers {
+            if !target.spent_nullifiers.contains(&nullifier) {
+                target.spent_nullifiers.push(nullifier);
+            }
+        }
+        for spend in result.transparent_spends {
+            if !target.transparent_spends.contains(&spend) {
+                target.transparent_spends.push(spend);
+            }
+        }
+        for output in result.transparent_outputs {
+            if !target
+                .transparent_outputs
+                .iter()
+                .any(|o| o.index == output.index)
+            {
+                target.transparent_received += output.value;
+                target.transparent_outputs.push(output);
+            }
+        }
+        for pool in result.scanned_pools {
+            if !target.scanned_pools.contains(&pool) {
+                target.scanned_pools.push(pool);
+            }
         }
     }
+    merged
 }
 
 #[cfg(test)]
@@ -1316,6 +2491,13 @@ mod tests {
             nullifier: Some("nf456".to_string()),
             memo: Some("test memo".to_string()),
             address: Some("zs1addr".to_string()),
+            debug: None,
+            scope: None,
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
         };
 
         let stored = StoredNote::from_scanned_note(
@@ -1323,7 +2505,10 @@ mod tests {
             "txid789",
             "wallet_123",
             "2024-01-01T00:00:00Z",
-        );
+            Some(2_000_000),
+            false,
+        )
+        .expect("incoming notes are stored");
 
         assert_eq!(stored.id, "txid789-sapling-2");
         assert_eq!(stored.wallet_id, "wallet_123");
@@ -1337,6 +2522,8 @@ mod tests {
         assert_eq!(stored.address, Some("zs1addr".to_string()));
         assert_eq!(stored.spent_txid, None);
         assert_eq!(stored.created_at, "2024-01-01T00:00:00Z");
+        assert_eq!(stored.received_at_height, Some(2_000_000));
+        assert!(!stored.is_coinbase);
     }
 
     #[test]
@@ -1355,6 +2542,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         };
 
         assert!(!note.is_spent());
@@ -1381,6 +2570,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T12:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         };
 
         let json = serde_json::to_string(&note).unwrap();
@@ -1410,6 +2601,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         };
 
         // Add new note
@@ -1442,6 +2635,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         collection.notes.push(StoredNote {
@@ -1458,6 +2653,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         let nullifiers = vec![SpentNullifier {
@@ -1491,6 +2688,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         let spends = vec![TransparentSpend {
@@ -1524,6 +2723,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         // Try to spend nf1 (exists) and nf_unknown (doesn't exist)
@@ -1564,6 +2765,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         // Try to spend tx1:0 (exists) and tx_unknown:0 (doesn't exist)
@@ -1604,6 +2807,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         // Unspent sapling note
@@ -1621,6 +2826,8 @@ mod tests {
             spent_txid: None,
             spent_at_height: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         // Spent note (should not count)
@@ -1638,6 +2845,8 @@ mod tests {
             spent_txid: Some("tx4".to_string()),
             spent_at_height: Some(300),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: None,
+            is_coinbase: false,
         });
 
         assert_eq!(collection.total_balance(), 3000);
@@ -1801,6 +3010,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         };
 
         assert!(entry.is_incoming());
@@ -1815,6 +3025,30 @@ mod tests {
         assert!(!entry.is_outgoing());
     }
 
+    #[test]
+    fn test_confirmations() {
+        // Deep
+        assert_eq!(confirmations(1_000, 2_000), 1_001);
+        assert_eq!(confirmations(0, u32::MAX), u32::MAX);
+        // Shallow
+        assert_eq!(confirmations(2_000, 2_000), 1);
+        assert_eq!(confirmations(2_001, 2_000), 0);
+
+        let mut entry = LedgerEntry::from_scan_result(
+            &ScanResult::sample(Network::TestNetwork),
+            "w1",
+            &[],
+            vec![],
+            vec![],
+            &[],
+            "2024-01-01T00:00:00Z",
+        );
+        // Unmined
+        assert_eq!(entry.confirmations(2_000), 0);
+        entry.block_height = Some(1_995);
+        assert_eq!(entry.confirmations(2_000), 6);
+    }
+
     #[test]
     fn test_ledger_entry_from_scan_result() {
         let scan_result = ScanResult {
@@ -1828,6 +3062,13 @@ mod tests {
                     nullifier: Some("nf1".to_string()),
                     memo: Some("Hello".to_string()),
                     address: None,
+                    debug: None,
+                    scope: None,
+                    position: None,
+                    from_tex: false,
+                    memo_bytes: None,
+                    commitment_verified: false,
+                    is_external: false,
                 },
                 ScannedNote {
                     output_index: 1,
@@ -1837,17 +3078,33 @@ mod tests {
                     nullifier: Some("nf2".to_string()),
                     memo: None,
                     address: None,
+                    debug: None,
+                    scope: None,
+                    position: None,
+                    from_tex: false,
+                    memo_bytes: None,
+                    commitment_verified: false,
+                    is_external: false,
                 },
             ],
             spent_nullifiers: vec![],
             transparent_spends: vec![],
             transparent_received: 0,
             transparent_outputs: vec![],
+            lock_time: 0,
+            value_balance_consistent: true,
+            scanned_pools: vec![],
+            sapling_decryption: DecryptionCounts::default(),
+            orchard_decryption: DecryptionCounts::default(),
+            consensus_branch_id: String::new(),
+            warnings: Vec::new(),
+            is_coinbase: false,
         };
 
         let entry = LedgerEntry::from_scan_result(
             &scan_result,
             "wallet_1",
+            &[],
             vec!["note1".to_string(), "note2".to_string()],
             vec!["spent_note".to_string()],
             &[200],
@@ -1882,6 +3139,7 @@ mod tests {
             primary_pool: "sapling".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T12:00:00Z".to_string(),
+            running_balance: None,
         };
 
         let json = serde_json::to_string(&entry).unwrap();
@@ -1912,6 +3170,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         };
 
         // Add new entry
@@ -1948,6 +3207,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         };
 
         collection.add_or_update(entry);
@@ -1977,6 +3237,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         });
 
         collection.add_or_update(LedgerEntry {
@@ -1994,6 +3255,7 @@ mod tests {
             primary_pool: "sapling".to_string(),
             created_at: "2024-01-02T00:00:00Z".to_string(),
             updated_at: "2024-01-02T00:00:00Z".to_string(),
+            running_balance: None,
         });
 
         // Add entry for wallet w2
@@ -2012,6 +3274,7 @@ mod tests {
             primary_pool: "transparent".to_string(),
             created_at: "2024-01-01T12:00:00Z".to_string(),
             updated_at: "2024-01-01T12:00:00Z".to_string(),
+            running_balance: None,
         });
 
         let w1_entries = collection.entries_for_wallet("w1");
@@ -2044,6 +3307,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         });
 
         // Outgoing transaction
@@ -2062,6 +3326,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-02T00:00:00Z".to_string(),
             updated_at: "2024-01-02T00:00:00Z".to_string(),
+            running_balance: None,
         });
 
         assert_eq!(collection.compute_balance("w1"), 700);
@@ -2087,6 +3352,7 @@ mod tests {
             primary_pool: "orchard".to_string(),
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
+            running_balance: None,
         });
 
         let csv = collection.export_csv("w1");
@@ -2097,4 +3363,691 @@ mod tests {
         assert!(csv.contains("orchard"));
         assert!(csv.contains("Test memo"));
     }
+
+    // ========================================================================
+    // ScanResult serialization tests
+    // ========================================================================
+
+    fn sample_scan_result() -> ScanResult {
+        ScanResult {
+            txid: "abc123".to_string(),
+            notes: vec![
+                ScannedNote {
+                    output_index: 0,
+                    pool: Pool::Orchard,
+                    value: 50_000,
+                    commitment: "cmx".to_string(),
+                    nullifier: Some("nf".to_string()),
+                    memo: Some("Hello".to_string()),
+                    address: None,
+                    debug: None,
+                    scope: Some(NoteScope::Incoming),
+                    position: None,
+                    from_tex: false,
+                    memo_bytes: None,
+                    commitment_verified: false,
+                    is_external: false,
+                },
+                ScannedNote {
+                    output_index: 1,
+                    pool: Pool::Transparent,
+                    value: 10_000,
+                    commitment: String::new(),
+                    nullifier: None,
+                    memo: None,
+                    address: Some("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd".to_string()),
+                    debug: None,
+                    scope: None,
+                    position: None,
+                    from_tex: false,
+                    memo_bytes: None,
+                    commitment_verified: false,
+                    is_external: false,
+                },
+            ],
+            spent_nullifiers: vec![SpentNullifier {
+                pool: Pool::Sapling,
+                nullifier: "nf_spent".to_string(),
+            }],
+            transparent_spends: vec![TransparentSpend {
+                prevout_txid: "prev".to_string(),
+                prevout_index: 3,
+            }],
+            transparent_received: 10_000,
+            transparent_outputs: vec![ScannedTransparentOutput {
+                index: 1,
+                value: 10_000,
+                address: Some("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd".to_string()),
+            }],
+            lock_time: 0,
+            value_balance_consistent: true,
+            scanned_pools: vec![],
+            sapling_decryption: DecryptionCounts::default(),
+            orchard_decryption: DecryptionCounts::default(),
+            consensus_branch_id: String::new(),
+            warnings: Vec::new(),
+            is_coinbase: false,
+        }
+    }
+
+    #[test]
+    fn test_scan_results_to_csv_escapes_memo() {
+        let mut result = ScanResult::sample(Network::TestNetwork);
+        result.notes[0].memo = Some("rent, \"March\"".to_string());
+        // The next transaction spends both notes
+        let spending = ScanResult {
+            txid: "def456".to_string(),
+            notes: vec![],
+            spent_nullifiers: vec![SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: result.notes[0].nullifier.clone().unwrap(),
+            }],
+            transparent_spends: vec![TransparentSpend {
+                prevout_txid: result.txid.clone(),
+                prevout_index: 0,
+            }],
+            ..result.clone()
+        };
+
+        let watched = vec![result.notes[2].address.clone().unwrap()];
+        let csv = scan_results_to_csv(&[result.clone(), spending.clone()], &watched);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Date,TxID,Pool,Value (ZEC),Memo,Spent");
+        assert_eq!(
+            lines[1],
+            format!(
+                ",{},orchard,0.00150000,\"rent, \"\"March\"\"\",true",
+                result.txid
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!(",{},transparent,0.00025000,,true", result.txid)
+        );
+        assert_eq!(lines.len(), 3);
+
+        // Transparent outputs to unwatched addresses are not the wallet's
+        let csv = scan_results_to_csv(&[result, spending], &[]);
+        assert_eq!(csv.lines().count(), 2);
+        assert!(!csv.contains("transparent"));
+    }
+
+    #[test]
+    fn test_build_ledger_running_balance() {
+        let sample = ScanResult::sample(Network::TestNetwork);
+        let orchard_note = |value: u64, nullifier: &str| ScannedNote {
+            value,
+            nullifier: Some(nullifier.to_string()),
+            ..sample.notes[0].clone()
+        };
+        let result = |txid: &str, notes: Vec<ScannedNote>, spent: &[&str]| ScanResult {
+            txid: txid.to_string(),
+            notes,
+            spent_nullifiers: spent
+                .iter()
+                .map(|nf| SpentNullifier {
+                    pool: Pool::Orchard,
+                    nullifier: nf.to_string(),
+                })
+                .collect(),
+            transparent_spends: vec![],
+            ..sample.clone()
+        };
+
+        let results = vec![
+            result("c", vec![sample.notes[2].clone()], &[]),
+            result("a", vec![orchard_note(50_000, "nf_a")], &["nf_other"]),
+            // Spends the note from "a", sending 20,000 back as change
+            result("b", vec![orchard_note(20_000, "nf_b")], &["nf_a"]),
+        ];
+        let heights = std::collections::HashMap::from([
+            ("a".to_string(), 100),
+            ("b".to_string(), 200),
+            ("c".to_string(), 200),
+        ]);
+
+        let watched = vec![sample.notes[2].address.clone().unwrap()];
+        let ledger = build_ledger(&results, &heights, &watched);
+        let txids: Vec<&str> = ledger.iter().map(|e| e.txid.as_str()).collect();
+        assert_eq!(txids, ["a", "b", "c"]);
+
+        assert_eq!(ledger[0].value_received, 50_000);
+        assert_eq!(ledger[0].running_balance, Some(50_000));
+        assert_eq!(ledger[1].value_spent, 50_000);
+        assert_eq!(ledger[1].net_change, -30_000);
+        assert_eq!(ledger[1].spent_note_ids, vec!["a-orchard-0"]);
+        assert_eq!(ledger[1].running_balance, Some(20_000));
+        assert_eq!(ledger[2].primary_pool, "transparent");
+        assert_eq!(ledger[2].block_height, Some(200));
+        assert_eq!(ledger[2].running_balance, Some(45_000));
+
+        // Outputs to transparent addresses the wallet does not watch are
+        // someone else's
+        let ledger = build_ledger(&results, &heights, &[]);
+        assert_eq!(ledger[2].value_received, 0);
+        assert!(ledger[2].received_note_ids.is_empty());
+        assert_eq!(ledger[2].running_balance, Some(20_000));
+    }
+
+    #[test]
+    fn test_scan_result_serialization_roundtrip() {
+        let result = sample_scan_result();
+
+        let json = serde_json::to_string(&result).unwrap();
+        let deserialized: ScanResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(result, deserialized);
+    }
+
+    #[test]
+    fn test_scanned_note_optional_fields_serialize_as_null() {
+        let result = sample_scan_result();
+        let value = serde_json::to_value(&result.notes[1]).unwrap();
+
+        for field in ["nullifier", "memo", "debug", "scope"] {
+            assert!(value[field].is_null(), "{} should be null", field);
+            assert!(value.get(field).is_some(), "{} should be present", field);
+        }
+        assert_eq!(value["pool"], "transparent");
+    }
+
+    #[test]
+    fn test_scan_result_value_balance_consistent_defaults_to_true() {
+        let mut value = serde_json::to_value(sample_scan_result()).unwrap();
+        value
+            .as_object_mut()
+            .unwrap()
+            .remove("value_balance_consistent");
+
+        let result: ScanResult = serde_json::from_value(value).unwrap();
+        assert!(result.value_balance_consistent);
+    }
+
+    // ========================================================================
+    // MemoTrim tests
+    // ========================================================================
+
+    fn memo_with_zeros() -> [u8; 512] {
+        let mut memo = [0u8; 512];
+        memo[..6].copy_from_slice(b"ab\0cd\0");
+        memo
+    }
+
+    #[test]
+    fn test_memo_trim_trailing_zeros() {
+        let memo = memo_with_zeros();
+        assert_eq!(MemoTrim::TrailingZeros.apply(&memo), b"ab\0cd");
+        assert_eq!(MemoTrim::default(), MemoTrim::TrailingZeros);
+    }
+
+    #[test]
+    fn test_memo_trim_none() {
+        let memo = memo_with_zeros();
+        assert_eq!(MemoTrim::None.apply(&memo), &memo[..]);
+    }
+
+    #[test]
+    fn test_memo_trim_up_to_sentinel() {
+        let mut memo = memo_with_zeros();
+        // Without a sentinel the memo is kept whole
+        assert_eq!(MemoTrim::UpTo0xF6Sentinel.apply(&memo).len(), 512);
+
+        memo[6] = 0xf6;
+        assert_eq!(MemoTrim::UpTo0xF6Sentinel.apply(&memo), b"ab\0cd\0");
+    }
+
+    #[test]
+    fn test_memo_trim_serialization() {
+        assert_eq!(
+            serde_json::to_string(&MemoTrim::UpTo0xF6Sentinel).unwrap(),
+            "\"up_to_0xf6_sentinel\""
+        );
+        let options: ScanOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options.memo_trim, MemoTrim::TrailingZeros);
+    }
+
+    // ========================================================================
+    // Spendable balance tests
+    // ========================================================================
+
+    fn note_at_height(id: &str, value: u64, height: Option<u32>, is_coinbase: bool) -> StoredNote {
+        StoredNote {
+            id: id.to_string(),
+            wallet_id: "wallet_1".to_string(),
+            txid: id.to_string(),
+            output_index: 0,
+            pool: Pool::Transparent,
+            value,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            spent_at_height: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            received_at_height: height,
+            is_coinbase,
+        }
+    }
+
+    #[test]
+    fn test_needs_shielding_transparent_only() {
+        let mut collection = NoteCollection::new();
+        collection.add_or_update(note_at_height("t", 50_000, Some(90), false));
+
+        assert!(collection.needs_shielding(true));
+        assert!(!collection.needs_shielding(false));
+
+        let mut shielded = note_at_height("o", 10_000, Some(95), false);
+        shielded.pool = Pool::Orchard;
+        collection.add_or_update(shielded);
+        assert!(!collection.needs_shielding(true));
+
+        // Nothing to shield in an empty wallet
+        assert!(!NoteCollection::new().needs_shielding(true));
+    }
+
+    #[test]
+    fn test_spendable_balance_confirmed() {
+        let mut collection = NoteCollection::new();
+        collection.add_or_update(note_at_height("a", 1000, Some(90), false));
+        collection.add_or_update(note_at_height("b", 2000, Some(100), false));
+
+        // Tip 100: "a" has 11 confirmations, "b" has 1
+        assert_eq!(collection.spendable_balance(100, 1), 3000);
+        assert_eq!(collection.spendable_balance(100, 10), 1000);
+        assert_eq!(collection.total_balance(), 3000);
+    }
+
+    #[test]
+    fn test_spendable_balance_unconfirmed() {
+        let mut collection = NoteCollection::new();
+        collection.add_or_update(note_at_height("mempool", 1000, None, false));
+        collection.add_or_update(note_at_height("future", 2000, Some(101), false));
+
+        assert_eq!(collection.spendable_balance(100, 0), 0);
+        assert_eq!(collection.spendable_balance(100, 1), 0);
+        assert_eq!(collection.total_balance(), 3000);
+    }
+
+    #[test]
+    fn test_spendable_balance_immature_coinbase() {
+        let mut collection = NoteCollection::new();
+        collection.add_or_update(note_at_height("coinbase", 5000, Some(1000), true));
+
+        // 99 confirmations is not enough, even with a lower requirement
+        assert_eq!(collection.spendable_balance(1098, 1), 0);
+        assert_eq!(collection.spendable_balance(1099, 1), 5000);
+        // A stricter requirement still applies to mature coinbase
+        assert_eq!(collection.spendable_balance(1099, 200), 0);
+    }
+
+    #[test]
+    fn test_spendable_balance_excludes_spent() {
+        let mut note = note_at_height("a", 1000, Some(10), false);
+        note.mark_spent("spender", Some(20));
+        let collection = NoteCollection { notes: vec![note] };
+
+        assert_eq!(collection.spendable_balance(100, 1), 0);
+    }
+
+    #[test]
+    fn test_scan_options_pools_deserialize() {
+        let options: ScanOptions = serde_json::from_str(r#"{"pools":["orchard"]}"#).unwrap();
+        assert!(options.scans_pool(Pool::Orchard));
+        assert!(!options.scans_pool(Pool::Sapling));
+        assert!(!options.scans_pool(Pool::Transparent));
+
+        let options: ScanOptions = serde_json::from_str("{}").unwrap();
+        assert!(options.scans_pool(Pool::Sapling));
+    }
+
+    #[test]
+    fn test_scan_result_dust_notes() {
+        let mut result = sample_scan_result();
+        let note = |output_index, value, scope| ScannedNote {
+            output_index,
+            pool: Pool::Orchard,
+            value,
+            commitment: "cmx".to_string(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            debug: None,
+            scope,
+            position: None,
+            from_tex: false,
+            memo_bytes: None,
+            commitment_verified: false,
+            is_external: false,
+        };
+        result.notes.push(note(2, 1_000, Some(NoteScope::Incoming)));
+        // Outgoing and undecrypted notes are never dust
+        result.notes.push(note(3, 500, Some(NoteScope::Outgoing)));
+        result.notes.push(note(4, 0, None));
+
+        let dust = result.dust_notes(5_000);
+        assert_eq!(dust.len(), 1);
+        assert_eq!(dust[0].output_index, 2);
+
+        // The 10,000 zatoshi transparent output is dust at a higher threshold
+        let dust: Vec<usize> = result
+            .dust_notes(20_000)
+            .iter()
+            .map(|n| n.output_index)
+            .collect();
+        assert_eq!(dust, vec![1, 2]);
+
+        assert!(result.dust_notes(0).is_empty());
+    }
+
+    #[test]
+    fn test_privacy_dashboard() {
+        // Orchard receipt plus transparent receipt, spending Sapling and
+        // transparent inputs: crosses the turnstile
+        let mixed = sample_scan_result();
+
+        // Pure Orchard receipt to a reused address
+        let mut shielded = sample_scan_result();
+        shielded.txid = "def456".to_string();
+        shielded.notes.truncate(1);
+        shielded.notes[0].address = Some("u1reused".to_string());
+        shielded.spent_nullifiers[0].pool = Pool::Orchard;
+        shielded.transparent_spends.clear();
+
+        let mut again = shielded.clone();
+        again.txid = "789abc".to_string();
+        // An outgoing note is not a receipt
+        let mut sent = shielded.notes[0].clone();
+        sent.scope = Some(NoteScope::Outgoing);
+        sent.address = Some("u1payee".to_string());
+        again.notes.push(sent);
+
+        let watched = vec![mixed.notes[1].address.clone().unwrap()];
+        let results = [mixed.clone(), shielded, again];
+        let dashboard = privacy_dashboard(&results, &watched);
+        assert_eq!(
+            dashboard,
+            PrivacyDashboard {
+                shielded_receipts: 3,
+                transparent_receipts: 1,
+                reused_addresses: 1,
+                turnstile_crossings: 1,
+            }
+        );
+        assert_eq!(
+            privacy_dashboard(&[], &watched),
+            PrivacyDashboard::default()
+        );
+
+        // Without the transparent output, the first transaction only
+        // receives Orchard funds: its spends are not the wallet's notes
+        let dashboard = privacy_dashboard(&results, &[]);
+        assert_eq!(dashboard.transparent_receipts, 0);
+        assert_eq!(dashboard.turnstile_crossings, 0);
+
+        // Spending the wallet's Orchard note to a transparent output
+        let deshielding = ScanResult {
+            txid: "fed321".to_string(),
+            notes: vec![ScannedNote {
+                address: Some("tmOther".to_string()),
+                ..mixed.notes[1].clone()
+            }],
+            spent_nullifiers: vec![SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: "nf".to_string(),
+            }],
+            transparent_spends: vec![],
+            ..mixed.clone()
+        };
+        let watched = vec!["tmOther".to_string()];
+        let dashboard = privacy_dashboard(&[mixed, deshielding], &watched);
+        assert_eq!(dashboard.transparent_receipts, 1);
+        assert_eq!(dashboard.turnstile_crossings, 1);
+    }
+
+    #[test]
+    fn test_received_by_status() {
+        let mut collection = NoteCollection::new();
+        collection.add_or_update(note_at_height("confirmed", 40_000, Some(90), false));
+        let mut pending = note_at_height("pending", 15_000, None, false);
+        pending.pool = Pool::Orchard;
+        collection.add_or_update(pending);
+
+        let breakdown = collection.received_by_status(100, 10);
+        assert_eq!(breakdown.confirmed.get(&Pool::Transparent), Some(&40_000));
+        assert_eq!(breakdown.confirmed.get(&Pool::Orchard), None);
+        assert_eq!(breakdown.pending.get(&Pool::Orchard), Some(&15_000));
+        assert_eq!(breakdown.pending.get(&Pool::Transparent), None);
+
+        // Too few confirmations for a stricter threshold
+        let breakdown = collection.received_by_status(100, 12);
+        assert_eq!(breakdown.pending.get(&Pool::Transparent), Some(&40_000));
+        assert!(breakdown.confirmed.is_empty());
+    }
+
+    #[test]
+    fn test_scan_completeness() {
+        let mut result = sample_scan_result();
+        result.orchard_decryption = DecryptionCounts {
+            attempted: 1,
+            decrypted: 1,
+        };
+        let counts = OutputCounts {
+            orchard_actions: 1,
+            ..OutputCounts::default()
+        };
+        let completeness = scan_completeness(&result, &counts);
+        assert_eq!(
+            completeness.orchard,
+            PoolCompleteness {
+                attempted: 1,
+                total: 1
+            }
+        );
+        assert_eq!(completeness.sapling, PoolCompleteness::default());
+        assert!(completeness.is_complete());
+
+        result.orchard_decryption.attempted = 0;
+        assert!(!scan_completeness(&result, &counts).is_complete());
+    }
+
+    #[test]
+    fn test_dedup_scan_results_merges_notes() {
+        // The same transaction scanned once with only the transparent pool
+        // and once with only Orchard
+        let mut transparent = sample_scan_result();
+        transparent.notes.remove(0);
+        transparent.scanned_pools = vec!["transparent".to_string()];
+        let mut orchard = sample_scan_result();
+        orchard.notes.truncate(1);
+        orchard.transparent_outputs.clear();
+        orchard.transparent_received = 0;
+        orchard.scanned_pools = vec!["orchard".to_string()];
+
+        let mut other = sample_scan_result();
+        other.txid = "def456".to_string();
+
+        let merged = dedup_scan_results(vec![transparent, other, orchard]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].txid, "abc123");
+        assert_eq!(merged[1].txid, "def456");
+
+        let expected = sample_scan_result();
+        let mut notes = merged[0].notes.clone();
+        notes.sort_by_key(|n| n.output_index);
+        assert_eq!(notes, expected.notes);
+        assert_eq!(merged[0].spent_nullifiers, expected.spent_nullifiers);
+        assert_eq!(merged[0].transparent_outputs, expected.transparent_outputs);
+        assert_eq!(merged[0].transparent_received, 10_000);
+        assert_eq!(merged[0].scanned_pools, vec!["transparent", "orchard"]);
+    }
+
+    #[test]
+    fn test_dedup_scan_results_prefers_decrypted_note() {
+        let mut undecrypted = sample_scan_result();
+        undecrypted.notes[0].scope = None;
+        undecrypted.notes[0].value = 0;
+
+        let merged = dedup_scan_results(vec![undecrypted, sample_scan_result()]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].notes, sample_scan_result().notes);
+    }
+
+    #[test]
+    fn test_unified_capabilities_transparent_only() {
+        let transparent = UnifiedCapabilities {
+            has_transparent: true,
+            ..UnifiedCapabilities::default()
+        };
+        assert!(transparent.is_transparent_only());
+        assert!(
+            !UnifiedCapabilities {
+                has_orchard: true,
+                ..transparent.clone()
+            }
+            .is_transparent_only()
+        );
+        assert!(!UnifiedCapabilities::default().is_transparent_only());
+    }
+
+    #[test]
+    fn test_pool_balance_history() {
+        let sample = ScanResult::sample(Network::TestNetwork);
+        let received = ScanResult {
+            txid: "a".to_string(),
+            spent_nullifiers: vec![],
+            transparent_spends: vec![],
+            ..sample.clone()
+        };
+        // Spends the Orchard note from "a", with 20,000 Sapling change
+        let spend = ScanResult {
+            txid: "b".to_string(),
+            notes: vec![ScannedNote {
+                pool: Pool::Sapling,
+                value: 20_000,
+                nullifier: Some("nf_b".to_string()),
+                ..sample.notes[0].clone()
+            }],
+            spent_nullifiers: vec![SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: sample.notes[0].nullifier.clone().unwrap(),
+            }],
+            transparent_spends: vec![],
+            ..sample.clone()
+        };
+        let heights = std::collections::HashMap::from([("a".to_string(), 100)]);
+
+        let watched = vec![sample.notes[2].address.clone().unwrap()];
+        let history = pool_balance_history(&[spend.clone(), received.clone()], &heights, &watched);
+        assert_eq!(
+            history,
+            vec![
+                PoolBalanceSnapshot {
+                    txid: "a".to_string(),
+                    block_height: Some(100),
+                    transparent: 25_000,
+                    sapling: 0,
+                    orchard: 150_000,
+                },
+                PoolBalanceSnapshot {
+                    txid: "b".to_string(),
+                    block_height: None,
+                    transparent: 25_000,
+                    sapling: 20_000,
+                    orchard: 0,
+                },
+            ]
+        );
+
+        // The transparent output is someone else's unless it is watched
+        let history = pool_balance_history(&[spend, received], &heights, &[]);
+        assert!(history.iter().all(|snapshot| snapshot.transparent == 0));
+        assert_eq!(history[1].sapling, 20_000);
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let mut old = NoteCollection::new();
+        old.add_or_update(note_at_height("a", 50_000, Some(100), false));
+        old.add_or_update(note_at_height("b", 20_000, Some(100), false));
+
+        let mut new = old.clone();
+        new.notes[0].mark_spent("spend_tx", Some(110));
+        new.add_or_update(note_at_height("c", 30_000, Some(110), false));
+
+        let diff = snapshot_diff(&old, &new);
+        let ids = |notes: &[StoredNote]| notes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.received), ["c"]);
+        assert_eq!(ids(&diff.spent), ["a"]);
+        assert_eq!(diff.balance_delta, -20_000);
+
+        assert_eq!(snapshot_diff(&new, &new), SnapshotDiff::default());
+    }
+
+    #[test]
+    fn test_balance_confidence() {
+        let half = ScanCursor {
+            last_height: Some(1_500),
+            birthday_height: Some(1_000),
+            ..ScanCursor::default()
+        };
+        assert!((balance_confidence(&half, 2_000) - 0.5).abs() < 1e-6);
+
+        let synced = ScanCursor {
+            last_height: Some(2_000),
+            ..half.clone()
+        };
+        assert_eq!(balance_confidence(&synced, 2_000), 1.0);
+        assert_eq!(balance_confidence(&ScanCursor::default(), 2_000), 0.0);
+
+        // Without a birthday, coverage is measured from genesis
+        let no_birthday = ScanCursor {
+            birthday_height: None,
+            ..half
+        };
+        assert!((balance_confidence(&no_birthday, 2_000) - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_scan_result_sample() {
+        let sample = ScanResult::sample(Network::TestNetwork);
+        assert_eq!(sample.notes.len(), 3);
+        assert_eq!(sample.spent_nullifiers.len(), 1);
+        assert_eq!(sample, ScanResult::sample(Network::TestNetwork));
+        assert!(
+            sample.transparent_outputs[0]
+                .address
+                .as_deref()
+                .unwrap()
+                .starts_with("tm")
+        );
+
+        // The outgoing Sapling note is not exported
+        let watched = vec![sample.transparent_outputs[0].address.clone().unwrap()];
+        let csv = scan_results_to_csv(std::slice::from_ref(&sample), &watched);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].contains("orchard") && rows[1].contains("Thanks for the coffee"));
+        assert!(rows[2].contains("transparent"));
+    }
+
+    #[test]
+    fn test_build_ledger_over_sample() {
+        let sample = ScanResult::sample(Network::MainNetwork);
+        let heights = std::collections::HashMap::from([(sample.txid.clone(), 2_000_000)]);
+
+        let watched = vec![sample.transparent_outputs[0].address.clone().unwrap()];
+
+        let ledger = build_ledger(std::slice::from_ref(&sample), &heights, &watched);
+        assert_eq!(ledger.len(), 1);
+        assert_eq!(ledger[0].value_received, 175_000);
+        assert_eq!(ledger[0].block_height, Some(2_000_000));
+        assert_eq!(ledger[0].running_balance, Some(175_000));
+
+        // Only the Orchard note is the wallet's when the transparent
+        // address is not watched; the outgoing Sapling note never is
+        let ledger = build_ledger(std::slice::from_ref(&sample), &heights, &[]);
+        assert_eq!(ledger[0].value_received, 150_000);
+        assert_eq!(ledger[0].running_balance, Some(150_000));
+    }
 }
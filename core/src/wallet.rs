@@ -44,33 +44,78 @@
 //! ```
 
 use bip39::{Language, Mnemonic};
+use hkdf::Hkdf;
 use serde::{Deserialize, Serialize};
-use zcash_keys::encoding::AddressCodec;
-use zcash_keys::keys::{UnifiedAddressRequest, UnifiedSpendingKey};
-use zcash_protocol::consensus::Network;
+use sha2::Sha256;
+use zcash_address::unified::{self, Encoding};
+use zcash_keys::address::{Address, UnifiedAddress};
+use zcash_keys::encoding::{AddressCodec, encode_extended_full_viewing_key};
+use zcash_keys::keys::{
+    ReceiverRequirement, UnifiedAddressRequest, UnifiedFullViewingKey, UnifiedSpendingKey,
+};
+use zcash_protocol::consensus::{Network, NetworkConstants, NetworkType};
+use zcash_transparent::address::TransparentAddress;
 use zcash_transparent::keys::{IncomingViewingKey, NonHardenedChildIndex};
-use zip32::{AccountId, DiversifierIndex};
+use zip32::{AccountId, DiversifierIndex, Scope};
 
-use crate::types::NetworkKind;
+use crate::compact_size::{push_compact_size, take_compact_size};
+use crate::types::{NetworkKind, ViewingKeyType};
 
 /// Errors that can occur during wallet operations.
 #[derive(Debug)]
 pub enum WalletError {
     InvalidSeedPhrase(String),
+    InvalidSeed(String),
     MnemonicGeneration(String),
     SpendingKeyDerivation(String),
     AddressGeneration(String),
-    InvalidAccountIndex(String),
+    InvalidAccountIndex(u32),
+    InvalidAddress(String),
+    InvalidMemo(String),
+    InvalidNote(String),
+    InvalidExport(String),
+    InvalidViewingKey(String),
+    InvalidSignature(String),
+    OutputNotFound(String),
+    InvalidDisclosure(String),
+    InvalidEntropyLength(usize),
+    InvalidAmount(String),
+    InsufficientFunds { available: u64, required: u64 },
 }
 
 impl core::fmt::Display for WalletError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::InvalidSeedPhrase(msg) => write!(f, "Invalid seed phrase: {}", msg),
+            Self::InvalidSeed(msg) => write!(f, "Invalid seed: {}", msg),
             Self::MnemonicGeneration(msg) => write!(f, "Failed to generate mnemonic: {}", msg),
             Self::SpendingKeyDerivation(msg) => write!(f, "Failed to derive spending key: {}", msg),
             Self::AddressGeneration(msg) => write!(f, "Failed to generate address: {}", msg),
-            Self::InvalidAccountIndex(msg) => write!(f, "Invalid account index: {}", msg),
+            Self::InvalidAccountIndex(index) => write!(f, "Invalid account index: {}", index),
+            Self::InvalidAddress(msg) => write!(f, "Invalid address: {}", msg),
+            Self::InvalidMemo(msg) => write!(f, "Invalid memo: {}", msg),
+            Self::InvalidNote(msg) => write!(f, "Invalid note: {}", msg),
+            Self::InvalidExport(msg) => write!(f, "Invalid wallet export: {}", msg),
+            Self::InvalidViewingKey(msg) => write!(f, "Invalid viewing key: {}", msg),
+            Self::InvalidSignature(msg) => write!(f, "Invalid signature: {}", msg),
+            Self::OutputNotFound(msg) => write!(f, "Output not found: {}", msg),
+            Self::InvalidDisclosure(msg) => write!(f, "Invalid disclosure: {}", msg),
+            Self::InvalidEntropyLength(len) => write!(
+                f,
+                "Invalid entropy length: {} bytes (expected 16, 20, 24, 28 or 32)",
+                len
+            ),
+            Self::InvalidAmount(msg) => write!(f, "Invalid amount: {}", msg),
+            Self::InsufficientFunds {
+                available,
+                required,
+            } => write!(
+                f,
+                "Insufficient funds: available {} zatoshis, required {} zatoshis ({} short)",
+                available,
+                required,
+                required - available
+            ),
         }
     }
 }
@@ -94,6 +139,106 @@ pub struct WalletInfo {
     pub transparent_address: Option<String>,
     /// The Unified Full Viewing Key.
     pub unified_full_viewing_key: String,
+    /// Block height at which the wallet was created, if known.
+    ///
+    /// Transactions mined below this height cannot involve the wallet and
+    /// can be skipped when scanning.
+    #[serde(default)]
+    pub birthday_height: Option<u32>,
+}
+
+impl WalletInfo {
+    /// A copy of this wallet without the seed phrase, for sharing a
+    /// read-only view.
+    ///
+    /// The seed phrase is left empty; the viewing key, addresses and other
+    /// metadata are kept, so the result can still scan and receive.
+    pub fn to_watch_only(&self) -> WalletInfo {
+        WalletInfo {
+            seed_phrase: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Derive the unified address at a diversifier index from the stored
+    /// viewing key.
+    ///
+    /// Like the wallet's own address, the first valid index at or after
+    /// `index` is used, and the address includes every receiver available
+    /// there (no transparent receiver at indices of 2^31 and above).
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The diversifier index to start from (below 2^88)
+    pub fn address_at(&self, index: u128) -> Result<String, WalletError> {
+        Ok(self
+            .unified_address_at(index)?
+            .encode(&self.network.to_network()))
+    }
+
+    /// Every form of the wallet's address at its address index, derived
+    /// from the stored viewing key.
+    ///
+    /// The receivers of the unified address are also encoded on their own:
+    /// the transparent receiver as a t-address and, if it is P2PKH, a TEX
+    /// address; the Sapling receiver as a Sapling address; the Orchard
+    /// receiver as an Orchard-only unified address.
+    pub fn all_addresses(&self) -> Result<AddressSet, WalletError> {
+        let network = self.network.to_network();
+        let ua = self.unified_address_at(u128::from(self.address_index))?;
+        let transparent = ua.transparent().copied();
+        Ok(AddressSet {
+            unified: ua.encode(&network),
+            transparent: transparent.map(|taddr| taddr.encode(&network)),
+            sapling: ua
+                .sapling()
+                .map(|addr| Address::Sapling(*addr).encode(&network)),
+            orchard: ua
+                .orchard()
+                .and_then(|addr| UnifiedAddress::from_receivers(Some(*addr), None, None))
+                .map(|ua| ua.encode(&network)),
+            tex: match transparent {
+                Some(TransparentAddress::PublicKeyHash(key_hash)) => {
+                    Some(Address::Tex(key_hash).encode(&network))
+                }
+                _ => None,
+            },
+        })
+    }
+
+    /// The unified address at the first valid diversifier index at or after
+    /// `index`, with every receiver available there.
+    fn unified_address_at(&self, index: u128) -> Result<UnifiedAddress, WalletError> {
+        let network = self.network.to_network();
+        let ufvk = UnifiedFullViewingKey::decode(&network, &self.unified_full_viewing_key)
+            .map_err(WalletError::InvalidViewingKey)?;
+        let start = DiversifierIndex::try_from(index).map_err(|_| {
+            WalletError::AddressGeneration(format!("Diversifier index {} is out of range", index))
+        })?;
+        let (ua, _) = ufvk
+            .find_address(start, UnifiedAddressRequest::AllAvailableKeys)
+            .map_err(|e| WalletError::AddressGeneration(format!("{:?}", e)))?;
+        Ok(ua)
+    }
+}
+
+/// A wallet address in every format it can be given out in.
+///
+/// Returned by [`WalletInfo::all_addresses`]. Formats whose receiver the
+/// viewing key does not have are `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AddressSet {
+    /// The unified address with every available receiver.
+    pub unified: String,
+    /// The transparent (t-addr) address.
+    pub transparent: Option<String>,
+    /// The Sapling (zs) address.
+    pub sapling: Option<String>,
+    /// A unified address with only the Orchard receiver.
+    pub orchard: Option<String>,
+    /// The TEX address of the transparent receiver (ZIP 320), only for
+    /// P2PKH receivers.
+    pub tex: Option<String>,
 }
 
 /// Generate a new wallet with a random seed phrase.
@@ -104,6 +249,8 @@ pub struct WalletInfo {
 /// * `network` - The network to use (MainNetwork or TestNetwork).
 /// * `account_index` - The account index (BIP32 level 3, default 0).
 /// * `address_index` - The address/diversifier index (default 0).
+/// * `birthday_height` - The current chain tip height, recorded as the
+///   wallet birthday so scans can skip older blocks.
 ///
 /// # Returns
 ///
@@ -113,14 +260,167 @@ pub fn generate_wallet(
     network: Network,
     account_index: u32,
     address_index: u32,
+    birthday_height: Option<u32>,
+) -> Result<WalletInfo, WalletError> {
+    generate_wallet_with_entropy(
+        entropy,
+        network,
+        account_index,
+        address_index,
+        birthday_height,
+    )
+}
+
+/// Highest ZIP 32 account index (accounts are hardened BIP32 children).
+pub const MAX_ACCOUNT_INDEX: u32 = (1 << 31) - 1;
+
+/// Convert an account index to a ZIP 32 account ID.
+///
+/// # Returns
+///
+/// The `AccountId`, or `WalletError::InvalidAccountIndex` if the index is
+/// above [`MAX_ACCOUNT_INDEX`].
+pub(crate) fn account_id(account_index: u32) -> Result<AccountId, WalletError> {
+    AccountId::try_from(account_index).map_err(|_| WalletError::InvalidAccountIndex(account_index))
+}
+
+/// Entropy lengths in bytes accepted by BIP39 (12 to 24 words).
+pub const VALID_ENTROPY_LENGTHS: [usize; 5] = [16, 20, 24, 28, 32];
+
+/// Build the BIP39 mnemonic for entropy of any valid length.
+fn entropy_mnemonic(entropy: &[u8]) -> Result<Mnemonic, WalletError> {
+    if !VALID_ENTROPY_LENGTHS.contains(&entropy.len()) {
+        return Err(WalletError::InvalidEntropyLength(entropy.len()));
+    }
+    Mnemonic::from_entropy_in(Language::English, entropy)
+        .map_err(|e| WalletError::MnemonicGeneration(e.to_string()))
+}
+
+/// Encode raw entropy as a BIP39 seed phrase.
+///
+/// The word count follows from the entropy length: 12, 15, 18, 21 or 24
+/// words for 16, 20, 24, 28 or 32 bytes.
+///
+/// # Arguments
+///
+/// * `entropy` - The entropy, one of [`VALID_ENTROPY_LENGTHS`] bytes long.
+///
+/// # Returns
+///
+/// The seed phrase, or `WalletError::InvalidEntropyLength` for entropy of
+/// any other length.
+pub fn mnemonic_from_entropy(entropy: &[u8]) -> Result<String, WalletError> {
+    entropy_mnemonic(entropy).map(|mnemonic| mnemonic.to_string())
+}
+
+/// Generate a new wallet from entropy of a chosen strength.
+///
+/// The seed phrase has 12, 15, 18, 21 or 24 words for 16, 20, 24, 28 or 32
+/// bytes of entropy respectively.
+///
+/// # Arguments
+///
+/// * `entropy` - Random entropy, one of [`VALID_ENTROPY_LENGTHS`] bytes long.
+/// * `network` - The network to use (MainNetwork or TestNetwork).
+/// * `account_index` - The account index (BIP32 level 3, default 0).
+/// * `address_index` - The address/diversifier index (default 0).
+/// * `birthday_height` - The current chain tip height, recorded as the
+///   wallet birthday so scans can skip older blocks.
+///
+/// # Returns
+///
+/// A `WalletInfo` containing the seed phrase and derived addresses, or
+/// `WalletError::InvalidEntropyLength` for entropy of any other length.
+pub fn generate_wallet_with_entropy(
+    entropy: &[u8],
+    network: Network,
+    account_index: u32,
+    address_index: u32,
+    birthday_height: Option<u32>,
 ) -> Result<WalletInfo, WalletError> {
-    let mnemonic = Mnemonic::from_entropy_in(Language::English, entropy)
-        .map_err(|e| WalletError::MnemonicGeneration(e.to_string()))?;
+    let mnemonic = entropy_mnemonic(entropy)?;
 
     let seed_phrase = mnemonic.to_string();
     let seed = mnemonic.to_seed("");
 
-    derive_wallet(&seed, seed_phrase, network, account_index, address_index)
+    let mut wallet = derive_wallet(&seed, seed_phrase, network, account_index, address_index)?;
+    wallet.birthday_height = birthday_height;
+    Ok(wallet)
+}
+
+/// Generate a new wallet with entropy drawn from an RNG.
+///
+/// Fills 32 bytes of entropy (a 24-word seed phrase) from `rng` and derives
+/// account 0 at address index 0, without a birthday height. A seeded RNG
+/// produces the same wallet every time.
+///
+/// # Arguments
+///
+/// * `rng` - Source of the seed entropy
+/// * `network` - The network to use (MainNetwork or TestNetwork).
+///
+/// # Returns
+///
+/// A `WalletInfo` containing the seed phrase and derived addresses.
+pub fn generate_wallet_rng(
+    rng: &mut impl rand::RngCore,
+    network: Network,
+) -> Result<WalletInfo, WalletError> {
+    let mut entropy = [0u8; 32];
+    rng.fill_bytes(&mut entropy);
+    generate_wallet(&entropy, network, 0, 0, None)
+}
+
+/// Maximum number of wallets [`generate_wallets`] creates in one call.
+pub const MAX_GENERATED_WALLETS: usize = 1_000;
+
+/// Generate several random wallets, e.g. to seed test or development data.
+///
+/// Each wallet gets a fresh 24-word seed phrase from `rng` and uses account
+/// 0 and address index 0. Seed phrases are guaranteed to be distinct; a
+/// repeated phrase is drawn again, up to `count` extra times, so a broken
+/// `rng` returns an error instead of looping forever.
+///
+/// # Arguments
+///
+/// * `count` - Number of wallets to generate, at most
+///   [`MAX_GENERATED_WALLETS`]
+/// * `network` - The network to derive addresses for
+/// * `rng` - Source of the seed entropy
+///
+/// # Returns
+///
+/// The generated wallets, or `WalletError::MnemonicGeneration` if `count` is
+/// too large or `rng` keeps repeating seed phrases.
+pub fn generate_wallets(
+    count: usize,
+    network: Network,
+    rng: &mut impl rand::RngCore,
+) -> Result<Vec<WalletInfo>, WalletError> {
+    if count > MAX_GENERATED_WALLETS {
+        return Err(WalletError::MnemonicGeneration(format!(
+            "Cannot generate {} wallets, maximum is {}",
+            count, MAX_GENERATED_WALLETS
+        )));
+    }
+
+    let mut seed_phrases = std::collections::HashSet::new();
+    let mut wallets = Vec::with_capacity(count);
+    for _ in 0..count * 2 {
+        if wallets.len() == count {
+            break;
+        }
+        let wallet = generate_wallet_rng(rng, network)?;
+        if seed_phrases.insert(wallet.seed_phrase.clone()) {
+            wallets.push(wallet);
+        }
+    }
+    if wallets.len() < count {
+        return Err(WalletError::MnemonicGeneration(
+            "Random source keeps repeating seed phrases".to_string(),
+        ));
+    }
+    Ok(wallets)
 }
 
 /// Restore a wallet from an existing seed phrase.
@@ -154,6 +454,137 @@ pub fn restore_wallet(
     )
 }
 
+/// Length in bytes of a BIP39 seed.
+pub const SEED_LENGTH: usize = 64;
+
+/// HKDF info string for the local storage encryption key.
+const STORAGE_KEY_INFO: &[u8] = b"ZcashWebWallet storage key v1";
+
+/// Derive the key used to encrypt the wallet's local storage.
+///
+/// The key is derived from the seed with HKDF-SHA256 (no salt) under a
+/// fixed info string, so the same seed always unlocks the same storage
+/// without a separate password. It is independent of the spending and
+/// viewing keys derived from the seed.
+///
+/// # Arguments
+///
+/// * `seed` - The wallet seed (e.g. the 64-byte BIP39 seed)
+///
+/// # Returns
+///
+/// A 32-byte symmetric key.
+pub fn derive_storage_key(seed: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, seed)
+        .expand(STORAGE_KEY_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Check whether two wallets are the same account of the same seed.
+///
+/// The ZIP 32 and BIP 44 derivation paths include the network's coin type,
+/// so one seed yields different viewing keys on mainnet and testnet. Wallets
+/// on different networks are compared by re-deriving one wallet's seed
+/// phrase on the other network, which needs at least one of them to carry
+/// its seed phrase. Wallets on the same network compare their viewing keys.
+///
+/// # Arguments
+///
+/// * `a` - The first wallet
+/// * `b` - The second wallet
+///
+/// # Returns
+///
+/// True if both wallets belong to the same seed and account.
+pub fn same_account_cross_network(a: &WalletInfo, b: &WalletInfo) -> bool {
+    if a.network.to_network() == b.network.to_network() {
+        return a.unified_full_viewing_key.trim() == b.unified_full_viewing_key.trim();
+    }
+
+    let (seeded, other) = if !a.seed_phrase.is_empty() {
+        (a, b)
+    } else if !b.seed_phrase.is_empty() {
+        (b, a)
+    } else {
+        return false;
+    };
+    restore_wallet(
+        &seeded.seed_phrase,
+        other.network.to_network(),
+        other.account_index,
+        0,
+    )
+    .is_ok_and(|wallet| wallet.unified_full_viewing_key == other.unified_full_viewing_key.trim())
+}
+
+/// Restore a wallet from a raw hex-encoded seed, without a mnemonic.
+///
+/// The returned wallet has an empty seed phrase, since the mnemonic cannot
+/// be recovered from the seed. Keys are derived for account 0 and addresses
+/// at index 0.
+///
+/// # Arguments
+///
+/// * `seed_hex` - The 64-byte seed as hex (128 characters).
+/// * `network` - The network to use (MainNetwork or TestNetwork).
+///
+/// # Returns
+///
+/// A `WalletInfo` containing the derived addresses, or an error if the hex
+/// is invalid or the seed is not 64 bytes.
+pub fn restore_wallet_from_seed_hex(
+    seed_hex: &str,
+    network: Network,
+) -> Result<WalletInfo, WalletError> {
+    let seed = hex::decode(seed_hex.trim())
+        .map_err(|e| WalletError::InvalidSeed(format!("Invalid hex: {}", e)))?;
+    if seed.len() != SEED_LENGTH {
+        return Err(WalletError::InvalidSeed(format!(
+            "Expected {} bytes, got {}",
+            SEED_LENGTH,
+            seed.len()
+        )));
+    }
+
+    derive_wallet(&seed, String::new(), network, 0, 0)
+}
+
+/// Get the ZIP 32 derivation path of an account's shielded keys.
+///
+/// # Arguments
+///
+/// * `account` - The account index (hardened in the path)
+/// * `network` - The network, which selects the coin type (133 on mainnet,
+///   1 on testnet)
+///
+/// # Returns
+///
+/// The path, e.g. `m/32'/133'/0'`.
+pub fn derivation_path(account: u32, network: Network) -> String {
+    format!("m/32'/{}'/{}'", network.coin_type(), account)
+}
+
+/// Get the display name of an account, shared by the web UI and the CLI.
+///
+/// # Arguments
+///
+/// * `account` - The account index
+/// * `network` - The network the account is on
+///
+/// # Returns
+///
+/// The label, e.g. `Account 0 (mainnet) m/32'/133'/0'`.
+pub fn account_label(account: u32, network: Network) -> String {
+    format!(
+        "Account {} ({}) {}",
+        account,
+        NetworkKind::from(network),
+        derivation_path(account, network)
+    )
+}
+
 /// Derive wallet addresses and keys from a seed.
 ///
 /// # Arguments
@@ -175,12 +606,7 @@ pub fn derive_wallet(
     address_index: u32,
 ) -> Result<WalletInfo, WalletError> {
     // Convert account index to AccountId
-    let account = AccountId::try_from(account_index).map_err(|_| {
-        WalletError::InvalidAccountIndex(format!(
-            "Account index {} is out of valid range",
-            account_index
-        ))
-    })?;
+    let account = account_id(account_index)?;
 
     // Create UnifiedSpendingKey from seed
     let usk = UnifiedSpendingKey::from_seed(&network, seed, account)
@@ -188,6 +614,19 @@ pub fn derive_wallet(
 
     // Get the unified full viewing key
     let ufvk = usk.to_unified_full_viewing_key();
+
+    wallet_info_from_ufvk(&ufvk, seed_phrase, network, account_index, address_index)
+}
+
+/// Build a `WalletInfo` from a viewing key, deriving the addresses at
+/// `address_index`.
+fn wallet_info_from_ufvk(
+    ufvk: &UnifiedFullViewingKey,
+    seed_phrase: String,
+    network: Network,
+    account_index: u32,
+    address_index: u32,
+) -> Result<WalletInfo, WalletError> {
     let ufvk_encoded = ufvk.encode(&network);
 
     // Create diversifier index from address_index
@@ -234,6 +673,7 @@ pub fn derive_wallet(
         unified_address: ua_encoded,
         transparent_address,
         unified_full_viewing_key: ufvk_encoded,
+        birthday_height: None,
     })
 }
 
@@ -280,12 +720,7 @@ pub fn derive_unified_addresses(
     let seed = mnemonic.to_seed("");
 
     // Convert account index to AccountId
-    let account = AccountId::try_from(account_index).map_err(|_| {
-        WalletError::InvalidAccountIndex(format!(
-            "Account index {} is out of valid range",
-            account_index
-        ))
-    })?;
+    let account = account_id(account_index)?;
 
     // Create UnifiedSpendingKey from seed
     let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
@@ -294,10 +729,68 @@ pub fn derive_unified_addresses(
     // Get the unified full viewing key
     let ufvk = usk.to_unified_full_viewing_key();
 
+    Ok(unified_addresses_in_range(
+        &ufvk,
+        network,
+        start_index,
+        count,
+    ))
+}
+
+/// Maximum number of addresses [`derive_unified_addresses_from_ufvk`]
+/// derives in one call.
+pub const MAX_ADDRESS_BATCH: u32 = 1_000;
+
+/// Derive multiple unified addresses from a unified full viewing key.
+///
+/// Watch-only counterpart of [`derive_unified_addresses`].
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+/// * `network` - The network the key is for.
+/// * `start_index` - The starting address/diversifier index.
+/// * `count` - Number of addresses to derive, at most [`MAX_ADDRESS_BATCH`].
+///
+/// # Returns
+///
+/// A vector of encoded unified addresses, or
+/// `WalletError::AddressGeneration` if `count` is too large.
+pub fn derive_unified_addresses_from_ufvk(
+    ufvk: &str,
+    network: Network,
+    start_index: u32,
+    count: u32,
+) -> Result<Vec<String>, WalletError> {
+    if count > MAX_ADDRESS_BATCH {
+        return Err(WalletError::AddressGeneration(format!(
+            "Cannot derive {} addresses, maximum is {}",
+            count, MAX_ADDRESS_BATCH
+        )));
+    }
+
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    Ok(unified_addresses_in_range(
+        &ufvk,
+        network,
+        start_index,
+        count,
+    ))
+}
+
+/// Derive the unified addresses at diversifier indices
+/// `start_index..start_index + count`.
+fn unified_addresses_in_range(
+    ufvk: &UnifiedFullViewingKey,
+    network: Network,
+    start_index: u32,
+    count: u32,
+) -> Vec<String> {
     let mut addresses = Vec::with_capacity(count as usize);
 
     // Derive unified addresses at each diversifier index
-    for i in start_index..(start_index + count) {
+    for i in start_index..start_index.saturating_add(count) {
         let diversifier_index = DiversifierIndex::from(i);
         if let Ok((ua, _)) =
             ufvk.find_address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys)
@@ -306,7 +799,7 @@ pub fn derive_unified_addresses(
         }
     }
 
-    Ok(addresses)
+    addresses
 }
 
 /// Derive multiple transparent addresses from a seed phrase.
@@ -332,941 +825,3837 @@ pub fn derive_transparent_addresses(
     start_index: u32,
     count: u32,
 ) -> Result<Vec<String>, WalletError> {
-    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
-        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+    derive_transparent_chain(
+        seed_phrase,
+        network,
+        account_index,
+        start_index,
+        count,
+        TransparentChain::External,
+    )
+}
 
-    let seed = mnemonic.to_seed("");
+/// Derive multiple internal (change) transparent addresses from a seed phrase.
+///
+/// Change outputs of transparent transactions are sent to the internal chain
+/// (BIP44 change index 1) rather than to the external receiving addresses.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid 24-word BIP39 mnemonic.
+/// * `network` - The network to derive addresses for.
+/// * `account_index` - The account index (BIP32 level 3).
+/// * `start_index` - The starting address index.
+/// * `count` - Number of addresses to derive.
+///
+/// # Returns
+///
+/// A vector of internal transparent addresses.
+pub fn derive_internal_transparent_addresses(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    start_index: u32,
+    count: u32,
+) -> Result<Vec<String>, WalletError> {
+    derive_transparent_chain(
+        seed_phrase,
+        network,
+        account_index,
+        start_index,
+        count,
+        TransparentChain::Internal,
+    )
+}
 
-    // Convert account index to AccountId
-    let account = AccountId::try_from(account_index).map_err(|_| {
-        WalletError::InvalidAccountIndex(format!(
-            "Account index {} is out of valid range",
-            account_index
-        ))
-    })?;
+/// Derive the set of transparent addresses to watch when scanning.
+///
+/// Contains the first `count` external (receiving) addresses followed by
+/// the first `count` internal (change) addresses, so that change returned
+/// to the wallet is recognized as well.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - A valid 24-word BIP39 mnemonic.
+/// * `network` - The network to derive addresses for.
+/// * `account_index` - The account index (BIP32 level 3).
+/// * `count` - Number of addresses to derive on each chain.
+///
+/// # Returns
+///
+/// A vector of external addresses followed by internal addresses.
+pub fn derive_transparent_watch_set(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    count: u32,
+) -> Result<Vec<String>, WalletError> {
+    let mut addresses =
+        derive_transparent_addresses(seed_phrase, network, account_index, 0, count)?;
+    addresses.extend(derive_internal_transparent_addresses(
+        seed_phrase,
+        network,
+        account_index,
+        0,
+        count,
+    )?);
+    Ok(addresses)
+}
 
-    // Create UnifiedSpendingKey from seed
-    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
-        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+/// Derive the set of transparent addresses to watch from a viewing key.
+///
+/// Same as [`derive_transparent_watch_set`], for a wallet known only by its
+/// unified full viewing key. Empty if the key has no transparent component.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `network` - The network the key is for
+/// * `count` - Number of addresses to derive on each chain.
+///
+/// # Returns
+///
+/// A vector of external addresses followed by internal addresses.
+pub fn derive_transparent_watch_set_from_ufvk(
+    ufvk: &str,
+    network: Network,
+    count: u32,
+) -> Result<Vec<String>, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
 
-    // Get the unified full viewing key
-    let ufvk = usk.to_unified_full_viewing_key();
+    let mut addresses = Vec::with_capacity(2 * count as usize);
+    for chain in [TransparentChain::External, TransparentChain::Internal] {
+        push_ufvk_transparent_addresses(&ufvk, network, 0, count, chain, &mut addresses);
+    }
+    Ok(addresses)
+}
 
-    let mut addresses = Vec::with_capacity(count as usize);
+/// BIP44 change level of a transparent address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransparentChain {
+    /// Receiving addresses (change index 0).
+    External,
+    /// Change addresses (change index 1).
+    Internal,
+}
 
-    // Get transparent addresses
-    if let Some(tfvk) = ufvk.transparent()
-        && let Ok(ivk) = tfvk.derive_external_ivk()
-    {
-        for i in start_index..(start_index + count) {
-            if let Some(child_index) = NonHardenedChildIndex::from_index(i)
-                && let Ok(addr) = ivk.derive_address(child_index)
-            {
-                addresses.push(addr.encode(&network));
+/// Derive transparent addresses on the given BIP44 chain.
+fn derive_transparent_chain(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    start_index: u32,
+    count: u32,
+    chain: TransparentChain,
+) -> Result<Vec<String>, WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+
+    let seed = mnemonic.to_seed("");
+
+    // Convert account index to AccountId
+    let account = account_id(account_index)?;
+
+    // Create UnifiedSpendingKey from seed
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    // Get the unified full viewing key
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let mut addresses = Vec::with_capacity(count as usize);
+    push_ufvk_transparent_addresses(&ufvk, network, start_index, count, chain, &mut addresses);
+    Ok(addresses)
+}
+
+/// Derive `count` transparent addresses of a viewing key on the given chain,
+/// starting at `start_index`.
+fn push_ufvk_transparent_addresses(
+    ufvk: &UnifiedFullViewingKey,
+    network: Network,
+    start_index: u32,
+    count: u32,
+    chain: TransparentChain,
+    addresses: &mut Vec<String>,
+) {
+    // Get transparent addresses from the requested chain
+    if let Some(tfvk) = ufvk.transparent() {
+        match chain {
+            TransparentChain::External => {
+                if let Ok(ivk) = tfvk.derive_external_ivk() {
+                    push_transparent_addresses(&ivk, network, start_index, count, addresses);
+                }
+            }
+            TransparentChain::Internal => {
+                if let Ok(ivk) = tfvk.derive_internal_ivk() {
+                    push_transparent_addresses(&ivk, network, start_index, count, addresses);
+                }
             }
         }
     }
+}
 
-    Ok(addresses)
+/// Derive `count` addresses from a transparent IVK starting at `start_index`.
+fn push_transparent_addresses<K: IncomingViewingKey>(
+    ivk: &K,
+    network: Network,
+    start_index: u32,
+    count: u32,
+    addresses: &mut Vec<String>,
+) {
+    for i in start_index..(start_index + count) {
+        if let Some(child_index) = NonHardenedChildIndex::from_index(i)
+            && let Ok(addr) = ivk.derive_address(child_index)
+        {
+            addresses.push(addr.encode(&network));
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Current version of the wallet export format.
+const WALLET_EXPORT_VERSION: u32 = 1;
 
-    // Known test vector: a fixed seed phrase and its expected derived addresses
-    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+/// Portable wallet backup document.
+///
+/// Field aliases accept the naming used by other wallets (zecwallet,
+/// zcashd `z_exportwallet` converters) so their exports can be imported.
+#[derive(Debug, Serialize, Deserialize)]
+struct WalletExport {
+    #[serde(default = "default_export_version")]
+    version: u32,
+    network: NetworkKind,
+    #[serde(alias = "mnemonic", alias = "seed")]
+    seed_phrase: String,
+    #[serde(alias = "account", default)]
+    account_index: u32,
+    #[serde(default)]
+    address_index: u32,
+    #[serde(alias = "birthday", default)]
+    birthday_height: Option<u32>,
+    #[serde(alias = "ufvk", default)]
+    unified_full_viewing_key: Option<String>,
+    #[serde(default)]
+    addresses: WalletExportAddresses,
+}
+
+/// Addresses included in a wallet export.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WalletExportAddresses {
+    #[serde(default)]
+    unified: Option<String>,
+    #[serde(default)]
+    transparent: Option<String>,
+}
+
+fn default_export_version() -> u32 {
+    WALLET_EXPORT_VERSION
+}
+
+/// Export a wallet to the portable backup JSON format.
+///
+/// The document contains the seed phrase, account index, UFVK, birthday
+/// height and the derived addresses, so it can be imported by this wallet
+/// or converted for other Zcash wallets.
+///
+/// # Arguments
+///
+/// * `info` - The wallet to export.
+/// * `birthday` - The block height the wallet was created at. Falls back to
+///   the wallet's own `birthday_height` when `None`.
+///
+/// # Returns
+///
+/// The export document as a pretty-printed JSON string.
+pub fn export_wallet_json(info: &WalletInfo, birthday: Option<u32>) -> String {
+    let export = WalletExport {
+        version: WALLET_EXPORT_VERSION,
+        network: info.network,
+        seed_phrase: info.seed_phrase.clone(),
+        account_index: info.account_index,
+        address_index: info.address_index,
+        birthday_height: birthday.or(info.birthday_height),
+        unified_full_viewing_key: Some(info.unified_full_viewing_key.clone()),
+        addresses: WalletExportAddresses {
+            unified: Some(info.unified_address.clone()),
+            transparent: info.transparent_address.clone(),
+        },
+    };
+
+    serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+/// Viewing key import for a lightwalletd-backed light client.
+///
+/// Holds no spending key: the client can scan from `birthday` but not
+/// spend.
+#[derive(Debug, Serialize)]
+struct LightwalletdImport<'a> {
+    network: NetworkKind,
+    ufvk: &'a str,
+    birthday: u32,
+    account: u32,
+}
+
+/// Export the wallet's viewing key for import into a light client.
+///
+/// The payload carries the network, UFVK, account index and birthday
+/// height, which is what a lightwalletd-backed client needs to create a
+/// watch-only account and start syncing from the right block. Unlike
+/// [`export_wallet_json`], it never contains the seed phrase.
+///
+/// # Arguments
+///
+/// * `info` - The wallet to export.
+/// * `birthday` - The block height to start scanning from.
+///
+/// # Returns
+///
+/// The payload as a compact JSON string.
+pub fn lightwalletd_import_payload(info: &WalletInfo, birthday: u32) -> String {
+    let payload = LightwalletdImport {
+        network: info.network,
+        ufvk: &info.unified_full_viewing_key,
+        birthday,
+        account: info.account_index,
+    };
+    serde_json::to_string(&payload).unwrap_or_default()
+}
+
+/// Import a wallet from a backup JSON document.
+///
+/// The wallet is re-derived from the seed phrase. If the document also
+/// carries a UFVK, it must match the derived one, which catches exports
+/// for a different account or a corrupted seed phrase.
+///
+/// # Arguments
+///
+/// * `json` - The export document.
+/// * `network` - The network the wallet is expected to be on.
+///
+/// # Returns
+///
+/// The restored `WalletInfo`.
+pub fn import_wallet_json(json: &str, network: Network) -> Result<WalletInfo, WalletError> {
+    let export: WalletExport = serde_json::from_str(json)
+        .map_err(|e| WalletError::InvalidExport(format!("Invalid export JSON: {}", e)))?;
+
+    if export.network != NetworkKind::from(network) {
+        return Err(WalletError::InvalidExport(format!(
+            "Export is for {}, expected {}",
+            export.network.as_str(),
+            NetworkKind::from(network).as_str()
+        )));
+    }
+
+    let mut wallet = restore_wallet(
+        &export.seed_phrase,
+        network,
+        export.account_index,
+        export.address_index,
+    )?;
+
+    if let Some(ufvk) = &export.unified_full_viewing_key
+        && ufvk != &wallet.unified_full_viewing_key
+    {
+        return Err(WalletError::InvalidExport(
+            "Viewing key does not match the seed phrase".to_string(),
+        ));
+    }
+
+    wallet.birthday_height = export.birthday_height;
+    Ok(wallet)
+}
+
+/// Derive the Sapling extended full viewing key for an account.
+///
+/// Some services only accept the legacy Sapling viewing key format rather
+/// than a UFVK. The key is encoded with the network's extended FVK prefix
+/// (`zxviews` on mainnet, `zxviewtestsapling` on testnet).
+///
+/// # Arguments
+///
+/// * `seed` - The 64-byte seed derived from the mnemonic.
+/// * `account` - The account index (ZIP32 account).
+/// * `network` - The network to encode the key for.
+///
+/// # Returns
+///
+/// The Bech32-encoded Sapling extended full viewing key.
+pub fn derive_sapling_extfvk(
+    seed: &[u8],
+    account: u32,
+    network: Network,
+) -> Result<String, WalletError> {
+    // Convert account index to AccountId
+    let account_id = account_id(account)?;
+
+    // Create UnifiedSpendingKey from seed
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    // The extended FVK is the only way to obtain the legacy encoding
+    #[allow(deprecated)]
+    let extfvk = usk.sapling().to_extended_full_viewing_key();
+
+    Ok(encode_extended_full_viewing_key(
+        network.hrp_sapling_extended_full_viewing_key(),
+        &extfvk,
+    ))
+}
+
+/// A Sapling address derived with zcashd's legacy scheme, with the viewing
+/// key needed to scan for it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LegacySaplingKey {
+    /// The encoded Sapling payment address.
+    pub address: String,
+    /// The Bech32-encoded Sapling extended full viewing key for `address`.
+    pub extfvk: String,
+}
+
+/// Derive the n-th Sapling address of zcashd's pre-unified scheme.
+///
+/// Unified wallets derive every address of an account from the same
+/// Sapling key (`m/32'/coin'/account'`) at successive diversifier indices.
+/// Before unified accounts, zcashd's `z_getnewaddress` instead derived a
+/// new key for each Sapling address, using the default address of
+/// `m/32'/coin'/n'`. Funds sent to those addresses are only visible to
+/// that key's viewing key, so it is returned alongside the address and can
+/// be passed to the scanner.
+///
+/// # Arguments
+///
+/// * `seed` - The 64-byte seed derived from the mnemonic.
+/// * `network` - The network to encode the address and key for.
+/// * `index` - The legacy address index (`n` above).
+///
+/// # Returns
+///
+/// The legacy Sapling address and its extended full viewing key.
+pub fn derive_legacy_sapling_key(
+    seed: &[u8],
+    network: Network,
+    index: u32,
+) -> Result<LegacySaplingKey, WalletError> {
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id(index)?)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let (_, address) = usk
+        .sapling()
+        .to_diversifiable_full_viewing_key()
+        .default_address();
+
+    Ok(LegacySaplingKey {
+        address: Address::Sapling(address).encode(&network),
+        extfvk: derive_sapling_extfvk(seed, index, network)?,
+    })
+}
+
+/// Maximum number of accounts [`derive_accounts`] derives in one call.
+pub const MAX_DERIVED_ACCOUNTS: u32 = 100;
+
+/// Derive several consecutive accounts from a seed.
+///
+/// Used for account discovery after a restore: accounts `0..count` are
+/// derived so the caller can check each one for activity and stop after a
+/// gap of unused accounts.
+///
+/// Only the seed is known here, so `seed_phrase` is left empty in the
+/// returned wallets.
+///
+/// # Arguments
+///
+/// * `seed` - The 64-byte seed derived from the mnemonic.
+/// * `network` - The network to derive addresses for.
+/// * `count` - Number of accounts to derive, starting at account 0, at most
+///   [`MAX_DERIVED_ACCOUNTS`].
+///
+/// # Returns
+///
+/// One `WalletInfo` per account, in account order, each at address index 0,
+/// or `WalletError::InvalidAccountIndex` carrying the first account index
+/// past the cap if `count` is too large.
+pub fn derive_accounts(
+    seed: &[u8],
+    network: Network,
+    count: u32,
+) -> Result<Vec<WalletInfo>, WalletError> {
+    if count > MAX_DERIVED_ACCOUNTS {
+        return Err(WalletError::InvalidAccountIndex(MAX_DERIVED_ACCOUNTS));
+    }
+
+    (0..count)
+        .map(|account_index| derive_wallet(seed, String::new(), network, account_index, 0))
+        .collect()
+}
+
+/// Infer the network a unified full viewing key is encoded for.
+///
+/// The network is taken from the key's human-readable prefix (`uview` for
+/// mainnet, `uviewtest` for testnet).
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+///
+/// # Returns
+///
+/// The network of the key. Regtest keys are rejected since the wallet does
+/// not support regtest.
+pub fn network_from_ufvk(ufvk: &str) -> Result<Network, WalletError> {
+    let (network_type, _) = unified::Ufvk::decode(ufvk.trim())
+        .map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?;
+
+    match network_type {
+        NetworkType::Main => Ok(Network::MainNetwork),
+        NetworkType::Test => Ok(Network::TestNetwork),
+        NetworkType::Regtest => Err(WalletError::InvalidViewingKey(
+            "Regtest viewing keys are not supported".to_string(),
+        )),
+    }
+}
+
+/// The components present in a unified full viewing key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UfvkReport {
+    /// The human-readable part of the encoding (`uview` or `uviewtest`).
+    pub hrp: String,
+    /// The network the key is for.
+    pub network: NetworkKind,
+    /// Whether the key has an Orchard full viewing key.
+    pub has_orchard: bool,
+    /// Whether the key has a Sapling full viewing key.
+    pub has_sapling: bool,
+    /// Whether the key has a transparent account public key.
+    pub has_transparent: bool,
+    /// Last block height the key may be used at, from ZIP 316 expiry
+    /// metadata.
+    #[serde(default)]
+    pub expiry_height: Option<u32>,
+    /// Unix time after which the key must not be used, from ZIP 316 expiry
+    /// metadata.
+    #[serde(default)]
+    pub expiry_time: Option<u64>,
+}
+
+/// ZIP 316 typecode of the expiry height metadata item (4-byte LE height).
+const EXPIRY_HEIGHT_TYPECODE: u32 = 0xE0;
+
+/// ZIP 316 typecode of the expiry time metadata item (8-byte LE unix time).
+const EXPIRY_TIME_TYPECODE: u32 = 0xE1;
+
+/// Report which components a unified full viewing key contains.
+///
+/// The account index is not encoded in the key and cannot be recovered.
+/// Expiry metadata items, if present, are reported as well.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+///
+/// # Returns
+///
+/// A `UfvkReport` with the key's network and a flag per pool.
+pub fn ufvk_components(ufvk: &str) -> Result<UfvkReport, WalletError> {
+    let network = network_from_ufvk(ufvk)?;
+    let (network_type, decoded) = unified::Ufvk::decode(ufvk.trim())
+        .map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?;
+
+    let mut report = UfvkReport {
+        hrp: network_type.hrp_unified_fvk().to_string(),
+        network: NetworkKind::from(network),
+        has_orchard: false,
+        has_sapling: false,
+        has_transparent: false,
+        expiry_height: None,
+        expiry_time: None,
+    };
+    let malformed = |typecode: u32| {
+        WalletError::InvalidViewingKey(format!("Malformed expiry metadata item {:#x}", typecode))
+    };
+    for item in decoded.items() {
+        match item {
+            unified::Fvk::Orchard(_) => report.has_orchard = true,
+            unified::Fvk::Sapling(_) => report.has_sapling = true,
+            unified::Fvk::P2pkh(_) => report.has_transparent = true,
+            unified::Fvk::Unknown { typecode, data } if typecode == EXPIRY_HEIGHT_TYPECODE => {
+                let bytes = data.try_into().map_err(|_| malformed(typecode))?;
+                report.expiry_height = Some(u32::from_le_bytes(bytes));
+            }
+            unified::Fvk::Unknown { typecode, data } if typecode == EXPIRY_TIME_TYPECODE => {
+                let bytes = data.try_into().map_err(|_| malformed(typecode))?;
+                report.expiry_time = Some(u64::from_le_bytes(bytes));
+            }
+            unified::Fvk::Unknown { .. } => {}
+        }
+    }
+    Ok(report)
+}
+
+/// Check whether a unified full viewing key has expired.
+///
+/// A key expires once the chain is past its expiry height, or the current
+/// time is past its expiry time (ZIP 316 metadata). Keys without expiry
+/// metadata never expire.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `current_height` - Height of the current chain tip
+/// * `current_time` - Current unix time in seconds
+///
+/// # Returns
+///
+/// Whether the key has expired.
+pub fn is_expired(ufvk: &str, current_height: u32, current_time: u64) -> Result<bool, WalletError> {
+    let report = ufvk_components(ufvk)?;
+    Ok(report
+        .expiry_height
+        .is_some_and(|height| current_height > height)
+        || report.expiry_time.is_some_and(|time| current_time > time))
+}
+
+/// Build a view-only wallet from a unified full viewing key.
+///
+/// The seed phrase is unknown, so `seed_phrase` is left empty, and the
+/// account index is reported as 0 since it cannot be recovered from the key.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+/// * `network` - The expected network, or `None` to infer it from the key.
+/// * `address_index` - The address/diversifier index to derive addresses at.
+///
+/// # Returns
+///
+/// A `WalletInfo` with the addresses derived from the viewing key.
+pub fn wallet_from_ufvk(
+    ufvk: &str,
+    network: Option<Network>,
+    address_index: u32,
+) -> Result<WalletInfo, WalletError> {
+    let detected = network_from_ufvk(ufvk)?;
+    if let Some(expected) = network
+        && expected != detected
+    {
+        return Err(WalletError::InvalidViewingKey(format!(
+            "Viewing key is for {}, expected {}",
+            NetworkKind::from(detected).as_str(),
+            NetworkKind::from(expected).as_str()
+        )));
+    }
+
+    let decoded = UnifiedFullViewingKey::decode(&detected, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+
+    wallet_info_from_ufvk(&decoded, String::new(), detected, 0, address_index)
+}
+
+/// Convert a unified full viewing key to a unified incoming viewing key.
+///
+/// The UIVK can still detect and decrypt incoming notes, but cannot see
+/// outgoing payments or compute nullifiers, so it reveals less when shared
+/// (e.g. with an accountant or a watch-only service).
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+/// * `network` - The network the key is encoded for.
+///
+/// # Returns
+///
+/// The encoded UIVK (`uivk` prefix on mainnet, `uivktest` on testnet).
+pub fn ufvk_to_uivk(ufvk: &str, network: Network) -> Result<String, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    Ok(ufvk.to_unified_incoming_viewing_key().encode(&network))
+}
+
+/// Check whether a unified full viewing key belongs to a seed phrase.
+///
+/// Derives the UFVK for `account` from the seed phrase and compares it with
+/// `ufvk` after decoding and re-encoding both, so equivalent encodings
+/// compare equal.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key to check
+/// * `seed_phrase` - A valid 24-word BIP39 mnemonic
+/// * `account` - The account index the key is expected to belong to
+/// * `network` - The network the key is for
+///
+/// # Returns
+///
+/// True if the key was derived from the seed phrase for that account, or an
+/// error if the key or seed phrase is invalid.
+pub fn ufvk_matches_seed(
+    ufvk: &str,
+    seed_phrase: &str,
+    account: u32,
+    network: Network,
+) -> Result<bool, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+    let account_id = account_id(account)?;
+    let usk = UnifiedSpendingKey::from_seed(&network, &mnemonic.to_seed(""), account_id)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    Ok(usk.to_unified_full_viewing_key().encode(&network) == ufvk.encode(&network))
+}
+
+/// Derive a standalone Sapling address at a diversifier index.
+///
+/// Not every index yields a valid Sapling diversifier; invalid ones are
+/// skipped and the address at the next valid index is returned.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key (with a Sapling component)
+/// * `network` - The network the key is for
+/// * `index` - The diversifier index to start from (below 2^88)
+///
+/// # Returns
+///
+/// The encoded Sapling address (`zs...`, or `ztestsapling...` on testnet).
+pub fn derive_sapling_diversified_address(
+    ufvk: &str,
+    network: Network,
+    index: u128,
+) -> Result<String, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    let dfvk = ufvk.sapling().ok_or_else(|| {
+        WalletError::InvalidViewingKey("Viewing key has no Sapling component".to_string())
+    })?;
+    let start = DiversifierIndex::try_from(index).map_err(|_| {
+        WalletError::AddressGeneration(format!("Diversifier index {} is out of range", index))
+    })?;
+
+    let (_, address) = dfvk.find_address(start).ok_or_else(|| {
+        WalletError::AddressGeneration("No valid Sapling diversifier found".to_string())
+    })?;
+    Ok(Address::Sapling(address).encode(&network))
+}
+
+/// Derive the wallet's shielded change address.
+///
+/// Change is sent to the internal (ZIP 32 `Scope::Internal`) keys so it can
+/// be told apart from payments received. The address has Orchard and Sapling
+/// receivers for whichever of them the key has, at the Sapling change
+/// diversifier index. Transparent receivers are omitted since change must
+/// stay shielded.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `network` - The network the key is for
+///
+/// # Returns
+///
+/// The encoded internal unified address.
+pub fn change_address(ufvk: &str, network: Network) -> Result<String, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+
+    let (index, sapling) = match ufvk.sapling() {
+        Some(dfvk) => {
+            let (index, address) = dfvk.change_address();
+            (index, Some(address))
+        }
+        None => (DiversifierIndex::new(), None),
+    };
+    let orchard = ufvk
+        .orchard()
+        .map(|fvk| fvk.address_at(index, Scope::Internal));
+
+    UnifiedAddress::from_receivers(orchard, sapling, None)
+        .map(|ua| ua.encode(&network))
+        .ok_or_else(|| {
+            WalletError::AddressGeneration("Viewing key has no shielded component".to_string())
+        })
+}
+
+/// Number of diversifier indices searched when matching addresses while
+/// scanning.
+pub const DIVERSIFIER_SEARCH_LIMIT: u32 = 1_000;
+
+/// Default `max_search` bound for [`recover_diversifier_index`] and the
+/// other bounded diversifier searches.
+pub const DEFAULT_MAX_DIVERSIFIER_SEARCH: u128 = 10_000;
+
+/// Outcome of [`recover_diversifier_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiversifierSearch {
+    /// The address was derived at this diversifier index.
+    Found(u128),
+    /// None of the searched indices derive the address. It may still belong
+    /// to the key at a higher index.
+    BoundExhausted {
+        /// Number of indices searched.
+        searched: u128,
+    },
+}
+
+impl DiversifierSearch {
+    /// Get the diversifier index, if the address was found.
+    pub fn index(self) -> Option<u128> {
+        match self {
+            Self::Found(index) => Some(index),
+            Self::BoundExhausted { .. } => None,
+        }
+    }
+}
+
+/// Number of valid diversifier indices (2^88).
+const DIVERSIFIER_INDEX_COUNT: u128 = 1 << 88;
+
+/// Recover the diversifier index an address was derived at.
+///
+/// Searches indices `0..max_search` of the external scope for the one
+/// producing the address. Unified addresses are matched on their Orchard
+/// receiver, falling back to Sapling and then transparent, so a unified
+/// address is recognised whatever receiver types it was built with.
+///
+/// The search is linear in `max_search`, so the bound must be kept small
+/// for addresses from untrusted input; an address the key did not derive is
+/// only rejected after the whole range has been searched.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `address` - The encoded unified, Sapling, transparent or TEX address
+/// * `network` - The network both are encoded for
+/// * `max_search` - Number of indices to search, e.g.
+///   [`DEFAULT_MAX_DIVERSIFIER_SEARCH`]
+///
+/// # Returns
+///
+/// `DiversifierSearch::Found` with the diversifier index, or
+/// `DiversifierSearch::BoundExhausted` if the address was not derived from
+/// the key within the first `max_search` indices.
+pub fn recover_diversifier_index(
+    ufvk: &str,
+    address: &str,
+    network: Network,
+    max_search: u128,
+) -> Result<DiversifierSearch, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    let address = Address::decode(&network, address.trim()).ok_or_else(|| {
+        WalletError::InvalidAddress(format!("Could not decode address for {:?}", network))
+    })?;
+
+    let (orchard, sapling, transparent) = address_receivers(&address);
+
+    let transparent_ivk = ufvk
+        .transparent()
+        .and_then(|tfvk| tfvk.derive_external_ivk().ok());
+
+    let matches = |index: u128| -> bool {
+        let Ok(diversifier_index) = DiversifierIndex::try_from(index) else {
+            return false;
+        };
+        if let (Some(receiver), Some(fvk)) = (orchard, ufvk.orchard()) {
+            return fvk.address_at(diversifier_index, Scope::External) == *receiver;
+        }
+        if let (Some(receiver), Some(dfvk)) = (sapling, ufvk.sapling()) {
+            return dfvk.address(diversifier_index).as_ref() == Some(receiver);
+        }
+        if let (Some(receiver), Some(ivk)) = (transparent, transparent_ivk.as_ref()) {
+            return u32::try_from(index)
+                .ok()
+                .and_then(NonHardenedChildIndex::from_index)
+                .and_then(|child| ivk.derive_address(child).ok())
+                == Some(receiver);
+        }
+        false
+    };
+
+    let searched = max_search.min(DIVERSIFIER_INDEX_COUNT);
+    Ok(match (0..searched).find(|&index| matches(index)) {
+        Some(index) => DiversifierSearch::Found(index),
+        None => DiversifierSearch::BoundExhausted { searched },
+    })
+}
+
+/// Split an address into its Orchard, Sapling and transparent receivers.
+fn address_receivers(
+    address: &Address,
+) -> (
+    Option<&orchard::Address>,
+    Option<&sapling_crypto::PaymentAddress>,
+    Option<TransparentAddress>,
+) {
+    match address {
+        Address::Unified(ua) => (ua.orchard(), ua.sapling(), ua.transparent().copied()),
+        Address::Sapling(pa) => (None, Some(pa), None),
+        Address::Transparent(taddr) => (None, None, Some(*taddr)),
+        Address::Tex(key_hash) => (
+            None,
+            None,
+            Some(TransparentAddress::PublicKeyHash(*key_hash)),
+        ),
+    }
+}
+
+/// Check whether an address is one of a viewing key's internal (change)
+/// addresses.
+///
+/// Checks the Orchard internal addresses at index 0 and at the Sapling
+/// change index, the Sapling change address and the first internal
+/// transparent address. An address with any receiver matching counts.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `addr` - The encoded unified, Sapling, transparent or TEX address
+/// * `network` - The network both are encoded for
+pub(crate) fn is_internal_address(
+    ufvk: &str,
+    addr: &str,
+    network: Network,
+) -> Result<bool, WalletError> {
+    let decoded = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    let address = Address::decode(&network, addr.trim()).ok_or_else(|| {
+        WalletError::InvalidAddress(format!("Could not decode address for {:?}", network))
+    })?;
+    let (orchard, sapling, transparent) = address_receivers(&address);
+
+    let change = decoded.sapling().map(|dfvk| dfvk.change_address());
+    let change_index = change.map_or(DiversifierIndex::new(), |(index, _)| index);
+    let internal_orchard = decoded.orchard().is_some_and(|fvk| {
+        orchard.is_some_and(|receiver| {
+            [DiversifierIndex::new(), change_index]
+                .into_iter()
+                .any(|index| fvk.address_at(index, Scope::Internal) == *receiver)
+        })
+    });
+    let internal_sapling = change.is_some_and(|(_, address)| sapling == Some(&address));
+    let internal_transparent = decoded
+        .transparent()
+        .and_then(|tfvk| tfvk.derive_internal_ivk().ok())
+        .and_then(|ivk| ivk.derive_address(NonHardenedChildIndex::ZERO).ok())
+        .is_some_and(|address| transparent == Some(address));
+    Ok(internal_orchard || internal_sapling || internal_transparent)
+}
+
+/// Check whether an address belongs to a viewing key.
+///
+/// Checks the internal (change) addresses (see [`is_internal_address`]),
+/// then the external
+/// diversifier indices `0..max_diversifier_search` as
+/// [`recover_diversifier_index`] does. The external default address is
+/// always checked, even for a zero bound.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `addr` - The encoded unified, Sapling, transparent or TEX address
+/// * `network` - The network both are encoded for
+/// * `max_diversifier_search` - Number of external indices to search, e.g.
+///   [`DEFAULT_MAX_DIVERSIFIER_SEARCH`]
+///
+/// # Returns
+///
+/// Whether the address was derived from the key.
+pub fn is_own_address(
+    ufvk: &str,
+    addr: &str,
+    network: Network,
+    max_diversifier_search: u128,
+) -> Result<bool, WalletError> {
+    if is_internal_address(ufvk, addr, network)? {
+        return Ok(true);
+    }
+
+    let search = recover_diversifier_index(ufvk, addr, network, max_diversifier_search.max(1))?;
+    Ok(search.index().is_some())
+}
+
+/// List the wallet addresses an Orchard note could have been received at.
+///
+/// Searches external indices `0..max_search` for unified addresses whose
+/// Orchard receiver is the note's recipient. Each address includes every
+/// receiver the key supports at that index, or only the Orchard receiver
+/// where the index has no valid Sapling diversifier.
+///
+/// # Arguments
+///
+/// * `note` - The decrypted Orchard note
+/// * `ufvk` - The encoded unified full viewing key
+/// * `network` - The network the key is for
+/// * `max_search` - Number of indices to search, e.g.
+///   [`DEFAULT_MAX_DIVERSIFIER_SEARCH`]
+///
+/// # Returns
+///
+/// The matching encoded unified addresses in index order; empty if the note
+/// was not sent to the key within the search bound.
+pub fn receiving_addresses_for_note(
+    note: &orchard::Note,
+    ufvk: &str,
+    network: Network,
+    max_search: u128,
+) -> Result<Vec<String>, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+    let fvk = ufvk.orchard().ok_or_else(|| {
+        WalletError::InvalidViewingKey("Viewing key has no Orchard component".to_string())
+    })?;
+    let recipient = note.recipient();
+
+    let mut addresses = Vec::new();
+    for index in 0..max_search.min(DIVERSIFIER_INDEX_COUNT) {
+        let Ok(diversifier_index) = DiversifierIndex::try_from(index) else {
+            break;
+        };
+        if fvk.address_at(diversifier_index, Scope::External) != recipient {
+            continue;
+        }
+        let ua = ufvk
+            .address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys)
+            .ok()
+            .or_else(|| UnifiedAddress::from_receivers(Some(recipient), None, None))
+            .ok_or_else(|| {
+                WalletError::AddressGeneration("Invalid Orchard receiver".to_string())
+            })?;
+        addresses.push(ua.encode(&network));
+    }
+    Ok(addresses)
+}
+
+/// BLAKE2b personalization for wallet alias fingerprints (16 bytes).
+const WALLET_ALIAS_PERSONALIZATION: &[u8; 16] = b"ZcashWalletAlias";
+
+/// Derive a short human-readable alias for a wallet from its UFVK.
+///
+/// The alias is two BIP39 words followed by a two-digit check number, e.g.
+/// `"orbit-tunnel-42"`. It is a fingerprint of the viewing key, so users
+/// can confirm they are looking at the same wallet on different devices
+/// before spending. It is not a secret and cannot be used to recover keys.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+///
+/// # Returns
+///
+/// The alias, stable for a given UFVK.
+pub fn wallet_alias(ufvk: &str) -> Result<String, WalletError> {
+    let ufvk = ufvk.trim();
+    unified::Ufvk::decode(ufvk).map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?;
+
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(WALLET_ALIAS_PERSONALIZATION)
+        .hash(ufvk.as_bytes());
+    let bytes = hash.as_bytes();
+
+    // Two 11-bit indices into the BIP39 English word list
+    let first = (usize::from(bytes[0]) << 3) | (usize::from(bytes[1]) >> 5);
+    let second = ((usize::from(bytes[1]) & 0x1f) << 6) | (usize::from(bytes[2]) >> 2);
+    let words = Language::English.word_list();
+
+    // Check number covering the whole fingerprint, so a mistyped word pair
+    // is unlikely to still match
+    let check = bytes
+        .iter()
+        .fold(0u32, |acc, &b| (acc * 31 + u32::from(b)) % 100);
+
+    Ok(format!("{}-{}-{:02}", words[first], words[second], check))
+}
+
+/// BLAKE2b personalization for labeled address indices (16 bytes).
+const LABELED_ADDRESS_PERSONALIZATION: &[u8; 16] = b"ZcashLabeledAddr";
+
+/// Derive a stable unified address for a user-supplied label.
+///
+/// The diversifier index is taken from a hash of the label, so the same
+/// label always yields the same address for a given viewing key, while
+/// different labels yield unlinkable addresses. The address only has
+/// shielded receivers: a transparent receiver would publicly link the
+/// payments made to it.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+/// * `label` - The label, e.g. the name of a recurring payer.
+/// * `network` - The network the key is for.
+///
+/// # Returns
+///
+/// The encoded unified address for the label.
+pub fn labeled_address(ufvk: &str, label: &str, network: Network) -> Result<String, WalletError> {
+    hashed_index_address(ufvk, LABELED_ADDRESS_PERSONALIZATION, label, network)
+}
+
+/// BLAKE2b personalization for deriving contact address indices.
+const CONTACT_ADDRESS_PERSONALIZATION: &[u8; 16] = b"ZcashContactAddr";
+
+/// Derive a stable unified address to give to a counterparty.
+///
+/// Works like [`labeled_address`] with the contact's identifier in place
+/// of the label, under a separate hash personalization so a contact and a
+/// label with the same text get different addresses. The same contact
+/// always gets the same address, across sessions and devices. Like labeled
+/// addresses, it has no transparent receiver.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key.
+/// * `contact_id` - A stable identifier of the counterparty.
+/// * `network` - The network the key is for.
+///
+/// # Returns
+///
+/// The encoded unified address for the contact.
+pub fn address_for_contact(
+    ufvk: &str,
+    contact_id: &str,
+    network: Network,
+) -> Result<String, WalletError> {
+    hashed_index_address(ufvk, CONTACT_ADDRESS_PERSONALIZATION, contact_id, network)
+}
+
+/// Receivers requested for addresses handed out to counterparties: Orchard
+/// and Sapling where the key has them, never transparent.
+const SHIELDED_ADDRESS_REQUEST: UnifiedAddressRequest = UnifiedAddressRequest::unsafe_custom(
+    ReceiverRequirement::Allow,
+    ReceiverRequirement::Allow,
+    ReceiverRequirement::Omit,
+);
+
+/// Derive the shielded unified address at the first valid diversifier index
+/// from 31 bits of a hash of `input`.
+fn hashed_index_address(
+    ufvk: &str,
+    personalization: &[u8; 16],
+    input: &str,
+    network: Network,
+) -> Result<String, WalletError> {
+    let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+        .map_err(WalletError::InvalidViewingKey)?;
+
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .hash(input.as_bytes());
+    let mut index_bytes = [0u8; 4];
+    index_bytes.copy_from_slice(&hash.as_bytes()[..4]);
+    let index = u32::from_le_bytes(index_bytes) & 0x7fff_ffff;
+
+    let (ua, _) = ufvk
+        .find_address(DiversifierIndex::from(index), SHIELDED_ADDRESS_REQUEST)
+        .map_err(|e| WalletError::AddressGeneration(format!("{:?}", e)))?;
+    Ok(ua.encode(&network))
+}
+
+/// Number of zatoshis in one ZEC.
+const ZATOSHIS_PER_ZEC: u64 = 100_000_000;
+
+/// Maximum amount of zatoshis that can exist (21 million ZEC).
+const MAX_MONEY: u64 = 21_000_000 * ZATOSHIS_PER_ZEC;
+
+/// Parse a ZEC amount such as `"1.5"` or `"0.0001"` into zatoshis.
+///
+/// The amount is parsed exactly, without going through floating point. At
+/// most 8 decimal places are accepted, and signs, exponents and thousands
+/// separators are rejected.
+///
+/// # Arguments
+///
+/// * `amount` - The amount in ZEC as a decimal string
+///
+/// # Returns
+///
+/// The amount in zatoshis, or `WalletError::InvalidAmount` if the string is
+/// malformed or exceeds the 21 million ZEC supply.
+pub fn parse_zec_amount(amount: &str) -> Result<u64, WalletError> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(WalletError::InvalidAmount(format!(
+            "'{}' is not a decimal ZEC amount",
+            amount
+        )));
+    }
+    if fraction.len() > 8 {
+        return Err(WalletError::InvalidAmount(format!(
+            "'{}' has more than 8 decimal places",
+            amount
+        )));
+    }
+
+    let whole_zats = if whole.is_empty() {
+        Some(0)
+    } else {
+        whole
+            .parse::<u64>()
+            .ok()
+            .and_then(|zec| zec.checked_mul(ZATOSHIS_PER_ZEC))
+    };
+    let fraction_zats = format!("{:0<8}", fraction)
+        .parse::<u64>()
+        .expect("8 ASCII digits fit in a u64");
+    whole_zats
+        .and_then(|zats| zats.checked_add(fraction_zats))
+        .filter(|&zats| zats <= MAX_MONEY)
+        .ok_or_else(|| {
+            WalletError::InvalidAmount(format!("'{}' exceeds the total ZEC supply", amount))
+        })
+}
+
+/// Parse a send amount and check it against the available balance.
+///
+/// # Arguments
+///
+/// * `amount_str` - The amount to send in ZEC as a decimal string
+/// * `available_zats` - The spendable balance in zatoshis
+/// * `fee` - The transaction fee in zatoshis
+///
+/// # Returns
+///
+/// The amount in zatoshis, `WalletError::InvalidAmount` if it is malformed
+/// or zero, or `WalletError::InsufficientFunds` if the amount plus fee
+/// exceeds the balance.
+pub fn validate_send_amount(
+    amount_str: &str,
+    available_zats: u64,
+    fee: u64,
+) -> Result<u64, WalletError> {
+    let amount = parse_zec_amount(amount_str)?;
+    if amount == 0 {
+        return Err(WalletError::InvalidAmount(
+            "Amount must be greater than zero".to_string(),
+        ));
+    }
+    let required = amount.saturating_add(fee);
+    if required > available_zats {
+        return Err(WalletError::InsufficientFunds {
+            available: available_zats,
+            required,
+        });
+    }
+    Ok(amount)
+}
+
+/// Issues single-use addresses, never handing out a diversifier index twice.
+///
+/// Meant for invoice-style flows where every payment request gets its own
+/// address. The pool only records indices, so it is cheap to persist (it
+/// serializes with serde) and must be kept per viewing key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OneTimeAddressPool {
+    /// Diversifier indices handed out so far, in ascending order.
+    #[serde(default)]
+    pub issued: std::collections::BTreeSet<u128>,
+}
+
+impl OneTimeAddressPool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a fresh unified address and record its diversifier index.
+    ///
+    /// The address is derived at the first valid diversifier index above
+    /// every index issued so far. It only has shielded receivers, since a
+    /// transparent receiver would not be single-use: all transparent
+    /// receivers of a key are linkable on chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `ufvk` - The encoded unified full viewing key the pool belongs to
+    /// * `network` - The network the key is for
+    ///
+    /// # Returns
+    ///
+    /// The encoded unified address.
+    pub fn issue(&mut self, ufvk: &str, network: Network) -> Result<String, WalletError> {
+        let ufvk = UnifiedFullViewingKey::decode(&network, ufvk.trim())
+            .map_err(WalletError::InvalidViewingKey)?;
+
+        let start = self.issued.last().map_or(0, |index| index + 1);
+        let start = DiversifierIndex::try_from(start).map_err(|_| {
+            WalletError::AddressGeneration("Diversifier indices exhausted".to_string())
+        })?;
+        let (ua, index) = ufvk
+            .find_address(start, SHIELDED_ADDRESS_REQUEST)
+            .map_err(|e| WalletError::AddressGeneration(format!("{:?}", e)))?;
+
+        let mut index_bytes = [0u8; 16];
+        index_bytes[..11].copy_from_slice(index.as_bytes());
+        self.issued.insert(u128::from_le_bytes(index_bytes));
+        Ok(ua.encode(&network))
+    }
+
+    /// Check whether a diversifier index has been issued.
+    pub fn is_issued(&self, index: u128) -> bool {
+        self.issued.contains(&index)
+    }
+}
+
+/// Maximum number of suggestions returned by [`suggest_word`].
+pub const MAX_WORD_SUGGESTIONS: usize = 3;
+
+/// Maximum edit distance of a suggested BIP39 word from the typed one.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// A seed phrase word that is not in the BIP39 English word list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnknownWord {
+    /// Position of the word in the phrase (0-based).
+    pub position: usize,
+    /// The word as typed.
+    pub word: String,
+    /// Likely intended words (see [`suggest_word`]).
+    pub suggestions: Vec<String>,
+}
+
+/// Levenshtein distance between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Suggest BIP39 English words for a possibly misspelled seed word.
+///
+/// # Arguments
+///
+/// * `word` - The word as typed (case and surrounding whitespace ignored)
+///
+/// # Returns
+///
+/// The word itself if it is in the word list. Otherwise up to
+/// [`MAX_WORD_SUGGESTIONS`] words within edit distance 2, closest first
+/// (ties in word list order), or an empty list if none is close enough.
+pub fn suggest_word(word: &str) -> Vec<String> {
+    let word = word.trim().to_lowercase();
+    let words = Language::English.word_list();
+    if words.contains(&word.as_str()) {
+        return vec![word];
+    }
+
+    let mut candidates: Vec<(usize, &str)> = words
+        .iter()
+        .map(|candidate| (edit_distance(&word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    // Stable sort keeps word list order among equal distances
+    candidates.sort_by_key(|(distance, _)| *distance);
+    candidates
+        .into_iter()
+        .take(MAX_WORD_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Find the words of a seed phrase that are not in the BIP39 word list.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The seed phrase, words separated by whitespace
+///
+/// # Returns
+///
+/// Each unknown word with its position and suggested replacements, in
+/// phrase order. Empty if every word is valid.
+pub fn find_unknown_words(seed_phrase: &str) -> Vec<UnknownWord> {
+    let words = Language::English.word_list();
+    seed_phrase
+        .split_whitespace()
+        .enumerate()
+        .filter(|(_, word)| !words.contains(&word.to_lowercase().as_str()))
+        .map(|(position, word)| UnknownWord {
+            position,
+            word: word.to_string(),
+            suggestions: suggest_word(word),
+        })
+        .collect()
+}
+
+/// What an imported key lets the wallet do.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyCapabilities {
+    /// The kind of key.
+    pub key_type: ViewingKeyType,
+    /// Whether received notes can be detected and decrypted.
+    pub can_view_incoming: bool,
+    /// Whether sent outputs can be recovered (outgoing viewing key).
+    pub can_view_outgoing: bool,
+    /// Whether funds can be spent. Always false for viewing keys.
+    pub can_spend: bool,
+}
+
+/// Report what an imported viewing key can do, for labeling it in the UI.
+///
+/// Viewing keys never carry spending authority, so `can_spend` is false
+/// for every key accepted here; spending needs the seed phrase.
+///
+/// # Arguments
+///
+/// * `key` - An encoded UFVK, UIVK or legacy Sapling extended full viewing key
+///
+/// # Returns
+///
+/// The `KeyCapabilities`, or `WalletError::InvalidViewingKey` if the key is
+/// not recognized.
+pub fn key_capabilities(key: &str) -> Result<KeyCapabilities, WalletError> {
+    let key = key.trim();
+    let (key_type, can_view_outgoing) = if unified::Ufvk::decode(key).is_ok() {
+        (ViewingKeyType::Ufvk, true)
+    } else if unified::Uivk::decode(key).is_ok() {
+        (ViewingKeyType::Uivk, false)
+    } else if key.starts_with("zxview") {
+        (ViewingKeyType::SaplingExtFvk, true)
+    } else {
+        return Err(WalletError::InvalidViewingKey(
+            "Not a UFVK, UIVK or Sapling viewing key".to_string(),
+        ));
+    };
+
+    Ok(KeyCapabilities {
+        key_type,
+        can_view_incoming: true,
+        can_view_outgoing,
+        can_spend: false,
+    })
+}
+
+/// Check whether a seed phrase is a publicly known test vector.
+///
+/// Flags phrases whose entropy repeats a single byte, which covers the
+/// BIP39 test vectors of every length: all-`abandon` (`0x00`), all-`zoo`
+/// (`0xFF`), `legal winner ...` (`0x7F`) and `letter advice ...` (`0x80`).
+/// Funds sent to such a wallet can be taken by anyone, so the UI should
+/// warn before importing one.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The seed phrase (case and extra whitespace ignored)
+///
+/// # Returns
+///
+/// True for a valid phrase with repeated-byte entropy, false otherwise
+/// (including phrases that are not valid BIP39).
+pub fn is_known_weak_seed(seed_phrase: &str) -> bool {
+    let normalized = seed_phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let Ok(mnemonic) = Mnemonic::parse_in_normalized(Language::English, &normalized) else {
+        return false;
+    };
+    let entropy = mnemonic.to_entropy();
+    entropy.windows(2).all(|pair| pair[0] == pair[1])
+}
+
+/// ZIP 316 typecodes of the unified viewing key items the wallet decodes.
+const P2PKH_TYPECODE: u32 = 0x00;
+const SAPLING_TYPECODE: u32 = 0x02;
+const ORCHARD_TYPECODE: u32 = 0x03;
+
+/// Encode a unified full viewing key as bytes, for compact storage.
+///
+/// The bytes are the key's items in their encoded order, each as a
+/// CompactSize typecode, a CompactSize length and the item data, as in the
+/// ZIP 316 raw encoding but without padding or F4Jumble. The network is not
+/// included and must be stored alongside.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+///
+/// # Returns
+///
+/// The item bytes, decoded again by [`ufvk_from_bytes`].
+pub fn ufvk_to_bytes(ufvk: &str) -> Result<Vec<u8>, WalletError> {
+    let (_, decoded) =
+        unified::Ufvk::decode(ufvk).map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?;
+
+    let mut bytes = Vec::new();
+    for item in decoded.items_as_parsed() {
+        let (typecode, data) = match item {
+            unified::Fvk::P2pkh(data) => (P2PKH_TYPECODE, data.as_slice()),
+            unified::Fvk::Sapling(data) => (SAPLING_TYPECODE, data.as_slice()),
+            unified::Fvk::Orchard(data) => (ORCHARD_TYPECODE, data.as_slice()),
+            unified::Fvk::Unknown { typecode, data } => (*typecode, data.as_slice()),
+        };
+        push_compact_size(&mut bytes, u64::from(typecode));
+        push_compact_size(&mut bytes, data.len() as u64);
+        bytes.extend_from_slice(data);
+    }
+    Ok(bytes)
+}
+
+/// Decode a unified full viewing key stored with [`ufvk_to_bytes`].
+///
+/// # Arguments
+///
+/// * `bytes` - The item bytes
+/// * `network` - The network to encode the key for
+///
+/// # Returns
+///
+/// The encoded unified full viewing key, identical to the one the bytes
+/// were produced from.
+pub fn ufvk_from_bytes(bytes: &[u8], network: Network) -> Result<String, WalletError> {
+    let malformed = || WalletError::InvalidViewingKey("Malformed viewing key bytes".to_string());
+
+    let mut rest = bytes;
+    let mut items = Vec::new();
+    while !rest.is_empty() {
+        let typecode = take_compact_size(&mut rest)
+            .and_then(|typecode| u32::try_from(typecode).ok())
+            .ok_or_else(malformed)?;
+        let len = take_compact_size(&mut rest)
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|&len| len <= rest.len())
+            .ok_or_else(malformed)?;
+        let (data, tail) = rest.split_at(len);
+        rest = tail;
+
+        let item = match typecode {
+            P2PKH_TYPECODE => unified::Fvk::P2pkh(data.try_into().map_err(|_| malformed())?),
+            SAPLING_TYPECODE => unified::Fvk::Sapling(data.try_into().map_err(|_| malformed())?),
+            ORCHARD_TYPECODE => unified::Fvk::Orchard(data.try_into().map_err(|_| malformed())?),
+            typecode => unified::Fvk::Unknown {
+                typecode,
+                data: data.to_vec(),
+            },
+        };
+        items.push(item);
+    }
+
+    let network_type = match network {
+        Network::MainNetwork => NetworkType::Main,
+        Network::TestNetwork => NetworkType::Test,
+    };
+    let ufvk = unified::Ufvk::try_from_items(items)
+        .map_err(|e| WalletError::InvalidViewingKey(e.to_string()))?;
+    Ok(ufvk.encode(&network_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Known test vector: a fixed seed phrase and its expected derived addresses
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn test_derive_wallet_is_deterministic_testnet() {
+        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let wallet2 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        assert_eq!(wallet1.unified_address, wallet2.unified_address);
+        assert_eq!(wallet1.transparent_address, wallet2.transparent_address);
+        assert_eq!(
+            wallet1.unified_full_viewing_key,
+            wallet2.unified_full_viewing_key
+        );
+    }
+
+    #[test]
+    fn test_derive_wallet_testnet_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // Verify addresses are non-empty and have expected prefixes for testnet
+        assert_eq!(wallet.network, NetworkKind::Testnet);
+        assert_eq!(wallet.account_index, 0);
+        assert!(
+            wallet.unified_address.starts_with("utest"),
+            "unified address should start with 'utest' for testnet"
+        );
+        assert!(
+            wallet
+                .transparent_address
+                .as_ref()
+                .map(|s| s.starts_with("tm"))
+                .unwrap_or(false),
+            "transparent address should start with 'tm' for testnet"
+        );
+        assert!(
+            wallet.unified_full_viewing_key.starts_with("uviewtest"),
+            "UFVK should start with 'uviewtest' for testnet"
+        );
+    }
+
+    #[test]
+    fn test_derive_wallet_mainnet_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // Verify addresses are non-empty and have expected prefixes for mainnet
+        assert_eq!(wallet.network, NetworkKind::Mainnet);
+        assert_eq!(wallet.account_index, 0);
+        assert!(
+            wallet.unified_address.starts_with("u1"),
+            "unified address should start with 'u1' for mainnet"
+        );
+        assert!(
+            wallet
+                .transparent_address
+                .as_ref()
+                .map(|s| s.starts_with("t1"))
+                .unwrap_or(false),
+            "transparent address should start with 't1' for mainnet"
+        );
+        assert!(
+            wallet.unified_full_viewing_key.starts_with("uview1"),
+            "UFVK should start with 'uview1' for mainnet"
+        );
+    }
+
+    #[test]
+    fn test_derive_wallet_known_vector_testnet() {
+        // This test uses a known seed and verifies exact output
+        // If this test fails after a library update, it indicates a breaking change
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // These are the expected values for the standard BIP39 test vector
+        // "abandon abandon ... art" on Zcash testnet
+        assert_eq!(
+            wallet.transparent_address,
+            Some("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd".to_string()),
+            "transparent address mismatch - library may have changed derivation"
+        );
+
+        assert_eq!(
+            wallet.unified_full_viewing_key,
+            "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd",
+            "UFVK mismatch - library may have changed derivation"
+        );
+    }
+
+    #[test]
+    fn test_different_seeds_produce_different_wallets() {
+        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // Different seed phrase
+        let different_seed = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
+        let wallet2 = restore_wallet(different_seed, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        assert_ne!(
+            wallet1.unified_address, wallet2.unified_address,
+            "different seeds should produce different unified addresses"
+        );
+        assert_ne!(
+            wallet1.transparent_address, wallet2.transparent_address,
+            "different seeds should produce different transparent addresses"
+        );
+        assert_ne!(
+            wallet1.unified_full_viewing_key, wallet2.unified_full_viewing_key,
+            "different seeds should produce different UFVKs"
+        );
+    }
+
+    #[test]
+    fn test_same_seed_different_networks() {
+        let testnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let mainnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        // Same seed should produce different addresses on different networks
+        assert_ne!(
+            testnet_wallet.unified_address, mainnet_wallet.unified_address,
+            "same seed should produce different addresses on different networks"
+        );
+        assert_ne!(
+            testnet_wallet.transparent_address, mainnet_wallet.transparent_address,
+            "same seed should produce different transparent addresses on different networks"
+        );
+    }
+
+    #[test]
+    fn test_restore_invalid_seed_fails() {
+        let result = restore_wallet("invalid seed phrase", Network::TestNetwork, 0, 0);
+        assert!(result.is_err(), "should fail with invalid seed phrase");
+    }
+
+    #[test]
+    fn test_generate_wallet_testnet() {
+        let entropy = [0u8; 32]; // Deterministic for testing
+        let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+            .expect("wallet generation should succeed");
+
+        assert!(!wallet.seed_phrase.is_empty());
+        assert!(!wallet.unified_address.is_empty());
+        assert!(wallet.transparent_address.is_some());
+        assert!(!wallet.unified_full_viewing_key.is_empty());
+        assert_eq!(wallet.network, NetworkKind::Testnet);
+        assert_eq!(wallet.account_index, 0);
+        assert_eq!(wallet.address_index, 0);
+    }
+
+    #[test]
+    fn test_generate_wallet_mainnet() {
+        let entropy = [0u8; 32]; // Deterministic for testing
+        let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0, None)
+            .expect("wallet generation should succeed");
+
+        assert!(!wallet.seed_phrase.is_empty());
+        assert!(wallet.unified_address.starts_with("u1"));
+        assert!(
+            wallet
+                .transparent_address
+                .as_ref()
+                .map(|s| s.starts_with("t1"))
+                .unwrap_or(false)
+        );
+        assert!(wallet.unified_full_viewing_key.starts_with("uview1"));
+        assert_eq!(wallet.network, NetworkKind::Mainnet);
+        assert_eq!(wallet.account_index, 0);
+        assert_eq!(wallet.address_index, 0);
+    }
+
+    #[test]
+    fn test_different_account_indices() {
+        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
+
+        assert_ne!(
+            wallet0.unified_address, wallet1.unified_address,
+            "different accounts should produce different unified addresses"
+        );
+        assert_ne!(
+            wallet0.transparent_address, wallet1.transparent_address,
+            "different accounts should produce different transparent addresses"
+        );
+        assert_ne!(
+            wallet0.unified_full_viewing_key, wallet1.unified_full_viewing_key,
+            "different accounts should produce different UFVKs"
+        );
+        assert_eq!(wallet0.account_index, 0);
+        assert_eq!(wallet1.account_index, 1);
+    }
+
+    #[test]
+    fn test_different_address_indices() {
+        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 1)
+            .expect("wallet derivation should succeed");
+
+        assert_ne!(
+            wallet0.unified_address, wallet1.unified_address,
+            "different address indices should produce different unified addresses"
+        );
+        assert_ne!(
+            wallet0.transparent_address, wallet1.transparent_address,
+            "different address indices should produce different transparent addresses"
+        );
+        // Same account should have same UFVK
+        assert_eq!(
+            wallet0.unified_full_viewing_key, wallet1.unified_full_viewing_key,
+            "same account should have same UFVK regardless of address index"
+        );
+        assert_eq!(wallet0.address_index, 0);
+        assert_eq!(wallet1.address_index, 1);
+    }
+
+    // =========================================================================
+    // Regression tests for address derivation at different indices (Issue #51)
+    // =========================================================================
+
+    #[test]
+    fn test_transparent_address_derivation_regression() {
+        // Test derivation at specific indices
+        // These values were captured from the current implementation and serve
+        // as regression tests to detect any changes in derivation logic.
+        let addresses = derive_transparent_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,   // account index
+            0,   // start index
+            100, // count
+        )
+        .expect("derivation should succeed");
+
+        // Verify address at index 0 matches the known wallet derivation
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        assert_eq!(
+            addresses[0],
+            wallet.transparent_address.unwrap(),
+            "transparent address at index 0 should match wallet derivation"
+        );
+
+        // Verify all addresses are unique (transparent addresses should be unique)
+        let unique_count = addresses
+            .iter()
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        assert_eq!(
+            unique_count,
+            addresses.len(),
+            "all derived transparent addresses should be unique"
+        );
+
+        // Verify all addresses have correct prefix
+        for addr in &addresses {
+            assert!(
+                addr.starts_with("tm"),
+                "testnet transparent address should start with 'tm'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_transparent_address_known_values_testnet() {
+        // Regression test with hardcoded expected values for the standard BIP39 test vector
+        // If these values change, it indicates a breaking change in derivation logic.
+        let addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 11)
+                .expect("derivation should succeed");
+
+        // Known values for testnet transparent addresses at indices 0-10
+        let expected = [
+            "tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd",
+            "tmVNYZhAQMp61GkauCYV5R4eVKpxzzMZBJF",
+            "tmRSjpqFr1j7P3WBXWFgsRuhQbHaiJBXXRW",
+            "tm9kikz8PQd5MycWhWVoQTUMVZWc3ZSTQhK",
+            "tmLPjXjbVNVmVVRidMqtNMA1qKxECkzRYZb",
+            "tmThgiqZedVKDxxdghpVwx2xLAd7SLPv1Y7",
+            "tmJkkSBtcXubDmGU5B8K6x9KuiQn4mL8HKT",
+            "tmJ4ZQtgpJ5u4kg271iDDBVN5BSBqeMB2vv",
+            "tmNkX2tj76BgjZ8QiiyKY48S8qes2rrAkMt",
+            "tmXwLcGMqtojBmcJQZYYHS48U2c4SWZjqjg",
+            "tmHBscZuX1bqL5F3WbY9D8pRkJFLXQngsTR",
+        ];
+
+        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
+            assert_eq!(
+                actual, expected,
+                "transparent address mismatch at index {}: got {}, expected {}",
+                i, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_transparent_address_known_values_mainnet() {
+        let addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 11)
+                .expect("derivation should succeed");
+
+        let expected = [
+            "t1dUDJ62ANtmebE8drFg7g2MWYwXHQ6Xu3F",
+            "t1QArW6GKrvHngPMPn9cjAk2A9rjKenbhmc",
+            "t1eD9JjoWnwMppWvgVbV2xZ9UhNNScPjh4C",
+            "t1NPeWksS1wHCD49e4RSjteeGx6zCq2hF2n",
+            "t1XN7c6eGuMZTusTWg8tsSm34Lwi3yvhLXv",
+            "t1feU67psWJQCwME9tsWq2yLzg5VoNAUd5N",
+            "t1fh83WS4uQAH9iiPhqSih4cQYJsmb2Ebyb",
+            "t1WdzBz3S7ngE9oqXT6srz1MA19MNgnTUvt",
+            "t1NHbs4gi5zLgHmrTKMN5fcEd7WZsCyfmZG",
+            "t1gWSBjnM4ULq7s4RrJyPVWev8rCRVPSXrk",
+            "t1V8w3SG3f1n8vCCSzCQ7iFsFs1rdfpoTFi",
+        ];
+
+        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
+            assert_eq!(
+                actual, expected,
+                "mainnet transparent address mismatch at index {}: got {}, expected {}",
+                i, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_unified_address_known_values_testnet() {
+        // Regression test with hardcoded expected values
+        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
+            .expect("derivation should succeed");
+
+        let expected = [
+            "utest1cqt4k0wjtvhalr5xfsd2s8enlgn72a3zcvecg7zg55qyyku4z4u5t9yansklfuznvuz077hfu2s8gq50nlln5x4wdxhjleapsn9fs6aua6z9kgpypckjxeku3xzh9qaqj96lk9lpq958ruem58kdfstaec8s725rz4l52xtrlj4cudds53aflght5c433gqkk6h8qvn6vq3u2wxuea5",
+            "utest18v9xkrysfmwf9hr4q05p6x4j8p8e0kfrjvd4q5c4run3e8rp5lyp0uxw328kneznz8ee4v67dcwxnu9x2r3pe6ew6ke0g5sfw6uxy96zlw3zyqls0gxnryw0uh2h3cga9vf5ndes0gc39n8tlgx2r82ummv4cwp2xwan7fwmsxaaqcvx4w63f37nl93pvrwemht3yss0kvgqsn2h3yj",
+            "utest1te4fv8ttz4wn9324g4z6zwmfqc0df56qdzck5f5f69hshxg9xwdsgkzvkrxa50997sqhjj3v3ct5t0ynea5rc42cw44zv6fleryv6rev9rds54lzhne86x3lrzdftgkl5gp0wnzfqeg7enpmwvyfwtee46clfv7fz7v7dkmx6lf4mqfewm838etkfmhfdqktgcvsxp70ewncw3r3h3g",
+            "utest1584ks4v9pwmea9c8gzrmyywtakh4hyzf5ws5tjrlerqq6sfnmajtkgc4kjv5ld2tkn2wwm6gwucwu82y4dz77kg3v0fm099hyh49p00mc9pl4kqdldc3n4yjy4me4p6mggf8l50frtscp6xvzvhqhg4djm3sugvte5shzdx0vwanvpmzqntkr9c2y25qw9dqt6gt8cep3ffwzh76dx0",
+            "utest10pdde234pem6htca00fw68mn5xkv4pjtz766vmkpm0fszqsgjgyqxv2yzp0xs0fvp8zu5y695jjzvges0fqhsxhtfmynx5qamy8pnkxkc6vnztkyvjndzhx2wgh8vv6s32kunqkutf6qkut8j6u83xffwfqlvgvffa6pq6ylxkpgsht0cs4wg2g8agsv9xargrq623ll0edj64rpv3t",
+        ];
+
+        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
+            assert_eq!(
+                actual, expected,
+                "testnet unified address mismatch at index {}: got {}, expected {}",
+                i, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_unified_address_known_values_mainnet() {
+        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
+            .expect("derivation should succeed");
+
+        let expected = [
+            "u16sw4v6wy7f4jzdny55yzl020tp3yqg3c85dc6n7mmq0urfm6adqg79hxmyk85ufn4lun4pfh5q48cc3kvxhxm3w978eqqecdd260gkzjrkun6z7m9mcrt2zszaj0mvk6ufux2zteqwh57cq906hz3rkg63duaeqsvjelv9h5srct0zq8rvlv23wz5hed7zuatqd7p6p4ztugc4t4w2g",
+            "u1wzfync9q7mh8d7r9n2xxdqsyfx62su8em5653t9p5n2yaw6j2km730pu7phcs0j9vk7u8egjhm47k4d7r2uvzns3d7pdvv444km7ngzd3rcy3j6q5lsl563ej5hhtkuaf8flpjl76lmawna8thq0jcj57ncc2knrwgralx60v3jv35s9jsx8fkg76qp6p84d7xah7qrmrmrvgf9rhrq",
+            "u1l47x3uqwucgkqkw3er2m7ljnnqjzaw33xgz57cslyx50tlnp7ctz7vvkwhp900zjry88dl88wecxt2pp27u49yg8l5r7yxj3595sghs4h6jrf29tc220247x55uttf8g3qutczax32jwydj72ze2a22xw06fcx2anqsume3t4gyuwp230lqntzxrz7qen9ylnmhlqr5ssxpu7wcenfn",
+            "u1l47x3uqwucgkqkw3er2m7ljnnqjzaw33xgz57cslyx50tlnp7ctz7vvkwhp900zjry88dl88wecxt2pp27u49yg8l5r7yxj3595sghs4h6jrf29tc220247x55uttf8g3qutczax32jwydj72ze2a22xw06fcx2anqsume3t4gyuwp230lqntzxrz7qen9ylnmhlqr5ssxpu7wcenfn",
+            "u1ntpny3ye6h2cjz4ddpse9xhum3kj2dg320a8392usqf6wzl49et0a5r4c2nan9wwcf7yqrz863skgm9tcfqx0m6wxx5zayc9sskazlvppyvxmrkzxcq57yw36xqea7undvgv8q46cecky0csavswpl3e6e0d03t3cu3h6eh2ntgcwm8r07sxsfa20699cdc4v6z3lf0n278cxf624xn",
+        ];
+
+        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
+            assert_eq!(
+                actual, expected,
+                "mainnet unified address mismatch at index {}: got {}, expected {}",
+                i, actual, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_viewing_key_known_values() {
+        // Known viewing keys for testnet accounts 0, 1, 2
+        let testnet_expected = [
+            "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd",
+            "uviewtest1dhvev7n65razxvmdfxy95j787rpu74fe3yyl4j70mt0mw6z3u2kyujax2vxhfazagvsdhsxyqjyjxau4uyklzktxgjrqnm2xk9rcmucm7hq6gv49pq3p3wrn0dn8z07e37p277rsk8rn0ffgqqnl49927vc053k2fs3mkvhwfyhasjze62waaguynmh3fws5gz5klmxl803dshsekra7cwwhde7h0kvwmdkfdwewcdqpjz79088gplj5uptw7zlsg0hlfjtx3sah0mn2en3rqk42gpwsr4wr8qrh36kt5g82vk8wdv5jr0vrcxdv6wppqv6vsmymzel0j6lj94zaq76t43hqdush5d2rp88d2rqlangsqm62z5tf75fjz8sktehj6r9r0fm93qs8lm5vga4avhtpw6de9qfjpd5rxze6hfq9y2gvwskchj9nxche6qnvr5v6sszgx27zrxst5q4dxtg0w9r0rrw7uyxqnk2as443as4xy4l4",
+            "uviewtest14dgt80533jn30s3jzv0h8nnzc4dt8zvtyqeqtrlcmvhv20ed4z39r79un6su307u8nhguk9elzdweau4zped4s0aqht2ke30zjkr8wafuah38hn0mww439r5an9mjtek8c5k7add7ed0yd45k6eyhrg5gcl7adpman4l8yl36k9gzk74mqyfxlcqwj054nceqwfuzlwe5xuf7sfzx4yuw43q24y9w2juc5an0engy33xl2mwv50z6crz2yvugwskjzk62473kv3s32h89dk88sqqgp6jz95l42q478gz33cl7z3yah2q6krawr97tk5cxn5glw29hz82jpfmhtwkn923ej9l8ws7sj3fvsachtfeq7g44ker72d6edypllewgesnhr923fxp0ct06pvtvjksqhnhpwmrt3cgnu2u58jjnuted6apycs5h9e8xmrm273ff0d4fqfy8uz0v0ckx4cxfjfnaa9z5vqq9tc76qht6nqusysxfzh4",
+        ];
+
+        for (account, expected) in testnet_expected.iter().enumerate() {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
+                .expect("wallet derivation should succeed");
+            assert_eq!(
+                &wallet.unified_full_viewing_key, expected,
+                "testnet UFVK mismatch for account {}: got {}, expected {}",
+                account, wallet.unified_full_viewing_key, expected
+            );
+        }
+
+        // Known viewing keys for mainnet accounts 0, 1, 2
+        let mainnet_expected = [
+            "uview1wj07tp4y3rwzjplg68c3lum2avq4v3j0w0mf0urdlxzthnfr26q8ssz9hvylspj638tuh2r233gaxm2qh27gj6m9q25prk7gt8xwqzmwxm580tg0f5llvr7d6h4y6jc2t7zl7lz9ge60ta6226jyysgk8xpu2wqxesrw4q2mydrhj5dea5l9scl0p3l4ayqgfej54wex5aa2ylq89nyqg94l4lh6dawuc2e3s8v7737zn7p5fl96hhpjqg4jucnp2r2jjqxev3z7lp3k9ulfpl2gw0lng8vfe8hj8afggqzdwxgfaq6dy82guvh34kv4q5ay7gq6n0ujg7exu0mgznpr4wf0agjdhnd4k6af5md3f3msqedw364vx3lyd3hwekvrulywa4c0ja4ze2fxtcm0vrz0278g9n37y0jg6dx847g3peyq9lwmm04ac3tt4sldnrcfc5ew3k0aqgycnryfvv44zxzng485ks27wky2ulfy9q8hu97l",
+            "uview1fzmv506uqhq5ktmj9ldyf7ww00avknney4zpdh73n9suwjhx9je7tswv93e4uxmeunn23cqc2wg0e3l4eqvq0qhheh0hdzznmp4pwlmp9mfa3klm3a28d436anavapwecctk3ul4qqcaankh0lgk5jz9jl4t82p4j03505h92k92d5t23y6rmktp9z0dc7a0v0dzjgl2qtdcrq4z6kmf6xy78xqwqxq27v39r2d3tpwfpzkdjccenxsee7vlv8t533p4y3pckf6xzcq7e226nev4yuevvtfsrxgj0lxxzt9x8949jwn8kdwyslzyjcugk8f2akux3rjgwprtwxpcux26nk8ujk2llrjpg77l2mj9ac6lmva2zeawzwtyrtsmdgrs75snd2exdge33kll6dj4spp6wvrfczjnkrqugy8qm4y238nt850ezpmsse9z0w4rs2klj0hydnkxssasc3cndrvzr55l8u6mfyhm9peg0v8htg4j39u4",
+            "uview1lwrudrcshvvqd3c0dj7l30wx0zfthzvkdw82q07ylw4pppk7mymjwqc7c3y2fl7spq4ypd64hkz93w99nhu5qcc4ctynf5gz0xuqugqfgg7q6hv5a88kaa3w657z7rm2u2ssnrynz3qnsjsurfagqp8qm2hxw70agztdrd82h4tdaqwhfec2kyl4y8d3ef04pw308mn5xdk57rvt99cfyz95ndc4yarf8q8w20wkreqk95s2gk3kfxn882zc00kml2238y25v820747dyj93j9nylf56z49ddwcpzm9je3yqzdf0fzwgm3d9vl225z6y927pqzcewkztfh2a9w4truyhqxeh4d0nw2ty9p7zvy904wqvwa49udz70pydf6w75zhjrh92cl6qqmcpe7n642007qas5h0wx4d7hvtmtu5hh9c9yktcy7u0hu9p30asa7gx4ac9ur87p9rwvnxxp76n04zpcmst289y8u5s7pcdgen3xcfa2kwm",
+        ];
+
+        for (account, expected) in mainnet_expected.iter().enumerate() {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, account as u32, 0)
+                .expect("wallet derivation should succeed");
+            assert_eq!(
+                &wallet.unified_full_viewing_key, expected,
+                "mainnet UFVK mismatch for account {}: got {}, expected {}",
+                account, wallet.unified_full_viewing_key, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_different_account_addresses_known_values() {
+        // Known transparent addresses for testnet accounts 0, 1, 2 at index 0
+        let testnet_transparent_expected = [
+            "tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd",
+            "tmFHMD21fowpyfY4wv1WcBHkzfhD5rW51Ds",
+            "tmBoHxWs8iDkhpUi7jYVFN6aKieWjHVSQcB",
+        ];
+
+        for (account, expected) in testnet_transparent_expected.iter().enumerate() {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
+                .expect("wallet derivation should succeed");
+            assert_eq!(
+                wallet.transparent_address.as_ref().unwrap(),
+                expected,
+                "testnet transparent address mismatch for account {}: got {}, expected {}",
+                account,
+                wallet.transparent_address.as_ref().unwrap(),
+                expected
+            );
+        }
+
+        // Known unified addresses for testnet accounts 0, 1, 2 at index 0
+        let testnet_unified_expected = [
+            "utest1cqt4k0wjtvhalr5xfsd2s8enlgn72a3zcvecg7zg55qyyku4z4u5t9yansklfuznvuz077hfu2s8gq50nlln5x4wdxhjleapsn9fs6aua6z9kgpypckjxeku3xzh9qaqj96lk9lpq958ruem58kdfstaec8s725rz4l52xtrlj4cudds53aflght5c433gqkk6h8qvn6vq3u2wxuea5",
+            "utest134n9sjw63v6n5mfg6wyn3475yw4lp9ejmjeftrdmuz7kld0kgcpw7ql8we7kcurclnznlx9veet8kk44y5u00vtzf4x37502p2jk30hefla25ag3ehx7dkzztngycusx5kcfwrz5rhd838krt4sfzqx28haj6h234m4rvj85dvy7y7df4rlkln8r0kavn4r268seyy0t93q86wyaaz0",
+            "utest1f5x7a4p6e4svnsyhwesms4jheplek0hjrtlq9pckjyft5gvet7pf4c3zq53qascteg2rl7vkadamhjy9wukcwwrr0td2m2t92m4y6ywyypr36hvzq2z5ghhwzdn9625x7cezxaa67g0hq4hrucaj30y0k59cssdy3k9f66kjpmm65ntjmludkdf5mdtuj0qpyaxkfj92ysua5pjv855",
+        ];
+
+        for (account, expected) in testnet_unified_expected.iter().enumerate() {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
+                .expect("wallet derivation should succeed");
+            assert_eq!(
+                &wallet.unified_address, expected,
+                "testnet unified address mismatch for account {}: got {}, expected {}",
+                account, wallet.unified_address, expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_unified_address_derivation_regression() {
+        // Test derivation at specific indices
+        let addresses = derive_unified_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,   // account index
+            0,   // start index
+            100, // count
+        )
+        .expect("derivation should succeed");
+
+        // Verify addresses start with expected prefix
+        for (i, addr) in addresses.iter().enumerate() {
+            assert!(
+                addr.starts_with("utest"),
+                "testnet unified address at index {} should start with 'utest'",
+                i
+            );
+        }
+
+        // Note: Unified addresses may have duplicates because find_address()
+        // returns the next valid diversifier. Multiple input indices may
+        // resolve to the same valid diversifier, producing the same address.
+        // This is expected behavior for Sapling diversifiers.
+        let unique_count = addresses
+            .iter()
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        assert!(
+            unique_count > 0,
+            "should have at least some unique addresses"
+        );
+
+        // Verify first address matches wallet derivation at index 0
+        let wallet_0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        assert_eq!(
+            addresses[0], wallet_0.unified_address,
+            "unified address at index 0 should match wallet derivation"
+        );
+    }
+
+    #[test]
+    fn test_transparent_derivation_deterministic() {
+        // Verify that calling derive_transparent_addresses multiple times
+        // produces the same results
+        let addresses1 =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+                .expect("derivation should succeed");
+
+        let addresses2 =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+                .expect("derivation should succeed");
+
+        assert_eq!(addresses1, addresses2, "derivation should be deterministic");
+    }
+
+    #[test]
+    fn test_unified_derivation_deterministic() {
+        let addresses1 = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+            .expect("derivation should succeed");
+
+        let addresses2 = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+            .expect("derivation should succeed");
+
+        assert_eq!(addresses1, addresses2, "derivation should be deterministic");
+    }
+
+    #[test]
+    fn test_cross_verification_transparent() {
+        // Verify that derive_transparent_addresses produces the same addresses
+        // as individual wallet derivations
+        let batch_addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+                .expect("batch derivation should succeed");
+
+        for i in 0..10u32 {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, i)
+                .expect("wallet derivation should succeed");
+
+            assert_eq!(
+                batch_addresses[i as usize],
+                wallet.transparent_address.unwrap(),
+                "batch address at index {} should match wallet derivation",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_cross_verification_unified() {
+        // Verify that derive_unified_addresses produces the same addresses
+        // as individual wallet derivations
+        let batch_addresses =
+            derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
+                .expect("batch derivation should succeed");
+
+        for i in 0..10u32 {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, i)
+                .expect("wallet derivation should succeed");
+
+            assert_eq!(
+                batch_addresses[i as usize], wallet.unified_address,
+                "batch address at index {} should match wallet derivation",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_derivation_with_offset() {
+        // Test that derivation with start_index offset works correctly
+        let all_addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 20)
+                .expect("derivation should succeed");
+
+        let offset_addresses = derive_transparent_addresses(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            10, // start at index 10
+            10,
+        )
+        .expect("derivation should succeed");
+
+        // Addresses starting at index 10 should match
+        assert_eq!(
+            &all_addresses[10..20],
+            &offset_addresses[..],
+            "offset derivation should match"
+        );
+    }
+
+    #[test]
+    fn test_different_accounts_produce_different_addresses() {
+        let account0 =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
+                .expect("derivation should succeed");
+
+        let account1 =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0, 5)
+                .expect("derivation should succeed");
+
+        // No address from account 0 should appear in account 1
+        for addr in &account0 {
+            assert!(
+                !account1.contains(addr),
+                "different accounts should produce completely different addresses"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mainnet_transparent_addresses() {
+        let addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
+                .expect("derivation should succeed");
+
+        for addr in &addresses {
+            assert!(
+                addr.starts_with("t1"),
+                "mainnet transparent address should start with 't1'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mainnet_unified_addresses() {
+        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
+            .expect("derivation should succeed");
+
+        for addr in &addresses {
+            assert!(
+                addr.starts_with("u1"),
+                "mainnet unified address should start with 'u1'"
+            );
+        }
+    }
+
+    // =========================================================================
+    // Property-based tests (PBT) using random seeds
+    // These tests verify address prefix properties hold for any valid seed
+    // =========================================================================
+
+    #[test]
+    fn test_pbt_testnet_transparent_address_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        // Run multiple iterations with random seeds
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet
+                    .transparent_address
+                    .as_ref()
+                    .map(|s| s.starts_with("tm"))
+                    .unwrap_or(false),
+                "testnet transparent address should start with 'tm' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_mainnet_transparent_address_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet
+                    .transparent_address
+                    .as_ref()
+                    .map(|s| s.starts_with("t1"))
+                    .unwrap_or(false),
+                "mainnet transparent address should start with 't1' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_testnet_unified_address_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet.unified_address.starts_with("utest"),
+                "testnet unified address should start with 'utest' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_mainnet_unified_address_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet.unified_address.starts_with("u1"),
+                "mainnet unified address should start with 'u1' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_testnet_ufvk_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet.unified_full_viewing_key.starts_with("uviewtest"),
+                "testnet UFVK should start with 'uviewtest' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_mainnet_ufvk_prefix() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert!(
+                wallet.unified_full_viewing_key.starts_with("uview1"),
+                "mainnet UFVK should start with 'uview1' for any seed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_seed_phrase_is_24_words() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let entropy: [u8; 32] = rng.r#gen();
+            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            let word_count = wallet.seed_phrase.split_whitespace().count();
+            assert_eq!(
+                word_count, 24,
+                "generated seed phrase should have 24 words for any entropy"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_deterministic_derivation() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..5 {
+            let entropy: [u8; 32] = rng.r#gen();
+
+            let wallet1 = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+            let wallet2 = generate_wallet(&entropy, Network::TestNetwork, 0, 0, None)
+                .expect("wallet generation should succeed");
+
+            assert_eq!(
+                wallet1.unified_address, wallet2.unified_address,
+                "same entropy should produce same addresses"
+            );
+            assert_eq!(
+                wallet1.transparent_address, wallet2.transparent_address,
+                "same entropy should produce same transparent addresses"
+            );
+            assert_eq!(
+                wallet1.unified_full_viewing_key, wallet2.unified_full_viewing_key,
+                "same entropy should produce same UFVK"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbt_different_entropy_different_addresses() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let entropy1: [u8; 32] = rng.r#gen();
+        let entropy2: [u8; 32] = rng.r#gen();
+
+        // Ensure they're different (astronomically unlikely to be same)
+        assert_ne!(entropy1, entropy2, "random entropy should be different");
 
-    #[test]
-    fn test_derive_wallet_is_deterministic_testnet() {
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet2 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+        let wallet1 = generate_wallet(&entropy1, Network::TestNetwork, 0, 0, None)
+            .expect("wallet generation should succeed");
+        let wallet2 = generate_wallet(&entropy2, Network::TestNetwork, 0, 0, None)
+            .expect("wallet generation should succeed");
 
-        assert_eq!(wallet1.unified_address, wallet2.unified_address);
-        assert_eq!(wallet1.transparent_address, wallet2.transparent_address);
-        assert_eq!(
-            wallet1.unified_full_viewing_key,
-            wallet2.unified_full_viewing_key
+        assert_ne!(
+            wallet1.unified_address, wallet2.unified_address,
+            "different entropy should produce different addresses"
+        );
+        assert_ne!(
+            wallet1.transparent_address, wallet2.transparent_address,
+            "different entropy should produce different transparent addresses"
         );
     }
 
+    /// Test that verifies unified address uniqueness behavior at different indices.
+    ///
+    /// IMPORTANT: Due to Sapling diversifier behavior, not all diversifier indices
+    /// produce valid diversifiers. The `find_address` function skips invalid
+    /// diversifiers and returns the next valid one. This means consecutive indices
+    /// may produce the SAME unified address.
+    ///
+    /// For example, if index 2 and 3 both have invalid diversifiers, both will
+    /// skip to index 4's diversifier, producing identical addresses.
     #[test]
-    fn test_derive_wallet_testnet_addresses() {
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+    fn test_unified_address_index_uniqueness_behavior() {
+        // Derive addresses at indices 0-9
+        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 10)
+            .expect("derivation should succeed");
 
-        // Verify addresses are non-empty and have expected prefixes for testnet
-        assert_eq!(wallet.network, NetworkKind::Testnet);
-        assert_eq!(wallet.account_index, 0);
-        assert!(
-            wallet.unified_address.starts_with("utest"),
-            "unified address should start with 'utest' for testnet"
-        );
-        assert!(
-            wallet
-                .transparent_address
-                .as_ref()
-                .map(|s| s.starts_with("tm"))
-                .unwrap_or(false),
-            "transparent address should start with 'tm' for testnet"
+        // Count unique addresses
+        let unique: std::collections::HashSet<_> = addresses.iter().collect();
+        let unique_count = unique.len();
+
+        // Print the actual uniqueness for visibility
+        eprintln!(
+            "Unified addresses: {} indices -> {} unique addresses",
+            addresses.len(),
+            unique_count
         );
+
+        // With 10 indices, we expect at least 5 unique addresses (50%)
+        // In practice, about 87.5% of Sapling diversifier indices are valid
         assert!(
-            wallet.unified_full_viewing_key.starts_with("uviewtest"),
-            "UFVK should start with 'uviewtest' for testnet"
+            unique_count >= 5,
+            "expected at least 5 unique addresses from 10 indices, got {}",
+            unique_count
         );
+
+        // Document which indices produce duplicates
+        for i in 1..addresses.len() {
+            if addresses[i] == addresses[i - 1] {
+                eprintln!(
+                    "Note: index {} produces same address as index {} (both found same valid diversifier)",
+                    i,
+                    i - 1
+                );
+            }
+        }
     }
 
+    /// Test that transparent addresses are ALWAYS unique at each index.
+    /// Unlike unified addresses, transparent addresses don't have the diversifier
+    /// validity issue - each index produces a distinct address.
     #[test]
-    fn test_derive_wallet_mainnet_addresses() {
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
+    fn test_transparent_address_always_unique_per_index() {
+        let addresses =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 100)
+                .expect("derivation should succeed");
 
-        // Verify addresses are non-empty and have expected prefixes for mainnet
-        assert_eq!(wallet.network, NetworkKind::Mainnet);
-        assert_eq!(wallet.account_index, 0);
-        assert!(
-            wallet.unified_address.starts_with("u1"),
-            "unified address should start with 'u1' for mainnet"
-        );
-        assert!(
-            wallet
-                .transparent_address
-                .as_ref()
-                .map(|s| s.starts_with("t1"))
-                .unwrap_or(false),
-            "transparent address should start with 't1' for mainnet"
-        );
-        assert!(
-            wallet.unified_full_viewing_key.starts_with("uview1"),
-            "UFVK should start with 'uview1' for mainnet"
+        // Verify ALL transparent addresses are unique
+        let unique: std::collections::HashSet<_> = addresses.iter().collect();
+        assert_eq!(
+            unique.len(),
+            addresses.len(),
+            "every transparent address index MUST produce a unique address"
         );
+
+        // Verify adjacent indices never produce the same address
+        for i in 1..addresses.len() {
+            assert_ne!(
+                addresses[i],
+                addresses[i - 1],
+                "adjacent transparent addresses at indices {} and {} must be different",
+                i,
+                i - 1
+            );
+        }
     }
 
+    // =========================================================================
+    // Wallet export/import tests
+    // =========================================================================
+
     #[test]
-    fn test_derive_wallet_known_vector_testnet() {
-        // This test uses a known seed and verifies exact output
-        // If this test fails after a library update, it indicates a breaking change
-        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+    fn test_export_import_wallet_json_round_trip() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
             .expect("wallet derivation should succeed");
 
-        // These are the expected values for the standard BIP39 test vector
-        // "abandon abandon ... art" on Zcash testnet
+        let json = export_wallet_json(&wallet, Some(2_500_000));
+        let value: serde_json::Value = serde_json::from_str(&json).expect("valid JSON");
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["network"], "testnet");
+        assert_eq!(value["account_index"], 1);
+        assert_eq!(value["birthday_height"], 2_500_000);
         assert_eq!(
-            wallet.transparent_address,
-            Some("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd".to_string()),
-            "transparent address mismatch - library may have changed derivation"
+            value["unified_full_viewing_key"],
+            wallet.unified_full_viewing_key.as_str()
+        );
+        assert_eq!(
+            value["addresses"]["unified"],
+            wallet.unified_address.as_str()
         );
 
+        let imported =
+            import_wallet_json(&json, Network::TestNetwork).expect("import should succeed");
+        assert_eq!(imported.seed_phrase, wallet.seed_phrase);
+        assert_eq!(imported.account_index, wallet.account_index);
+        assert_eq!(imported.unified_address, wallet.unified_address);
+        assert_eq!(imported.transparent_address, wallet.transparent_address);
         assert_eq!(
-            wallet.unified_full_viewing_key,
-            "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd",
-            "UFVK mismatch - library may have changed derivation"
+            imported.unified_full_viewing_key,
+            wallet.unified_full_viewing_key
         );
     }
 
     #[test]
-    fn test_different_seeds_produce_different_wallets() {
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-
-        // Different seed phrase
-        let different_seed = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote";
-        let wallet2 = restore_wallet(different_seed, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-
-        assert_ne!(
-            wallet1.unified_address, wallet2.unified_address,
-            "different seeds should produce different unified addresses"
-        );
-        assert_ne!(
-            wallet1.transparent_address, wallet2.transparent_address,
-            "different seeds should produce different transparent addresses"
+    fn test_import_wallet_json_from_other_wallet() {
+        // Export in the style of other wallets: "mnemonic", "account" and
+        // "birthday" keys, no viewing key or addresses
+        let json = format!(
+            r#"{{"network": "testnet", "mnemonic": "{}", "account": 0, "birthday": 419200}}"#,
+            TEST_SEED_PHRASE
         );
-        assert_ne!(
-            wallet1.unified_full_viewing_key, wallet2.unified_full_viewing_key,
-            "different seeds should produce different UFVKs"
+
+        let imported =
+            import_wallet_json(&json, Network::TestNetwork).expect("import should succeed");
+        assert_eq!(
+            imported.transparent_address,
+            Some("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd".to_string())
         );
+        assert_eq!(imported.account_index, 0);
     }
 
     #[test]
-    fn test_same_seed_different_networks() {
-        let testnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let mainnet_wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+    fn test_import_wallet_json_rejects_wrong_network() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
             .expect("wallet derivation should succeed");
+        let json = export_wallet_json(&wallet, None);
 
-        // Same seed should produce different addresses on different networks
-        assert_ne!(
-            testnet_wallet.unified_address, mainnet_wallet.unified_address,
-            "same seed should produce different addresses on different networks"
-        );
-        assert_ne!(
-            testnet_wallet.transparent_address, mainnet_wallet.transparent_address,
-            "same seed should produce different transparent addresses on different networks"
-        );
+        let result = import_wallet_json(&json, Network::MainNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidExport(_))));
     }
 
     #[test]
-    fn test_restore_invalid_seed_fails() {
-        let result = restore_wallet("invalid seed phrase", Network::TestNetwork, 0, 0);
-        assert!(result.is_err(), "should fail with invalid seed phrase");
+    fn test_import_wallet_json_rejects_mismatched_ufvk() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let other = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
+        let json = export_wallet_json(&wallet, None).replace(
+            &wallet.unified_full_viewing_key,
+            &other.unified_full_viewing_key,
+        );
+
+        let result = import_wallet_json(&json, Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidExport(_))));
     }
 
     #[test]
-    fn test_generate_wallet_testnet() {
-        let entropy = [0u8; 32]; // Deterministic for testing
-        let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-            .expect("wallet generation should succeed");
-
-        assert!(!wallet.seed_phrase.is_empty());
-        assert!(!wallet.unified_address.is_empty());
-        assert!(wallet.transparent_address.is_some());
-        assert!(!wallet.unified_full_viewing_key.is_empty());
-        assert_eq!(wallet.network, NetworkKind::Testnet);
-        assert_eq!(wallet.account_index, 0);
-        assert_eq!(wallet.address_index, 0);
+    fn test_import_wallet_json_rejects_garbage() {
+        let result = import_wallet_json("not json", Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidExport(_))));
     }
 
+    // =========================================================================
+    // Birthday height tests
+    // =========================================================================
+
     #[test]
-    fn test_generate_wallet_mainnet() {
-        let entropy = [0u8; 32]; // Deterministic for testing
-        let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0)
+    fn test_generate_wallet_records_birthday() {
+        let entropy = [7u8; 32];
+        let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0, Some(3_000_000))
             .expect("wallet generation should succeed");
-
-        assert!(!wallet.seed_phrase.is_empty());
-        assert!(wallet.unified_address.starts_with("u1"));
-        assert!(
-            wallet
-                .transparent_address
-                .as_ref()
-                .map(|s| s.starts_with("t1"))
-                .unwrap_or(false)
-        );
-        assert!(wallet.unified_full_viewing_key.starts_with("uview1"));
-        assert_eq!(wallet.network, NetworkKind::Mainnet);
-        assert_eq!(wallet.account_index, 0);
-        assert_eq!(wallet.address_index, 0);
+        assert_eq!(wallet.birthday_height, Some(3_000_000));
+
+        // Serializing and deserializing keeps the birthday
+        let json = serde_json::to_string(&wallet).expect("should serialize");
+        let restored: WalletInfo = serde_json::from_str(&json).expect("should deserialize");
+        assert_eq!(restored.birthday_height, Some(3_000_000));
+
+        // The birthday survives an export/import round trip
+        let export = export_wallet_json(&wallet, None);
+        let imported =
+            import_wallet_json(&export, Network::TestNetwork).expect("import should succeed");
+        assert_eq!(imported.birthday_height, Some(3_000_000));
+        assert_eq!(imported.unified_address, wallet.unified_address);
     }
 
     #[test]
-    fn test_different_account_indices() {
-        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+    fn test_restored_wallet_has_no_birthday() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
             .expect("wallet derivation should succeed");
-
-        assert_ne!(
-            wallet0.unified_address, wallet1.unified_address,
-            "different accounts should produce different unified addresses"
-        );
-        assert_ne!(
-            wallet0.transparent_address, wallet1.transparent_address,
-            "different accounts should produce different transparent addresses"
-        );
-        assert_ne!(
-            wallet0.unified_full_viewing_key, wallet1.unified_full_viewing_key,
-            "different accounts should produce different UFVKs"
-        );
-        assert_eq!(wallet0.account_index, 0);
-        assert_eq!(wallet1.account_index, 1);
+        assert_eq!(wallet.birthday_height, None);
     }
 
     #[test]
-    fn test_different_address_indices() {
-        let wallet0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
-            .expect("wallet derivation should succeed");
-        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 1)
-            .expect("wallet derivation should succeed");
-
-        assert_ne!(
-            wallet0.unified_address, wallet1.unified_address,
-            "different address indices should produce different unified addresses"
-        );
-        assert_ne!(
-            wallet0.transparent_address, wallet1.transparent_address,
-            "different address indices should produce different transparent addresses"
-        );
-        // Same account should have same UFVK
-        assert_eq!(
-            wallet0.unified_full_viewing_key, wallet1.unified_full_viewing_key,
-            "same account should have same UFVK regardless of address index"
-        );
-        assert_eq!(wallet0.address_index, 0);
-        assert_eq!(wallet1.address_index, 1);
+    fn test_wallet_info_without_birthday_deserializes() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let mut value = serde_json::to_value(&wallet).expect("should serialize");
+        value
+            .as_object_mut()
+            .expect("should be an object")
+            .remove("birthday_height");
+
+        let restored: WalletInfo = serde_json::from_value(value).expect("should deserialize");
+        assert_eq!(restored.birthday_height, None);
     }
 
     // =========================================================================
-    // Regression tests for address derivation at different indices (Issue #51)
+    // Sapling extended full viewing key tests
     // =========================================================================
 
+    fn test_seed() -> [u8; 64] {
+        Mnemonic::parse_in_normalized(Language::English, TEST_SEED_PHRASE)
+            .expect("valid mnemonic")
+            .to_seed("")
+    }
+
     #[test]
-    fn test_transparent_address_derivation_regression() {
-        // Test derivation at specific indices
-        // These values were captured from the current implementation and serve
-        // as regression tests to detect any changes in derivation logic.
-        let addresses = derive_transparent_addresses(
-            TEST_SEED_PHRASE,
-            Network::TestNetwork,
-            0,   // account index
-            0,   // start index
-            100, // count
+    fn test_derive_sapling_extfvk_testnet() {
+        let extfvk = derive_sapling_extfvk(&test_seed(), 0, Network::TestNetwork)
+            .expect("derivation should succeed");
+        assert!(
+            extfvk.starts_with("zxviewtestsapling1"),
+            "testnet Sapling extended FVK should start with 'zxviewtestsapling1'"
+        );
+    }
+
+    #[test]
+    fn test_derive_sapling_extfvk_mainnet() {
+        let extfvk = derive_sapling_extfvk(&test_seed(), 0, Network::MainNetwork)
+            .expect("derivation should succeed");
+        assert!(
+            extfvk.starts_with("zxviews1"),
+            "mainnet Sapling extended FVK should start with 'zxviews1'"
+        );
+    }
+
+    #[test]
+    fn test_derive_sapling_extfvk_matches_ufvk() {
+        let extfvk = derive_sapling_extfvk(&test_seed(), 0, Network::TestNetwork)
+            .expect("derivation should succeed");
+        let decoded = zcash_keys::encoding::decode_extended_full_viewing_key(
+            Network::TestNetwork.hrp_sapling_extended_full_viewing_key(),
+            &extfvk,
         )
-        .expect("derivation should succeed");
+        .expect("extended FVK should decode");
 
-        // Verify address at index 0 matches the known wallet derivation
         let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
             .expect("wallet derivation should succeed");
-        assert_eq!(
-            addresses[0],
-            wallet.transparent_address.unwrap(),
-            "transparent address at index 0 should match wallet derivation"
-        );
+        let ufvk = zcash_keys::keys::UnifiedFullViewingKey::decode(
+            &Network::TestNetwork,
+            &wallet.unified_full_viewing_key,
+        )
+        .expect("UFVK should decode");
 
-        // Verify all addresses are unique (transparent addresses should be unique)
-        let unique_count = addresses
-            .iter()
-            .collect::<std::collections::HashSet<_>>()
-            .len();
         assert_eq!(
-            unique_count,
-            addresses.len(),
-            "all derived transparent addresses should be unique"
+            decoded.to_diversifiable_full_viewing_key().to_bytes(),
+            ufvk.sapling().expect("UFVK has Sapling").to_bytes()
         );
+    }
 
-        // Verify all addresses have correct prefix
-        for addr in &addresses {
-            assert!(
-                addr.starts_with("tm"),
-                "testnet transparent address should start with 'tm'"
-            );
-        }
+    #[test]
+    fn test_derive_sapling_extfvk_differs_per_account() {
+        let account0 = derive_sapling_extfvk(&test_seed(), 0, Network::TestNetwork)
+            .expect("derivation should succeed");
+        let account1 = derive_sapling_extfvk(&test_seed(), 1, Network::TestNetwork)
+            .expect("derivation should succeed");
+        assert_ne!(account0, account1);
     }
 
+    // =========================================================================
+    // Internal (change) transparent address tests
+    // =========================================================================
+
     #[test]
-    fn test_transparent_address_known_values_testnet() {
-        // Regression test with hardcoded expected values for the standard BIP39 test vector
-        // If these values change, it indicates a breaking change in derivation logic.
-        let addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 11)
+    fn test_internal_transparent_address_differs_from_external() {
+        let external =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
+                .expect("derivation should succeed");
+        let internal =
+            derive_internal_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
                 .expect("derivation should succeed");
 
-        // Known values for testnet transparent addresses at indices 0-10
-        let expected = [
-            "tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd",
-            "tmVNYZhAQMp61GkauCYV5R4eVKpxzzMZBJF",
-            "tmRSjpqFr1j7P3WBXWFgsRuhQbHaiJBXXRW",
-            "tm9kikz8PQd5MycWhWVoQTUMVZWc3ZSTQhK",
-            "tmLPjXjbVNVmVVRidMqtNMA1qKxECkzRYZb",
-            "tmThgiqZedVKDxxdghpVwx2xLAd7SLPv1Y7",
-            "tmJkkSBtcXubDmGU5B8K6x9KuiQn4mL8HKT",
-            "tmJ4ZQtgpJ5u4kg271iDDBVN5BSBqeMB2vv",
-            "tmNkX2tj76BgjZ8QiiyKY48S8qes2rrAkMt",
-            "tmXwLcGMqtojBmcJQZYYHS48U2c4SWZjqjg",
-            "tmHBscZuX1bqL5F3WbY9D8pRkJFLXQngsTR",
-        ];
-
-        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
-            assert_eq!(
-                actual, expected,
-                "transparent address mismatch at index {}: got {}, expected {}",
-                i, actual, expected
+        assert_eq!(internal.len(), 5);
+        for addr in &internal {
+            assert!(addr.starts_with("tm"));
+            assert!(
+                !external.contains(addr),
+                "internal address {} must not be an external address",
+                addr
             );
         }
     }
 
     #[test]
-    fn test_transparent_address_known_values_mainnet() {
-        let addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 11)
-                .expect("derivation should succeed");
-
-        let expected = [
-            "t1dUDJ62ANtmebE8drFg7g2MWYwXHQ6Xu3F",
-            "t1QArW6GKrvHngPMPn9cjAk2A9rjKenbhmc",
-            "t1eD9JjoWnwMppWvgVbV2xZ9UhNNScPjh4C",
-            "t1NPeWksS1wHCD49e4RSjteeGx6zCq2hF2n",
-            "t1XN7c6eGuMZTusTWg8tsSm34Lwi3yvhLXv",
-            "t1feU67psWJQCwME9tsWq2yLzg5VoNAUd5N",
-            "t1fh83WS4uQAH9iiPhqSih4cQYJsmb2Ebyb",
-            "t1WdzBz3S7ngE9oqXT6srz1MA19MNgnTUvt",
-            "t1NHbs4gi5zLgHmrTKMN5fcEd7WZsCyfmZG",
-            "t1gWSBjnM4ULq7s4RrJyPVWev8rCRVPSXrk",
-            "t1V8w3SG3f1n8vCCSzCQ7iFsFs1rdfpoTFi",
-        ];
+    fn test_internal_and_external_share_transparent_fvk() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = zcash_keys::keys::UnifiedFullViewingKey::decode(
+            &Network::TestNetwork,
+            &wallet.unified_full_viewing_key,
+        )
+        .expect("UFVK should decode");
+        let tfvk = ufvk.transparent().expect("UFVK has a transparent key");
+        let child = NonHardenedChildIndex::from_index(0).unwrap();
+
+        let external = tfvk
+            .derive_external_ivk()
+            .unwrap()
+            .derive_address(child)
+            .unwrap()
+            .encode(&Network::TestNetwork);
+        let internal = tfvk
+            .derive_internal_ivk()
+            .unwrap()
+            .derive_address(child)
+            .unwrap()
+            .encode(&Network::TestNetwork);
+
+        assert_eq!(Some(external.clone()), wallet.transparent_address);
+        assert_eq!(
+            vec![internal.clone()],
+            derive_internal_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap()
+        );
+        assert_ne!(external, internal);
+    }
 
-        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
-            assert_eq!(
-                actual, expected,
-                "mainnet transparent address mismatch at index {}: got {}, expected {}",
-                i, actual, expected
-            );
-        }
+    #[test]
+    fn test_transparent_watch_set_contains_both_chains() {
+        let watch_set = derive_transparent_watch_set(TEST_SEED_PHRASE, Network::TestNetwork, 0, 3)
+            .expect("derivation should succeed");
+        let external =
+            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 3).unwrap();
+        let internal =
+            derive_internal_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 3)
+                .unwrap();
+
+        assert_eq!(watch_set.len(), 6);
+        assert_eq!(&watch_set[..3], &external[..]);
+        assert_eq!(&watch_set[3..], &internal[..]);
     }
 
+    // =========================================================================
+    // Account discovery tests
+    // =========================================================================
+
     #[test]
-    fn test_unified_address_known_values_testnet() {
-        // Regression test with hardcoded expected values
-        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
+    fn test_derive_accounts_produces_distinct_ufvks() {
+        let accounts = derive_accounts(&test_seed(), Network::TestNetwork, 3)
             .expect("derivation should succeed");
+        assert_eq!(accounts.len(), 3);
 
-        let expected = [
-            "utest1cqt4k0wjtvhalr5xfsd2s8enlgn72a3zcvecg7zg55qyyku4z4u5t9yansklfuznvuz077hfu2s8gq50nlln5x4wdxhjleapsn9fs6aua6z9kgpypckjxeku3xzh9qaqj96lk9lpq958ruem58kdfstaec8s725rz4l52xtrlj4cudds53aflght5c433gqkk6h8qvn6vq3u2wxuea5",
-            "utest18v9xkrysfmwf9hr4q05p6x4j8p8e0kfrjvd4q5c4run3e8rp5lyp0uxw328kneznz8ee4v67dcwxnu9x2r3pe6ew6ke0g5sfw6uxy96zlw3zyqls0gxnryw0uh2h3cga9vf5ndes0gc39n8tlgx2r82ummv4cwp2xwan7fwmsxaaqcvx4w63f37nl93pvrwemht3yss0kvgqsn2h3yj",
-            "utest1te4fv8ttz4wn9324g4z6zwmfqc0df56qdzck5f5f69hshxg9xwdsgkzvkrxa50997sqhjj3v3ct5t0ynea5rc42cw44zv6fleryv6rev9rds54lzhne86x3lrzdftgkl5gp0wnzfqeg7enpmwvyfwtee46clfv7fz7v7dkmx6lf4mqfewm838etkfmhfdqktgcvsxp70ewncw3r3h3g",
-            "utest1584ks4v9pwmea9c8gzrmyywtakh4hyzf5ws5tjrlerqq6sfnmajtkgc4kjv5ld2tkn2wwm6gwucwu82y4dz77kg3v0fm099hyh49p00mc9pl4kqdldc3n4yjy4me4p6mggf8l50frtscp6xvzvhqhg4djm3sugvte5shzdx0vwanvpmzqntkr9c2y25qw9dqt6gt8cep3ffwzh76dx0",
-            "utest10pdde234pem6htca00fw68mn5xkv4pjtz766vmkpm0fszqsgjgyqxv2yzp0xs0fvp8zu5y695jjzvges0fqhsxhtfmynx5qamy8pnkxkc6vnztkyvjndzhx2wgh8vv6s32kunqkutf6qkut8j6u83xffwfqlvgvffa6pq6ylxkpgsht0cs4wg2g8agsv9xargrq623ll0edj64rpv3t",
-        ];
+        let ufvks: std::collections::HashSet<_> = accounts
+            .iter()
+            .map(|a| a.unified_full_viewing_key.clone())
+            .collect();
+        assert_eq!(ufvks.len(), 3, "each account must have its own UFVK");
 
-        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
-            assert_eq!(
-                actual, expected,
-                "testnet unified address mismatch at index {}: got {}, expected {}",
-                i, actual, expected
-            );
+        for (i, account) in accounts.iter().enumerate() {
+            assert_eq!(account.account_index, i as u32);
         }
     }
 
     #[test]
-    fn test_unified_address_known_values_mainnet() {
-        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
+    fn test_derive_accounts_matches_restore() {
+        let accounts = derive_accounts(&test_seed(), Network::TestNetwork, 2)
             .expect("derivation should succeed");
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
 
-        let expected = [
-            "u16sw4v6wy7f4jzdny55yzl020tp3yqg3c85dc6n7mmq0urfm6adqg79hxmyk85ufn4lun4pfh5q48cc3kvxhxm3w978eqqecdd260gkzjrkun6z7m9mcrt2zszaj0mvk6ufux2zteqwh57cq906hz3rkg63duaeqsvjelv9h5srct0zq8rvlv23wz5hed7zuatqd7p6p4ztugc4t4w2g",
-            "u1wzfync9q7mh8d7r9n2xxdqsyfx62su8em5653t9p5n2yaw6j2km730pu7phcs0j9vk7u8egjhm47k4d7r2uvzns3d7pdvv444km7ngzd3rcy3j6q5lsl563ej5hhtkuaf8flpjl76lmawna8thq0jcj57ncc2knrwgralx60v3jv35s9jsx8fkg76qp6p84d7xah7qrmrmrvgf9rhrq",
-            "u1l47x3uqwucgkqkw3er2m7ljnnqjzaw33xgz57cslyx50tlnp7ctz7vvkwhp900zjry88dl88wecxt2pp27u49yg8l5r7yxj3595sghs4h6jrf29tc220247x55uttf8g3qutczax32jwydj72ze2a22xw06fcx2anqsume3t4gyuwp230lqntzxrz7qen9ylnmhlqr5ssxpu7wcenfn",
-            "u1l47x3uqwucgkqkw3er2m7ljnnqjzaw33xgz57cslyx50tlnp7ctz7vvkwhp900zjry88dl88wecxt2pp27u49yg8l5r7yxj3595sghs4h6jrf29tc220247x55uttf8g3qutczax32jwydj72ze2a22xw06fcx2anqsume3t4gyuwp230lqntzxrz7qen9ylnmhlqr5ssxpu7wcenfn",
-            "u1ntpny3ye6h2cjz4ddpse9xhum3kj2dg320a8392usqf6wzl49et0a5r4c2nan9wwcf7yqrz863skgm9tcfqx0m6wxx5zayc9sskazlvppyvxmrkzxcq57yw36xqea7undvgv8q46cecky0csavswpl3e6e0d03t3cu3h6eh2ntgcwm8r07sxsfa20699cdc4v6z3lf0n278cxf624xn",
-        ];
+        assert_eq!(
+            accounts[1].unified_full_viewing_key,
+            wallet.unified_full_viewing_key
+        );
+        assert_eq!(accounts[1].unified_address, wallet.unified_address);
+    }
 
-        for (i, (actual, expected)) in addresses.iter().zip(expected.iter()).enumerate() {
-            assert_eq!(
-                actual, expected,
-                "mainnet unified address mismatch at index {}: got {}, expected {}",
-                i, actual, expected
-            );
-        }
+    #[test]
+    fn test_derive_accounts_zero_count() {
+        let accounts = derive_accounts(&test_seed(), Network::TestNetwork, 0)
+            .expect("derivation should succeed");
+        assert!(accounts.is_empty());
     }
 
     #[test]
-    fn test_viewing_key_known_values() {
-        // Known viewing keys for testnet accounts 0, 1, 2
-        let testnet_expected = [
-            "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd",
-            "uviewtest1dhvev7n65razxvmdfxy95j787rpu74fe3yyl4j70mt0mw6z3u2kyujax2vxhfazagvsdhsxyqjyjxau4uyklzktxgjrqnm2xk9rcmucm7hq6gv49pq3p3wrn0dn8z07e37p277rsk8rn0ffgqqnl49927vc053k2fs3mkvhwfyhasjze62waaguynmh3fws5gz5klmxl803dshsekra7cwwhde7h0kvwmdkfdwewcdqpjz79088gplj5uptw7zlsg0hlfjtx3sah0mn2en3rqk42gpwsr4wr8qrh36kt5g82vk8wdv5jr0vrcxdv6wppqv6vsmymzel0j6lj94zaq76t43hqdush5d2rp88d2rqlangsqm62z5tf75fjz8sktehj6r9r0fm93qs8lm5vga4avhtpw6de9qfjpd5rxze6hfq9y2gvwskchj9nxche6qnvr5v6sszgx27zrxst5q4dxtg0w9r0rrw7uyxqnk2as443as4xy4l4",
-            "uviewtest14dgt80533jn30s3jzv0h8nnzc4dt8zvtyqeqtrlcmvhv20ed4z39r79un6su307u8nhguk9elzdweau4zped4s0aqht2ke30zjkr8wafuah38hn0mww439r5an9mjtek8c5k7add7ed0yd45k6eyhrg5gcl7adpman4l8yl36k9gzk74mqyfxlcqwj054nceqwfuzlwe5xuf7sfzx4yuw43q24y9w2juc5an0engy33xl2mwv50z6crz2yvugwskjzk62473kv3s32h89dk88sqqgp6jz95l42q478gz33cl7z3yah2q6krawr97tk5cxn5glw29hz82jpfmhtwkn923ej9l8ws7sj3fvsachtfeq7g44ker72d6edypllewgesnhr923fxp0ct06pvtvjksqhnhpwmrt3cgnu2u58jjnuted6apycs5h9e8xmrm273ff0d4fqfy8uz0v0ckx4cxfjfnaa9z5vqq9tc76qht6nqusysxfzh4",
-        ];
+    fn test_derive_accounts_rejects_excessive_count() {
+        assert!(matches!(
+            derive_accounts(&test_seed(), Network::TestNetwork, MAX_DERIVED_ACCOUNTS + 1),
+            Err(WalletError::InvalidAccountIndex(MAX_DERIVED_ACCOUNTS))
+        ));
+    }
 
-        for (account, expected) in testnet_expected.iter().enumerate() {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
-                .expect("wallet derivation should succeed");
-            assert_eq!(
-                &wallet.unified_full_viewing_key, expected,
-                "testnet UFVK mismatch for account {}: got {}, expected {}",
-                account, wallet.unified_full_viewing_key, expected
-            );
-        }
+    // =========================================================================
+    // Wallet alias tests
+    // =========================================================================
 
-        // Known viewing keys for mainnet accounts 0, 1, 2
-        let mainnet_expected = [
-            "uview1wj07tp4y3rwzjplg68c3lum2avq4v3j0w0mf0urdlxzthnfr26q8ssz9hvylspj638tuh2r233gaxm2qh27gj6m9q25prk7gt8xwqzmwxm580tg0f5llvr7d6h4y6jc2t7zl7lz9ge60ta6226jyysgk8xpu2wqxesrw4q2mydrhj5dea5l9scl0p3l4ayqgfej54wex5aa2ylq89nyqg94l4lh6dawuc2e3s8v7737zn7p5fl96hhpjqg4jucnp2r2jjqxev3z7lp3k9ulfpl2gw0lng8vfe8hj8afggqzdwxgfaq6dy82guvh34kv4q5ay7gq6n0ujg7exu0mgznpr4wf0agjdhnd4k6af5md3f3msqedw364vx3lyd3hwekvrulywa4c0ja4ze2fxtcm0vrz0278g9n37y0jg6dx847g3peyq9lwmm04ac3tt4sldnrcfc5ew3k0aqgycnryfvv44zxzng485ks27wky2ulfy9q8hu97l",
-            "uview1fzmv506uqhq5ktmj9ldyf7ww00avknney4zpdh73n9suwjhx9je7tswv93e4uxmeunn23cqc2wg0e3l4eqvq0qhheh0hdzznmp4pwlmp9mfa3klm3a28d436anavapwecctk3ul4qqcaankh0lgk5jz9jl4t82p4j03505h92k92d5t23y6rmktp9z0dc7a0v0dzjgl2qtdcrq4z6kmf6xy78xqwqxq27v39r2d3tpwfpzkdjccenxsee7vlv8t533p4y3pckf6xzcq7e226nev4yuevvtfsrxgj0lxxzt9x8949jwn8kdwyslzyjcugk8f2akux3rjgwprtwxpcux26nk8ujk2llrjpg77l2mj9ac6lmva2zeawzwtyrtsmdgrs75snd2exdge33kll6dj4spp6wvrfczjnkrqugy8qm4y238nt850ezpmsse9z0w4rs2klj0hydnkxssasc3cndrvzr55l8u6mfyhm9peg0v8htg4j39u4",
-            "uview1lwrudrcshvvqd3c0dj7l30wx0zfthzvkdw82q07ylw4pppk7mymjwqc7c3y2fl7spq4ypd64hkz93w99nhu5qcc4ctynf5gz0xuqugqfgg7q6hv5a88kaa3w657z7rm2u2ssnrynz3qnsjsurfagqp8qm2hxw70agztdrd82h4tdaqwhfec2kyl4y8d3ef04pw308mn5xdk57rvt99cfyz95ndc4yarf8q8w20wkreqk95s2gk3kfxn882zc00kml2238y25v820747dyj93j9nylf56z49ddwcpzm9je3yqzdf0fzwgm3d9vl225z6y927pqzcewkztfh2a9w4truyhqxeh4d0nw2ty9p7zvy904wqvwa49udz70pydf6w75zhjrh92cl6qqmcpe7n642007qas5h0wx4d7hvtmtu5hh9c9yktcy7u0hu9p30asa7gx4ac9ur87p9rwvnxxp76n04zpcmst289y8u5s7pcdgen3xcfa2kwm",
-        ];
+    #[test]
+    fn test_wallet_alias_is_stable_and_distinct() {
+        let wallet1 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let wallet2 = restore_wallet(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .expect("wallet derivation should succeed");
 
-        for (account, expected) in mainnet_expected.iter().enumerate() {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, account as u32, 0)
-                .expect("wallet derivation should succeed");
-            assert_eq!(
-                &wallet.unified_full_viewing_key, expected,
-                "mainnet UFVK mismatch for account {}: got {}, expected {}",
-                account, wallet.unified_full_viewing_key, expected
-            );
-        }
+        let alias1 = wallet_alias(&wallet1.unified_full_viewing_key).expect("alias");
+        let alias1_again = wallet_alias(&wallet1.unified_full_viewing_key).expect("alias");
+        let alias2 = wallet_alias(&wallet2.unified_full_viewing_key).expect("alias");
+
+        assert_eq!(alias1, alias1_again, "alias must be stable");
+        assert_ne!(
+            alias1, alias2,
+            "different UFVKs must have different aliases"
+        );
     }
 
     #[test]
-    fn test_different_account_addresses_known_values() {
-        // Known transparent addresses for testnet accounts 0, 1, 2 at index 0
-        let testnet_transparent_expected = [
-            "tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd",
-            "tmFHMD21fowpyfY4wv1WcBHkzfhD5rW51Ds",
-            "tmBoHxWs8iDkhpUi7jYVFN6aKieWjHVSQcB",
-        ];
+    fn test_wallet_alias_format() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let alias = wallet_alias(&wallet.unified_full_viewing_key).expect("alias");
+
+        let parts: Vec<&str> = alias.split('-').collect();
+        assert_eq!(parts.len(), 3);
+        let words = Language::English.word_list();
+        assert!(words.contains(&parts[0]));
+        assert!(words.contains(&parts[1]));
+        assert_eq!(parts[2].len(), 2);
+        assert!(parts[2].chars().all(|c| c.is_ascii_digit()));
+    }
 
-        for (account, expected) in testnet_transparent_expected.iter().enumerate() {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
+    #[test]
+    fn test_wallet_alias_rejects_invalid_key() {
+        let result = wallet_alias("not a viewing key");
+        assert!(matches!(result, Err(WalletError::InvalidViewingKey(_))));
+    }
+
+    // =========================================================================
+    // Viewing key import tests
+    // =========================================================================
+
+    #[test]
+    fn test_network_from_ufvk() {
+        let testnet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let mainnet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        assert_eq!(
+            network_from_ufvk(&testnet.unified_full_viewing_key).unwrap(),
+            Network::TestNetwork
+        );
+        assert_eq!(
+            network_from_ufvk(&mainnet.unified_full_viewing_key).unwrap(),
+            Network::MainNetwork
+        );
+        assert!(matches!(
+            network_from_ufvk("uview1notakey"),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_wallet_from_ufvk_infers_network() {
+        for network in [Network::TestNetwork, Network::MainNetwork] {
+            let wallet = restore_wallet(TEST_SEED_PHRASE, network, 0, 0)
                 .expect("wallet derivation should succeed");
+
+            let imported = wallet_from_ufvk(&wallet.unified_full_viewing_key, None, 0)
+                .expect("import should succeed");
+
+            assert_eq!(imported.network, NetworkKind::from(network));
+            assert!(imported.seed_phrase.is_empty());
+            assert_eq!(imported.unified_address, wallet.unified_address);
+            assert_eq!(imported.transparent_address, wallet.transparent_address);
             assert_eq!(
-                wallet.transparent_address.as_ref().unwrap(),
-                expected,
-                "testnet transparent address mismatch for account {}: got {}, expected {}",
-                account,
-                wallet.transparent_address.as_ref().unwrap(),
-                expected
+                imported.unified_full_viewing_key,
+                wallet.unified_full_viewing_key
             );
         }
+    }
+
+    #[test]
+    fn test_wallet_from_ufvk_rejects_network_mismatch() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let result = wallet_from_ufvk(
+            &wallet.unified_full_viewing_key,
+            Some(Network::MainNetwork),
+            0,
+        );
+        assert!(matches!(result, Err(WalletError::InvalidViewingKey(_))));
+
+        let result = wallet_from_ufvk(
+            &wallet.unified_full_viewing_key,
+            Some(Network::TestNetwork),
+            0,
+        );
+        assert!(result.is_ok());
+    }
+
+    // =========================================================================
+    // Diversifier index recovery tests
+    // =========================================================================
 
-        // Known unified addresses for testnet accounts 0, 1, 2 at index 0
-        let testnet_unified_expected = [
-            "utest1cqt4k0wjtvhalr5xfsd2s8enlgn72a3zcvecg7zg55qyyku4z4u5t9yansklfuznvuz077hfu2s8gq50nlln5x4wdxhjleapsn9fs6aua6z9kgpypckjxeku3xzh9qaqj96lk9lpq958ruem58kdfstaec8s725rz4l52xtrlj4cudds53aflght5c433gqkk6h8qvn6vq3u2wxuea5",
-            "utest134n9sjw63v6n5mfg6wyn3475yw4lp9ejmjeftrdmuz7kld0kgcpw7ql8we7kcurclnznlx9veet8kk44y5u00vtzf4x37502p2jk30hefla25ag3ehx7dkzztngycusx5kcfwrz5rhd838krt4sfzqx28haj6h234m4rvj85dvy7y7df4rlkln8r0kavn4r268seyy0t93q86wyaaz0",
-            "utest1f5x7a4p6e4svnsyhwesms4jheplek0hjrtlq9pckjyft5gvet7pf4c3zq53qascteg2rl7vkadamhjy9wukcwwrr0td2m2t92m4y6ywyypr36hvzq2z5ghhwzdn9625x7cezxaa67g0hq4hrucaj30y0k59cssdy3k9f66kjpmm65ntjmludkdf5mdtuj0qpyaxkfj92ysua5pjv855",
-        ];
+    #[test]
+    fn test_recover_diversifier_index_unified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 5)
+            .expect("wallet derivation should succeed");
 
-        for (account, expected) in testnet_unified_expected.iter().enumerate() {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, account as u32, 0)
-                .expect("wallet derivation should succeed");
-            assert_eq!(
-                &wallet.unified_address, expected,
-                "testnet unified address mismatch for account {}: got {}, expected {}",
-                account, wallet.unified_address, expected
-            );
-        }
+        // Index 5 may be skipped for Sapling, so compare with the index used
+        let recovered = recover_diversifier_index(
+            &wallet.unified_full_viewing_key,
+            &wallet.unified_address,
+            Network::TestNetwork,
+            DEFAULT_MAX_DIVERSIFIER_SEARCH,
+        )
+        .expect("inputs should decode");
+        assert_eq!(
+            recovered,
+            DiversifierSearch::Found(u128::from(wallet.address_index))
+        );
+        assert!(wallet.address_index >= 5);
     }
 
     #[test]
-    fn test_unified_address_derivation_regression() {
-        // Test derivation at specific indices
-        let addresses = derive_unified_addresses(
-            TEST_SEED_PHRASE,
+    fn test_recover_diversifier_index_transparent_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 5)
+            .expect("wallet derivation should succeed");
+        let taddr = wallet.transparent_address.expect("should have t-addr");
+
+        let recovered = recover_diversifier_index(
+            &wallet.unified_full_viewing_key,
+            &taddr,
             Network::TestNetwork,
-            0,   // account index
-            0,   // start index
-            100, // count
+            DEFAULT_MAX_DIVERSIFIER_SEARCH,
         )
-        .expect("derivation should succeed");
+        .expect("inputs should decode");
+        assert_eq!(recovered, DiversifierSearch::Found(5));
+    }
 
-        // Verify addresses start with expected prefix
-        for (i, addr) in addresses.iter().enumerate() {
-            assert!(
-                addr.starts_with("utest"),
-                "testnet unified address at index {} should start with 'utest'",
-                i
-            );
-        }
+    #[test]
+    fn test_recover_diversifier_index_foreign_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let other = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
 
-        // Note: Unified addresses may have duplicates because find_address()
-        // returns the next valid diversifier. Multiple input indices may
-        // resolve to the same valid diversifier, producing the same address.
-        // This is expected behavior for Sapling diversifiers.
-        let unique_count = addresses
-            .iter()
-            .collect::<std::collections::HashSet<_>>()
-            .len();
-        assert!(
-            unique_count > 0,
-            "should have at least some unique addresses"
+        let recovered = recover_diversifier_index(
+            &wallet.unified_full_viewing_key,
+            &other.unified_address,
+            Network::TestNetwork,
+            1_000,
+        )
+        .expect("inputs should decode");
+        assert_eq!(
+            recovered,
+            DiversifierSearch::BoundExhausted { searched: 1_000 }
         );
 
-        // Verify first address matches wallet derivation at index 0
-        let wallet_0 = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+        let result = recover_diversifier_index(
+            &wallet.unified_full_viewing_key,
+            "not an address",
+            Network::TestNetwork,
+            DEFAULT_MAX_DIVERSIFIER_SEARCH,
+        );
+        assert!(matches!(result, Err(WalletError::InvalidAddress(_))));
+    }
+
+    // =========================================================================
+    // UIVK conversion tests
+    // =========================================================================
+
+    #[test]
+    fn test_ufvk_to_uivk_prefixes() {
+        let testnet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let uivk = ufvk_to_uivk(&testnet.unified_full_viewing_key, Network::TestNetwork)
+            .expect("conversion should succeed");
+        assert!(uivk.starts_with("uivktest"));
+
+        let mainnet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let uivk = ufvk_to_uivk(&mainnet.unified_full_viewing_key, Network::MainNetwork)
+            .expect("conversion should succeed");
+        assert!(uivk.starts_with("uivk1"));
+    }
+
+    #[test]
+    fn test_ufvk_to_uivk_rejects_wrong_network() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let result = ufvk_to_uivk(&wallet.unified_full_viewing_key, Network::MainNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidViewingKey(_))));
+    }
+
+    #[test]
+    fn test_restore_wallet_from_seed_hex_matches_mnemonic() {
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, TEST_SEED_PHRASE).unwrap();
+        let seed_hex = hex::encode(mnemonic.to_seed(""));
+
+        let from_mnemonic = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let from_seed = restore_wallet_from_seed_hex(&seed_hex, Network::TestNetwork)
             .expect("wallet derivation should succeed");
+
+        assert!(from_seed.seed_phrase.is_empty());
+        assert_eq!(from_seed.unified_address, from_mnemonic.unified_address);
         assert_eq!(
-            addresses[0], wallet_0.unified_address,
-            "unified address at index 0 should match wallet derivation"
+            from_seed.transparent_address,
+            from_mnemonic.transparent_address
+        );
+        assert_eq!(
+            from_seed.unified_full_viewing_key,
+            from_mnemonic.unified_full_viewing_key
         );
     }
 
     #[test]
-    fn test_transparent_derivation_deterministic() {
-        // Verify that calling derive_transparent_addresses multiple times
-        // produces the same results
-        let addresses1 =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-                .expect("derivation should succeed");
+    fn test_restore_wallet_from_seed_hex_rejects_invalid_input() {
+        // Not hex
+        let result = restore_wallet_from_seed_hex(&"zz".repeat(64), Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidSeed(_))));
+
+        // Too short
+        let result = restore_wallet_from_seed_hex(&"00".repeat(32), Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidSeed(_))));
+
+        // Too long
+        let result = restore_wallet_from_seed_hex(&"00".repeat(65), Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidSeed(_))));
+    }
 
-        let addresses2 =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-                .expect("derivation should succeed");
+    #[test]
+    fn test_ufvk_matches_seed() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
 
-        assert_eq!(addresses1, addresses2, "derivation should be deterministic");
+        assert!(ufvk_matches_seed(ufvk, TEST_SEED_PHRASE, 0, Network::TestNetwork).unwrap());
+
+        // Same seed, different account
+        assert!(!ufvk_matches_seed(ufvk, TEST_SEED_PHRASE, 1, Network::TestNetwork).unwrap());
+
+        // Different seed
+        let other = generate_wallet(&[7u8; 32], Network::TestNetwork, 0, 0, None).unwrap();
+        assert!(!ufvk_matches_seed(ufvk, &other.seed_phrase, 0, Network::TestNetwork).unwrap());
     }
 
     #[test]
-    fn test_unified_derivation_deterministic() {
-        let addresses1 = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-            .expect("derivation should succeed");
+    fn test_ufvk_matches_seed_rejects_invalid_input() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
 
-        let addresses2 = derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-            .expect("derivation should succeed");
+        let result = ufvk_matches_seed("not a ufvk", TEST_SEED_PHRASE, 0, Network::TestNetwork);
+        assert!(matches!(result, Err(WalletError::InvalidViewingKey(_))));
 
-        assert_eq!(addresses1, addresses2, "derivation should be deterministic");
+        let result = ufvk_matches_seed(
+            &wallet.unified_full_viewing_key,
+            "not a seed phrase",
+            0,
+            Network::TestNetwork,
+        );
+        assert!(matches!(result, Err(WalletError::InvalidSeedPhrase(_))));
     }
 
     #[test]
-    fn test_cross_verification_transparent() {
-        // Verify that derive_transparent_addresses produces the same addresses
-        // as individual wallet derivations
-        let batch_addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-                .expect("batch derivation should succeed");
+    fn test_derivation_path() {
+        assert_eq!(derivation_path(0, Network::MainNetwork), "m/32'/133'/0'");
+        assert_eq!(derivation_path(2, Network::TestNetwork), "m/32'/1'/2'");
+    }
 
-        for i in 0..10u32 {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, i)
-                .expect("wallet derivation should succeed");
+    #[test]
+    fn test_labeled_address_is_stable_per_label() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+
+        let rent1 = labeled_address(ufvk, "rent", Network::TestNetwork).unwrap();
+        let rent2 = labeled_address(ufvk, "rent", Network::TestNetwork).unwrap();
+        let salary = labeled_address(ufvk, "salary", Network::TestNetwork).unwrap();
+
+        assert_eq!(rent1, rent2);
+        assert_ne!(rent1, salary);
+        assert_ne!(rent1, wallet.unified_address);
+
+        // The address belongs to the wallet
+        let decoded = UnifiedFullViewingKey::decode(&Network::TestNetwork, ufvk).unwrap();
+        let Some(Address::Unified(ua)) = Address::decode(&Network::TestNetwork, &rent1) else {
+            panic!("labeled address should be a unified address");
+        };
+        let sapling = ua.sapling().expect("should have a Sapling receiver");
+        assert!(
+            decoded
+                .sapling()
+                .unwrap()
+                .decrypt_diversifier(sapling)
+                .is_some()
+        );
 
-            assert_eq!(
-                batch_addresses[i as usize],
-                wallet.transparent_address.unwrap(),
-                "batch address at index {} should match wallet derivation",
-                i
-            );
-        }
+        // Only shielded receivers are included
+        assert!(ua.orchard().is_some());
+        assert!(ua.transparent().is_none());
     }
 
     #[test]
-    fn test_cross_verification_unified() {
-        // Verify that derive_unified_addresses produces the same addresses
-        // as individual wallet derivations
-        let batch_addresses =
-            derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 10)
-                .expect("batch derivation should succeed");
+    fn test_labeled_address_rejects_invalid_ufvk() {
+        assert!(matches!(
+            labeled_address("not a ufvk", "rent", Network::TestNetwork),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
 
-        for i in 0..10u32 {
-            let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, i)
-                .expect("wallet derivation should succeed");
+    #[test]
+    fn test_derive_sapling_diversified_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
 
-            assert_eq!(
-                batch_addresses[i as usize], wallet.unified_address,
-                "batch address at index {} should match wallet derivation",
-                i
-            );
-        }
+        let first = derive_sapling_diversified_address(ufvk, Network::MainNetwork, 0).unwrap();
+        let second = derive_sapling_diversified_address(ufvk, Network::MainNetwork, 1_000).unwrap();
+
+        assert!(first.starts_with("zs1"));
+        assert!(second.starts_with("zs1"));
+        assert_ne!(first, second);
+        assert_eq!(
+            derive_sapling_diversified_address(ufvk, Network::MainNetwork, 0).unwrap(),
+            first
+        );
     }
 
     #[test]
-    fn test_derivation_with_offset() {
-        // Test that derivation with start_index offset works correctly
-        let all_addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 20)
-                .expect("derivation should succeed");
+    fn test_derive_sapling_diversified_address_rejects_invalid_input() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+
+        assert!(matches!(
+            derive_sapling_diversified_address(ufvk, Network::MainNetwork, 1u128 << 88),
+            Err(WalletError::AddressGeneration(_))
+        ));
+        assert!(matches!(
+            derive_sapling_diversified_address(ufvk, Network::TestNetwork, 0),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
 
-        let offset_addresses = derive_transparent_addresses(
-            TEST_SEED_PHRASE,
+    #[test]
+    fn test_derive_unified_addresses_from_ufvk_matches_seed() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let from_seed =
+            derive_unified_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 3).unwrap();
+        let from_ufvk = derive_unified_addresses_from_ufvk(
+            &wallet.unified_full_viewing_key,
             Network::TestNetwork,
             0,
-            10, // start at index 10
-            10,
+            3,
         )
-        .expect("derivation should succeed");
+        .unwrap();
+
+        assert_eq!(from_ufvk, from_seed);
+        assert!(matches!(
+            derive_unified_addresses_from_ufvk("invalid", Network::TestNetwork, 0, 3),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+        assert!(matches!(
+            derive_unified_addresses_from_ufvk(
+                &wallet.unified_full_viewing_key,
+                Network::TestNetwork,
+                0,
+                MAX_ADDRESS_BATCH + 1,
+            ),
+            Err(WalletError::AddressGeneration(_))
+        ));
+    }
 
-        // Addresses starting at index 10 should match
+    #[test]
+    fn test_ufvk_components() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+
+        let report = ufvk_components(&wallet.unified_full_viewing_key).unwrap();
+        assert_eq!(report.hrp, "uviewtest");
+        assert_eq!(report.network, NetworkKind::Testnet);
+        assert!(report.has_orchard);
+        assert!(report.has_sapling);
+        assert!(report.has_transparent);
+
+        assert!(matches!(
+            ufvk_components("invalid"),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
+
+    /// Re-encode a UFVK with extra metadata items.
+    fn ufvk_with_metadata(ufvk: &str, metadata: Vec<unified::Fvk>) -> String {
+        let (network_type, decoded) = unified::Ufvk::decode(ufvk).unwrap();
+        let mut items = decoded.items();
+        items.extend(metadata);
+        unified::Ufvk::try_from_items(items)
+            .unwrap()
+            .encode(&network_type)
+    }
+
+    #[test]
+    fn test_ufvk_expiry_metadata() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let plain = &wallet.unified_full_viewing_key;
+
+        let report = ufvk_components(plain).unwrap();
+        assert_eq!(report.expiry_height, None);
+        assert_eq!(report.expiry_time, None);
+        assert!(!is_expired(plain, u32::MAX, u64::MAX).unwrap());
+
+        let expiring = ufvk_with_metadata(
+            plain,
+            vec![
+                unified::Fvk::Unknown {
+                    typecode: EXPIRY_HEIGHT_TYPECODE,
+                    data: 3_000_000u32.to_le_bytes().to_vec(),
+                },
+                unified::Fvk::Unknown {
+                    typecode: EXPIRY_TIME_TYPECODE,
+                    data: 1_900_000_000u64.to_le_bytes().to_vec(),
+                },
+            ],
+        );
+        let report = ufvk_components(&expiring).unwrap();
+        assert_eq!(report.expiry_height, Some(3_000_000));
+        assert_eq!(report.expiry_time, Some(1_900_000_000));
+        assert!(report.has_orchard);
+
+        assert!(!is_expired(&expiring, 3_000_000, 1_800_000_000).unwrap());
+        assert!(is_expired(&expiring, 3_000_001, 1_800_000_000).unwrap());
+        assert!(is_expired(&expiring, 2_000_000, 1_900_000_001).unwrap());
+
+        let malformed = ufvk_with_metadata(
+            plain,
+            vec![unified::Fvk::Unknown {
+                typecode: EXPIRY_HEIGHT_TYPECODE,
+                data: vec![1, 2],
+            }],
+        );
+        assert!(matches!(
+            ufvk_components(&malformed),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_change_address_is_internal() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+
+        let change = change_address(ufvk, Network::TestNetwork).unwrap();
+        assert_ne!(change, wallet.unified_address);
+
+        let Some(Address::Unified(ua)) = Address::decode(&Network::TestNetwork, &change) else {
+            panic!("change address should be a unified address");
+        };
+        assert!(ua.transparent().is_none());
+        let decoded = UnifiedFullViewingKey::decode(&Network::TestNetwork, ufvk).unwrap();
         assert_eq!(
-            &all_addresses[10..20],
-            &offset_addresses[..],
-            "offset derivation should match"
+            decoded
+                .orchard()
+                .unwrap()
+                .scope_for_address(ua.orchard().unwrap()),
+            Some(Scope::Internal)
         );
+        assert!(matches!(
+            decoded
+                .sapling()
+                .unwrap()
+                .decrypt_diversifier(ua.sapling().unwrap()),
+            Some((_, Scope::Internal))
+        ));
     }
 
     #[test]
-    fn test_different_accounts_produce_different_addresses() {
-        let account0 =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 5)
-                .expect("derivation should succeed");
+    fn test_address_for_contact_is_stable_per_contact() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
 
-        let account1 =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0, 5)
-                .expect("derivation should succeed");
+        let alice1 = address_for_contact(ufvk, "alice@example.com", Network::TestNetwork).unwrap();
+        let alice2 = address_for_contact(ufvk, "alice@example.com", Network::TestNetwork).unwrap();
+        let bob = address_for_contact(ufvk, "bob@example.com", Network::TestNetwork).unwrap();
 
-        // No address from account 0 should appear in account 1
-        for addr in &account0 {
-            assert!(
-                !account1.contains(addr),
-                "different accounts should produce completely different addresses"
-            );
-        }
+        assert_eq!(alice1, alice2);
+        assert_ne!(alice1, bob);
+        assert_ne!(
+            alice1,
+            labeled_address(ufvk, "alice@example.com", Network::TestNetwork).unwrap()
+        );
+
+        // A transparent receiver would link every payment from the contact
+        let Some(Address::Unified(ua)) = Address::decode(&Network::TestNetwork, &alice1) else {
+            panic!("contact address should be a unified address");
+        };
+        assert!(ua.orchard().is_some() && ua.sapling().is_some());
+        assert!(ua.transparent().is_none());
     }
 
     #[test]
-    fn test_mainnet_transparent_addresses() {
-        let addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
-                .expect("derivation should succeed");
+    fn test_generate_wallet_with_entropy_strengths() {
+        for (len, words) in [(16, 12), (20, 15), (24, 18), (28, 21), (32, 24)] {
+            let wallet =
+                generate_wallet_with_entropy(&vec![0u8; len], Network::TestNetwork, 0, 0, None)
+                    .expect("wallet generation should succeed");
+            assert_eq!(wallet.seed_phrase.split_whitespace().count(), words);
+        }
+    }
 
-        for addr in &addresses {
-            assert!(
-                addr.starts_with("t1"),
-                "mainnet transparent address should start with 't1'"
-            );
+    #[test]
+    fn test_generate_wallet_with_entropy_rejects_invalid_length() {
+        for len in [10, 33] {
+            assert!(matches!(
+                generate_wallet_with_entropy(&vec![0u8; len], Network::TestNetwork, 0, 0, None),
+                Err(WalletError::InvalidEntropyLength(l)) if l == len
+            ));
         }
     }
 
     #[test]
-    fn test_mainnet_unified_addresses() {
-        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 5)
-            .expect("derivation should succeed");
+    fn test_recover_diversifier_index_stops_at_bound() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 20)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+        let taddr = wallet.transparent_address.expect("should have t-addr");
 
-        for addr in &addresses {
-            assert!(
-                addr.starts_with("u1"),
-                "mainnet unified address should start with 'u1'"
-            );
-        }
+        // The address lies just beyond the bound
+        assert_eq!(
+            recover_diversifier_index(ufvk, &taddr, Network::TestNetwork, 20).unwrap(),
+            DiversifierSearch::BoundExhausted { searched: 20 }
+        );
+        assert_eq!(
+            recover_diversifier_index(ufvk, &taddr, Network::TestNetwork, 21).unwrap(),
+            DiversifierSearch::Found(20)
+        );
+
+        // An address the key never derives is given up on at the bound
+        let other = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0)
+            .expect("wallet derivation should succeed");
+        assert_eq!(
+            recover_diversifier_index(ufvk, &other.unified_address, Network::TestNetwork, 50)
+                .unwrap(),
+            DiversifierSearch::BoundExhausted { searched: 50 }
+        );
+    }
+
+    #[test]
+    fn test_receiving_addresses_for_note() {
+        use orchard::note::{RandomSeed, Rho};
+        use orchard::value::NoteValue;
+
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+        let fvk = UnifiedFullViewingKey::decode(&Network::TestNetwork, ufvk)
+            .unwrap()
+            .orchard()
+            .cloned()
+            .unwrap();
+
+        // A note sent to the address at index 3
+        let recipient = fvk.address_at(3u32, Scope::External);
+        let rho = Rho::from_bytes(&[0u8; 32]).unwrap();
+        let note = (1u8..)
+            .find_map(|b| {
+                let rseed = Option::from(RandomSeed::from_bytes([b; 32], &rho))?;
+                Option::from(orchard::Note::from_parts(
+                    recipient,
+                    NoteValue::from_raw(5_000),
+                    rho,
+                    rseed,
+                ))
+            })
+            .unwrap();
+
+        let addresses =
+            receiving_addresses_for_note(&note, ufvk, Network::TestNetwork, 10).unwrap();
+        assert_eq!(addresses.len(), 1);
+        let Some(Address::Unified(ua)) = Address::decode(&Network::TestNetwork, &addresses[0])
+        else {
+            panic!("should be a unified address");
+        };
+        assert_eq!(ua.orchard(), Some(&recipient));
+
+        // Index 3 lies outside a bound of 3
+        assert!(
+            receiving_addresses_for_note(&note, ufvk, Network::TestNetwork, 3)
+                .unwrap()
+                .is_empty()
+        );
     }
 
-    // =========================================================================
-    // Property-based tests (PBT) using random seeds
-    // These tests verify address prefix properties hold for any valid seed
-    // =========================================================================
+    #[test]
+    fn test_derive_storage_key() {
+        let key = derive_storage_key(&[0u8; 64]);
+        assert_eq!(
+            hex::encode(key),
+            "8d0814d9d82d29da8fbf84aa0b1e84a39047eefb842ba555986b263e8bdc6228"
+        );
+        assert_eq!(derive_storage_key(&[0u8; 64]), key);
+        assert_ne!(derive_storage_key(&[1u8; 64]), key);
+    }
 
     #[test]
-    fn test_pbt_testnet_transparent_address_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_same_account_cross_network() {
+        let mainnet = restore_wallet(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0).unwrap();
+        let testnet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        assert!(same_account_cross_network(&mainnet, &testnet));
+        assert!(same_account_cross_network(&testnet, &mainnet));
+
+        // One side may be view-only as long as the other has its seed
+        let view_only = wallet_from_ufvk(
+            &testnet.unified_full_viewing_key,
+            Some(Network::TestNetwork),
+            0,
+        )
+        .unwrap();
+        assert!(same_account_cross_network(&view_only, &mainnet));
 
-        // Run multiple iterations with random seeds
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        let other_account = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0).unwrap();
+        assert!(!same_account_cross_network(&mainnet, &other_account));
+
+        let other_seed = restore_wallet(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+        assert!(!same_account_cross_network(&mainnet, &other_seed));
+    }
 
+    #[test]
+    fn test_parse_zec_amount() {
+        assert_eq!(parse_zec_amount("1").unwrap(), 100_000_000);
+        assert_eq!(parse_zec_amount(" 1.5 ").unwrap(), 150_000_000);
+        assert_eq!(parse_zec_amount("0.00000001").unwrap(), 1);
+        assert_eq!(parse_zec_amount(".25").unwrap(), 25_000_000);
+        assert_eq!(parse_zec_amount("21000000").unwrap(), MAX_MONEY);
+
+        for malformed in [
+            "",
+            ".",
+            "-1",
+            "+1",
+            "1e3",
+            "1,000",
+            "1.2.3",
+            "0.000000001",
+            "abc",
+        ] {
             assert!(
-                wallet
-                    .transparent_address
-                    .as_ref()
-                    .map(|s| s.starts_with("tm"))
-                    .unwrap_or(false),
-                "testnet transparent address should start with 'tm' for any seed"
+                matches!(
+                    parse_zec_amount(malformed),
+                    Err(WalletError::InvalidAmount(_))
+                ),
+                "{:?} should be rejected",
+                malformed
             );
         }
+        assert!(parse_zec_amount("21000000.00000001").is_err());
+        assert!(parse_zec_amount("99999999999999999999").is_err());
     }
 
     #[test]
-    fn test_pbt_mainnet_transparent_address_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_validate_send_amount() {
+        assert_eq!(
+            validate_send_amount("0.5", 60_000_000, 10_000).unwrap(),
+            50_000_000
+        );
+        // The whole balance can be sent when it covers the fee
+        assert_eq!(
+            validate_send_amount("0.5", 50_010_000, 10_000).unwrap(),
+            50_000_000
+        );
 
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        let err = validate_send_amount("0.5", 50_000_000, 10_000).unwrap_err();
+        assert!(matches!(
+            err,
+            WalletError::InsufficientFunds {
+                available: 50_000_000,
+                required: 50_010_000
+            }
+        ));
+        assert!(err.to_string().contains("10000 short"));
+
+        assert!(matches!(
+            validate_send_amount("0.5 ZEC", 60_000_000, 10_000),
+            Err(WalletError::InvalidAmount(_))
+        ));
+        assert!(matches!(
+            validate_send_amount("0", 60_000_000, 10_000),
+            Err(WalletError::InvalidAmount(_))
+        ));
+    }
 
-            assert!(
-                wallet
-                    .transparent_address
-                    .as_ref()
-                    .map(|s| s.starts_with("t1"))
-                    .unwrap_or(false),
-                "mainnet transparent address should start with 't1' for any seed"
-            );
-        }
+    #[test]
+    fn test_is_own_address() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        let ufvk = &wallet.unified_full_viewing_key;
+        let own = |addr: &str| is_own_address(ufvk, addr, Network::TestNetwork, 10).unwrap();
+
+        assert!(own(&wallet.unified_address));
+        assert!(own(wallet.transparent_address.as_ref().unwrap()));
+        assert!(own(&change_address(ufvk, Network::TestNetwork).unwrap()));
+        // The default address is found even without searching
+        assert!(is_own_address(ufvk, &wallet.unified_address, Network::TestNetwork, 0).unwrap());
+
+        let other = restore_wallet(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+        assert!(!own(&other.unified_address));
+        assert!(!own(other.transparent_address.as_ref().unwrap()));
+
+        assert!(matches!(
+            is_own_address(ufvk, "not an address", Network::TestNetwork, 10),
+            Err(WalletError::InvalidAddress(_))
+        ));
     }
 
     #[test]
-    fn test_pbt_testnet_unified_address_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_legacy_sapling_address_differs_from_standard() {
+        let seed = Mnemonic::parse_in_normalized(Language::English, TEST_SEED_PHRASE)
+            .unwrap()
+            .to_seed("");
+        let network = Network::TestNetwork;
+        let standard = derive_wallet(&seed, String::new(), network, 0, 0).unwrap();
+
+        let legacy = derive_legacy_sapling_key(&seed, network, 1).unwrap();
+        assert!(legacy.address.starts_with("ztestsapling"));
+        assert!(legacy.extfvk.starts_with("zxviewtestsapling"));
+        assert_eq!(
+            legacy.extfvk,
+            derive_sapling_extfvk(&seed, 1, network).unwrap()
+        );
 
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        // Legacy address 1 is the default address of account 1, not
+        // diversifier 1 of account 0
+        let standard_sapling =
+            derive_sapling_diversified_address(&standard.unified_full_viewing_key, network, 1)
+                .unwrap();
+        assert_ne!(legacy.address, standard_sapling);
+        let account_1 = derive_wallet(&seed, String::new(), network, 1, 0).unwrap();
+        assert_eq!(
+            legacy.address,
+            derive_sapling_diversified_address(&account_1.unified_full_viewing_key, network, 0)
+                .unwrap()
+        );
 
-            assert!(
-                wallet.unified_address.starts_with("utest"),
-                "testnet unified address should start with 'utest' for any seed"
-            );
-        }
+        // For the first address both schemes agree
+        let legacy_0 = derive_legacy_sapling_key(&seed, network, 0).unwrap();
+        assert_eq!(
+            legacy_0.address,
+            derive_sapling_diversified_address(&standard.unified_full_viewing_key, network, 0)
+                .unwrap()
+        );
     }
 
     #[test]
-    fn test_pbt_mainnet_unified_address_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_generate_wallets_are_distinct() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
 
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        let wallets = generate_wallets(5, Network::TestNetwork, &mut rng).unwrap();
+        assert_eq!(wallets.len(), 5);
+        let ufvks: std::collections::HashSet<_> = wallets
+            .iter()
+            .map(|wallet| wallet.unified_full_viewing_key.as_str())
+            .collect();
+        assert_eq!(ufvks.len(), 5);
+        assert!(
+            wallets
+                .iter()
+                .all(|wallet| wallet.network == NetworkKind::Testnet)
+        );
 
-            assert!(
-                wallet.unified_address.starts_with("u1"),
-                "mainnet unified address should start with 'u1' for any seed"
-            );
-        }
+        assert!(
+            generate_wallets(0, Network::TestNetwork, &mut rng)
+                .unwrap()
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_pbt_testnet_ufvk_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+    fn test_generate_wallets_is_bounded() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        assert!(matches!(
+            generate_wallets(MAX_GENERATED_WALLETS + 1, Network::TestNetwork, &mut rng),
+            Err(WalletError::MnemonicGeneration(_))
+        ));
+
+        // A constant source repeats its seed phrase, which must not loop
+        let mut constant = rand::rngs::mock::StepRng::new(0, 0);
+        assert!(matches!(
+            generate_wallets(2, Network::TestNetwork, &mut constant),
+            Err(WalletError::MnemonicGeneration(_))
+        ));
+    }
 
-            assert!(
-                wallet.unified_full_viewing_key.starts_with("uviewtest"),
-                "testnet UFVK should start with 'uviewtest' for any seed"
-            );
+    #[test]
+    fn test_one_time_address_pool() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
+
+        let mut pool = OneTimeAddressPool::new();
+        let first = pool.issue(ufvk, Network::TestNetwork).unwrap();
+        let second = pool.issue(ufvk, Network::TestNetwork).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(pool.issued.len(), 2);
+        assert!(pool.is_issued(0));
+
+        // The first address is the wallet's default one, without its
+        // transparent receiver
+        let decode = |address: &str| match Address::decode(&Network::TestNetwork, address) {
+            Some(Address::Unified(ua)) => ua,
+            _ => panic!("issued address should be a unified address"),
+        };
+        let default = decode(&wallet.unified_address);
+        let first_ua = decode(&first);
+        assert_eq!(first_ua.orchard(), default.orchard());
+        assert_eq!(first_ua.sapling(), default.sapling());
+        assert!(first_ua.transparent().is_none());
+
+        // The state survives a reload
+        let json = serde_json::to_string(&pool).unwrap();
+        let mut reloaded: OneTimeAddressPool = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded, pool);
+        let third = reloaded.issue(ufvk, Network::TestNetwork).unwrap();
+        assert!(third != first && third != second);
+        assert_eq!(reloaded.issued.len(), 3);
+        for index in &reloaded.issued {
+            assert!(reloaded.is_issued(*index));
         }
     }
 
     #[test]
-    fn test_pbt_mainnet_ufvk_prefix() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::MainNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+    fn test_suggest_word() {
+        assert_eq!(suggest_word("abandn")[0], "abandon");
+        assert_eq!(suggest_word("abandon"), vec!["abandon".to_string()]);
+        assert_eq!(suggest_word(" Zoo "), vec!["zoo".to_string()]);
+        assert!(suggest_word("xxxxxxxxxx").is_empty());
+        assert!(suggest_word("tabel").len() <= MAX_WORD_SUGGESTIONS);
+    }
 
-            assert!(
-                wallet.unified_full_viewing_key.starts_with("uview1"),
-                "mainnet UFVK should start with 'uview1' for any seed"
-            );
-        }
+    #[test]
+    fn test_find_unknown_words() {
+        assert!(find_unknown_words(TEST_SEED_PHRASE).is_empty());
+
+        let unknown = find_unknown_words("abandon abandn zoo zooo");
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(unknown[0].position, 1);
+        assert_eq!(unknown[0].word, "abandn");
+        assert_eq!(unknown[0].suggestions[0], "abandon");
+        assert_eq!(unknown[1].position, 3);
+        assert!(unknown[1].suggestions.contains(&"zoo".to_string()));
     }
 
     #[test]
-    fn test_pbt_seed_phrase_is_24_words() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_mnemonic_from_entropy() {
+        let phrase = mnemonic_from_entropy(&[0u8; 16]).unwrap();
+        assert_eq!(phrase.split_whitespace().count(), 12);
+        assert!(phrase.ends_with("about"));
 
-        for _ in 0..10 {
-            let entropy: [u8; 32] = rng.r#gen();
-            let wallet = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        let phrase = mnemonic_from_entropy(&[0u8; 32]).unwrap();
+        assert_eq!(phrase, TEST_SEED_PHRASE);
 
-            let word_count = wallet.seed_phrase.split_whitespace().count();
-            assert_eq!(
-                word_count, 24,
-                "generated seed phrase should have 24 words for any entropy"
-            );
+        for (len, words) in VALID_ENTROPY_LENGTHS.iter().zip([12, 15, 18, 21, 24]) {
+            let phrase = mnemonic_from_entropy(&vec![0x5a; *len]).unwrap();
+            assert_eq!(phrase.split_whitespace().count(), words);
         }
+        assert!(matches!(
+            mnemonic_from_entropy(&[0u8; 17]),
+            Err(WalletError::InvalidEntropyLength(17))
+        ));
+        assert!(mnemonic_from_entropy(&[]).is_err());
     }
 
     #[test]
-    fn test_pbt_deterministic_derivation() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_key_capabilities() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+
+        let ufvk = key_capabilities(&wallet.unified_full_viewing_key).unwrap();
+        assert_eq!(ufvk.key_type, ViewingKeyType::Ufvk);
+        assert!(ufvk.can_view_incoming);
+        assert!(ufvk.can_view_outgoing);
+        assert!(!ufvk.can_spend);
+
+        let uivk_str =
+            ufvk_to_uivk(&wallet.unified_full_viewing_key, Network::TestNetwork).unwrap();
+        let uivk = key_capabilities(&uivk_str).unwrap();
+        assert_eq!(uivk.key_type, ViewingKeyType::Uivk);
+        assert!(uivk.can_view_incoming);
+        assert!(!uivk.can_view_outgoing);
+        assert!(!uivk.can_spend);
+
+        assert!(matches!(
+            key_capabilities(TEST_SEED_PHRASE),
+            Err(WalletError::InvalidViewingKey(_))
+        ));
+    }
 
-        for _ in 0..5 {
-            let entropy: [u8; 32] = rng.r#gen();
+    #[test]
+    fn test_to_watch_only_drops_seed_phrase() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        let watch_only = wallet.to_watch_only();
 
-            let wallet1 = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
-            let wallet2 = generate_wallet(&entropy, Network::TestNetwork, 0, 0)
-                .expect("wallet generation should succeed");
+        assert!(watch_only.seed_phrase.is_empty());
+        assert_eq!(
+            watch_only.unified_full_viewing_key,
+            wallet.unified_full_viewing_key
+        );
+        assert_eq!(watch_only.unified_address, wallet.unified_address);
+        assert_eq!(watch_only.transparent_address, wallet.transparent_address);
+        assert!(
+            !serde_json::to_string(&watch_only)
+                .unwrap()
+                .contains("abandon")
+        );
+    }
 
-            assert_eq!(
-                wallet1.unified_address, wallet2.unified_address,
-                "same entropy should produce same addresses"
-            );
-            assert_eq!(
-                wallet1.transparent_address, wallet2.transparent_address,
-                "same entropy should produce same transparent addresses"
-            );
-            assert_eq!(
-                wallet1.unified_full_viewing_key, wallet2.unified_full_viewing_key,
-                "same entropy should produce same UFVK"
-            );
-        }
+    #[test]
+    fn test_account_index_bounds() {
+        assert!(account_id(MAX_ACCOUNT_INDEX).is_ok());
+        assert!(matches!(
+            account_id(1 << 31),
+            Err(WalletError::InvalidAccountIndex(index)) if index == 1 << 31
+        ));
+        assert!(matches!(
+            account_id(u32::MAX),
+            Err(WalletError::InvalidAccountIndex(u32::MAX))
+        ));
+
+        assert!(restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, (1 << 31) - 1, 0).is_ok());
+        assert!(matches!(
+            restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1 << 31, 0),
+            Err(WalletError::InvalidAccountIndex(index)) if index == 1 << 31
+        ));
     }
 
     #[test]
-    fn test_pbt_different_entropy_different_addresses() {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn test_is_known_weak_seed() {
+        assert!(is_known_weak_seed(TEST_SEED_PHRASE));
+        assert!(is_known_weak_seed(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ));
+        assert!(is_known_weak_seed(
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"
+        ));
+        assert!(is_known_weak_seed(&TEST_SEED_PHRASE.to_uppercase()));
+
+        let entropy: Vec<u8> = (1..=32).collect();
+        let random = mnemonic_from_entropy(&entropy).unwrap();
+        assert!(!is_known_weak_seed(&random));
+        assert!(!is_known_weak_seed("not a seed phrase"));
+    }
 
-        let entropy1: [u8; 32] = rng.r#gen();
-        let entropy2: [u8; 32] = rng.r#gen();
+    #[test]
+    fn test_wallet_info_address_at() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        assert_eq!(wallet.address_at(0).unwrap(), wallet.unified_address);
 
-        // Ensure they're different (astronomically unlikely to be same)
-        assert_ne!(entropy1, entropy2, "random entropy should be different");
+        let later = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 5).unwrap();
+        assert_eq!(wallet.address_at(5).unwrap(), later.unified_address);
+        assert!(wallet.address_at(1 << 88).is_err());
+    }
 
-        let wallet1 = generate_wallet(&entropy1, Network::TestNetwork, 0, 0)
-            .expect("wallet generation should succeed");
-        let wallet2 = generate_wallet(&entropy2, Network::TestNetwork, 0, 0)
-            .expect("wallet generation should succeed");
+    #[test]
+    fn test_all_addresses() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        let addresses = wallet.all_addresses().unwrap();
 
-        assert_ne!(
-            wallet1.unified_address, wallet2.unified_address,
-            "different entropy should produce different addresses"
-        );
-        assert_ne!(
-            wallet1.transparent_address, wallet2.transparent_address,
-            "different entropy should produce different transparent addresses"
+        assert_eq!(addresses.unified, wallet.unified_address);
+        assert_eq!(addresses.transparent, wallet.transparent_address);
+        assert!(addresses.transparent.is_some());
+        assert!(
+            addresses
+                .sapling
+                .as_deref()
+                .unwrap()
+                .starts_with("ztestsapling")
         );
+        assert!(addresses.orchard.as_deref().unwrap().starts_with("utest"));
+        assert!(addresses.tex.as_deref().unwrap().starts_with("textest"));
     }
 
-    /// Test that verifies unified address uniqueness behavior at different indices.
-    ///
-    /// IMPORTANT: Due to Sapling diversifier behavior, not all diversifier indices
-    /// produce valid diversifiers. The `find_address` function skips invalid
-    /// diversifiers and returns the next valid one. This means consecutive indices
-    /// may produce the SAME unified address.
-    ///
-    /// For example, if index 2 and 3 both have invalid diversifiers, both will
-    /// skip to index 4's diversifier, producing identical addresses.
     #[test]
-    fn test_unified_address_index_uniqueness_behavior() {
-        // Derive addresses at indices 0-9
-        let addresses = derive_unified_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 10)
-            .expect("derivation should succeed");
-
-        // Count unique addresses
-        let unique: std::collections::HashSet<_> = addresses.iter().collect();
-        let unique_count = unique.len();
-
-        // Print the actual uniqueness for visibility
-        eprintln!(
-            "Unified addresses: {} indices -> {} unique addresses",
-            addresses.len(),
-            unique_count
+    fn test_generate_wallet_rng_is_reproducible() {
+        use rand::SeedableRng;
+        // StdRng is ChaCha-based and deterministic for a given seed
+        let wallet = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            generate_wallet_rng(&mut rng, Network::TestNetwork).unwrap()
+        };
+
+        let first = wallet(42);
+        let again = wallet(42);
+        assert_eq!(first.seed_phrase, again.seed_phrase);
+        assert_eq!(
+            first.unified_full_viewing_key,
+            again.unified_full_viewing_key
         );
+        assert_eq!(first.unified_address, again.unified_address);
+        assert_eq!(first.seed_phrase.split_whitespace().count(), 24);
+        assert_ne!(first.seed_phrase, wallet(43).seed_phrase);
+    }
 
-        // With 10 indices, we expect at least 5 unique addresses (50%)
-        // In practice, about 87.5% of Sapling diversifier indices are valid
-        assert!(
-            unique_count >= 5,
-            "expected at least 5 unique addresses from 10 indices, got {}",
-            unique_count
+    #[test]
+    fn test_account_label() {
+        assert_eq!(
+            account_label(2, Network::TestNetwork),
+            "Account 2 (testnet) m/32'/1'/2'"
+        );
+        assert_eq!(
+            account_label(0, Network::MainNetwork),
+            "Account 0 (mainnet) m/32'/133'/0'"
         );
+    }
 
-        // Document which indices produce duplicates
-        for i in 1..addresses.len() {
-            if addresses[i] == addresses[i - 1] {
-                eprintln!(
-                    "Note: index {} produces same address as index {} (both found same valid diversifier)",
-                    i,
-                    i - 1
-                );
-            }
-        }
+    #[test]
+    fn test_lightwalletd_import_payload() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 1, 0).unwrap();
+        let payload = lightwalletd_import_payload(&wallet, 2_000_000);
+
+        let value: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(value["ufvk"], wallet.unified_full_viewing_key.as_str());
+        assert_eq!(value["birthday"], 2_000_000);
+        assert_eq!(value["account"], 1);
+        assert_eq!(value["network"], "testnet");
+        assert!(!payload.contains(&wallet.seed_phrase));
     }
 
-    /// Test that transparent addresses are ALWAYS unique at each index.
-    /// Unlike unified addresses, transparent addresses don't have the diversifier
-    /// validity issue - each index produces a distinct address.
     #[test]
-    fn test_transparent_address_always_unique_per_index() {
-        let addresses =
-            derive_transparent_addresses(TEST_SEED_PHRASE, Network::MainNetwork, 0, 0, 100)
-                .expect("derivation should succeed");
+    fn test_ufvk_bytes_roundtrip() {
+        let wallet = restore_wallet(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .expect("wallet derivation should succeed");
+        let ufvk = &wallet.unified_full_viewing_key;
 
-        // Verify ALL transparent addresses are unique
-        let unique: std::collections::HashSet<_> = addresses.iter().collect();
+        let bytes = ufvk_to_bytes(ufvk).unwrap();
+        // Orchard, Sapling and P2PKH items, each with a 2-byte header
+        assert_eq!(bytes.len(), 96 + 128 + 65 + 3 * 2);
+        assert!(bytes.len() < ufvk.len());
         assert_eq!(
-            unique.len(),
-            addresses.len(),
-            "every transparent address index MUST produce a unique address"
+            ufvk_from_bytes(&bytes, Network::TestNetwork).unwrap(),
+            *ufvk
         );
 
-        // Verify adjacent indices never produce the same address
-        for i in 1..addresses.len() {
-            assert_ne!(
-                addresses[i],
-                addresses[i - 1],
-                "adjacent transparent addresses at indices {} and {} must be different",
-                i,
-                i - 1
-            );
-        }
+        // Unknown items are kept
+        let with_metadata = ufvk_with_metadata(
+            ufvk,
+            vec![unified::Fvk::Unknown {
+                typecode: EXPIRY_HEIGHT_TYPECODE,
+                data: 3_000_000u32.to_le_bytes().to_vec(),
+            }],
+        );
+        let bytes = ufvk_to_bytes(&with_metadata).unwrap();
+        assert_eq!(
+            ufvk_from_bytes(&bytes, Network::TestNetwork).unwrap(),
+            with_metadata
+        );
+
+        assert!(ufvk_from_bytes(&bytes[..bytes.len() - 1], Network::TestNetwork).is_err());
+        assert!(ufvk_to_bytes("not a key").is_err());
     }
 }
@@ -222,6 +222,12 @@ export function processScanResult(
   const knownAddressSet = new Set(knownTransparentAddresses);
 
   for (const note of scanResult.notes) {
+    // Outputs recovered with the outgoing viewing key belong to the recipient
+    if (note.scope === "outgoing") {
+      notesSkipped++;
+      continue;
+    }
+
     if (note.pool !== "transparent") {
       if (note.value === 0 && !note.nullifier) {
         notesSkipped++;
@@ -238,7 +244,15 @@ export function processScanResult(
       }
     }
 
-    if (addNote(note, scanResult.txid, walletId)) {
+    if (
+      addNote(
+        note,
+        scanResult.txid,
+        walletId,
+        blockHeight,
+        scanResult.is_coinbase || false
+      )
+    ) {
       notesAdded++;
     }
     if (note.value > 0) {
@@ -272,7 +286,11 @@ export function processScanResult(
     saveNotes(transparentResult.notes);
   }
 
-  const ledgerEntry = createLedgerEntry(scanResult, walletId);
+  const ledgerEntry = createLedgerEntry(
+    scanResult,
+    walletId,
+    knownTransparentAddresses
+  );
   let ledgerUpdated = false;
   if (ledgerEntry) {
     ledgerUpdated = addLedgerEntry(ledgerEntry);
@@ -22,7 +22,11 @@ export function saveLedger(ledger) {
 }
 
 // Create a ledger entry from scan result
-export function createLedgerEntry(scanResult, walletId) {
+export function createLedgerEntry(
+  scanResult,
+  walletId,
+  knownTransparentAddresses = []
+) {
   const wasmModule = getWasm();
   if (!wasmModule) {
     console.error("WASM module not loaded");
@@ -31,7 +35,11 @@ export function createLedgerEntry(scanResult, walletId) {
 
   const scanResultJson = JSON.stringify(scanResult);
 
-  const resultJson = wasmModule.create_ledger_entry(scanResultJson, walletId);
+  const resultJson = wasmModule.create_ledger_entry(
+    scanResultJson,
+    walletId,
+    JSON.stringify(knownTransparentAddresses)
+  );
 
   try {
     const result = JSON.parse(resultJson);
@@ -22,7 +22,13 @@ export function saveNotes(notes) {
 }
 
 // Add a note to storage
-export function addNote(note, txid, walletId) {
+export function addNote(
+  note,
+  txid,
+  walletId,
+  blockHeight = null,
+  isCoinbase = false
+) {
   const wasmModule = getWasm();
   if (!wasmModule) {
     console.error("WASM module not loaded");
@@ -43,7 +49,10 @@ export function addNote(note, txid, walletId) {
     note.nullifier || null,
     note.memo || null,
     note.address || null,
-    new Date().toISOString()
+    note.scope || null,
+    new Date().toISOString(),
+    blockHeight,
+    isCoinbase
   );
 
   // Unwrap the StorageResult to get the raw StoredNote
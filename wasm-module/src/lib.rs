@@ -240,6 +240,7 @@ fn decrypt_transaction_inner(
                 value: u64::from(output.value()),
                 script_pubkey: hex::encode(&script_bytes),
                 address: None, // TODO: decode address from script
+                op_return: zcash_wallet_core::op_return_data(&output.script_pubkey().0.0),
             });
         }
     }
@@ -344,6 +345,26 @@ pub fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// Render an address as a QR code SVG image.
+///
+/// # Arguments
+///
+/// * `address` - The address to encode
+///
+/// # Returns
+///
+/// JSON with `{success: bool, data?: string, error?: string}`, where `data`
+/// is the SVG document.
+#[wasm_bindgen]
+pub fn address_qr_svg(address: &str) -> String {
+    let result = match zcash_wallet_core::address_qr_svg(address) {
+        Ok(svg) => StorageResult::ok(svg),
+        Err(e) => StorageResult::err(e.to_string()),
+    };
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string())
+}
+
 // ============================================================================
 // Transaction Signing
 // ============================================================================
@@ -714,8 +735,16 @@ fn is_leap_year(year: u64) -> bool {
 }
 
 /// Generate a new wallet with a random seed phrase
+///
+/// `birthday_height` should be the current chain tip so that later scans can
+/// skip blocks mined before the wallet existed.
 #[wasm_bindgen]
-pub fn generate_wallet(network_str: &str, account_index: u32, address_index: u32) -> String {
+pub fn generate_wallet(
+    network_str: &str,
+    account_index: u32,
+    address_index: u32,
+    birthday_height: Option<u32>,
+) -> String {
     let network = parse_network(network_str);
     let network_name = if matches!(network, Network::MainNetwork) {
         "mainnet"
@@ -734,37 +763,44 @@ pub fn generate_wallet(network_str: &str, account_index: u32, address_index: u32
         rand::thread_rng().fill_bytes(&mut entropy);
     });
 
-    let result =
-        match zcash_wallet_core::generate_wallet(&entropy, network, account_index, address_index) {
-            Ok(wallet) => {
-                console_log(&format!(
-                    "Wallet generated: {}",
-                    &wallet.unified_address[..20]
-                ));
-                WalletResult {
-                    success: true,
-                    seed_phrase: Some(wallet.seed_phrase),
-                    network: wallet.network,
-                    account_index: wallet.account_index,
-                    address_index: wallet.address_index,
-                    unified_address: Some(wallet.unified_address),
-                    transparent_address: wallet.transparent_address,
-                    unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
-                    error: None,
-                }
+    let result = match zcash_wallet_core::generate_wallet(
+        &entropy,
+        network,
+        account_index,
+        address_index,
+        birthday_height,
+    ) {
+        Ok(wallet) => {
+            console_log(&format!(
+                "Wallet generated: {}",
+                &wallet.unified_address[..20]
+            ));
+            WalletResult {
+                success: true,
+                seed_phrase: Some(wallet.seed_phrase),
+                network: wallet.network,
+                account_index: wallet.account_index,
+                address_index: wallet.address_index,
+                unified_address: Some(wallet.unified_address),
+                transparent_address: wallet.transparent_address,
+                unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
+                birthday_height: wallet.birthday_height,
+                error: None,
             }
-            Err(e) => WalletResult {
-                success: false,
-                seed_phrase: None,
-                network: NetworkKind::Mainnet, // Default for error case
-                account_index: 0,
-                address_index: 0,
-                unified_address: None,
-                transparent_address: None,
-                unified_full_viewing_key: None,
-                error: Some(e.to_string()),
-            },
-        };
+        }
+        Err(e) => WalletResult {
+            success: false,
+            seed_phrase: None,
+            network: NetworkKind::Mainnet, // Default for error case
+            account_index: 0,
+            address_index: 0,
+            unified_address: None,
+            transparent_address: None,
+            unified_full_viewing_key: None,
+            birthday_height: None,
+            error: Some(e.to_string()),
+        },
+    };
 
     serde_json::to_string(&result).unwrap_or_else(|e| {
         serde_json::to_string(&WalletResult {
@@ -776,6 +812,7 @@ pub fn generate_wallet(network_str: &str, account_index: u32, address_index: u32
             unified_address: None,
             transparent_address: None,
             unified_full_viewing_key: None,
+            birthday_height: None,
             error: Some(format!("Serialization error: {}", e)),
         })
         .unwrap()
@@ -818,6 +855,7 @@ pub fn restore_wallet(
                     unified_address: Some(wallet.unified_address),
                     transparent_address: wallet.transparent_address,
                     unified_full_viewing_key: Some(wallet.unified_full_viewing_key),
+                    birthday_height: wallet.birthday_height,
                     error: None,
                 }
             }
@@ -830,6 +868,7 @@ pub fn restore_wallet(
                 unified_address: None,
                 transparent_address: None,
                 unified_full_viewing_key: None,
+                birthday_height: None,
                 error: Some(e.to_string()),
             },
         };
@@ -844,6 +883,7 @@ pub fn restore_wallet(
             unified_address: None,
             transparent_address: None,
             unified_full_viewing_key: None,
+            birthday_height: None,
             error: Some(format!("Serialization error: {}", e)),
         })
         .unwrap()
@@ -897,6 +937,94 @@ pub fn derive_unified_addresses(
     }
 }
 
+/// Result type for address batch derivation
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AddressBatchResult {
+    success: bool,
+    addresses: Vec<String>,
+    error: Option<String>,
+}
+
+/// Derive a batch of unified addresses from a unified full viewing key.
+///
+/// Watch-only wallets use this to pre-generate receiving addresses without
+/// access to the seed phrase.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `network` - The network ("mainnet" or "testnet")
+/// * `start_index` - The starting address/diversifier index
+/// * `count` - Number of addresses to derive (at most 1,000)
+///
+/// # Returns
+///
+/// JSON string with `{success, addresses, error}`. On success `addresses`
+/// holds one entry per index in the range. Consecutive indices may yield the
+/// same address, because an index without a valid Sapling diversifier falls
+/// through to the next valid one. On failure `error` is set and `addresses`
+/// is empty.
+#[wasm_bindgen]
+pub fn derive_addresses_batch(
+    ufvk: &str,
+    network_str: &str,
+    start_index: u32,
+    count: u32,
+) -> String {
+    let network = parse_network(network_str);
+    console_log(&format!(
+        "Deriving {} unified addresses from viewing key starting at {}...",
+        count, start_index
+    ));
+
+    let result = match zcash_wallet_core::derive_unified_addresses_from_ufvk(
+        ufvk,
+        network,
+        start_index,
+        count,
+    ) {
+        Ok(addresses) => {
+            console_log(&format!("Derived {} unified addresses", addresses.len()));
+            AddressBatchResult {
+                success: true,
+                addresses,
+                error: None,
+            }
+        }
+        Err(e) => {
+            console_log(&format!("Failed to derive unified addresses: {}", e));
+            AddressBatchResult {
+                success: false,
+                addresses: vec![],
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    serde_json::to_string(&result).unwrap_or_else(|_| {
+        r#"{"success":false,"addresses":[],"error":"Serialization error"}"#.to_string()
+    })
+}
+
+/// Get the default unified address of a unified full viewing key.
+///
+/// # Arguments
+///
+/// * `ufvk` - The encoded unified full viewing key
+/// * `network` - The network ("mainnet" or "testnet")
+///
+/// # Returns
+///
+/// The unified address at index 0. Throws if the key cannot be decoded or is
+/// for a different network.
+#[wasm_bindgen]
+pub fn ufvk_default_address(ufvk: &str, network_str: &str) -> Result<String, JsValue> {
+    let network = parse_network(network_str);
+    zcash_wallet_core::wallet_from_ufvk(ufvk, Some(network), 0)
+        .map(|wallet| wallet.unified_address)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
 /// Derive multiple transparent addresses from a seed phrase.
 ///
 /// This is useful for scanning transactions - we need to check if transparent
@@ -1076,11 +1204,17 @@ struct UtxoOperationResult {
 /// * `nullifier` - Nullifier (optional, for shielded notes)
 /// * `memo` - Memo field (optional)
 /// * `address` - Recipient address (optional)
+/// * `scope` - Scan scope ("incoming" or "outgoing", optional)
 /// * `created_at` - ISO 8601 timestamp
+/// * `received_at_height` - Height of the block that mined the transaction
+///   (optional)
+/// * `is_coinbase` - Whether the transaction is a coinbase transaction
 ///
 /// # Returns
 ///
-/// JSON string containing the StoredNote or an error.
+/// JSON string containing the StoredNote or an error. Outgoing notes,
+/// recovered with the outgoing viewing key, are rejected: they were sent to
+/// someone else and are not the wallet's funds.
 #[wasm_bindgen]
 #[allow(clippy::too_many_arguments)]
 pub fn create_stored_note(
@@ -1093,8 +1227,18 @@ pub fn create_stored_note(
     nullifier: Option<String>,
     memo: Option<String>,
     address: Option<String>,
+    scope: Option<String>,
     created_at: &str,
+    received_at_height: Option<u32>,
+    is_coinbase: bool,
 ) -> String {
+    if scope.as_deref() == Some("outgoing") {
+        return serde_json::to_string(&StorageResult::<StoredNote>::err(
+            "Outgoing notes are not owned by the wallet",
+        ))
+        .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
+    }
+
     let pool_enum = match pool.to_lowercase().as_str() {
         "orchard" => Pool::Orchard,
         "sapling" => Pool::Sapling,
@@ -1124,6 +1268,8 @@ pub fn create_stored_note(
         spent_txid: None,
         spent_at_height: None,
         created_at: created_at.to_string(),
+        received_at_height,
+        is_coinbase,
     };
 
     serde_json::to_string(&StorageResult::ok(note))
@@ -1877,6 +2023,8 @@ struct ValidationResult {
     word_count: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown_words: Option<Vec<zcash_wallet_core::UnknownWord>>,
 }
 
 impl ValidationResult {
@@ -1887,6 +2035,7 @@ impl ValidationResult {
             address_type: None,
             word_count: None,
             count: None,
+            unknown_words: None,
         }
     }
 
@@ -1897,6 +2046,7 @@ impl ValidationResult {
             address_type: None,
             word_count: None,
             count: None,
+            unknown_words: None,
         }
     }
 }
@@ -1943,7 +2093,8 @@ pub fn validate_txid(txid: &str) -> String {
 
 /// Validate a Zcash address.
 ///
-/// Supports transparent (t-addr), Sapling (zs), and unified addresses (u).
+/// Supports transparent (t-addr), TEX, Sapling (zs), and unified addresses
+/// (u), in both their mainnet and testnet forms.
 ///
 /// # Arguments
 ///
@@ -1962,97 +2113,47 @@ pub fn validate_address(address: &str, network: &str) -> String {
             .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
     }
 
-    let is_mainnet = matches!(network.to_lowercase().as_str(), "mainnet" | "main");
-
-    // Check for unified address
-    if address.starts_with("u1") || address.starts_with("utest1") {
-        let expected_prefix = if is_mainnet { "u1" } else { "utest1" };
-        if (is_mainnet && !address.starts_with("u1"))
-            || (!is_mainnet && !address.starts_with("utest1"))
-        {
-            return serde_json::to_string(&ValidationResult::err(format!(
-                "Unified address should start with '{}' for {}",
-                expected_prefix,
-                if is_mainnet { "mainnet" } else { "testnet" }
-            )))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-        }
-
-        // Try to decode the unified address
-        if zcash_address::unified::Address::decode(address).is_ok() {
-            let mut result = ValidationResult::ok();
-            result.address_type = Some("unified".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"unified"}"#.to_string());
-        }
-        return serde_json::to_string(&ValidationResult::err("Invalid unified address encoding"))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-    }
-
-    // Check for Sapling address
-    if address.starts_with("zs") || address.starts_with("ztestsapling") {
-        let expected_prefix = if is_mainnet { "zs" } else { "ztestsapling" };
-        if (is_mainnet && !address.starts_with("zs"))
-            || (!is_mainnet && !address.starts_with("ztestsapling"))
-        {
-            return serde_json::to_string(&ValidationResult::err(format!(
-                "Sapling address should start with '{}' for {}",
-                expected_prefix,
-                if is_mainnet { "mainnet" } else { "testnet" }
-            )))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-        }
-
-        // Basic bech32 validation
-        if bech32::decode(address).is_ok() {
-            let mut result = ValidationResult::ok();
-            result.address_type = Some("sapling".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"sapling"}"#.to_string());
-        } else {
-            return serde_json::to_string(&ValidationResult::err(
-                "Invalid Sapling address encoding",
-            ))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-        }
-    }
-
-    // Check for transparent address
-    if address.starts_with('t') {
-        let expected_prefix = if is_mainnet { "t1" } else { "tm" };
-        if (is_mainnet && !address.starts_with("t1")) || (!is_mainnet && !address.starts_with("tm"))
-        {
-            return serde_json::to_string(&ValidationResult::err(format!(
-                "Transparent address should start with '{}' for {}",
-                expected_prefix,
-                if is_mainnet { "mainnet" } else { "testnet" }
-            )))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
-        }
+    let expected = if matches!(network.to_lowercase().as_str(), "mainnet" | "main") {
+        Network::MainNetwork
+    } else {
+        Network::TestNetwork
+    };
+    let network_name = |network: Network| match network {
+        Network::MainNetwork => "mainnet",
+        Network::TestNetwork => "testnet",
+    };
 
-        // Basic base58check validation (length check)
-        if address.len() >= 26 && address.len() <= 35 {
+    let result = match zcash_wallet_core::classify_address(address) {
+        Some((kind, found)) if found == expected => {
+            // Both transparent address kinds are reported as "transparent"
+            let address_type = if kind.is_transparent() {
+                "transparent"
+            } else {
+                kind.as_str()
+            };
             let mut result = ValidationResult::ok();
-            result.address_type = Some("transparent".to_string());
-            return serde_json::to_string(&result)
-                .unwrap_or_else(|_| r#"{"valid":true,"address_type":"transparent"}"#.to_string());
-        } else {
-            return serde_json::to_string(&ValidationResult::err(
-                "Invalid transparent address length",
-            ))
-            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+            result.address_type = Some(address_type.to_string());
+            result
         }
-    }
+        Some((kind, found)) => ValidationResult::err(format!(
+            "This is a {} {} address, but the wallet is on {}",
+            network_name(found),
+            kind.as_str(),
+            network_name(expected)
+        )),
+        None => ValidationResult::err(
+            "Unrecognized address format. Expected unified (u1/utest1), Sapling (zs/ztestsapling), transparent (t1/t3/tm/t2) or TEX (tex1/textest1) address",
+        ),
+    };
 
-    serde_json::to_string(&ValidationResult::err(
-        "Unrecognized address format. Expected unified (u1/utest1), Sapling (zs/ztestsapling), or transparent (t1/tm) address",
-    ))
-    .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string())
+    serde_json::to_string(&result)
+        .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string())
 }
 
 /// Validate a BIP39 seed phrase.
 ///
-/// Checks word count and basic format. Valid phrases have 12, 15, 18, 21, or 24 words.
+/// Checks word count, basic format and BIP39 word list membership. Valid
+/// phrases have 12, 15, 18, 21, or 24 words.
 ///
 /// # Arguments
 ///
@@ -2060,7 +2161,8 @@ pub fn validate_address(address: &str, network: &str) -> String {
 ///
 /// # Returns
 ///
-/// JSON with `{valid: bool, word_count?: u8, error?: string}`
+/// JSON with `{valid: bool, word_count?: u8, error?: string,
+/// unknown_words?: [{position, word, suggestions}]}`
 #[wasm_bindgen]
 pub fn validate_seed_phrase(seed_phrase: &str) -> String {
     let seed_phrase = seed_phrase.trim();
@@ -2093,9 +2195,23 @@ pub fn validate_seed_phrase(seed_phrase: &str) -> String {
         }
     }
 
-    // Basic validation passed (format and word count)
-    // Note: Full BIP39 wordlist validation happens during wallet creation
-    // to minimize dependencies in the WASM module
+    // Report misspelled words with suggestions so users can fix typos.
+    // The checksum is still only verified during wallet creation.
+    let unknown_words = zcash_wallet_core::find_unknown_words(seed_phrase);
+    if !unknown_words.is_empty() {
+        let mut result = ValidationResult::err(format!(
+            "Unknown seed phrase words: {}",
+            unknown_words
+                .iter()
+                .map(|unknown| unknown.word.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        result.unknown_words = Some(unknown_words);
+        return serde_json::to_string(&result)
+            .unwrap_or_else(|_| r#"{"valid":false,"error":"Serialization error"}"#.to_string());
+    }
+
     let mut result = ValidationResult::ok();
     result.word_count = Some(word_count as u8);
     serde_json::to_string(&result)
@@ -2195,6 +2311,8 @@ struct LedgerOperationResult {
 ///
 /// * `scan_result_json` - JSON of ScanResult from scanning a transaction
 /// * `wallet_id` - The wallet ID this entry belongs to
+/// * `transparent_addresses_json` - JSON array of the wallet's transparent
+///   addresses; transparent outputs to other addresses are not received
 /// * `received_note_ids_json` - JSON array of note IDs that were received
 /// * `spent_note_ids_json` - JSON array of note IDs that were spent
 /// * `spent_values_json` - JSON array of values (u64) for spent notes
@@ -2204,7 +2322,11 @@ struct LedgerOperationResult {
 ///
 /// JSON containing the created LedgerEntry or an error.
 #[wasm_bindgen]
-pub fn create_ledger_entry(scan_result_json: &str, wallet_id: &str) -> String {
+pub fn create_ledger_entry(
+    scan_result_json: &str,
+    wallet_id: &str,
+    transparent_addresses_json: &str,
+) -> String {
     let scan_result: ScanResult = match serde_json::from_str(scan_result_json) {
         Ok(r) => r,
         Err(e) => {
@@ -2221,6 +2343,23 @@ pub fn create_ledger_entry(scan_result_json: &str, wallet_id: &str) -> String {
             .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
         }
     };
+    let transparent_addresses: Vec<String> = match serde_json::from_str(transparent_addresses_json)
+    {
+        Ok(a) => a,
+        Err(e) => {
+            return serde_json::to_string(&LedgerOperationResult {
+                success: false,
+                entries: vec![],
+                entry: None,
+                ledger: None,
+                is_new: None,
+                balance: None,
+                csv: None,
+                error: Some(format!("Failed to parse transparent addresses: {}", e)),
+            })
+            .unwrap_or_else(|_| r#"{"success":false,"error":"Serialization error"}"#.to_string());
+        }
+    };
 
     // Generate note IDs from scan result
     let received_note_ids: Vec<String> = scan_result
@@ -2247,6 +2386,7 @@ pub fn create_ledger_entry(scan_result_json: &str, wallet_id: &str) -> String {
     let entry = LedgerEntry::from_scan_result(
         &scan_result,
         wallet_id,
+        &transparent_addresses,
         received_note_ids,
         spent_note_ids,
         &spent_values,
@@ -3554,12 +3694,7 @@ pub fn render_send_utxos_table(utxos_json: &str, network: &str) -> String {
         let value_zec = format_zec(utxo.value);
 
         let addr_display = match &utxo.address {
-            Some(addr) if addr.len() > 14 => {
-                let start = &addr[..8];
-                let end = &addr[addr.len() - 6..];
-                format!("{}...{}", start, end)
-            }
-            Some(addr) => addr.clone(),
+            Some(addr) => zcash_wallet_core::truncate_address(addr, 8, 6),
             None => "-".to_string(),
         };
 
@@ -3718,25 +3853,8 @@ pub fn render_derived_addresses_table(addresses_json: &str, network: &str) -> St
         let is_duplicate = duplicate_indices.contains(&addr.index);
 
         // Truncate addresses for display
-        let transparent_display = if addr.transparent.len() > 14 {
-            format!(
-                "{}...{}",
-                &addr.transparent[..8],
-                &addr.transparent[addr.transparent.len() - 6..]
-            )
-        } else {
-            addr.transparent.clone()
-        };
-
-        let unified_display = if addr.unified.len() > 18 {
-            format!(
-                "{}...{}",
-                &addr.unified[..10],
-                &addr.unified[addr.unified.len() - 8..]
-            )
-        } else {
-            addr.unified.clone()
-        };
+        let transparent_display = zcash_wallet_core::truncate_address(&addr.transparent, 8, 6);
+        let unified_display = zcash_wallet_core::truncate_address(&addr.unified, 8, 8);
 
         let transparent_id = format!("copy-transparent-{}", idx);
         let unified_id = format!("copy-unified-{}", idx);
@@ -4057,15 +4175,7 @@ pub fn render_contacts_list(contacts_json: &str) -> String {
             "bg-info"
         };
 
-        let address_short = if contact.address.len() > 24 {
-            format!(
-                "{}...{}",
-                &contact.address[..12],
-                &contact.address[contact.address.len() - 8..]
-            )
-        } else {
-            contact.address.clone()
-        };
+        let address_short = zcash_wallet_core::truncate_address(&contact.address, 10, 8);
 
         let copy_onclick = format!(
             "copyContactAddress('{}')",
@@ -4219,4 +4329,85 @@ mod tests {
         assert_eq!(format_zec(123456789), "1.23456789");
         assert_eq!(format_zec(0), "0.00000000");
     }
+
+    #[test]
+    fn test_validate_testnet_addresses() {
+        let wallet = zcash_wallet_core::restore_wallet(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+        let taddr = wallet.transparent_address.unwrap();
+
+        let result: serde_json::Value =
+            serde_json::from_str(&validate_address(&wallet.unified_address, "testnet")).unwrap();
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["address_type"], "unified");
+
+        let result: serde_json::Value =
+            serde_json::from_str(&validate_address(&taddr, "testnet")).unwrap();
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["address_type"], "transparent");
+
+        // Testnet addresses are rejected on mainnet
+        let result: serde_json::Value =
+            serde_json::from_str(&validate_address(&taddr, "mainnet")).unwrap();
+        assert_eq!(result["valid"], false);
+    }
+
+    #[test]
+    fn test_derive_addresses_batch() {
+        let wallet = zcash_wallet_core::restore_wallet(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+
+        let result: AddressBatchResult = serde_json::from_str(&derive_addresses_batch(
+            &wallet.unified_full_viewing_key,
+            "testnet",
+            0,
+            5,
+        ))
+        .unwrap();
+        assert!(result.success);
+        assert!(result.error.is_none());
+        assert_eq!(result.addresses.len(), 5);
+        assert_eq!(result.addresses[0], wallet.unified_address);
+
+        let result: AddressBatchResult =
+            serde_json::from_str(&derive_addresses_batch("invalid", "testnet", 0, 5)).unwrap();
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert!(result.addresses.is_empty());
+
+        let result: AddressBatchResult = serde_json::from_str(&derive_addresses_batch(
+            &wallet.unified_full_viewing_key,
+            "testnet",
+            0,
+            zcash_wallet_core::MAX_ADDRESS_BATCH + 1,
+        ))
+        .unwrap();
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert!(result.addresses.is_empty());
+    }
+
+    #[test]
+    fn test_ufvk_default_address() {
+        let wallet = zcash_wallet_core::restore_wallet(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+            Network::TestNetwork,
+            0,
+            0,
+        )
+        .unwrap();
+
+        let address = ufvk_default_address(&wallet.unified_full_viewing_key, "testnet").unwrap();
+        assert_eq!(address, wallet.unified_address);
+    }
 }